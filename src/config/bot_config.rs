@@ -0,0 +1,1538 @@
+use std::{env, fmt, fs, path::Path, str::FromStr};
+
+use dotenvy::dotenv;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use solana_sdk::signature::Keypair;
+use thiserror::Error;
+
+use crate::{Gateway, MeanReversion, DEFAULT_SENDERS, STRATEGY_NAMES};
+use toml::{Table, Value};
+
+/// Config file used when `BOT_CONFIG` is not set.
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("failed to parse config file {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+
+    #[error("invalid configuration:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+#[derive(Clone, Serialize)]
+pub struct EndpointConfig {
+    #[serde(skip_serializing)]
+    pub private_key: String,
+    pub rpc_endpoint: String,
+    pub grpc_endpoint: String,
+    #[serde(skip_serializing)]
+    pub grpc_token: String,
+}
+
+// Keep secrets out of logs.
+impl fmt::Debug for EndpointConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EndpointConfig")
+            .field("private_key", &"<redacted>")
+            .field("rpc_endpoint", &self.rpc_endpoint)
+            .field("grpc_endpoint", &self.grpc_endpoint)
+            .field("grpc_token", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PathConfig {
+    pub pool_addr: String,
+    pub black_list: String,
+    pub enermy_list: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeConfig {
+    pub on_debug: bool,
+    pub submit_tx: bool,
+    /// Fills intents against the observed pool state into the paper account
+    /// instead of submitting them; read at startup only.
+    pub paper_trade: bool,
+    pub is_racing: bool,
+    pub show_buy: bool,
+    pub show_sell: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterConfig {
+    pub acceptable_liquidity: u64,
+    pub take_profit: f64,
+}
+
+/// Sizing of buys into dumps.
+///
+/// `tiers` decides amount, tip and slippage; when it is not set, the table is
+/// built from the `amount_in_factor_*`, `tip_factor_*` and
+/// `slippage.factor_*` keys at the thresholds earlier versions used (see
+/// `legacy_tiers` for how it differs from their sizing).
+/// Buys are capped at `max_amount` SOL and tip at least `tip_min` SOL.
+#[derive(Debug, Clone, Serialize)]
+pub struct DumpConfig {
+    pub tiers: Option<Vec<SizingTier>>,
+    pub max_amount: f64,
+    pub amount_in_factor_low: f64,
+    pub amount_in_factor_median: f64,
+    pub amount_in_factor_high: f64,
+    pub tip_min: f64,
+    pub tip_factor_low: f64,
+    pub tip_factor_median: f64,
+    pub tip_factor_high: f64,
+    pub tip_factor_ultra: f64,
+}
+
+/// One row of the sizing table, matched against a dump of at least
+/// `min_drop_pct` % on a pool holding at least `min_liquidity_usd`; the first
+/// matching row wins.
+///
+/// It buys `amount_pct` % of the SOL the dump sold, tips `tip_pct` % of the
+/// buy and allows `slippage_factor` times the drop as slippage (clamped to
+/// `slippage.min_pct..=max_pct`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SizingTier {
+    pub min_drop_pct: f64,
+    pub min_liquidity_usd: f64,
+    pub amount_pct: f64,
+    pub tip_pct: f64,
+    pub slippage_factor: f64,
+}
+
+/// Slippage tolerance put on every submitted swap, in percent of its quoted output.
+///
+/// Entries scale with the observed price drop (`factor_*` times its size, by
+/// drop and liquidity tier) and are clamped to `min_pct..=max_pct`; exits use
+/// `sell_pct` as is.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlippageConfig {
+    pub factor_low: f64,
+    pub factor_median: f64,
+    pub factor_high: f64,
+    pub min_pct: f64,
+    pub max_pct: f64,
+    pub sell_pct: f64,
+}
+
+/// Exits of open positions, checked on every reserve update of their pool.
+///
+/// They come on top of the strategies' own take profit; a zero turns the
+/// stop-loss, trailing stop or holding time off.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExitConfig {
+    /// Sell everything once unrealized PnL falls to `-stop_loss_pct` %.
+    pub stop_loss_pct: f64,
+    /// Sell everything once the position's exit value falls this many % below its peak.
+    pub trailing_stop_pct: f64,
+    /// Sell everything once the oldest open lot was bought this many seconds ago.
+    pub max_hold_secs: u64,
+    /// Partial take profits, by increasing unrealized PnL.
+    pub scale_out: Vec<ScaleOutLevel>,
+}
+
+/// Sell `sell_pct` % of the tokens still held once unrealized PnL reaches
+/// `at_pct` %; written `"<at_pct>:<sell_pct>"`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ScaleOutLevel {
+    pub at_pct: f64,
+    pub sell_pct: f64,
+}
+
+impl FromStr for ScaleOutLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (at_pct, sell_pct) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected \"<at_pct>:<sell_pct>\", got {:?}", s))?;
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .map_err(|e| format!("{:?} in {:?}: {}", value, s, e))
+        };
+
+        Ok(ScaleOutLevel {
+            at_pct: parse(at_pct)?,
+            sell_pct: parse(sell_pct)?,
+        })
+    }
+}
+
+impl TryFrom<String> for ScaleOutLevel {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Limits the risk manager enforces before every entry; a zero turns one off.
+///
+/// Breaking any of them trips the kill switch, which halts entries until an
+/// operator resets it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskConfig {
+    /// Pools holding tokens at once, entries in flight included.
+    pub max_open_positions: u32,
+    /// Open cost basis in one mint, in SOL.
+    pub max_mint_exposure_sol: f64,
+    /// Open cost basis across every mint, in SOL.
+    pub max_total_exposure_sol: f64,
+    /// Submissions, exits included, over the last minute.
+    pub max_trades_per_minute: u32,
+    /// Realized loss over the last 24 hours, in SOL.
+    pub daily_loss_limit_sol: f64,
+    /// SOL the wallet must keep after an entry's spend and tip.
+    pub min_wallet_reserve_sol: f64,
+}
+
+/// Circuit breakers on landing gateways and pools; a zero threshold turns one
+/// kind off.
+///
+/// A breaker opens after its threshold of consecutive failed submissions and
+/// lets a single probe through once its cooldown ran out. The cooldown
+/// doubles every time the probe fails too, up to `max_cooldown_secs`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerConfig {
+    /// Consecutive submissions a gateway rejected or never landed.
+    pub gateway_failures: u32,
+    /// Consecutive submissions on a pool that failed on-chain or expired.
+    pub pool_failures: u32,
+    pub cooldown_secs: u64,
+    pub max_cooldown_secs: u64,
+}
+
+/// Pre-flight simulation of every submission and its compute-unit limit.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightConfig {
+    /// Simulates the transaction first and drops it if it would fail or
+    /// receive less than its minimum out; off, the compute-unit limit comes
+    /// from the last measurement of the DEX.
+    pub simulate: bool,
+    /// Margin added to the measured compute units, in percent.
+    pub cu_headroom_pct: f64,
+}
+
+/// Compute-unit price of submissions, suggested from recent fees on the pool.
+#[derive(Debug, Clone, Serialize)]
+pub struct PriorityFeeConfig {
+    /// Price used while no fee was seen on the pool, in micro-lamports per unit.
+    pub fallback_micro_lamports: u64,
+    /// Cap on any suggested price, in micro-lamports per unit.
+    pub max_micro_lamports: u64,
+    /// How often `getRecentPrioritizationFees` is asked for every pool's
+    /// accounts (0 = the observed stream only). Read at startup.
+    pub refresh_secs: u64,
+}
+
+/// Recording of the raw Geyser updates the bot receives, for `replay`. Read
+/// at startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecorderConfig {
+    pub enabled: bool,
+    /// Directory the recording files are written to.
+    pub dir: String,
+    /// Minutes after which the recording moves on to a new file.
+    pub rotate_minutes: u64,
+}
+
+/// A landing gateway submissions go through, from `[senders]`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SenderRoute {
+    pub gateway: Gateway,
+    /// Regions the transaction is posted to (`<gateway>_regions`).
+    pub regions: Vec<String>,
+    /// Tip floor in SOL (`<gateway>_min_tip`); the strategy's tip is used when higher.
+    pub min_tip: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SenderConfig {
+    /// Enabled gateways in the order of `senders.enabled`; only the first one
+    /// is used when `mode.is_racing` is off.
+    pub routes: Vec<SenderRoute>,
+    /// Names listed in `senders.enabled`, kept to report unknown or repeated ones.
+    #[serde(skip)]
+    enabled: Vec<String>,
+}
+
+impl SenderConfig {
+    /// Routes a submission goes through: all of them when racing, else the first.
+    pub fn active(&self, is_racing: bool) -> &[SenderRoute] {
+        let count = if is_racing { self.routes.len() } else { 1 };
+        &self.routes[..count.min(self.routes.len())]
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyConfig {
+    /// Strategies run on every observed swap, in order.
+    pub enabled: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct GatewayKeys {
+    #[serde(skip_serializing)]
+    pub next_block_key: String,
+    #[serde(skip_serializing)]
+    pub nozomi_api_key: String,
+    #[serde(skip_serializing)]
+    pub blox_auth_header: String,
+    #[serde(skip_serializing)]
+    pub zslot_api_key: String,
+}
+
+impl fmt::Debug for GatewayKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = |key: &str| {
+            if key.is_empty() {
+                "<unset>"
+            } else {
+                "<redacted>"
+            }
+        };
+        f.debug_struct("GatewayKeys")
+            .field("next_block_key", &state(&self.next_block_key))
+            .field("nozomi_api_key", &state(&self.nozomi_api_key))
+            .field("blox_auth_header", &state(&self.blox_auth_header))
+            .field("zslot_api_key", &state(&self.zslot_api_key))
+            .finish()
+    }
+}
+
+/// Every tunable of the bot, loaded once and validated as a whole.
+///
+/// Values come from the TOML file named by `BOT_CONFIG` (default `config.toml`);
+/// each key can be overridden by its legacy environment variable, so an existing
+/// `.env` keeps working without a config file. The file keys an env var
+/// overrides are kept in `shadowed_keys`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BotConfig {
+    pub endpoints: EndpointConfig,
+    pub paths: PathConfig,
+    pub mode: ModeConfig,
+    pub filter: FilterConfig,
+    pub dump: DumpConfig,
+    pub slippage: SlippageConfig,
+    pub exit: ExitConfig,
+    pub risk: RiskConfig,
+    pub breaker: BreakerConfig,
+    pub preflight: PreflightConfig,
+    pub priority_fee: PriorityFeeConfig,
+    pub recorder: RecorderConfig,
+    pub senders: SenderConfig,
+    pub strategy: StrategyConfig,
+    pub gateways: GatewayKeys,
+    /// File keys overridden by an env var, so edits to them have no effect.
+    #[serde(skip_serializing)]
+    pub shadowed_keys: Vec<ShadowedKey>,
+}
+
+/// A config file key whose env var override takes precedence.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ShadowedKey {
+    /// `section.key`
+    pub key: String,
+    pub env_key: String,
+    /// The value the file sets, as written.
+    pub file_value: String,
+}
+
+// The file value may be a secret.
+impl fmt::Debug for ShadowedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShadowedKey")
+            .field("key", &self.key)
+            .field("env_key", &self.env_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BotConfig {
+    /// Loads `.env`, the config file and env overrides, then validates the result.
+    pub fn load() -> Result<Self, ConfigError> {
+        dotenv().ok();
+
+        match Self::config_path() {
+            Some(path) => Self::from_file(&path),
+            None => Self::from_table(Table::new()),
+        }
+    }
+
+    /// Config file in use: `BOT_CONFIG` if set, else `config.toml` when present.
+    pub fn config_path() -> Option<String> {
+        match env::var("BOT_CONFIG") {
+            Ok(path) => Some(path),
+            Err(_) if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Some(DEFAULT_CONFIG_PATH.to_string())
+            }
+            Err(_) => None,
+        }
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_string(),
+            source,
+        })?;
+        let table: Table = content.parse().map_err(|source| ConfigError::Parse {
+            path: path.to_string(),
+            source,
+        })?;
+
+        Self::from_table(table)
+    }
+
+    /// Builds the config from a parsed file, applying env overrides and
+    /// collecting every missing or invalid key before failing.
+    pub fn from_table(table: Table) -> Result<Self, ConfigError> {
+        let mut reader = ConfigReader {
+            table,
+            errors: Vec::new(),
+            shadowed: Vec::new(),
+        };
+
+        let mut config = BotConfig {
+            endpoints: EndpointConfig {
+                private_key: reader.required("endpoints", "private_key", "PRIVATE_KEY"),
+                rpc_endpoint: reader.required("endpoints", "rpc_endpoint", "RPC_ENDPOINT"),
+                grpc_endpoint: reader.required("endpoints", "grpc_endpoint", "GRPC_ENDPOINT"),
+                grpc_token: reader.required("endpoints", "grpc_token", "GRPC_TOKEN"),
+            },
+            paths: PathConfig {
+                pool_addr: reader.required("paths", "pool_addr", "POOL_ADDR_DIR"),
+                black_list: reader.required("paths", "black_list", "BLACK_LIST_DIR"),
+                enermy_list: reader.required("paths", "enermy_list", "ENERMY_LIST_DIR"),
+            },
+            mode: ModeConfig {
+                on_debug: reader.required("mode", "on_debug", "ON_DEBUG"),
+                submit_tx: reader.required("mode", "submit_tx", "SUBMIT_TX"),
+                paper_trade: reader.defaulted("mode", "paper_trade", "PAPER_TRADE", false),
+                is_racing: reader.required("mode", "is_racing", "IS_RACING"),
+                show_buy: reader.required("mode", "show_buy", "SHOW_BUY"),
+                show_sell: reader.required("mode", "show_sell", "SHOW_SELL"),
+            },
+            filter: FilterConfig {
+                acceptable_liquidity: reader.required(
+                    "filter",
+                    "acceptable_liquidity",
+                    "ACCEPTABLE_LIQUIDITY",
+                ),
+                take_profit: reader.required("filter", "take_profit", "TAKE_PROFIT"),
+            },
+            dump: DumpConfig {
+                tiers: reader.table_list("dump", "tiers"),
+                max_amount: reader.required("dump", "max_amount", "MAX_AMOUNT"),
+                amount_in_factor_low: reader.required(
+                    "dump",
+                    "amount_in_factor_low",
+                    "AMOUNT_IN_FACTOR_LOW",
+                ),
+                amount_in_factor_median: reader.required(
+                    "dump",
+                    "amount_in_factor_median",
+                    "AMOUNT_IN_FACTOR_MEDIAN",
+                ),
+                amount_in_factor_high: reader.required(
+                    "dump",
+                    "amount_in_factor_high",
+                    "AMOUNT_IN_FACTOR_HIGH",
+                ),
+                tip_min: reader.required("dump", "tip_min", "TIP_MIN"),
+                tip_factor_low: reader.required("dump", "tip_factor_low", "TIP_FACTOR_LOW"),
+                tip_factor_median: reader.required(
+                    "dump",
+                    "tip_factor_median",
+                    "TIP_FACTOR_MEDIAN",
+                ),
+                tip_factor_high: reader.required("dump", "tip_factor_high", "TIP_FACTOR_HIGH"),
+                tip_factor_ultra: reader.required("dump", "tip_factor_ultra", "TIP_FACTOR_ULTRA"),
+            },
+            slippage: SlippageConfig {
+                factor_low: reader.defaulted("slippage", "factor_low", "SLIPPAGE_FACTOR_LOW", 0.01),
+                factor_median: reader.defaulted(
+                    "slippage",
+                    "factor_median",
+                    "SLIPPAGE_FACTOR_MEDIAN",
+                    0.05,
+                ),
+                factor_high: reader.defaulted(
+                    "slippage",
+                    "factor_high",
+                    "SLIPPAGE_FACTOR_HIGH",
+                    0.1,
+                ),
+                min_pct: reader.defaulted("slippage", "min_pct", "SLIPPAGE_MIN_PCT", 0.5),
+                max_pct: reader.defaulted("slippage", "max_pct", "SLIPPAGE_MAX_PCT", 10.0),
+                sell_pct: reader.defaulted("slippage", "sell_pct", "SLIPPAGE_SELL_PCT", 1.0),
+            },
+            exit: ExitConfig {
+                stop_loss_pct: reader.defaulted("exit", "stop_loss_pct", "EXIT_STOP_LOSS_PCT", 0.0),
+                trailing_stop_pct: reader.defaulted(
+                    "exit",
+                    "trailing_stop_pct",
+                    "EXIT_TRAILING_STOP_PCT",
+                    0.0,
+                ),
+                max_hold_secs: reader.defaulted("exit", "max_hold_secs", "EXIT_MAX_HOLD_SECS", 0),
+                scale_out: reader.parsed_list("exit", "scale_out", "EXIT_SCALE_OUT"),
+            },
+            risk: RiskConfig {
+                max_open_positions: reader.defaulted(
+                    "risk",
+                    "max_open_positions",
+                    "RISK_MAX_OPEN_POSITIONS",
+                    0,
+                ),
+                max_mint_exposure_sol: reader.defaulted(
+                    "risk",
+                    "max_mint_exposure_sol",
+                    "RISK_MAX_MINT_EXPOSURE_SOL",
+                    0.0,
+                ),
+                max_total_exposure_sol: reader.defaulted(
+                    "risk",
+                    "max_total_exposure_sol",
+                    "RISK_MAX_TOTAL_EXPOSURE_SOL",
+                    0.0,
+                ),
+                max_trades_per_minute: reader.defaulted(
+                    "risk",
+                    "max_trades_per_minute",
+                    "RISK_MAX_TRADES_PER_MINUTE",
+                    0,
+                ),
+                daily_loss_limit_sol: reader.defaulted(
+                    "risk",
+                    "daily_loss_limit_sol",
+                    "RISK_DAILY_LOSS_LIMIT_SOL",
+                    0.0,
+                ),
+                min_wallet_reserve_sol: reader.defaulted(
+                    "risk",
+                    "min_wallet_reserve_sol",
+                    "RISK_MIN_WALLET_RESERVE_SOL",
+                    0.0,
+                ),
+            },
+            breaker: BreakerConfig {
+                gateway_failures: reader.defaulted(
+                    "breaker",
+                    "gateway_failures",
+                    "BREAKER_GATEWAY_FAILURES",
+                    5,
+                ),
+                pool_failures: reader.defaulted(
+                    "breaker",
+                    "pool_failures",
+                    "BREAKER_POOL_FAILURES",
+                    3,
+                ),
+                cooldown_secs: reader.defaulted(
+                    "breaker",
+                    "cooldown_secs",
+                    "BREAKER_COOLDOWN_SECS",
+                    30,
+                ),
+                max_cooldown_secs: reader.defaulted(
+                    "breaker",
+                    "max_cooldown_secs",
+                    "BREAKER_MAX_COOLDOWN_SECS",
+                    600,
+                ),
+            },
+            preflight: PreflightConfig {
+                simulate: reader.defaulted("preflight", "simulate", "PREFLIGHT_SIMULATE", false),
+                cu_headroom_pct: reader.defaulted(
+                    "preflight",
+                    "cu_headroom_pct",
+                    "PREFLIGHT_CU_HEADROOM_PCT",
+                    20.0,
+                ),
+            },
+            priority_fee: PriorityFeeConfig {
+                fallback_micro_lamports: reader.defaulted(
+                    "priority_fee",
+                    "fallback_micro_lamports",
+                    "PRIORITY_FEE_FALLBACK_MICRO_LAMPORTS",
+                    30_000,
+                ),
+                max_micro_lamports: reader.defaulted(
+                    "priority_fee",
+                    "max_micro_lamports",
+                    "PRIORITY_FEE_MAX_MICRO_LAMPORTS",
+                    1_000_000,
+                ),
+                refresh_secs: reader.defaulted(
+                    "priority_fee",
+                    "refresh_secs",
+                    "PRIORITY_FEE_REFRESH_SECS",
+                    10,
+                ),
+            },
+            recorder: RecorderConfig {
+                enabled: reader.defaulted("recorder", "enabled", "RECORD_STREAM", false),
+                dir: reader.defaulted(
+                    "recorder",
+                    "dir",
+                    "RECORD_DIR",
+                    "src/assets/recordings".to_string(),
+                ),
+                rotate_minutes: reader.defaulted(
+                    "recorder",
+                    "rotate_minutes",
+                    "RECORD_ROTATE_MINUTES",
+                    60,
+                ),
+            },
+            senders: reader.senders(),
+            strategy: StrategyConfig {
+                enabled: reader.list("strategy", "enabled", "STRATEGIES", &[MeanReversion::NAME]),
+            },
+            gateways: GatewayKeys {
+                next_block_key: reader.optional("gateways", "next_block_key", "NEXT_BLOCK_KEY"),
+                nozomi_api_key: reader.optional("gateways", "nozomi_api_key", "NOZOMI_API_KEY"),
+                blox_auth_header: reader.optional(
+                    "gateways",
+                    "blox_auth_header",
+                    "BLOX_AUTH_HEADER",
+                ),
+                zslot_api_key: reader.optional("gateways", "zslot_api_key", "ZSLOT_API_KEY"),
+            },
+            shadowed_keys: Vec::new(),
+        };
+        config.shadowed_keys = reader.shadowed;
+
+        let mut errors = reader.errors;
+        errors.extend(config.validate());
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+
+    /// Semantic checks on already-parsed values.
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if !self.endpoints.private_key.is_empty()
+            && parse_keypair(&self.endpoints.private_key).is_none()
+        {
+            errors.push("endpoints.private_key is not a valid base58 keypair".to_string());
+        }
+        for (key, url) in [
+            ("endpoints.rpc_endpoint", &self.endpoints.rpc_endpoint),
+            ("endpoints.grpc_endpoint", &self.endpoints.grpc_endpoint),
+        ] {
+            let is_http = url.starts_with("http://") || url.starts_with("https://");
+            if !url.is_empty() && !is_http {
+                errors.push(format!("{} must be an http(s) URL, got {:?}", key, url));
+            }
+        }
+
+        for (key, path) in [
+            ("paths.pool_addr", &self.paths.pool_addr),
+            ("paths.black_list", &self.paths.black_list),
+            ("paths.enermy_list", &self.paths.enermy_list),
+        ] {
+            if !path.is_empty() && !Path::new(path).is_file() {
+                errors.push(format!("{} points to a missing file: {}", key, path));
+            }
+        }
+
+        errors.extend(validate_take_profit("filter", self.filter.take_profit));
+        errors.extend(self.dump.validate("dump"));
+        errors.extend(self.slippage.validate("slippage"));
+        errors.extend(self.exit.validate("exit"));
+        for (key, sol) in [
+            ("max_mint_exposure_sol", self.risk.max_mint_exposure_sol),
+            ("max_total_exposure_sol", self.risk.max_total_exposure_sol),
+            ("daily_loss_limit_sol", self.risk.daily_loss_limit_sol),
+            ("min_wallet_reserve_sol", self.risk.min_wallet_reserve_sol),
+        ] {
+            if !sol.is_finite() || sol < 0.0 {
+                errors.push(format!("risk.{} must be >= 0, got {}", key, sol));
+            }
+        }
+
+        let breaker = &self.breaker;
+        if breaker.gateway_failures > 0 || breaker.pool_failures > 0 {
+            if breaker.cooldown_secs == 0 {
+                errors.push("breaker.cooldown_secs must be > 0".to_string());
+            }
+            if breaker.max_cooldown_secs < breaker.cooldown_secs {
+                errors.push(format!(
+                    "breaker.max_cooldown_secs ({}) must be >= breaker.cooldown_secs ({})",
+                    breaker.max_cooldown_secs, breaker.cooldown_secs
+                ));
+            }
+        }
+
+        let headroom = self.preflight.cu_headroom_pct;
+        if !headroom.is_finite() || headroom < 0.0 {
+            errors.push(format!(
+                "preflight.cu_headroom_pct must be >= 0, got {}",
+                headroom
+            ));
+        }
+
+        let priority_fee = &self.priority_fee;
+        if priority_fee.max_micro_lamports < priority_fee.fallback_micro_lamports {
+            errors.push(format!(
+                "priority_fee.max_micro_lamports ({}) must be >= priority_fee.fallback_micro_lamports ({})",
+                priority_fee.max_micro_lamports, priority_fee.fallback_micro_lamports
+            ));
+        }
+
+        if self.recorder.enabled && self.recorder.rotate_minutes == 0 {
+            errors.push("recorder.rotate_minutes must be > 0".to_string());
+        }
+
+        if self.strategy.enabled.is_empty() {
+            errors.push("strategy.enabled must name at least one strategy".to_string());
+        }
+        for (i, name) in self.strategy.enabled.iter().enumerate() {
+            if !STRATEGY_NAMES.contains(&name.as_str()) {
+                errors.push(format!(
+                    "strategy.enabled has unknown strategy {:?} (available: {})",
+                    name,
+                    STRATEGY_NAMES.join(", ")
+                ));
+            } else if self.strategy.enabled[..i].contains(name) {
+                errors.push(format!("strategy.enabled lists {:?} twice", name));
+            }
+        }
+
+        errors.extend(self.validate_senders());
+
+        errors
+    }
+
+    fn validate_senders(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let senders = &self.senders;
+
+        if senders.enabled.is_empty() {
+            errors.push("senders.enabled must name at least one gateway".to_string());
+        }
+        for (i, name) in senders.enabled.iter().enumerate() {
+            if let Err(e) = name.parse::<Gateway>() {
+                errors.push(format!("senders.enabled has {}", e));
+            } else if senders.enabled[..i].contains(name) {
+                errors.push(format!("senders.enabled lists {:?} twice", name));
+            }
+        }
+
+        for route in &senders.routes {
+            let name = route.gateway.name();
+            let known = route.gateway.region_names();
+            if !known.is_empty() && route.regions.is_empty() {
+                errors.push(format!(
+                    "senders.{}_regions must name at least one region",
+                    name
+                ));
+            }
+            for region in &route.regions {
+                if !known.contains(&region.as_str()) {
+                    errors.push(format!(
+                        "senders.{}_regions has unknown region {:?} (available: {})",
+                        name,
+                        region,
+                        known.join(", ")
+                    ));
+                }
+            }
+            if !route.min_tip.is_finite() || route.min_tip < 0.0 {
+                errors.push(format!(
+                    "senders.{}_min_tip must be >= 0, got {}",
+                    name, route.min_tip
+                ));
+            }
+        }
+
+        if self.mode.submit_tx && !self.mode.paper_trade {
+            for route in senders.active(self.mode.is_racing) {
+                if let Some((key, value)) = route.gateway.api_key(self) {
+                    if value.is_empty() {
+                        errors.push(format!(
+                            "gateways.{} ({}) is required while {} submits transactions",
+                            key,
+                            key.to_uppercase(),
+                            route.gateway
+                        ));
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Signing keypair; the key was validated at load time.
+    pub fn payer(&self) -> Keypair {
+        parse_keypair(&self.endpoints.private_key).expect("private key validated on load")
+    }
+}
+
+pub(crate) fn validate_take_profit(section: &str, take_profit: f64) -> Option<String> {
+    (!take_profit.is_finite()).then(|| format!("{}.take_profit must be a finite number", section))
+}
+
+impl DumpConfig {
+    /// The sizing table in effect: `tiers`, or the one built from the legacy factors.
+    pub fn sizing_tiers(&self, slippage: &SlippageConfig) -> Vec<SizingTier> {
+        match &self.tiers {
+            Some(tiers) => tiers.clone(),
+            None => self.legacy_tiers(slippage),
+        }
+    }
+
+    /// Table for pools without `tiers`, filled in with the factors of the
+    /// `[dump]` and `[slippage]` sections.
+    ///
+    /// It merges the drop / liquidity thresholds of the former `dump_setup`
+    /// and `slippage_setup`, but does not size like them: the 18% and 12% rows
+    /// only existed in `slippage_setup`, the amount is `amount_in_factor_*` %
+    /// of the sell rather than scaled by liquidity, every tip is floored at
+    /// `tip_min`, and a buy capped at `max_amount` keeps its row's tip and
+    /// slippage instead of `tip_min * 4` and 5%.
+    fn legacy_tiers(&self, slippage: &SlippageConfig) -> Vec<SizingTier> {
+        let tier =
+            |min_drop_pct, min_liquidity_usd, amount_pct, tip_pct, slippage_factor| SizingTier {
+                min_drop_pct,
+                min_liquidity_usd,
+                amount_pct,
+                tip_pct,
+                slippage_factor,
+            };
+        let (amount_low, amount_median, amount_high) = (
+            self.amount_in_factor_low,
+            self.amount_in_factor_median,
+            self.amount_in_factor_high,
+        );
+        let (tip_low, tip_median) = (self.tip_factor_low, self.tip_factor_median);
+        let (slip_low, slip_median, slip_high) = (
+            slippage.factor_low,
+            slippage.factor_median,
+            slippage.factor_high,
+        );
+
+        vec![
+            tier(
+                30.0,
+                5_000_000.0,
+                amount_high,
+                self.tip_factor_ultra,
+                slip_high,
+            ),
+            tier(
+                30.0,
+                2_000_000.0,
+                amount_high,
+                self.tip_factor_high,
+                slip_high,
+            ),
+            tier(30.0, 200_000.0, amount_high, tip_median, slip_high),
+            tier(30.0, 80_000.0, amount_high, tip_median, slip_low),
+            tier(25.0, 500_000.0, amount_median, tip_median, slip_high),
+            tier(25.0, 200_000.0, amount_median, tip_median, slip_median),
+            tier(25.0, 80_000.0, amount_median, tip_median, slip_low),
+            tier(20.0, 800_000.0, amount_median, tip_median, slip_high),
+            tier(20.0, 200_000.0, amount_median, tip_median, slip_median),
+            tier(20.0, 80_000.0, amount_median, tip_median, slip_low),
+            tier(18.0, 800_000.0, amount_median, tip_median, slip_high),
+            tier(18.0, 200_000.0, amount_median, tip_median, slip_median),
+            tier(18.0, 100_000.0, amount_median, tip_median, slip_low),
+            tier(15.0, 800_000.0, amount_median, tip_median, slip_high),
+            tier(15.0, 100_000.0, amount_median, tip_median, slip_low),
+            tier(12.0, 2_000_000.0, amount_median, tip_low, slip_median),
+            tier(12.0, 500_000.0, amount_median, tip_low, slip_low),
+            tier(10.0, 5_000_000.0, amount_median, tip_low, slip_median),
+            tier(10.0, 500_000.0, amount_median, tip_low, slip_low),
+            tier(8.0, 5_000_000.0, amount_low, tip_low, slip_median),
+            tier(8.0, 800_000.0, amount_low, tip_low, slip_low),
+            tier(5.0, 5_000_000.0, amount_low, tip_low, slip_low),
+            tier(0.0, 0.0, amount_low, tip_median, slip_low),
+        ]
+    }
+
+    /// Range checks shared by the global `[dump]` section and per-pool overrides.
+    pub(crate) fn validate(&self, section: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(tiers) = &self.tiers {
+            errors.extend(validate_tiers(section, tiers));
+        }
+
+        if self.max_amount.is_nan() || self.max_amount <= 0.0 {
+            errors.push(format!(
+                "{}.max_amount must be > 0, got {}",
+                section, self.max_amount
+            ));
+        }
+        for (key, factor) in [
+            ("amount_in_factor_low", self.amount_in_factor_low),
+            ("amount_in_factor_median", self.amount_in_factor_median),
+            ("amount_in_factor_high", self.amount_in_factor_high),
+        ] {
+            if !(0.0..=100.0).contains(&factor) {
+                errors.push(format!(
+                    "{}.{} must be a percentage in 0..=100, got {}",
+                    section, key, factor
+                ));
+            }
+        }
+        for (key, value) in [
+            ("tip_min", self.tip_min),
+            ("tip_factor_low", self.tip_factor_low),
+            ("tip_factor_median", self.tip_factor_median),
+            ("tip_factor_high", self.tip_factor_high),
+            ("tip_factor_ultra", self.tip_factor_ultra),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                errors.push(format!("{}.{} must be >= 0, got {}", section, key, value));
+            }
+        }
+
+        errors
+    }
+}
+
+fn validate_tiers(section: &str, tiers: &[SizingTier]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if tiers.is_empty() {
+        errors.push(format!(
+            "{}.tiers must list at least one tier (use enable_buy to stop buying)",
+            section
+        ));
+    }
+    for (i, tier) in tiers.iter().enumerate() {
+        for (key, value) in [
+            ("min_drop_pct", tier.min_drop_pct),
+            ("min_liquidity_usd", tier.min_liquidity_usd),
+            ("tip_pct", tier.tip_pct),
+            ("slippage_factor", tier.slippage_factor),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                errors.push(format!(
+                    "{}.tiers[{}].{} must be >= 0, got {}",
+                    section, i, key, value
+                ));
+            }
+        }
+        if !(0.0..=100.0).contains(&tier.amount_pct) {
+            errors.push(format!(
+                "{}.tiers[{}].amount_pct must be a percentage in 0..=100, got {}",
+                section, i, tier.amount_pct
+            ));
+        }
+        // The first match wins, so a row looser than a later one hides it
+        if let Some(shadow) = tiers[..i].iter().position(|earlier| {
+            earlier.min_drop_pct <= tier.min_drop_pct
+                && earlier.min_liquidity_usd <= tier.min_liquidity_usd
+        }) {
+            errors.push(format!(
+                "{}.tiers[{}] can never match, tiers[{}] already covers it",
+                section, i, shadow
+            ));
+        }
+    }
+
+    errors
+}
+
+impl SlippageConfig {
+    /// Range checks shared by the global `[slippage]` section and per-pool overrides.
+    pub(crate) fn validate(&self, section: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for (key, factor) in [
+            ("factor_low", self.factor_low),
+            ("factor_median", self.factor_median),
+            ("factor_high", self.factor_high),
+        ] {
+            if !factor.is_finite() || factor < 0.0 {
+                errors.push(format!("{}.{} must be >= 0, got {}", section, key, factor));
+            }
+        }
+        for (key, pct) in [
+            ("min_pct", self.min_pct),
+            ("max_pct", self.max_pct),
+            ("sell_pct", self.sell_pct),
+        ] {
+            if !(0.0..100.0).contains(&pct) {
+                errors.push(format!(
+                    "{}.{} must be a percentage in 0..100, got {}",
+                    section, key, pct
+                ));
+            }
+        }
+        if self.min_pct > self.max_pct {
+            errors.push(format!(
+                "{}.min_pct ({}) must not exceed {}.max_pct ({})",
+                section, self.min_pct, section, self.max_pct
+            ));
+        }
+
+        errors
+    }
+}
+
+impl ExitConfig {
+    /// Whether any exit rule is on.
+    pub fn is_enabled(&self) -> bool {
+        self.stop_loss_pct > 0.0
+            || self.trailing_stop_pct > 0.0
+            || self.max_hold_secs > 0
+            || !self.scale_out.is_empty()
+    }
+
+    /// Whether a rule values the position: the stop-loss, the trailing stop
+    /// or a scale-out level.
+    pub fn prices_positions(&self) -> bool {
+        self.stop_loss_pct > 0.0 || self.trailing_stop_pct > 0.0 || !self.scale_out.is_empty()
+    }
+
+    /// Range checks shared by the global `[exit]` section and per-pool overrides.
+    pub(crate) fn validate(&self, section: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for (key, pct) in [
+            ("stop_loss_pct", self.stop_loss_pct),
+            ("trailing_stop_pct", self.trailing_stop_pct),
+        ] {
+            if !(0.0..100.0).contains(&pct) {
+                errors.push(format!(
+                    "{}.{} must be a percentage in 0..100, got {}",
+                    section, key, pct
+                ));
+            }
+        }
+        for (i, level) in self.scale_out.iter().enumerate() {
+            if !level.at_pct.is_finite() || level.at_pct <= 0.0 {
+                errors.push(format!(
+                    "{}.scale_out[{}] must trigger at a PnL > 0 %, got {}",
+                    section, i, level.at_pct
+                ));
+            }
+            if !(level.sell_pct > 0.0 && level.sell_pct <= 100.0) {
+                errors.push(format!(
+                    "{}.scale_out[{}] must sell a percentage in (0, 100], got {}",
+                    section, i, level.sell_pct
+                ));
+            }
+            if i > 0 && level.at_pct <= self.scale_out[i - 1].at_pct {
+                errors.push(format!(
+                    "{}.scale_out levels must trigger at increasing PnL, {} follows {}",
+                    section,
+                    level.at_pct,
+                    self.scale_out[i - 1].at_pct
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+fn parse_keypair(private_key: &str) -> Option<Keypair> {
+    let bytes = bs58::decode(private_key).into_vec().ok()?;
+    Keypair::try_from(bytes.as_slice()).ok()
+}
+
+/// Reads `section.key` from the config table, letting `env_key` override it,
+/// and records an error instead of panicking when a value is missing or malformed.
+struct ConfigReader {
+    table: Table,
+    errors: Vec<String>,
+    shadowed: Vec<ShadowedKey>,
+}
+
+impl ConfigReader {
+    fn lookup(&mut self, section: &str, key: &str, env_key: &str) -> Option<(String, String)> {
+        if let Ok(value) = env::var(env_key) {
+            self.shadow(section, key, env_key);
+            return Some((value.trim().to_string(), env_key.to_string()));
+        }
+
+        let value = self.table.get(section)?.get(key)?;
+        Some((raw_value(value), format!("{}.{}", section, key)))
+    }
+
+    /// Notes that `env_key` overrides `section.key`, if the file sets it.
+    fn shadow(&mut self, section: &str, key: &str, env_key: &str) {
+        let Some(value) = self.table.get(section).and_then(|table| table.get(key)) else {
+            return;
+        };
+        let key = format!("{}.{}", section, key);
+        if self.shadowed.iter().any(|shadowed| shadowed.key == key) {
+            return;
+        }
+
+        self.shadowed.push(ShadowedKey {
+            key,
+            env_key: env_key.to_string(),
+            file_value: raw_value(value),
+        });
+    }
+
+    fn required<T: FromStr + Default>(&mut self, section: &str, key: &str, env_key: &str) -> T
+    where
+        T::Err: fmt::Display,
+    {
+        match self.lookup(section, key, env_key) {
+            Some((raw, _)) if raw.is_empty() => {
+                self.errors.push(format!(
+                    "{}.{} ({}) must not be empty",
+                    section, key, env_key
+                ));
+                T::default()
+            }
+            Some((raw, source)) => match raw.parse() {
+                Ok(value) => value,
+                Err(e) => {
+                    self.errors
+                        .push(format!("{} has invalid value {:?}: {}", source, raw, e));
+                    T::default()
+                }
+            },
+            None => {
+                self.errors.push(format!(
+                    "{}.{} is missing (set it in the config file or via {})",
+                    section, key, env_key
+                ));
+                T::default()
+            }
+        }
+    }
+
+    /// Like `required`, but falls back to `default` when the key is not set anywhere.
+    fn defaulted<T: FromStr + Default>(
+        &mut self,
+        section: &str,
+        key: &str,
+        env_key: &str,
+        default: T,
+    ) -> T
+    where
+        T::Err: fmt::Display,
+    {
+        if self.lookup(section, key, env_key).is_none() {
+            return default;
+        }
+        self.required(section, key, env_key)
+    }
+
+    /// Reads a list given as a TOML array or a comma separated string (the env form).
+    fn list(&mut self, section: &str, key: &str, env_key: &str, default: &[&str]) -> Vec<String> {
+        let split = |raw: &str| -> Vec<String> {
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        if let Ok(value) = env::var(env_key) {
+            self.shadow(section, key, env_key);
+            return split(&value);
+        }
+
+        match self.table.get(section).and_then(|table| table.get(key)) {
+            None => default.iter().map(|item| item.to_string()).collect(),
+            Some(Value::String(s)) => split(s),
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::String(s) => Some(s.trim().to_string()),
+                    other => {
+                        self.errors.push(format!(
+                            "{}.{} must only contain strings, got {}",
+                            section, key, other
+                        ));
+                        None
+                    }
+                })
+                .collect(),
+            Some(other) => {
+                self.errors.push(format!(
+                    "{}.{} must be a list of strings, got {}",
+                    section, key, other
+                ));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Reads `[senders]`: the enabled gateways, then `<gateway>_regions` and
+    /// `<gateway>_min_tip` of each (env `<GATEWAY>_REGIONS`, `<GATEWAY>_MIN_TIP`).
+    fn senders(&mut self) -> SenderConfig {
+        let enabled = self.list("senders", "enabled", "SENDERS", DEFAULT_SENDERS);
+
+        let mut routes = Vec::new();
+        for gateway in enabled
+            .iter()
+            .filter_map(|name| name.parse::<Gateway>().ok())
+        {
+            if routes
+                .iter()
+                .any(|route: &SenderRoute| route.gateway == gateway)
+            {
+                continue;
+            }
+            let name = gateway.name();
+            let env_prefix = name.to_uppercase();
+            routes.push(SenderRoute {
+                gateway,
+                regions: self.list(
+                    "senders",
+                    &format!("{}_regions", name),
+                    &format!("{}_REGIONS", env_prefix),
+                    gateway.default_regions(),
+                ),
+                min_tip: self.defaulted(
+                    "senders",
+                    &format!("{}_min_tip", name),
+                    &format!("{}_MIN_TIP", env_prefix),
+                    gateway.default_min_tip(),
+                ),
+            });
+        }
+
+        SenderConfig { routes, enabled }
+    }
+
+    /// Reads an array of tables; these are only read from the file, having no env form.
+    fn table_list<T: DeserializeOwned>(&mut self, section: &str, key: &str) -> Option<Vec<T>> {
+        let value = self.table.get(section)?.get(key)?.clone();
+
+        match value.try_into() {
+            Ok(items) => Some(items),
+            Err(e) => {
+                self.errors.push(format!(
+                    "{}.{} is not a valid table list: {}",
+                    section, key, e
+                ));
+                None
+            }
+        }
+    }
+
+    /// Like `list`, parsing every item and recording the ones that don't parse.
+    fn parsed_list<T: FromStr>(&mut self, section: &str, key: &str, env_key: &str) -> Vec<T>
+    where
+        T::Err: fmt::Display,
+    {
+        let items = self.list(section, key, env_key, &[]);
+
+        items
+            .iter()
+            .filter_map(|item| match item.parse() {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    self.errors
+                        .push(format!("{}.{} has invalid item: {}", section, key, e));
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn optional(&mut self, section: &str, key: &str, env_key: &str) -> String {
+        self.lookup(section, key, env_key)
+            .map(|(raw, _)| raw)
+            .unwrap_or_default()
+    }
+}
+
+/// A file value as the env form of its key would spell it.
+fn raw_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, PoisonError};
+
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    /// Env vars are process-wide, so tests reading them take turns.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    /// Every key the tests need unset unless they set it themselves.
+    const ENV_KEYS: [&str; 30] = [
+        "PRIVATE_KEY",
+        "RPC_ENDPOINT",
+        "GRPC_ENDPOINT",
+        "GRPC_TOKEN",
+        "POOL_ADDR_DIR",
+        "BLACK_LIST_DIR",
+        "ENERMY_LIST_DIR",
+        "ON_DEBUG",
+        "SUBMIT_TX",
+        "PAPER_TRADE",
+        "IS_RACING",
+        "SHOW_BUY",
+        "SHOW_SELL",
+        "ACCEPTABLE_LIQUIDITY",
+        "TAKE_PROFIT",
+        "MAX_AMOUNT",
+        "AMOUNT_IN_FACTOR_LOW",
+        "AMOUNT_IN_FACTOR_MEDIAN",
+        "AMOUNT_IN_FACTOR_HIGH",
+        "TIP_MIN",
+        "TIP_FACTOR_LOW",
+        "TIP_FACTOR_MEDIAN",
+        "TIP_FACTOR_HIGH",
+        "TIP_FACTOR_ULTRA",
+        "SLIPPAGE_MAX_PCT",
+        "EXIT_SCALE_OUT",
+        "BREAKER_COOLDOWN_SECS",
+        "RECORD_DIR",
+        "STRATEGIES",
+        "SENDERS",
+    ];
+
+    /// Loads `file` with only `vars` set among `ENV_KEYS`.
+    fn load(file: &str, vars: &[(&str, &str)]) -> Result<BotConfig, ConfigError> {
+        let _env = ENV.lock().unwrap_or_else(PoisonError::into_inner);
+        for key in ENV_KEYS {
+            env::remove_var(key);
+        }
+        for (key, value) in vars {
+            env::set_var(key, value);
+        }
+        let config = BotConfig::from_table(file.parse().expect("test config parses"));
+        for (key, _) in vars {
+            env::remove_var(key);
+        }
+        config
+    }
+
+    fn errors(result: Result<BotConfig, ConfigError>) -> Vec<String> {
+        match result {
+            Err(ConfigError::Invalid(errors)) => errors,
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("config should not load"),
+        }
+    }
+
+    /// Every required key, with files that exist and no submissions.
+    fn minimal_file() -> String {
+        file_with_key(&Keypair::new().to_base58_string())
+    }
+
+    fn file_with_key(private_key: &str) -> String {
+        format!(
+            r#"
+            [endpoints]
+            private_key = "{}"
+            rpc_endpoint = "http://127.0.0.1:8899"
+            grpc_endpoint = "http://127.0.0.1:10000"
+            grpc_token = "token"
+
+            [paths]
+            pool_addr = "Cargo.toml"
+            black_list = "Cargo.toml"
+            enermy_list = "Cargo.toml"
+
+            [mode]
+            on_debug = false
+            submit_tx = false
+            is_racing = false
+            show_buy = true
+            show_sell = false
+
+            [filter]
+            acceptable_liquidity = 30000
+            take_profit = 0.2
+
+            [dump]
+            max_amount = 50.0
+            amount_in_factor_low = 25.0
+            amount_in_factor_median = 42.0
+            amount_in_factor_high = 52.0
+            tip_min = 0.05
+            tip_factor_low = 4.0
+            tip_factor_median = 6.0
+            tip_factor_high = 8.0
+            tip_factor_ultra = 10.0
+            "#,
+            private_key
+        )
+    }
+
+    #[test]
+    fn unset_keys_take_their_defaults() {
+        let config = load(&minimal_file(), &[]).expect("minimal config loads");
+
+        assert!(!config.mode.paper_trade);
+        assert_eq!(config.slippage.min_pct, 0.5);
+        assert_eq!(config.slippage.max_pct, 10.0);
+        assert_eq!(config.slippage.sell_pct, 1.0);
+        assert!(!config.exit.is_enabled());
+        assert_eq!(config.breaker.gateway_failures, 5);
+        assert_eq!(config.breaker.cooldown_secs, 30);
+        assert_eq!(config.preflight.cu_headroom_pct, 20.0);
+        assert_eq!(config.priority_fee.fallback_micro_lamports, 30_000);
+        assert_eq!(config.recorder.dir, "src/assets/recordings");
+        assert_eq!(config.strategy.enabled, [MeanReversion::NAME]);
+        assert_eq!(config.senders.enabled, DEFAULT_SENDERS);
+        assert_eq!(config.senders.routes.len(), DEFAULT_SENDERS.len());
+        assert!(config.gateways.nozomi_api_key.is_empty());
+        assert!(config.shadowed_keys.is_empty());
+    }
+
+    #[test]
+    fn every_missing_key_is_reported() {
+        let errors = errors(load("", &[]));
+
+        let missing = errors.iter().filter(|e| e.contains("is missing")).count();
+        assert_eq!(missing, 23, "{:#?}", errors);
+        assert!(errors.contains(
+            &"endpoints.private_key is missing (set it in the config file or via PRIVATE_KEY)"
+                .to_string()
+        ));
+        assert!(errors.contains(
+            &"dump.tip_factor_ultra is missing (set it in the config file or via TIP_FACTOR_ULTRA)"
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn invalid_values_are_all_reported() {
+        let file = minimal_file()
+            .replace("on_debug = false", "on_debug = \"maybe\"")
+            .replace("http://127.0.0.1:8899", "ws://127.0.0.1:8900")
+            .replace("grpc_token = \"token\"", "grpc_token = \"\"")
+            .replace(
+                "enermy_list = \"Cargo.toml\"",
+                "enermy_list = \"missing.json\"",
+            )
+            + r#"
+            [breaker]
+            cooldown_secs = 60
+            max_cooldown_secs = 30
+
+            [strategy]
+            enabled = ["mean_reversion", "martingale"]
+
+            [senders]
+            enabled = ["jito", "carrier_pigeon"]
+            jito_regions = ["mars"]
+            "#;
+        let errors = errors(load(&file, &[]));
+
+        for expected in [
+            "mode.on_debug has invalid value \"maybe\"",
+            "endpoints.rpc_endpoint must be an http(s) URL",
+            "endpoints.grpc_token (GRPC_TOKEN) must not be empty",
+            "paths.enermy_list points to a missing file: missing.json",
+            "breaker.max_cooldown_secs (30) must be >= breaker.cooldown_secs (60)",
+            "strategy.enabled has unknown strategy \"martingale\"",
+            "senders.enabled has",
+            "senders.jito_regions has unknown region \"mars\"",
+        ] {
+            assert!(
+                errors.iter().any(|e| e.starts_with(expected)),
+                "no {:?} in {:#?}",
+                expected,
+                errors
+            );
+        }
+        assert_eq!(errors.len(), 8, "{:#?}", errors);
+    }
+
+    #[test]
+    fn env_vars_override_the_file() {
+        let config = load(
+            &minimal_file(),
+            &[
+                ("TAKE_PROFIT", "0.5"),
+                ("SLIPPAGE_MAX_PCT", "7"),
+                ("STRATEGIES", " mean_reversion , "),
+                ("SENDERS", "jito,rpc"),
+            ],
+        )
+        .expect("config loads");
+
+        assert_eq!(config.filter.take_profit, 0.5);
+        assert_eq!(config.slippage.max_pct, 7.0);
+        assert_eq!(config.strategy.enabled, [MeanReversion::NAME]);
+        assert_eq!(config.senders.enabled, ["jito", "rpc"]);
+        assert_eq!(
+            config.senders.routes[0].regions,
+            Gateway::Jito.default_regions()
+        );
+
+        // Only keys the file sets are shadowed
+        assert_eq!(
+            config.shadowed_keys,
+            [ShadowedKey {
+                key: "filter.take_profit".to_string(),
+                env_key: "TAKE_PROFIT".to_string(),
+                file_value: "0.2".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_env_values_name_the_env_var() {
+        let errors = errors(load(
+            &minimal_file(),
+            &[("SUBMIT_TX", "sometimes"), ("EXIT_SCALE_OUT", "oops")],
+        ));
+
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.starts_with("SUBMIT_TX has invalid value \"sometimes\"")),
+            "{:#?}",
+            errors
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.starts_with("exit.scale_out has invalid item")),
+            "{:#?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn payer_comes_from_the_private_key() {
+        let keypair = Keypair::new();
+        let file = file_with_key(&keypair.to_base58_string());
+        let config = load(&file, &[]).expect("config loads");
+
+        assert_eq!(config.payer().pubkey(), keypair.pubkey());
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    validate_take_profit, BotConfig, ConfigError, DumpConfig, ExitConfig, ScaleOutLevel,
+    SizingTier, SlippageConfig,
+};
+
+/// One entry of the pool file.
+///
+/// Entries are either a bare address or an object with the address and any of
+/// the optional overrides below; unset fields fall back to the global config.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PoolConfig {
+    pub address: String,
+    pub take_profit: Option<f64>,
+    /// Smallest price drop (in %) of an observed sell that is worth buying into.
+    pub min_price_impact: Option<f64>,
+    /// Buy into dumps on this pool (still gated by `mode.show_sell`).
+    pub enable_buy: Option<bool>,
+    /// Take profit on this pool (still gated by `mode.show_buy`).
+    pub enable_sell: Option<bool>,
+    /// Replaces the sizing table; without it, the global table (or the one
+    /// built from this pool's factors) applies.
+    pub tiers: Option<Vec<SizingTier>>,
+    pub max_amount: Option<f64>,
+    pub amount_in_factor_low: Option<f64>,
+    pub amount_in_factor_median: Option<f64>,
+    pub amount_in_factor_high: Option<f64>,
+    pub tip_min: Option<f64>,
+    pub tip_factor_low: Option<f64>,
+    pub tip_factor_median: Option<f64>,
+    pub tip_factor_high: Option<f64>,
+    pub tip_factor_ultra: Option<f64>,
+    pub slippage_factor_low: Option<f64>,
+    pub slippage_factor_median: Option<f64>,
+    pub slippage_factor_high: Option<f64>,
+    pub slippage_min_pct: Option<f64>,
+    pub slippage_max_pct: Option<f64>,
+    pub slippage_sell_pct: Option<f64>,
+    pub stop_loss_pct: Option<f64>,
+    pub trailing_stop_pct: Option<f64>,
+    pub max_hold_secs: Option<u64>,
+    /// Replaces the global scale-out levels, `[]` turns them off for this pool.
+    pub scale_out: Option<Vec<ScaleOutLevel>>,
+}
+
+/// Effective parameters of a pool once its overrides are applied.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolSettings {
+    pub take_profit: f64,
+    pub min_price_impact: f64,
+    pub enable_buy: bool,
+    pub enable_sell: bool,
+    pub dump: DumpConfig,
+    pub slippage: SlippageConfig,
+    pub exit: ExitConfig,
+}
+
+impl PoolSettings {
+    /// Settings of a pool without overrides.
+    pub fn defaults(config: &BotConfig) -> Self {
+        PoolSettings {
+            take_profit: config.filter.take_profit,
+            min_price_impact: 0.0,
+            enable_buy: true,
+            enable_sell: true,
+            dump: config.dump.clone(),
+            slippage: config.slippage.clone(),
+            exit: config.exit.clone(),
+        }
+    }
+}
+
+impl PoolConfig {
+    pub fn settings(&self, config: &BotConfig) -> PoolSettings {
+        let global = &config.dump;
+        let slippage = &config.slippage;
+        let exit = &config.exit;
+
+        PoolSettings {
+            take_profit: self.take_profit.unwrap_or(config.filter.take_profit),
+            min_price_impact: self.min_price_impact.unwrap_or(0.0),
+            enable_buy: self.enable_buy.unwrap_or(true),
+            enable_sell: self.enable_sell.unwrap_or(true),
+            dump: DumpConfig {
+                tiers: self.tiers.clone().or_else(|| global.tiers.clone()),
+                max_amount: self.max_amount.unwrap_or(global.max_amount),
+                amount_in_factor_low: self
+                    .amount_in_factor_low
+                    .unwrap_or(global.amount_in_factor_low),
+                amount_in_factor_median: self
+                    .amount_in_factor_median
+                    .unwrap_or(global.amount_in_factor_median),
+                amount_in_factor_high: self
+                    .amount_in_factor_high
+                    .unwrap_or(global.amount_in_factor_high),
+                tip_min: self.tip_min.unwrap_or(global.tip_min),
+                tip_factor_low: self.tip_factor_low.unwrap_or(global.tip_factor_low),
+                tip_factor_median: self.tip_factor_median.unwrap_or(global.tip_factor_median),
+                tip_factor_high: self.tip_factor_high.unwrap_or(global.tip_factor_high),
+                tip_factor_ultra: self.tip_factor_ultra.unwrap_or(global.tip_factor_ultra),
+            },
+            slippage: SlippageConfig {
+                factor_low: self.slippage_factor_low.unwrap_or(slippage.factor_low),
+                factor_median: self
+                    .slippage_factor_median
+                    .unwrap_or(slippage.factor_median),
+                factor_high: self.slippage_factor_high.unwrap_or(slippage.factor_high),
+                min_pct: self.slippage_min_pct.unwrap_or(slippage.min_pct),
+                max_pct: self.slippage_max_pct.unwrap_or(slippage.max_pct),
+                sell_pct: self.slippage_sell_pct.unwrap_or(slippage.sell_pct),
+            },
+            exit: ExitConfig {
+                stop_loss_pct: self.stop_loss_pct.unwrap_or(exit.stop_loss_pct),
+                trailing_stop_pct: self.trailing_stop_pct.unwrap_or(exit.trailing_stop_pct),
+                max_hold_secs: self.max_hold_secs.unwrap_or(exit.max_hold_secs),
+                scale_out: self
+                    .scale_out
+                    .clone()
+                    .unwrap_or_else(|| exit.scale_out.clone()),
+            },
+        }
+    }
+}
+
+/// Parses the pool file, accepting both `"<address>"` and `{ "address": .. }` entries.
+pub fn parse_pool_list(content: &str) -> Result<Vec<PoolConfig>, ConfigError> {
+    let entries: Vec<Value> = serde_json::from_str(content)
+        .map_err(|e| ConfigError::Invalid(vec![format!("pool file is not a JSON array: {}", e)]))?;
+
+    let mut pools = Vec::with_capacity(entries.len());
+    let mut errors = Vec::new();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        match entry {
+            Value::String(address) => pools.push(PoolConfig {
+                address,
+                ..Default::default()
+            }),
+            entry @ Value::Object(_) => match serde_json::from_value(entry) {
+                Ok(pool) => pools.push(pool),
+                Err(e) => errors.push(format!("pools[{}]: {}", index, e)),
+            },
+            other => errors.push(format!(
+                "pools[{}]: expected an address or an object, got {}",
+                index, other
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(pools)
+    } else {
+        Err(ConfigError::Invalid(errors))
+    }
+}
+
+/// Applies every pool's overrides to `config` and validates the result, keyed by pool address.
+pub fn resolve_pools(
+    config: &BotConfig,
+    pools: &[PoolConfig],
+) -> Result<HashMap<String, PoolSettings>, ConfigError> {
+    let mut resolved = HashMap::with_capacity(pools.len());
+    let mut errors = Vec::new();
+
+    for pool in pools {
+        let section = format!("pools[{}]", pool.address);
+
+        if pool.address.parse::<Pubkey>().is_err() {
+            errors.push(format!("{} is not a valid pool address", section));
+            continue;
+        }
+
+        let settings = pool.settings(config);
+        errors.extend(validate_take_profit(&section, settings.take_profit));
+        errors.extend(settings.dump.validate(&section));
+        errors.extend(settings.slippage.validate(&section));
+        errors.extend(settings.exit.validate(&section));
+        if !settings.min_price_impact.is_finite() || settings.min_price_impact < 0.0 {
+            errors.push(format!(
+                "{}.min_price_impact must be >= 0, got {}",
+                section, settings.min_price_impact
+            ));
+        }
+
+        if resolved.insert(pool.address.clone(), settings).is_some() {
+            errors.push(format!("{} is listed more than once", section));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(ConfigError::Invalid(errors))
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use serde_json::Value;
+use tokio::sync::watch;
+
+use crate::{
+    build_senders, build_strategies, load_black_list, load_enermy_list, load_pool_info, log,
+    resolve_pools, BotConfig, PoolSettings, SenderGroup, Strategy,
+};
+
+/// How often the config file and address lists are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Everything a freshly spawned `swap_handler` reads that may change at runtime.
+#[derive(Debug)]
+pub struct LiveConfig {
+    pub config: Arc<BotConfig>,
+    pub black_list: Vec<String>,
+    pub enemy_set: Arc<HashSet<String>>,
+    /// Per-pool settings keyed by pool address, overrides already applied.
+    pub pools: Arc<HashMap<String, PoolSettings>>,
+    pub strategies: Arc<Vec<Arc<dyn Strategy>>>,
+    /// Landing gateways built from `[senders]`.
+    pub senders: Arc<Vec<SenderGroup>>,
+}
+
+/// Receiving side of the hot-reload channel; `borrow()` always yields the latest snapshot.
+pub type LiveConfigRx = watch::Receiver<Arc<LiveConfig>>;
+
+impl LiveConfig {
+    /// Loads the address lists and pool overrides referenced by `config`.
+    pub fn load(config: Arc<BotConfig>) -> Result<Self> {
+        let black_list = load_black_list(&config)?;
+        let enemy_set = Arc::new(load_enermy_list(&config)?.into_iter().collect());
+        let pools = Arc::new(resolve_pools(&config, &load_pool_info(&config)?)?);
+        let strategies = Arc::new(build_strategies(&config.strategy.enabled));
+        let senders = Arc::new(build_senders(&config));
+
+        Ok(LiveConfig {
+            config,
+            black_list,
+            enemy_set,
+            pools,
+            strategies,
+            senders,
+        })
+    }
+
+    /// Settings for `pool_id`, falling back to the global config for unlisted pools.
+    pub fn pool_settings(&self, pool_id: &str) -> PoolSettings {
+        self.pools
+            .get(pool_id)
+            .cloned()
+            .unwrap_or_else(|| PoolSettings::defaults(&self.config))
+    }
+}
+
+/// Watches the config file, the pool file and the black/enemy list JSONs, swapping
+/// in a new `LiveConfig` whenever one of them changes and the result is valid.
+///
+/// Endpoints, the keypair and the set of subscribed pools are fixed for the
+/// lifetime of the gRPC stream, `mode.paper_trade` for the trade store's
+/// account and `[recorder]` for the recording, so changes to them are reported and ignored
+/// until a restart; per-pool overrides are applied live.
+/// Env var overrides are read once at startup and keep taking precedence, so
+/// edits to the file keys they shadow are reported and ignored.
+pub fn spawn_config_watcher(initial: LiveConfig) -> LiveConfigRx {
+    let (sender, receiver) = watch::channel(Arc::new(initial));
+
+    tokio::spawn(async move {
+        let mut mtimes = watched_mtimes(&sender.borrow().config);
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let current = Arc::clone(&sender.borrow());
+            let latest = watched_mtimes(&current.config);
+            if latest == mtimes {
+                continue;
+            }
+            mtimes = latest;
+
+            match reload(&current) {
+                Ok(Some(next)) => {
+                    // Paths may have moved, track the new files from now on.
+                    mtimes = watched_mtimes(&next.config);
+                    if sender.send(Arc::new(next)).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log!(
+                        format!("🔴 Config reload rejected, keeping previous config: {}", e),
+                        "error"
+                    );
+                }
+            }
+        }
+    });
+
+    receiver
+}
+
+fn watched_mtimes(config: &BotConfig) -> HashMap<String, Option<SystemTime>> {
+    BotConfig::config_path()
+        .into_iter()
+        .chain([
+            config.paths.pool_addr.clone(),
+            config.paths.black_list.clone(),
+            config.paths.enermy_list.clone(),
+        ])
+        .map(|path| {
+            let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, mtime)
+        })
+        .collect()
+}
+
+/// Builds the next snapshot, or `None` when nothing effective changed.
+fn reload(current: &LiveConfig) -> Result<Option<LiveConfig>> {
+    let mut config = BotConfig::load()?;
+
+    let old = &current.config;
+    let mut pinned = Vec::new();
+    if config.endpoints.private_key != old.endpoints.private_key {
+        pinned.push("endpoints.private_key");
+    }
+    if config.endpoints.rpc_endpoint != old.endpoints.rpc_endpoint {
+        pinned.push("endpoints.rpc_endpoint");
+    }
+    if config.endpoints.grpc_endpoint != old.endpoints.grpc_endpoint {
+        pinned.push("endpoints.grpc_endpoint");
+    }
+    if config.endpoints.grpc_token != old.endpoints.grpc_token {
+        pinned.push("endpoints.grpc_token");
+    }
+    if config.paths.pool_addr != old.paths.pool_addr {
+        pinned.push("paths.pool_addr");
+    }
+    if config.mode.paper_trade != old.mode.paper_trade {
+        pinned.push("mode.paper_trade");
+    }
+    let recorder = (&config.recorder, &old.recorder);
+    if recorder.0.enabled != recorder.1.enabled
+        || recorder.0.dir != recorder.1.dir
+        || recorder.0.rotate_minutes != recorder.1.rotate_minutes
+    {
+        pinned.push("recorder");
+    }
+    if !pinned.is_empty() {
+        log!(
+            format!(
+                "⚠️ Restart required to apply: {} (keeping current values)",
+                pinned.join(", ")
+            ),
+            "update"
+        );
+        config.endpoints = old.endpoints.clone();
+        config.paths.pool_addr = old.paths.pool_addr.clone();
+        config.mode.paper_trade = old.mode.paper_trade;
+        config.recorder = old.recorder.clone();
+    }
+
+    let shadowed: Vec<String> = config
+        .shadowed_keys
+        .iter()
+        .filter(|shadowed| !old.shadowed_keys.contains(shadowed))
+        .map(|shadowed| format!("{} (set by {})", shadowed.key, shadowed.env_key))
+        .collect();
+    if !shadowed.is_empty() {
+        log!(
+            format!(
+                "⚠️ Env vars override these edited keys, unset them to apply: {}",
+                shadowed.join(", ")
+            ),
+            "update"
+        );
+    }
+
+    let next = LiveConfig::load(Arc::new(config))?;
+
+    let old_pools: HashSet<&String> = current.pools.keys().collect();
+    let new_pools: HashSet<&String> = next.pools.keys().collect();
+    if old_pools != new_pools {
+        log!(
+            "⚠️ Restart required to subscribe to added or drop removed pools",
+            "update"
+        );
+    }
+
+    let mut changes = value_diff(
+        serde_json::to_value(old.as_ref()).unwrap_or_default(),
+        serde_json::to_value(next.config.as_ref()).unwrap_or_default(),
+    );
+    changes.extend(value_diff(
+        serde_json::json!({ "pools": current.pools.as_ref() }),
+        serde_json::json!({ "pools": next.pools.as_ref() }),
+    ));
+    changes.extend(list_diff(
+        "black_list",
+        current.black_list.iter(),
+        next.black_list.iter(),
+    ));
+    changes.extend(list_diff(
+        "enermy_list",
+        current.enemy_set.iter(),
+        next.enemy_set.iter(),
+    ));
+
+    if changes.is_empty() {
+        return Ok(None);
+    }
+
+    log!(
+        format!("♻️ Config reloaded:\n  {}", changes.join("\n  ")),
+        "update"
+    );
+    Ok(Some(next))
+}
+
+/// One line per changed key, e.g. `dump.max_amount: 50.0 -> 40.0`.
+/// Secrets are skipped because they are not serialized.
+fn value_diff(old: Value, new: Value) -> Vec<String> {
+    let old = flatten(old);
+    let new = flatten(new);
+
+    let changed = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, value)| {
+            let before = old.get(key).map(Value::to_string).unwrap_or_default();
+            format!("{}: {} -> {}", key, before, value)
+        });
+    let removed = old
+        .iter()
+        .filter(|(key, _)| !new.contains_key(*key))
+        .map(|(key, value)| format!("{}: {} -> (removed)", key, value));
+
+    changed.chain(removed).collect()
+}
+
+fn flatten(value: Value) -> BTreeMap<String, Value> {
+    fn walk(prefix: &str, value: Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            other => {
+                out.insert(prefix.to_string(), other);
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk("", value, &mut out);
+    out
+}
+
+fn list_diff<'a>(
+    name: &str,
+    old: impl Iterator<Item = &'a String>,
+    new: impl Iterator<Item = &'a String>,
+) -> Option<String> {
+    let old: HashSet<&String> = old.collect();
+    let new: HashSet<&String> = new.collect();
+
+    let added: Vec<&&String> = new.difference(&old).collect();
+    let removed: Vec<&&String> = old.difference(&new).collect();
+    if added.is_empty() && removed.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}: +{:?} -{:?}", name, added, removed))
+}
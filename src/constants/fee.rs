@@ -1,8 +1,15 @@
 pub const NEXT_MIN_FEE_AMT: u64 = 1000000;
-pub const RAYDIUM_AMM_FEE_PCT: f64 = 0.0025;
 
 pub const NEXT_BLOCK_MIN_TIP: f64 = 0.001;
 pub const NOZOMI_MIN_TIP: f64 = 0.001;
 pub const ZSLOT_MIN_TIP: f64 = 0.001;
 pub const BLOXROUTE_MIN_TIP: f64 = 0.002;
 pub const JITO_MIN_TIP: f64 = 0.000001; //    use a 70/30 split between priority fee and jito tip
+
+/// Fixed tips used when selling.
+pub const DEFAULT_SELL_TIP: f64 = 0.0005;
+pub const PUMPSWAP_SELL_TIP: f64 = 0.0025;
+
+/// Network fee of one of our swaps: the 5000 lamport signature fee plus the
+/// 30k micro-lamport compute unit price over the default 200k units.
+pub const EST_SWAP_NETWORK_FEE: u64 = 11_000;
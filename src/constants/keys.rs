@@ -1,21 +1,32 @@
-use std::collections::HashMap;
-
 pub const SYSTEM_PRO: &str = "11111111111111111111111111111111";
 pub const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
 pub const ASSOCIATED_TOKEN_PRO: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
 pub const TOKEN_PRO: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PRO: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const MEMO_PRO: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
 
 pub const RAY_AMM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
 pub const PUMP_SWAP_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+pub const RAY_CPMM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+pub const ORCA_WHIRLPOOL_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+pub const METEORA_DLMM_ID: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
 pub const RACE_PRO: &str = "AQepcEVNGFvjbfMfJVJe5h6RcnSUCudishPYVJWqJWvf";
 
 pub const RAY_AMM_AUTH: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+// PDA of [b"vault_and_lp_mint_auth_seed"] under the CPMM program
+pub const RAY_CPMM_AUTH: &str = "GpMZbSM2GgvTKHJirzeGfMFoaZ8UR2X7F4v8vHTvxFbL";
+// PDA of [b"__event_authority"] under the DLMM program
+pub const METEORA_DLMM_EVENT_AUTH: &str = "D1ZN9Wj1fRSUQfCjhvnu1hqDMT7hzjzBBpi12nVniYD6";
 
 // Pre-computed Pubkey constants for faster comparisons
 use solana_sdk::pubkey::Pubkey;
 pub const RAY_AMM_ID_PUBKEY: Pubkey = Pubkey::from_str_const(RAY_AMM_ID);
 pub const PUMP_SWAP_ID_PUBKEY: Pubkey = Pubkey::from_str_const(PUMP_SWAP_ID);
 pub const RAY_AMM_AUTH_PUBKEY: Pubkey = Pubkey::from_str_const(RAY_AMM_AUTH);
+pub const RAY_CPMM_ID_PUBKEY: Pubkey = Pubkey::from_str_const(RAY_CPMM_ID);
+pub const RAY_CPMM_AUTH_PUBKEY: Pubkey = Pubkey::from_str_const(RAY_CPMM_AUTH);
+pub const ORCA_WHIRLPOOL_ID_PUBKEY: Pubkey = Pubkey::from_str_const(ORCA_WHIRLPOOL_ID);
+pub const METEORA_DLMM_ID_PUBKEY: Pubkey = Pubkey::from_str_const(METEORA_DLMM_ID);
 
 pub const NOZOMI_TIP: [&str; 17] = [
     "TEMPaMeCRFAS9EKF53Jd6KpHxgL47uWLcpFArU1Fanq",
@@ -105,29 +116,98 @@ pub const PUMPSWAP_FEE: [&str; 8] = [
 ];
 
 pub const PROGRAM_ID: [(&str, &str); 25] = [
-    ("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "Jupiter Aggregator v6"),
-    ("JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB", "Jupiter Aggregator v4"),
-    ("DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M", "Jupiter DCA"),
-    ("j1o2qRpjcyUwEvwtcfhEQefh773ZgjxcVRry7LDqg5X", "Jupiter Limit Order V2"),
-    ("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", "Raydium Concentrated Liquidity"),
-    ("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", "Raydium CPMM"),
-    ("5quBtoiQqxF9Jv6KYKctB59NT3gtJD2Y65kdnB1Uev3h", "Raydium Liquidity Pool AMM"),
-    ("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "Raydium Liquidity Pool V4"),
-    ("27haf8L6oxUeXrHrgEgsexjSY5hbVUWEmvv9Nyxg8vQv", "Raydium Liquidity Pool V3"),
-    ("RVKd61ztZW9GUwhRbbLoYVRE5Xf1B2tVscKqwZqXgEr", "Raydium Liquidity Pool V2"),
-    ("routeUGWgWzqBWFcrCfv8tritsqukccJPu3q5GPP3xS", "Raydium AMM Routing"),
-    ("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", "Orca Whirlpool"),
-    ("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP", "Orca Swap V2"),
-    ("DjVE6JNiYqPL2QXyCUUh8rNjHrbz9hXHNYt99MQ59qw1", "Orca Swap V1"),
-    ("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo", "Meteora DLMM Program"),
-    ("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB", "Meteora Pools Program"),
+    (
+        "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
+        "Jupiter Aggregator v6",
+    ),
+    (
+        "JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB",
+        "Jupiter Aggregator v4",
+    ),
+    (
+        "DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M",
+        "Jupiter DCA",
+    ),
+    (
+        "j1o2qRpjcyUwEvwtcfhEQefh773ZgjxcVRry7LDqg5X",
+        "Jupiter Limit Order V2",
+    ),
+    (
+        "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK",
+        "Raydium Concentrated Liquidity",
+    ),
+    (
+        "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C",
+        "Raydium CPMM",
+    ),
+    (
+        "5quBtoiQqxF9Jv6KYKctB59NT3gtJD2Y65kdnB1Uev3h",
+        "Raydium Liquidity Pool AMM",
+    ),
+    (
+        "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        "Raydium Liquidity Pool V4",
+    ),
+    (
+        "27haf8L6oxUeXrHrgEgsexjSY5hbVUWEmvv9Nyxg8vQv",
+        "Raydium Liquidity Pool V3",
+    ),
+    (
+        "RVKd61ztZW9GUwhRbbLoYVRE5Xf1B2tVscKqwZqXgEr",
+        "Raydium Liquidity Pool V2",
+    ),
+    (
+        "routeUGWgWzqBWFcrCfv8tritsqukccJPu3q5GPP3xS",
+        "Raydium AMM Routing",
+    ),
+    (
+        "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+        "Orca Whirlpool",
+    ),
+    (
+        "9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP",
+        "Orca Swap V2",
+    ),
+    (
+        "DjVE6JNiYqPL2QXyCUUh8rNjHrbz9hXHNYt99MQ59qw1",
+        "Orca Swap V1",
+    ),
+    (
+        "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo",
+        "Meteora DLMM Program",
+    ),
+    (
+        "Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB",
+        "Meteora Pools Program",
+    ),
     ("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY", "Phoenix"),
-    ("2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEyPy64bL7aD3c", "Lifinity Swap V2"),
-    ("EewxydAPCCVuNEyrVN68PuSYdQ7wKn27V9Gjeoi8dy3S", "Lifinity Swap"),
-    ("SSwpkEEcbUqx4vtoEByFjSkhKdCT862DNVb52nZg1UZ", "Saber Stable Swap"),
-    ("DecZY86MU5Gj7kppfUCEmd4LbXXuyZH1yHaP2NTqdiZB", "Saber Decimal Wrapper"),
-    ("HyaB3W9q6XdA5xwpU4XnSZV94htfmbmqJXZcEbRaJutt", "Invariant Swap"),
-    ("stkitrT1Uoy18Dk1fTrgPw8W6MVzoCfYoAFT4MLsmhq", "Sanctum Router"),
-    ("5ocnV1qiCgaQR8Jb8xWnVbApfaygJ8tNoZfgPwsgx9kx", "Sanctum S Controller"),
+    (
+        "2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEyPy64bL7aD3c",
+        "Lifinity Swap V2",
+    ),
+    (
+        "EewxydAPCCVuNEyrVN68PuSYdQ7wKn27V9Gjeoi8dy3S",
+        "Lifinity Swap",
+    ),
+    (
+        "SSwpkEEcbUqx4vtoEByFjSkhKdCT862DNVb52nZg1UZ",
+        "Saber Stable Swap",
+    ),
+    (
+        "DecZY86MU5Gj7kppfUCEmd4LbXXuyZH1yHaP2NTqdiZB",
+        "Saber Decimal Wrapper",
+    ),
+    (
+        "HyaB3W9q6XdA5xwpU4XnSZV94htfmbmqJXZcEbRaJutt",
+        "Invariant Swap",
+    ),
+    (
+        "stkitrT1Uoy18Dk1fTrgPw8W6MVzoCfYoAFT4MLsmhq",
+        "Sanctum Router",
+    ),
+    (
+        "5ocnV1qiCgaQR8Jb8xWnVbApfaygJ8tNoZfgPwsgx9kx",
+        "Sanctum S Controller",
+    ),
     ("opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb", "Openbook V2"),
-];
\ No newline at end of file
+];
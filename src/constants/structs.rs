@@ -1,9 +1,10 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use bytemuck::checked::try_from_bytes;
 use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use std::marker::Copy;
-use serde::{Deserialize, Serialize};
+
+use crate::TOKEN_PRO;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SwapBaseIn {
@@ -30,6 +31,34 @@ pub struct RayAMMSwapBaseInParams {
     pub payer: Pubkey,
 }
 
+pub struct RayAMMSwapBaseOutParams {
+    pub max_amount_in: u64,
+    pub amount_out: u64,
+    pub pool_id: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub payer: Pubkey,
+}
+
+pub struct RayCpmmSwapParams {
+    /// `amount_in` for swap_base_input, `max_amount_in` for swap_base_output
+    pub amount_in: u64,
+    /// `minimum_amount_out` for swap_base_input, `amount_out` for swap_base_output
+    pub amount_out: u64,
+    pub pool_id: Pubkey,
+    pub amm_config: Pubkey,
+    pub observation_state: Pubkey,
+    pub input_vault: Pubkey,
+    pub output_vault: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub input_token_program: Pubkey,
+    pub output_token_program: Pubkey,
+    pub payer: Pubkey,
+}
+
 pub struct PumpSwapBuyParams {
     pub base_amount_out: u64,
     pub max_quote_amount_in: u64,
@@ -179,6 +208,250 @@ pub struct LiquidityStateLayoutV4 {
     pub padding: [u64; 3],
 }
 
+/// Raydium CPMM `PoolState` account, after the 8-byte Anchor discriminator.
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub struct CpmmPoolState {
+    pub amm_config: Pubkey,
+    pub pool_creator: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation_key: Pubkey,
+    pub auth_bump: u8,
+    // Bit 0 deposit, bit 1 withdraw, bit 2 swap; a set bit disables it
+    pub status: u8,
+    pub lp_mint_decimals: u8,
+    pub mint_0_decimals: u8,
+    pub mint_1_decimals: u8,
+    pub lp_supply: u64,
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    pub open_time: u64,
+    pub recent_epoch: u64,
+    pub padding: [u64; 31],
+}
+
+/// Raydium CPMM `AmmConfig` account, after the 8-byte Anchor discriminator.
+/// Fee rates are in millionths.
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub struct CpmmAmmConfig {
+    pub bump: u8,
+    pub disable_create_pool: bool,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub create_pool_fee: u64,
+    pub protocol_owner: Pubkey,
+    pub fund_owner: Pubkey,
+    pub padding: [u64; 16],
+}
+
+/// Extra accounts a CPMM swap needs on top of the vaults and mints.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CpmmPoolKeys {
+    pub amm_config: String,
+    pub observation_state: String,
+    pub base_token_program: String,
+    pub quote_token_program: String,
+    pub trade_fee_rate: u64,
+}
+
+/// Orca `Whirlpool` account, after the 8-byte Anchor discriminator.
+/// Reward infos are not decoded. `fee_rate` is in millionths.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct WhirlpoolState {
+    pub whirlpools_config: Pubkey,
+    pub whirlpool_bump: [u8; 1],
+    pub tick_spacing: u16,
+    pub fee_tier_index_seed: [u8; 2],
+    pub fee_rate: u16,
+    pub protocol_fee_rate: u16,
+    pub liquidity: u128,
+    // Q64.64 sqrt of token B per token A, raw units
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+    pub protocol_fee_owed_a: u64,
+    pub protocol_fee_owed_b: u64,
+    pub token_mint_a: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub fee_growth_global_a: u128,
+    pub token_mint_b: Pubkey,
+    pub token_vault_b: Pubkey,
+    pub fee_growth_global_b: u128,
+    pub reward_last_updated_timestamp: u64,
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct WhirlpoolTick {
+    pub initialized: bool,
+    pub liquidity_net: i128,
+    pub liquidity_gross: u128,
+    pub fee_growth_outside_a: u128,
+    pub fee_growth_outside_b: u128,
+    pub reward_growths_outside: [u128; 3],
+}
+
+/// Orca fixed-size `TickArray` account, after the 8-byte Anchor discriminator.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct WhirlpoolTickArray {
+    pub start_tick_index: i32,
+    pub ticks: [WhirlpoolTick; 88],
+    pub whirlpool: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct DlmmStaticParameters {
+    pub base_factor: u16,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub variable_fee_control: u32,
+    pub max_volatility_accumulator: u32,
+    pub min_bin_id: i32,
+    pub max_bin_id: i32,
+    pub protocol_share: u16,
+    pub base_fee_power_factor: u8,
+    pub padding: [u8; 5],
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct DlmmVariableParameters {
+    pub volatility_accumulator: u32,
+    pub volatility_reference: u32,
+    pub index_reference: i32,
+    pub padding: [u8; 4],
+    pub last_update_timestamp: i64,
+    pub padding1: [u8; 8],
+}
+
+/// Meteora DLMM `LbPair` account, after the 8-byte Anchor discriminator.
+/// Reward infos are kept as raw bytes.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct DlmmLbPair {
+    pub parameters: DlmmStaticParameters,
+    pub v_parameters: DlmmVariableParameters,
+    pub bump_seed: [u8; 1],
+    pub bin_step_seed: [u8; 2],
+    pub pair_type: u8,
+    pub active_id: i32,
+    // Price step between two bins, in basis points
+    pub bin_step: u16,
+    pub status: u8,
+    pub require_base_factor_seed: u8,
+    pub base_factor_seed: [u8; 2],
+    pub activation_type: u8,
+    pub padding0: u8,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub reserve_x: Pubkey,
+    pub reserve_y: Pubkey,
+    pub protocol_fee: [u64; 2],
+    pub padding1: [u8; 32],
+    pub reward_infos: [u8; 288],
+    pub oracle: Pubkey,
+    // One bit per bin array with liquidity, indexes -512..=511
+    pub bin_array_bitmap: [u64; 16],
+    pub last_updated_at: i64,
+    pub padding2: [u8; 32],
+    pub pre_activation_swap_address: Pubkey,
+    pub base_key: Pubkey,
+    pub activation_point: u64,
+    pub pre_activation_duration: u64,
+    pub padding3: [u8; 8],
+    pub padding4: u64,
+    pub creator: Pubkey,
+    // 0 for SPL Token, 1 for Token-2022
+    pub token_mint_x_program_flag: u8,
+    pub token_mint_y_program_flag: u8,
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct DlmmBin {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    // Q64.64 price of token X in token Y, 0 until the bin is first used
+    pub price: u128,
+    pub liquidity_supply: u128,
+    pub reward_per_token_stored: [u128; 2],
+    pub fee_amount_x_per_token_stored: u128,
+    pub fee_amount_y_per_token_stored: u128,
+    pub amount_x_in: u128,
+    pub amount_y_in: u128,
+}
+
+/// Meteora DLMM `BinArray` account, after the 8-byte Anchor discriminator.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct DlmmBinArray {
+    pub index: i64,
+    pub version: u8,
+    pub padding: [u8; 7],
+    pub lb_pair: Pubkey,
+    pub bins: [DlmmBin; 70],
+}
+
+/// Static Whirlpool accounts recorded by pre_start; tick arrays follow the price
+/// and are derived when trading.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WhirlpoolPoolKeys {
+    pub tick_spacing: u16,
+    pub oracle: String,
+}
+
+/// Static DLMM accounts recorded by pre_start; bin arrays follow the price and
+/// are derived when trading.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DlmmPoolKeys {
+    pub bin_step: u16,
+    pub oracle: String,
+}
+
+pub struct WhirlpoolSwapParams {
+    /// Input amount when `amount_specified_is_input`, output amount otherwise
+    pub amount: u64,
+    /// Minimum output for exact-in swaps, maximum input for exact-out swaps
+    pub other_amount_threshold: u64,
+    pub sqrt_price_limit: u128,
+    pub amount_specified_is_input: bool,
+    pub a_to_b: bool,
+    pub pool_id: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_vault_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    /// Tick arrays in swap direction, starting with the one holding the current tick
+    pub tick_arrays: [Pubkey; 3],
+    pub oracle: Pubkey,
+    pub payer: Pubkey,
+}
+
+pub struct DlmmSwapParams {
+    /// `amount_in` for swap, `max_in_amount` for swap_exact_out
+    pub amount_in: u64,
+    /// `min_amount_out` for swap, `out_amount` for swap_exact_out
+    pub amount_out: u64,
+    pub swap_for_y: bool,
+    pub lb_pair: Pubkey,
+    pub reserve_x: Pubkey,
+    pub reserve_y: Pubkey,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub token_x_program: Pubkey,
+    pub token_y_program: Pubkey,
+    pub oracle: Pubkey,
+    /// Bin arrays in swap direction, starting with the one holding the active bin
+    pub bin_arrays: Vec<Pubkey>,
+    pub payer: Pubkey,
+}
+
 pub struct PoolKeys {
     pub base_mint: Pubkey,
     pub quote_mint: Pubkey,
@@ -194,6 +467,54 @@ pub struct TokenListInfos {
     pub clean_symbol: String,
     pub ata: String,
     pub dex: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpmm: Option<CpmmPoolKeys>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whirlpool: Option<WhirlpoolPoolKeys>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dlmm: Option<DlmmPoolKeys>,
+    /// Owning program of the traded (non-SOL) mint: SPL Token or Token-2022
+    #[serde(default = "spl_token_program")]
+    pub token_program: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_fee: Option<TokenTransferFee>,
+    /// First epoch of a scheduled transfer fee change, when the fee is re-read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_fee_change_epoch: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees: Option<ConstantProductFees>,
+}
+
+fn spl_token_program() -> String {
+    TOKEN_PRO.to_string()
+}
+
+/// Swap fees of a constant-product pool as recorded by pre_start, from the pool
+/// itself (Raydium AMM v4), its amm config (CPMM) or the global config (PumpSwap).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "venue", rename_all = "snake_case")]
+pub enum ConstantProductFees {
+    RaydiumAmm {
+        swap_fee_numerator: u64,
+        swap_fee_denominator: u64,
+    },
+    /// In millionths of the input
+    RaydiumCpmm { trade_fee_rate: u64 },
+    /// In basis points of the SOL side, added to buys and taken from sells
+    PumpSwap {
+        lp_fee_basis_points: u64,
+        protocol_fee_basis_points: u64,
+        coin_creator_fee_basis_points: u64,
+    },
+}
+
+/// Token-2022 transfer fee of the traded mint, as in effect when pre_start ran
+/// or when its last scheduled change started.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct TokenTransferFee {
+    pub basis_points: u16,
+    /// Cap on the fee of a single transfer, in raw units
+    pub maximum_fee: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -203,7 +524,6 @@ pub struct JsonRpcResponse {
     pub result: String,
 }
 
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuyTxHistory {
     pub signature: String,
@@ -226,8 +546,7 @@ pub struct BuyHistoryInfo {
     pub total_ui_amount_in: f64,
     pub total_token_amount_out: u64,
     pub total_ui_token_amount_out: f64,
-    pub take_profit: u8,
+    pub take_profit: f64,
     pub transactions: Vec<BuyTxHistory>,
-    pub dex : String,
+    pub dex: String,
 }
-
@@ -0,0 +1,49 @@
+use std::env;
+
+use anyhow::{bail, Result};
+use raydium_trade_bot::{log, with_trade_store};
+
+/// Shows, trips or resets the kill switch that halts new entries; a running
+/// bot picks the change up on its next entry.
+///
+/// Usage: `kill_switch [status | trip <reason> | reset]`.
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str).unwrap_or("status") {
+        "status" => match with_trade_store(|store| store.kill_switch())? {
+            Some(kill_switch) => log!(
+                format!(
+                    "🛑 Kill switch tripped at {} ms : {}",
+                    kill_switch.tripped_at_ms, kill_switch.reason
+                ),
+                "info"
+            ),
+            None => log!(format!("✅ Kill switch not tripped"), "info"),
+        },
+        "trip" => {
+            let reason = match args[1..].join(" ") {
+                reason if reason.is_empty() => "tripped by the operator".to_string(),
+                reason => reason,
+            };
+            if with_trade_store(|store| store.trip_kill_switch(&reason))? {
+                log!(format!("🛑 Kill switch tripped : {}", reason), "success");
+            } else {
+                log!(format!("Kill switch already tripped"), "info");
+            }
+        }
+        "reset" => {
+            if with_trade_store(|store| store.reset_kill_switch())? {
+                log!(format!("✅ Kill switch reset, entries resume"), "success");
+            } else {
+                log!(format!("Kill switch was not tripped"), "info");
+            }
+        }
+        other => bail!(
+            "unknown command {:?}, expected status, trip or reset",
+            other
+        ),
+    }
+
+    Ok(())
+}
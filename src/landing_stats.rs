@@ -0,0 +1,86 @@
+use std::env;
+
+use anyhow::{Context, Result};
+use raydium_trade_bot::{lamports_to_sol, now_ms, with_trade_store};
+
+/// Prints the landing record of every gateway and the answers of each of its
+/// regions, from the submissions the bot logged.
+///
+/// Usage: `landing_stats [<hours back>]`; all submissions by default.
+fn main() -> Result<()> {
+    let since_ms = match env::args().nth(1) {
+        Some(hours) => {
+            let hours = hours
+                .parse::<f64>()
+                .with_context(|| format!("{:?} is not a number of hours", hours))?;
+            now_ms() - (hours * 3_600_000.0) as i64
+        }
+        None => 0,
+    };
+
+    let (gateways, regions) = with_trade_store(|store| {
+        Ok((
+            store.gateway_stats(since_ms)?,
+            store.region_stats(since_ms)?,
+        ))
+    })?;
+    if gateways.is_empty() {
+        println!("No submissions recorded");
+        return Ok(());
+    }
+
+    println!(
+        "{:<11} {:>6} {:>6} {:>6} {:>7} {:>8} {:>7} {:>10} {:>12} {:>14}",
+        "gateway",
+        "sent",
+        "landed",
+        "failed",
+        "expired",
+        "rejected",
+        "rate %",
+        "slot delay",
+        "cost SOL",
+        "SOL / landing"
+    );
+    for stats in &gateways {
+        println!(
+            "{:<11} {:>6} {:>6} {:>6} {:>7} {:>8} {:>7.1} {:>10} {:>12.6} {:>14}",
+            stats.gateway,
+            stats.submissions,
+            stats.landed,
+            stats.failed,
+            stats.expired,
+            stats.rejected,
+            stats.landing_rate_pct(),
+            stats
+                .avg_slot_delay
+                .map_or("-".to_string(), |delay| format!("{:.2}", delay)),
+            lamports_to_sol(stats.cost_lamports),
+            stats
+                .cost_per_landing()
+                .map_or("-".to_string(), |cost| format!(
+                    "{:.6}",
+                    lamports_to_sol(cost)
+                ))
+        );
+    }
+
+    println!();
+    println!(
+        "{:<11} {:<6} {:>6} {:>8} {:>12} {:>12}",
+        "gateway", "region", "sends", "accepted", "avg ms", "max ms"
+    );
+    for stats in &regions {
+        println!(
+            "{:<11} {:<6} {:>6} {:>8} {:>12.1} {:>12}",
+            stats.gateway,
+            stats.region,
+            stats.sends,
+            stats.accepted,
+            stats.avg_latency_ms,
+            stats.max_latency_ms
+        );
+    }
+
+    Ok(())
+}
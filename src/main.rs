@@ -1,11 +1,17 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::Result;
 use raydium_trade_bot::{
-    get_sol_price, load_black_list, load_enermy_list, load_env_file, load_pool_info, log,
-    TxGrpcStreamManager,
+    get_sol_price, load_pool_info, load_position_book, log, select_trade_account,
+    spawn_config_watcher, spawn_transfer_fee_watch, BotConfig, ConfirmationService, ExitManager,
+    GrpcAccountStreamManager, LiveConfig, PoolStateCache, PriorityFeeEstimator, RiskManager,
+    StreamRecorder, TradeAccount, TxGrpcStreamManager,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    signer::Signer,
 };
-use solana_sdk::{commitment_config::CommitmentLevel, signer::Signer};
 use yellowstone_grpc_proto::geyser::{SubscribeRequest, SubscribeRequestFilterTransactions};
 
 #[tokio::main]
@@ -15,62 +21,210 @@ async fn main() -> Result<()> {
         "info"
     );
 
-    let (payer, rpc, grpc, token, _, _, _, _) = load_env_file();
-    log!(format!("✅ Load .env Successfully"), "info");
-    let pool_info_list = load_pool_info();
+    let config = match BotConfig::load() {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            log!(format!("🔴 {}", e), "error");
+            return Err(e.into());
+        }
+    };
+    let payer = config.payer();
+    log!(format!("✅ Load config Successfully"), "info");
+    // Paper trading books simulated fills in its own account, before anything opens the store
+    let account = select_trade_account(if config.mode.paper_trade {
+        TradeAccount::Paper
+    } else {
+        TradeAccount::Live
+    });
+    if account == TradeAccount::Paper {
+        log!(
+            format!(
+                "📝 Paper trading : nothing is submitted, fills are booked in {}",
+                account.db_path()
+            ),
+            "info"
+        );
+    }
+    let pool_info_list = load_pool_info(&config)?;
     log!(format!("✅ Load pool_info Successfully"), "info");
-    let black_list: Vec<String> = load_black_list();
-    log!(format!("✅ Load black_list Successfully"), "info");
-    let enermy_list: Vec<String> = load_enermy_list();
-    log!(format!("✅ Load enermy_list Successfully"), "info");
+    let live_config = LiveConfig::load(Arc::clone(&config))?;
+    log!(
+        format!("✅ Load black_list & enermy_list Successfully"),
+        "info"
+    );
 
     // let token_list = load_token_list();
     log!(format!("✅ Load token_list Successfully"), "info");
 
-    let sol_price: f64 = get_sol_price().await.flatten().unwrap_or(0.0);
+    let sol_price: f64 = get_sol_price().await.ok().flatten().unwrap_or(0.0);
     log!(
         format!("✅ Load Solana Price Successfully : {}", sol_price),
         "info"
     );
 
-    log!(format!("RPC: {}", rpc), "info");
-    log!(format!("GRPC: {}", grpc), "info");
+    log!(format!("RPC: {}", config.endpoints.rpc_endpoint), "info");
+    log!(format!("GRPC: {}", config.endpoints.grpc_endpoint), "info");
     log!(format!("Payer: {}", payer.pubkey()), "info");
 
     log!(format!("Token & Pool List: {:#?}", pool_info_list), "info");
-    log!(format!("Black List: {:#?}", black_list), "info");
-    log!(format!("Enermy List: {:#?}", enermy_list), "info");
+    log!(format!("Black List: {:#?}", live_config.black_list), "info");
+    log!(format!("Enermy List: {:#?}", live_config.enemy_set), "info");
+
+    // Live reserves of the listed pools, fed by their own account subscription
+    let pool_cache = Arc::new(PoolStateCache::new(&pool_info_list));
+    let rpc_client = RpcClient::new(config.endpoints.rpc_endpoint.clone());
+    if let Err(e) = pool_cache.seed(&rpc_client).await {
+        log!(format!("🔴 Failed to load pool reserves: {:?}", e), "error");
+    }
+    // Every received update is kept for `replay` when recording is on
+    let recorder = if config.recorder.enabled {
+        Some(StreamRecorder::start(
+            &config.recorder.dir,
+            Duration::from_secs(config.recorder.rotate_minutes * 60),
+        )?)
+    } else {
+        None
+    };
+    let account_manager = GrpcAccountStreamManager::new(
+        &config.endpoints.grpc_endpoint,
+        &config.endpoints.grpc_token,
+        Arc::clone(&pool_cache),
+        recorder.clone(),
+    )
+    .await?;
+    let account_request = pool_cache.subscribe_request();
+    tokio::spawn(async move {
+        if let Err(e) = account_manager.lock().await.connect(account_request).await {
+            log!(
+                format!("🔴 Pool account subscription error: {:?}", e),
+                "error"
+            );
+        }
+    });
+    log!(format!("✅ Pool reserve cache subscribed"), "info");
+
+    // Open positions are read once, every booked fill keeps them current
+    match load_position_book() {
+        Ok(()) => log!(format!("✅ Position book loaded"), "info"),
+        Err(e) => log!(format!("🔴 {}", e), "error"),
+    }
+
+    // Every submission goes through the risk limits, the wallet balance backs the reserve check
+    let risk = Arc::new(RiskManager::new());
+    risk.spawn_balance_watch(
+        Arc::new(RpcClient::new_with_commitment(
+            config.endpoints.rpc_endpoint.clone(),
+            CommitmentConfig::processed(),
+        )),
+        payer.pubkey(),
+    );
+    match risk.load_kill_switch() {
+        Ok(Some(kill_switch)) => log!(
+            format!(
+                "🛑 Kill switch tripped ({}), entries stay halted until `make resume`",
+                kill_switch.reason
+            ),
+            "error"
+        ),
+        Ok(None) => log!(format!("✅ Risk manager ready"), "info"),
+        Err(e) => log!(format!("🔴 {}", e), "error"),
+    }
+    risk.spawn_kill_switch_watch();
+
+    // Our own transactions are followed on the swap stream until they land or expire
+    let confirmations = ConfirmationService::new(
+        Arc::new(RpcClient::new_with_commitment(
+            config.endpoints.rpc_endpoint.clone(),
+            CommitmentConfig::confirmed(),
+        )),
+        payer.pubkey(),
+    );
+
+    // Token-2022 fees scheduled to change are re-read when the change starts
+    spawn_transfer_fee_watch(
+        Arc::new(RpcClient::new(config.endpoints.rpc_endpoint.clone())),
+        pool_info_list
+            .iter()
+            .map(|pool| pool.address.clone())
+            .collect(),
+    );
+
+    // Compute-unit prices follow the fees recently paid on each pool
+    let fees = Arc::new(PriorityFeeEstimator::new(&pool_info_list));
+    if config.priority_fee.refresh_secs > 0 {
+        fees.spawn_refresh(
+            Arc::new(RpcClient::new_with_commitment(
+                config.endpoints.rpc_endpoint.clone(),
+                CommitmentConfig::processed(),
+            )),
+            Duration::from_secs(config.priority_fee.refresh_secs),
+        );
+    }
+    log!(format!("✅ Priority fee estimator started"), "info");
 
     // ✅ Flatten all values into a single Vec<String>
 
-    let manager =
-        TxGrpcStreamManager::new(grpc.as_ref(), rpc.as_ref(), token.as_ref(), sol_price).await?;
+    let manager = TxGrpcStreamManager::new(
+        &config.endpoints.grpc_endpoint,
+        &config.endpoints.rpc_endpoint,
+        &config.endpoints.grpc_token,
+        sol_price,
+        Arc::clone(&pool_cache),
+        Arc::clone(&risk),
+        Arc::clone(&confirmations),
+        Arc::clone(&fees),
+        recorder,
+    )
+    .await?;
 
     let mut manager_lock = manager.lock().await;
 
-    let request: SubscribeRequest = SubscribeRequest {
+    let mut request: SubscribeRequest = SubscribeRequest {
         transactions: HashMap::from_iter(vec![(
             "transactions".to_string(),
             SubscribeRequestFilterTransactions {
                 vote: Some(false),
                 failed: Some(false),
                 signature: None,
-                account_include: pool_info_list,
-                account_exclude: black_list,
+                account_include: pool_info_list
+                    .iter()
+                    .map(|pool| pool.address.clone())
+                    .collect(),
+                account_exclude: live_config.black_list.clone(),
                 account_required: vec![],
             },
         )]),
         commitment: Some(CommitmentLevel::Processed as i32),
         ..Default::default()
     };
+    confirmations.add_filters(&mut request);
 
     log!(
         format!("✅ Starting subscription for PumpSwap & Raydium"),
         "info"
     );
 
+    // Watch config & lists so parameters can change without dropping the stream
+    let live = spawn_config_watcher(live_config);
+
+    // Stop-loss, trailing stop, holding time and scale-out exits
+    let exit_manager = ExitManager::new(
+        Arc::new(RpcClient::new_with_commitment(
+            config.endpoints.rpc_endpoint.clone(),
+            CommitmentConfig::processed(),
+        )),
+        Arc::new(config.payer()),
+        live.clone(),
+        pool_cache,
+        risk,
+        confirmations,
+        fees,
+    );
+    tokio::spawn(exit_manager.run());
+    log!(format!("✅ Exit manager started"), "info");
+
     // Start the subscription
-    let result = manager_lock.connect(request, enermy_list).await;
+    let result = manager_lock.connect(request, live).await;
     if let Err(e) = &result {
         log!(format!("🔴 Subscription error: {:?}", e), "error");
     }
@@ -0,0 +1,31 @@
+use std::{env, path::Path};
+
+use anyhow::Result;
+use raydium_trade_bot::{
+    import_json_history, log, TradeStore, JSON_TRADE_HISTORY_DIR, TRADE_DB_PATH,
+};
+
+/// Imports the per-pool JSON trade history files into the trade store.
+///
+/// Usage: `migrate_history [<json dir>] [<database>]`, defaulting to the
+/// paths the bot uses. Pools already in the store are skipped.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let dir = args
+        .next()
+        .unwrap_or_else(|| JSON_TRADE_HISTORY_DIR.to_string());
+    let db_path = args.next().unwrap_or_else(|| TRADE_DB_PATH.to_string());
+
+    let mut store = TradeStore::open(&db_path)?;
+    let summary = import_json_history(&mut store, Path::new(&dir))?;
+
+    log!(
+        format!(
+            "✅ {} -> {} : {} imported, {} already present, {} failed",
+            dir, db_path, summary.imported, summary.skipped, summary.failed
+        ),
+        "success"
+    );
+
+    Ok(())
+}
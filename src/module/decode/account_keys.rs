@@ -0,0 +1,16 @@
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::{Message, TransactionStatusMeta};
+
+/// Full account list of a transaction: static keys, then addresses loaded
+/// from lookup tables (writable first, then readonly), as the runtime orders them.
+///
+/// Instruction account indices and `TokenBalance::account_index` both index into this list.
+pub fn resolve_account_keys(message: &Message, meta: &TransactionStatusMeta) -> Vec<Pubkey> {
+    message
+        .account_keys
+        .iter()
+        .chain(meta.loaded_writable_addresses.iter())
+        .chain(meta.loaded_readonly_addresses.iter())
+        .map(|key| Pubkey::try_from(key.as_slice()).unwrap_or_default())
+        .collect()
+}
@@ -0,0 +1,83 @@
+use solana_sdk::{compute_budget, pubkey::Pubkey};
+use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
+
+use crate::read_u64;
+
+/// `SetComputeUnitPrice` tag of the compute budget program.
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Compute-unit price a streamed transaction set, in micro-lamports; none
+/// when it has no `SetComputeUnitPrice` instruction, so it says nothing about
+/// the fee market. The compute budget program is never loaded from a lookup
+/// table, so the static keys are enough.
+pub fn observed_compute_unit_price(update: &SubscribeUpdateTransaction) -> Option<u64> {
+    let message = update
+        .transaction
+        .as_ref()?
+        .transaction
+        .as_ref()?
+        .message
+        .as_ref()?;
+
+    message
+        .instructions
+        .iter()
+        .filter(|ix| {
+            message
+                .account_keys
+                .get(ix.program_id_index as usize)
+                .and_then(|key| Pubkey::try_from(key.as_slice()).ok())
+                .is_some_and(|program| program == compute_budget::id())
+        })
+        .find(|ix| ix.data.first() == Some(&SET_COMPUTE_UNIT_PRICE))
+        .and_then(|ix| read_u64(&ix.data, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::prelude::{
+        CompiledInstruction, Message, SubscribeUpdateTransactionInfo, Transaction,
+    };
+
+    use super::*;
+
+    fn with_compute_budget_ixs(data: &[Vec<u8>]) -> SubscribeUpdateTransaction {
+        SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                transaction: Some(Transaction {
+                    message: Some(Message {
+                        account_keys: vec![compute_budget::id().to_bytes().to_vec()],
+                        instructions: data
+                            .iter()
+                            .map(|data| CompiledInstruction {
+                                program_id_index: 0,
+                                accounts: vec![],
+                                data: data.clone(),
+                            })
+                            .collect(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_the_set_price() {
+        let mut set_price = vec![SET_COMPUTE_UNIT_PRICE];
+        set_price.extend(25_000u64.to_le_bytes());
+        let update = with_compute_budget_ixs(&[vec![2, 0x40, 0x0d, 0x03, 0x00], set_price]);
+
+        assert_eq!(observed_compute_unit_price(&update), Some(25_000));
+    }
+
+    #[test]
+    fn no_price_without_the_instruction() {
+        let update = with_compute_budget_ixs(&[vec![2, 0x40, 0x0d, 0x03, 0x00]]);
+
+        assert_eq!(observed_compute_unit_price(&update), None);
+    }
+}
@@ -0,0 +1,84 @@
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::TransactionStatusMeta;
+
+use crate::{
+    read_u64, SwapInstruction, SwapLimits, DLMM_SWAP, DLMM_SWAP2, DLMM_SWAP_EXACT_OUT,
+    DLMM_SWAP_EXACT_OUT2, DLMM_SWAP_WITH_PRICE_IMPACT, DLMM_SWAP_WITH_PRICE_IMPACT2,
+    METEORA_DLMM_ID_PUBKEY,
+};
+
+/// Prefix of Anchor events emitted through a self-CPI.
+pub const ANCHOR_EVENT_IX_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+/// Discriminator of the DLMM `Swap` event.
+pub const DLMM_SWAP_EVENT: [u8; 8] = [81, 108, 227, 190, 205, 208, 10, 196];
+
+/// Decodes a Meteora DLMM `swap`, `swap_exact_out`, `swap_with_price_impact`
+/// or their Token-2022 capable `*2` variants.
+///
+/// The pair is account 0 and reserves X / Y are 2 and 3.
+pub fn decode_meteora_dlmm_swap(data: &[u8], accounts: &[Pubkey]) -> Option<SwapInstruction> {
+    let discriminator = data.get(..8)?;
+    let limits = if discriminator == DLMM_SWAP || discriminator == DLMM_SWAP2 {
+        SwapLimits::ExactIn {
+            amount_in: read_u64(data, 8)?,
+            min_amount_out: read_u64(data, 16)?,
+        }
+    } else if discriminator == DLMM_SWAP_EXACT_OUT || discriminator == DLMM_SWAP_EXACT_OUT2 {
+        SwapLimits::ExactOut {
+            max_amount_in: read_u64(data, 8)?,
+            amount_out: read_u64(data, 16)?,
+        }
+    } else if discriminator == DLMM_SWAP_WITH_PRICE_IMPACT
+        || discriminator == DLMM_SWAP_WITH_PRICE_IMPACT2
+    {
+        // Bounded by a price impact rather than an output amount
+        SwapLimits::ExactIn {
+            amount_in: read_u64(data, 8)?,
+            min_amount_out: 0,
+        }
+    } else {
+        return None;
+    };
+
+    if accounts.len() < 15 {
+        return None;
+    }
+
+    Some(SwapInstruction {
+        pool: accounts[0],
+        vaults: [accounts[2], accounts[3]],
+        limits,
+    })
+}
+
+/// Active bin before the first and after the last swap on `lb_pair`, from the
+/// `Swap` events the program emits as inner instructions.
+pub fn dlmm_swap_event_bins(
+    meta: &TransactionStatusMeta,
+    account_keys: &[Pubkey],
+    lb_pair: &Pubkey,
+) -> Option<(i32, i32)> {
+    let mut bins = meta
+        .inner_instructions
+        .iter()
+        .flat_map(|inner| inner.instructions.iter())
+        .filter(|ix| {
+            account_keys.get(ix.program_id_index as usize) == Some(&METEORA_DLMM_ID_PUBKEY)
+        })
+        .map(|ix| ix.data.as_slice())
+        .filter(|data| {
+            data.get(..8) == Some(ANCHOR_EVENT_IX_TAG.as_slice())
+                && data.get(8..16) == Some(DLMM_SWAP_EVENT.as_slice())
+                && data.get(16..48) == Some(lb_pair.as_ref())
+        })
+        .filter_map(|data| {
+            // lb_pair and from precede the bin ids
+            let start_bin_id = i32::from_le_bytes(data.get(80..84)?.try_into().ok()?);
+            let end_bin_id = i32::from_le_bytes(data.get(84..88)?.try_into().ok()?);
+            Some((start_bin_id, end_bin_id))
+        });
+
+    let (before, after) = bins.next()?;
+    let after = bins.next_back().map_or(after, |(_, last)| last);
+    Some((before, after))
+}
@@ -0,0 +1,68 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{read_u64, SwapInstruction, SwapLimits, WHIRLPOOL_SWAP, WHIRLPOOL_SWAP_V2};
+
+/// Discriminator of the `Traded` event logged by every Whirlpool swap.
+pub const WHIRLPOOL_TRADED_EVENT: [u8; 8] = [225, 202, 73, 175, 147, 43, 160, 150];
+
+/// Decodes a Whirlpool `swap` / `swap_v2`.
+///
+/// `swap` takes the pool at account 2 and vaults A / B at 4 and 6, `swap_v2`
+/// at 4, 8 and 10. Two-hop swaps are not decoded.
+pub fn decode_orca_whirlpool_swap(data: &[u8], accounts: &[Pubkey]) -> Option<SwapInstruction> {
+    let discriminator = data.get(..8)?;
+    let (pool, vault_a, vault_b, min_accounts) = if discriminator == WHIRLPOOL_SWAP {
+        (2, 4, 6, 11)
+    } else if discriminator == WHIRLPOOL_SWAP_V2 {
+        (4, 8, 10, 15)
+    } else {
+        return None;
+    };
+
+    let amount = read_u64(data, 8)?;
+    let other_amount_threshold = read_u64(data, 16)?;
+    let amount_specified_is_input = *data.get(40)? != 0;
+    let limits = if amount_specified_is_input {
+        SwapLimits::ExactIn {
+            amount_in: amount,
+            min_amount_out: other_amount_threshold,
+        }
+    } else {
+        SwapLimits::ExactOut {
+            max_amount_in: other_amount_threshold,
+            amount_out: amount,
+        }
+    };
+
+    if accounts.len() < min_accounts {
+        return None;
+    }
+
+    Some(SwapInstruction {
+        pool: accounts[pool],
+        vaults: [accounts[vault_a], accounts[vault_b]],
+        limits,
+    })
+}
+
+/// Sqrt prices (Q64.64, token B in token A) before the first and after the last
+/// swap on `pool`, from the `Traded` events in the transaction logs.
+pub fn whirlpool_traded_prices(log_messages: &[String], pool: &Pubkey) -> Option<(u128, u128)> {
+    let mut prices = log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|encoded| bs64::decode(encoded.as_bytes()).ok())
+        .filter(|event| {
+            event.get(..8) == Some(WHIRLPOOL_TRADED_EVENT.as_slice())
+                && event.get(8..40) == Some(pool.as_ref())
+        })
+        .filter_map(|event| {
+            let pre_sqrt_price = u128::from_le_bytes(event.get(41..57)?.try_into().ok()?);
+            let post_sqrt_price = u128::from_le_bytes(event.get(57..73)?.try_into().ok()?);
+            Some((pre_sqrt_price, post_sqrt_price))
+        });
+
+    let (before, after) = prices.next()?;
+    let after = prices.next_back().map_or(after, |(_, last)| last);
+    Some((before, after))
+}
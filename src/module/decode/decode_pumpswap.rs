@@ -0,0 +1,36 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{read_u64, SwapInstruction, SwapLimits};
+
+pub const PUMPSWAP_BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+pub const PUMPSWAP_SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+/// Decodes a PumpSwap `buy` (exact base out) / `sell` (exact base in).
+///
+/// The pool is account 0 and its base / quote token accounts are 7 and 8.
+pub fn decode_pumpswap_swap(data: &[u8], accounts: &[Pubkey]) -> Option<SwapInstruction> {
+    let discriminator = data.get(..8)?;
+    let limits = if discriminator == PUMPSWAP_BUY_DISCRIMINATOR {
+        SwapLimits::ExactOut {
+            amount_out: read_u64(data, 8)?,
+            max_amount_in: read_u64(data, 16)?,
+        }
+    } else if discriminator == PUMPSWAP_SELL_DISCRIMINATOR {
+        SwapLimits::ExactIn {
+            amount_in: read_u64(data, 8)?,
+            min_amount_out: read_u64(data, 16)?,
+        }
+    } else {
+        return None;
+    };
+
+    if accounts.len() < 9 {
+        return None;
+    }
+
+    Some(SwapInstruction {
+        pool: accounts[0],
+        vaults: [accounts[7], accounts[8]],
+        limits,
+    })
+}
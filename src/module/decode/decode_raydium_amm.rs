@@ -0,0 +1,36 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{read_u64, SwapInstruction, SwapLimits};
+
+pub const RAY_AMM_SWAP_BASE_IN: u8 = 9;
+pub const RAY_AMM_SWAP_BASE_OUT: u8 = 11;
+
+/// Decodes a Raydium AMM v4 `swap_base_in` / `swap_base_out`.
+///
+/// The instruction has 18 accounts when it still passes the AMM target orders
+/// account and 17 without it; the vaults shift by one accordingly.
+pub fn decode_raydium_amm_swap(data: &[u8], accounts: &[Pubkey]) -> Option<SwapInstruction> {
+    let limits = match *data.first()? {
+        RAY_AMM_SWAP_BASE_IN => SwapLimits::ExactIn {
+            amount_in: read_u64(data, 1)?,
+            min_amount_out: read_u64(data, 9)?,
+        },
+        RAY_AMM_SWAP_BASE_OUT => SwapLimits::ExactOut {
+            max_amount_in: read_u64(data, 1)?,
+            amount_out: read_u64(data, 9)?,
+        },
+        _ => return None,
+    };
+
+    let (coin_vault, pc_vault) = match accounts.len() {
+        17 => (4, 5),
+        18 => (5, 6),
+        _ => return None,
+    };
+
+    Some(SwapInstruction {
+        pool: accounts[1],
+        vaults: [accounts[coin_vault], accounts[pc_vault]],
+        limits,
+    })
+}
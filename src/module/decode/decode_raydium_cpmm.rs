@@ -0,0 +1,35 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    read_u64, SwapInstruction, SwapLimits, RAY_CPMM_SWAP_BASE_INPUT, RAY_CPMM_SWAP_BASE_OUTPUT,
+};
+
+/// Decodes a Raydium CPMM `swap_base_input` / `swap_base_output`.
+///
+/// The pool state is account 3 and the input / output vaults are 6 and 7.
+pub fn decode_raydium_cpmm_swap(data: &[u8], accounts: &[Pubkey]) -> Option<SwapInstruction> {
+    let discriminator = data.get(..8)?;
+    let limits = if discriminator == RAY_CPMM_SWAP_BASE_INPUT {
+        SwapLimits::ExactIn {
+            amount_in: read_u64(data, 8)?,
+            min_amount_out: read_u64(data, 16)?,
+        }
+    } else if discriminator == RAY_CPMM_SWAP_BASE_OUTPUT {
+        SwapLimits::ExactOut {
+            max_amount_in: read_u64(data, 8)?,
+            amount_out: read_u64(data, 16)?,
+        }
+    } else {
+        return None;
+    };
+
+    if accounts.len() < 13 {
+        return None;
+    }
+
+    Some(SwapInstruction {
+        pool: accounts[3],
+        vaults: [accounts[6], accounts[7]],
+        limits,
+    })
+}
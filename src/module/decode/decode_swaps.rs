@@ -0,0 +1,589 @@
+use std::collections::HashSet;
+
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_grpc_proto::{
+    geyser::SubscribeUpdateTransaction,
+    prelude::{TokenBalance, TransactionStatusMeta},
+};
+
+use crate::*;
+
+/// A decoded swap instruction: the pool, the vaults whose balances show the
+/// swap's effect, and the limits the trader signed for.
+#[derive(Debug, Clone, Copy)]
+pub struct SwapInstruction {
+    pub pool: Pubkey,
+    pub vaults: [Pubkey; 2],
+    pub limits: SwapLimits,
+}
+
+/// Little-endian `u64` instruction argument at `offset`.
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)?
+        .try_into()
+        .ok()
+        .map(u64::from_le_bytes)
+}
+
+/// Decodes every Raydium AMM v4 / CPMM, PumpSwap, Orca Whirlpool and Meteora DLMM
+/// swap of a streamed transaction, including swaps reached through inner
+/// instructions (e.g. Jupiter routes).
+///
+/// Amounts and reserves come from the pool vaults' pre/post token balances, so
+/// several swaps on the same pool within one transaction are reported once, netted.
+pub fn decode_swaps(update: &SubscribeUpdateTransaction) -> Vec<ObservedSwap> {
+    let mut swaps = Vec::new();
+
+    let Some(transaction) = &update.transaction else {
+        return swaps;
+    };
+    let (Some(message), Some(meta)) = (
+        transaction
+            .transaction
+            .as_ref()
+            .and_then(|tx| tx.message.as_ref()),
+        transaction.meta.as_ref(),
+    ) else {
+        return swaps;
+    };
+    if meta.err.is_some() {
+        return swaps;
+    }
+
+    let account_keys = resolve_account_keys(message, meta);
+    let signature = bs58::encode(&transaction.signature).into_string();
+    let signer = account_keys.first().copied();
+
+    let top_level = message.instructions.iter().map(|ix| {
+        (
+            ix.program_id_index,
+            ix.accounts.as_slice(),
+            ix.data.as_slice(),
+            false,
+        )
+    });
+    let inner = meta.inner_instructions.iter().flat_map(|inner| {
+        inner.instructions.iter().map(|ix| {
+            (
+                ix.program_id_index,
+                ix.accounts.as_slice(),
+                ix.data.as_slice(),
+                true,
+            )
+        })
+    });
+
+    let mut seen_pools = HashSet::new();
+    for (program_id_index, account_indexes, data, routed) in top_level.chain(inner) {
+        let Some(program_id) = account_keys.get(program_id_index as usize) else {
+            continue;
+        };
+        let accounts: Vec<Pubkey> = account_indexes
+            .iter()
+            .filter_map(|index| account_keys.get(*index as usize).copied())
+            .collect();
+
+        let (dex, swap_ix) = if *program_id == RAY_AMM_ID_PUBKEY {
+            (Dex::RaydiumAmm, decode_raydium_amm_swap(data, &accounts))
+        } else if *program_id == PUMP_SWAP_ID_PUBKEY {
+            (Dex::PumpSwap, decode_pumpswap_swap(data, &accounts))
+        } else if *program_id == RAY_CPMM_ID_PUBKEY {
+            (Dex::RaydiumCpmm, decode_raydium_cpmm_swap(data, &accounts))
+        } else if *program_id == ORCA_WHIRLPOOL_ID_PUBKEY {
+            (
+                Dex::OrcaWhirlpool,
+                decode_orca_whirlpool_swap(data, &accounts),
+            )
+        } else if *program_id == METEORA_DLMM_ID_PUBKEY {
+            (Dex::MeteoraDlmm, decode_meteora_dlmm_swap(data, &accounts))
+        } else {
+            continue;
+        };
+        let Some(swap_ix) = swap_ix else {
+            continue;
+        };
+        if !seen_pools.insert(swap_ix.pool) {
+            continue;
+        }
+
+        if let Some(mut swap) = swap_from_vaults(dex, &swap_ix, &account_keys, meta) {
+            swap.signer = signer;
+            swap.slot = update.slot;
+            swap.signature = signature.clone();
+            swap.routed = routed;
+            swaps.push(swap);
+        }
+    }
+
+    swaps
+}
+
+/// Reads the pool's SOL and token vault balances before and after the transaction,
+/// and the venue-reported price for concentrated and bin liquidity pools.
+fn swap_from_vaults(
+    dex: Dex,
+    swap_ix: &SwapInstruction,
+    account_keys: &[Pubkey],
+    meta: &TransactionStatusMeta,
+) -> Option<ObservedSwap> {
+    let balance_of = |balances: &[TokenBalance], vault: &Pubkey| {
+        balances
+            .iter()
+            .find(|balance| account_keys.get(balance.account_index as usize) == Some(vault))
+            .cloned()
+    };
+
+    let mut sol = None;
+    let mut token = None;
+    let mut sol_first = false;
+    for (index, vault) in swap_ix.vaults.iter().enumerate() {
+        let post = balance_of(&meta.post_token_balances, vault)?;
+        // A vault created in this transaction has no pre balance.
+        let pre_amount = balance_of(&meta.pre_token_balances, vault)
+            .map(|pre| raw_amount(&pre))
+            .unwrap_or(0);
+        let entry = (pre_amount, raw_amount(&post), post);
+
+        if entry.2.mint == NATIVE_MINT {
+            sol_first = index == 0;
+            sol = Some(entry);
+        } else {
+            token = Some(entry);
+        }
+    }
+    // Token/token pools are not traded.
+    let (sol_pre, sol_post, _) = sol?;
+    let (token_pre, token_post, token_balance) = token?;
+
+    if sol_pre == sol_post {
+        return None;
+    }
+    let side = if sol_post > sol_pre {
+        SwapSide::Buy
+    } else {
+        SwapSide::Sell
+    };
+    let (amount_in, amount_out) = match side {
+        SwapSide::Buy => (sol_post - sol_pre, token_pre.saturating_sub(token_post)),
+        SwapSide::Sell => (token_post.saturating_sub(token_pre), sol_pre - sol_post),
+    };
+
+    let pool_price = match dex {
+        Dex::OrcaWhirlpool => {
+            whirlpool_traded_prices(&meta.log_messages, &swap_ix.pool).map(|(before, after)| {
+                PoolPrice::SqrtPrice {
+                    before,
+                    after,
+                    sol_first,
+                }
+            })
+        }
+        Dex::MeteoraDlmm => {
+            dlmm_swap_event_bins(meta, account_keys, &swap_ix.pool).map(|(before, after)| {
+                PoolPrice::ActiveBin {
+                    before,
+                    after,
+                    bin_step: None,
+                    sol_first,
+                }
+            })
+        }
+        Dex::RaydiumAmm | Dex::RaydiumCpmm | Dex::PumpSwap => None,
+    };
+
+    Some(ObservedSwap {
+        dex,
+        pool: swap_ix.pool,
+        limits: swap_ix.limits,
+        mint: token_balance.mint.parse().ok()?,
+        token_decimals: token_balance
+            .ui_token_amount
+            .as_ref()
+            .map(|amount| amount.decimals as u8)
+            .unwrap_or(0),
+        side,
+        amount_in,
+        amount_out,
+        reserves_before: Reserves {
+            sol: sol_pre,
+            token: token_pre,
+        },
+        reserves_after: Reserves {
+            sol: sol_post,
+            token: token_post,
+        },
+        pool_price,
+        signer: None,
+        slot: 0,
+        signature: String::new(),
+        routed: false,
+    })
+}
+
+fn raw_amount(balance: &TokenBalance) -> u64 {
+    balance
+        .ui_token_amount
+        .as_ref()
+        .and_then(|amount| amount.amount.parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::prelude::{
+        CompiledInstruction, InnerInstruction, InnerInstructions, Message,
+        SubscribeUpdateTransactionInfo, Transaction, TransactionError, UiTokenAmount,
+    };
+
+    use super::*;
+
+    const SLOT: u64 = 321_000_000;
+    const TOKEN_MINT: Pubkey = Pubkey::new_from_array([50; 32]);
+
+    fn key(n: u8) -> Pubkey {
+        Pubkey::new_from_array([n; 32])
+    }
+
+    fn balance(account_index: u32, mint: &str, amount: u64) -> TokenBalance {
+        TokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: Some(UiTokenAmount {
+                decimals: if mint == NATIVE_MINT { 9 } else { 6 },
+                amount: amount.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Pre and post balances of a SOL vault and a token vault.
+    fn vault_balances(
+        (sol_index, sol_pre, sol_post): (u32, u64, u64),
+        (token_index, token_pre, token_post): (u32, u64, u64),
+    ) -> (Vec<TokenBalance>, Vec<TokenBalance>) {
+        let token_mint = TOKEN_MINT.to_string();
+        (
+            vec![
+                balance(sol_index, NATIVE_MINT, sol_pre),
+                balance(token_index, &token_mint, token_pre),
+            ],
+            vec![
+                balance(sol_index, NATIVE_MINT, sol_post),
+                balance(token_index, &token_mint, token_post),
+            ],
+        )
+    }
+
+    fn instruction(program_id_index: u32, accounts: Vec<u8>, data: Vec<u8>) -> CompiledInstruction {
+        CompiledInstruction {
+            program_id_index,
+            accounts,
+            data,
+        }
+    }
+
+    fn inner(program_id_index: u32, accounts: Vec<u8>, data: Vec<u8>) -> InnerInstruction {
+        InnerInstruction {
+            program_id_index,
+            accounts,
+            data,
+            stack_height: Some(2),
+        }
+    }
+
+    /// `count` account indexes, all `filler` but the ones `at` sets.
+    fn accounts(count: usize, filler: u8, at: &[(usize, u8)]) -> Vec<u8> {
+        let mut accounts = vec![filler; count];
+        for (position, index) in at {
+            accounts[*position] = *index;
+        }
+        accounts
+    }
+
+    fn args(prefix: &[u8], values: &[u64]) -> Vec<u8> {
+        let mut data = prefix.to_vec();
+        for value in values {
+            data.extend(value.to_le_bytes());
+        }
+        data
+    }
+
+    /// Transaction signed by the first of `static_keys`, with `loaded` from a
+    /// lookup table.
+    struct Fixture {
+        static_keys: Vec<Pubkey>,
+        loaded: Vec<Pubkey>,
+        instructions: Vec<CompiledInstruction>,
+        inner: Vec<InnerInstruction>,
+        balances: (Vec<TokenBalance>, Vec<TokenBalance>),
+        logs: Vec<String>,
+    }
+
+    impl Fixture {
+        fn update(self) -> SubscribeUpdateTransaction {
+            let to_bytes = |keys: &[Pubkey]| -> Vec<Vec<u8>> {
+                keys.iter().map(|key| key.to_bytes().to_vec()).collect()
+            };
+            SubscribeUpdateTransaction {
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; 64],
+                    transaction: Some(Transaction {
+                        message: Some(Message {
+                            account_keys: to_bytes(&self.static_keys),
+                            instructions: self.instructions,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    meta: Some(TransactionStatusMeta {
+                        inner_instructions: vec![InnerInstructions {
+                            index: 0,
+                            instructions: self.inner,
+                        }],
+                        log_messages: self.logs,
+                        pre_token_balances: self.balances.0,
+                        post_token_balances: self.balances.1,
+                        loaded_writable_addresses: to_bytes(&self.loaded),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                slot: SLOT,
+            }
+        }
+    }
+
+    /// Top-level Raydium AMM `swap_base_in` buying 10 tokens for 0.1 SOL:
+    /// signer, program, pool, coin (token) vault, pc (SOL) vault, filler.
+    fn raydium_amm_buy() -> Fixture {
+        Fixture {
+            static_keys: vec![key(1), RAY_AMM_ID_PUBKEY, key(2), key(3), key(4), key(5)],
+            loaded: vec![],
+            instructions: vec![instruction(
+                1,
+                accounts(18, 5, &[(1, 2), (5, 3), (6, 4)]),
+                args(&[RAY_AMM_SWAP_BASE_IN], &[100_000_000, 9_000_000]),
+            )],
+            inner: vec![],
+            balances: vault_balances(
+                (4, 10_000_000_000, 10_100_000_000),
+                (3, 1_000_000_000, 990_000_000),
+            ),
+            logs: vec![],
+        }
+    }
+
+    #[test]
+    fn top_level_swaps_read_their_vaults() {
+        let swaps = decode_swaps(&raydium_amm_buy().update());
+        assert_eq!(swaps.len(), 1);
+        let swap = &swaps[0];
+
+        assert_eq!(swap.dex, Dex::RaydiumAmm);
+        assert_eq!(swap.pool, key(2));
+        assert_eq!(swap.mint, TOKEN_MINT);
+        assert_eq!(swap.token_decimals, 6);
+        assert_eq!(swap.side, SwapSide::Buy);
+        assert_eq!(
+            swap.limits,
+            SwapLimits::ExactIn {
+                amount_in: 100_000_000,
+                min_amount_out: 9_000_000
+            }
+        );
+        assert_eq!((swap.amount_in, swap.amount_out), (100_000_000, 10_000_000));
+        assert_eq!(
+            swap.reserves_before,
+            Reserves {
+                sol: 10_000_000_000,
+                token: 1_000_000_000
+            }
+        );
+        assert_eq!(
+            swap.reserves_after,
+            Reserves {
+                sol: 10_100_000_000,
+                token: 990_000_000
+            }
+        );
+        assert_eq!(swap.pool_price, None);
+        assert_eq!(swap.signer, Some(key(1)));
+        assert_eq!(swap.slot, SLOT);
+        assert_eq!(swap.signature, bs58::encode([7; 64]).into_string());
+        assert!(!swap.routed);
+        assert!((swap.price_before() - 0.01).abs() < 1e-12);
+    }
+
+    #[test]
+    fn routed_swaps_resolve_lookup_table_accounts() {
+        // An aggregator calls PumpSwap twice on the same pool, whose accounts
+        // come from a lookup table: pool, base (token) vault, quote (SOL) vault
+        let aggregator = key(60);
+        let sell = inner(
+            2,
+            accounts(9, 6, &[(0, 3), (7, 4), (8, 5)]),
+            args(&PUMPSWAP_SELL_DISCRIMINATOR, &[50_000_000, 2_000_000_000]),
+        );
+        let fixture = Fixture {
+            static_keys: vec![key(1), aggregator, PUMP_SWAP_ID_PUBKEY],
+            loaded: vec![key(3), key(4), key(5), key(6)],
+            instructions: vec![instruction(1, vec![0, 3, 4, 5], vec![1, 2, 3])],
+            inner: vec![sell.clone(), sell],
+            balances: vault_balances(
+                (5, 50_000_000_000, 45_000_000_000),
+                (4, 1_000_000_000, 1_100_000_000),
+            ),
+            logs: vec![],
+        };
+
+        let swaps = decode_swaps(&fixture.update());
+        assert_eq!(swaps.len(), 1, "one pool, reported once");
+        let swap = &swaps[0];
+
+        assert_eq!(swap.dex, Dex::PumpSwap);
+        assert_eq!(swap.pool, key(3));
+        assert!(swap.routed);
+        assert_eq!(swap.side, SwapSide::Sell);
+        assert_eq!(
+            (swap.amount_in, swap.amount_out),
+            (100_000_000, 5_000_000_000),
+            "netted over both swaps"
+        );
+        assert_eq!(
+            swap.limits,
+            SwapLimits::ExactIn {
+                amount_in: 50_000_000,
+                min_amount_out: 2_000_000_000
+            }
+        );
+    }
+
+    #[test]
+    fn whirlpool_swaps_carry_the_traded_sqrt_prices() {
+        let (before, after): (u128, u128) =
+            (18_446_744_073_709_551_616, 18_500_000_000_000_000_000);
+        let mut data = args(&WHIRLPOOL_SWAP, &[1_000_000_000, 49_000_000]);
+        data.extend(0u128.to_le_bytes());
+        data.extend([1, 1]);
+        let mut event = WHIRLPOOL_TRADED_EVENT.to_vec();
+        event.extend(key(2).to_bytes());
+        event.push(1);
+        event.extend(before.to_le_bytes());
+        event.extend(after.to_le_bytes());
+
+        // signer, program, pool, vault A (SOL), vault B (token), filler
+        let fixture = Fixture {
+            static_keys: vec![
+                key(1),
+                ORCA_WHIRLPOOL_ID_PUBKEY,
+                key(2),
+                key(3),
+                key(4),
+                key(5),
+            ],
+            loaded: vec![],
+            instructions: vec![instruction(
+                1,
+                accounts(11, 5, &[(2, 2), (4, 3), (6, 4)]),
+                data,
+            )],
+            inner: vec![],
+            balances: vault_balances(
+                (3, 100_000_000_000, 101_000_000_000),
+                (4, 5_000_000_000, 4_950_000_000),
+            ),
+            logs: vec![
+                "Program log: Instruction: Swap".to_string(),
+                format!("Program data: {}", bs64::encode(&event)),
+            ],
+        };
+
+        let swaps = decode_swaps(&fixture.update());
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].dex, Dex::OrcaWhirlpool);
+        assert_eq!(swaps[0].side, SwapSide::Buy);
+        assert_eq!(
+            swaps[0].pool_price,
+            Some(PoolPrice::SqrtPrice {
+                before,
+                after,
+                sol_first: true
+            })
+        );
+    }
+
+    #[test]
+    fn dlmm_swaps_carry_the_event_bins() {
+        let mut event = ANCHOR_EVENT_IX_TAG.to_vec();
+        event.extend(DLMM_SWAP_EVENT);
+        event.extend(key(2).to_bytes());
+        event.extend(key(1).to_bytes());
+        event.extend((-10i32).to_le_bytes());
+        event.extend((-12i32).to_le_bytes());
+
+        // signer, program, pair, reserve X (token), reserve Y (SOL), filler
+        let fixture = Fixture {
+            static_keys: vec![
+                key(1),
+                METEORA_DLMM_ID_PUBKEY,
+                key(2),
+                key(3),
+                key(4),
+                key(5),
+            ],
+            loaded: vec![],
+            instructions: vec![instruction(
+                1,
+                accounts(15, 5, &[(0, 2), (2, 3), (3, 4)]),
+                args(&DLMM_SWAP, &[100_000_000, 1_900_000_000]),
+            )],
+            inner: vec![inner(1, vec![5], event)],
+            balances: vault_balances(
+                (4, 50_000_000_000, 48_000_000_000),
+                (3, 100_000_000, 200_000_000),
+            ),
+            logs: vec![],
+        };
+
+        let swaps = decode_swaps(&fixture.update());
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].dex, Dex::MeteoraDlmm);
+        assert_eq!(swaps[0].side, SwapSide::Sell);
+        assert_eq!(
+            swaps[0].pool_price,
+            Some(PoolPrice::ActiveBin {
+                before: -10,
+                after: -12,
+                bin_step: None,
+                sol_first: false
+            })
+        );
+    }
+
+    #[test]
+    fn failed_and_token_only_swaps_are_skipped() {
+        let mut failed = raydium_amm_buy().update();
+        if let Some(meta) = failed.transaction.as_mut().and_then(|tx| tx.meta.as_mut()) {
+            meta.err = Some(TransactionError { err: vec![1] });
+        }
+        assert!(decode_swaps(&failed).is_empty());
+
+        let mut token_pair = raydium_amm_buy();
+        let other_mint = key(51).to_string();
+        for balance in token_pair
+            .balances
+            .0
+            .iter_mut()
+            .chain(token_pair.balances.1.iter_mut())
+        {
+            balance.mint = other_mint.clone();
+        }
+        assert!(decode_swaps(&token_pair.update()).is_empty());
+
+        // Missing its target orders and vault accounts
+        let mut short = raydium_amm_buy();
+        short.instructions[0].accounts.truncate(16);
+        assert!(decode_swaps(&short.update()).is_empty());
+    }
+}
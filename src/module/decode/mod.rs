@@ -0,0 +1,19 @@
+pub mod account_keys;
+pub mod compute_unit_price;
+pub mod decode_meteora_dlmm;
+pub mod decode_orca_whirlpool;
+pub mod decode_pumpswap;
+pub mod decode_raydium_amm;
+pub mod decode_raydium_cpmm;
+pub mod decode_swaps;
+pub mod observed_swap;
+
+pub use account_keys::*;
+pub use compute_unit_price::*;
+pub use decode_meteora_dlmm::*;
+pub use decode_orca_whirlpool::*;
+pub use decode_pumpswap::*;
+pub use decode_raydium_amm::*;
+pub use decode_raydium_cpmm::*;
+pub use decode_swaps::*;
+pub use observed_swap::*;
@@ -0,0 +1,230 @@
+use std::fmt;
+
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+
+use crate::{DEFAULT_SELL_TIP, PUMPSWAP_SELL_TIP};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dex {
+    RaydiumAmm,
+    RaydiumCpmm,
+    PumpSwap,
+    OrcaWhirlpool,
+    MeteoraDlmm,
+}
+
+impl fmt::Display for Dex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dex::RaydiumAmm => write!(f, "Raydium"),
+            Dex::RaydiumCpmm => write!(f, "Raydium CPMM"),
+            Dex::PumpSwap => write!(f, "Pumpswap"),
+            Dex::OrcaWhirlpool => write!(f, "Orca Whirlpool"),
+            Dex::MeteoraDlmm => write!(f, "Meteora DLMM"),
+        }
+    }
+}
+
+impl Dex {
+    /// Venue of a pool from the `dex` name pre_start recorded for it.
+    pub fn from_recorded(dex: &str) -> Option<Dex> {
+        match dex {
+            "RAYDIUM_AMM" => Some(Dex::RaydiumAmm),
+            "RAYDIUM_CPMM" => Some(Dex::RaydiumCpmm),
+            "PUMPSWAP" => Some(Dex::PumpSwap),
+            "ORCA_WHIRLPOOL" => Some(Dex::OrcaWhirlpool),
+            "METEORA_DLMM" => Some(Dex::MeteoraDlmm),
+            _ => None,
+        }
+    }
+
+    /// Compute units one of our swaps is assumed to take until a simulation
+    /// measures it, compute budget, race and tip instructions included.
+    pub fn compute_unit_estimate(self) -> u32 {
+        match self {
+            Dex::RaydiumAmm | Dex::RaydiumCpmm => 80_000,
+            Dex::PumpSwap => 120_000,
+            Dex::OrcaWhirlpool | Dex::MeteoraDlmm => 150_000,
+        }
+    }
+
+    /// Fixed tip, in SOL, paid to land an exit.
+    pub fn sell_tip(self) -> f64 {
+        match self {
+            Dex::PumpSwap => PUMPSWAP_SELL_TIP,
+            Dex::RaydiumAmm | Dex::RaydiumCpmm | Dex::OrcaWhirlpool | Dex::MeteoraDlmm => {
+                DEFAULT_SELL_TIP
+            }
+        }
+    }
+}
+
+/// Direction of an observed swap, seen from the trader: `Buy` moves SOL into the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapSide {
+    Buy,
+    Sell,
+}
+
+/// Amounts the trader signed for, in raw units of the input / output mint.
+///
+/// These are bounds, the amounts that actually moved are on `ObservedSwap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapLimits {
+    ExactIn { amount_in: u64, min_amount_out: u64 },
+    ExactOut { max_amount_in: u64, amount_out: u64 },
+}
+
+/// Raw vault balances of a SOL pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Reserves {
+    pub sol: u64,
+    pub token: u64,
+}
+
+/// Spot price reported by the venue's own swap event, for pools whose vault
+/// balances do not price the token (concentrated and bin liquidity).
+///
+/// Raw prices are of the pool's second token in its first (B in A, Y in X),
+/// `sol_first` tells whether SOL is the first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolPrice {
+    /// Whirlpool Q64.64 sqrt prices.
+    SqrtPrice {
+        before: u128,
+        after: u128,
+        sol_first: bool,
+    },
+    /// DLMM active bins; the event has no bin step, it comes from the pool keys.
+    ActiveBin {
+        before: i32,
+        after: i32,
+        bin_step: Option<u16>,
+        sol_first: bool,
+    },
+}
+
+impl PoolPrice {
+    /// Token price in SOL (UI units) before and after, once the price is known.
+    pub fn sol_prices(&self, token_decimals: u8) -> Option<(f64, f64)> {
+        let (before, after, sol_first) = match *self {
+            PoolPrice::SqrtPrice {
+                before,
+                after,
+                sol_first,
+            } => {
+                let price = |sqrt_price: u128| (sqrt_price as f64 / 2f64.powi(64)).powi(2);
+                (price(before), price(after), sol_first)
+            }
+            PoolPrice::ActiveBin {
+                before,
+                after,
+                bin_step,
+                sol_first,
+            } => {
+                let base = 1.0 + bin_step? as f64 / 10_000.0;
+                (base.powi(before), base.powi(after), sol_first)
+            }
+        };
+
+        let to_sol = |raw: f64| {
+            let lamports_per_unit = match sol_first {
+                true if raw == 0.0 => 0.0,
+                true => 1.0 / raw,
+                false => raw,
+            };
+            lamports_per_unit * 10f64.powi(token_decimals as i32) / LAMPORTS_PER_SOL as f64
+        };
+        Some((to_sol(before), to_sol(after)))
+    }
+}
+
+/// A swap on a SOL pool, decoded from a streamed transaction.
+///
+/// Amounts are raw units: `amount_in` is in the input mint (SOL for a buy) and
+/// `amount_out` in the output mint. Reserves are the vault balances, which only
+/// price the token on constant-product pools; others report `pool_price`.
+#[derive(Debug, Clone)]
+pub struct ObservedSwap {
+    pub dex: Dex,
+    pub pool: Pubkey,
+    /// The non-SOL side of the pool.
+    pub mint: Pubkey,
+    pub token_decimals: u8,
+    pub side: SwapSide,
+    /// Exact-in or exact-out, with the trader's slippage bounds.
+    pub limits: SwapLimits,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub reserves_before: Reserves,
+    pub reserves_after: Reserves,
+    pub pool_price: Option<PoolPrice>,
+    pub signer: Option<Pubkey>,
+    pub slot: u64,
+    pub signature: String,
+    /// Reached through a CPI (aggregator route) rather than a top-level instruction.
+    pub routed: bool,
+}
+
+impl ObservedSwap {
+    pub fn is_exact_out(&self) -> bool {
+        matches!(self.limits, SwapLimits::ExactOut { .. })
+    }
+
+    /// Token price in SOL (UI units) for the given reserves.
+    pub fn price_in_sol(&self, reserves: &Reserves) -> f64 {
+        if reserves.token == 0 {
+            return 0.0;
+        }
+        let sol = reserves.sol as f64 / LAMPORTS_PER_SOL as f64;
+        let token = reserves.token as f64 / 10f64.powi(self.token_decimals as i32);
+        sol / token
+    }
+
+    pub fn price_before(&self) -> f64 {
+        match self.venue_prices() {
+            Some((before, _)) => before,
+            None => self.price_in_sol(&self.reserves_before),
+        }
+    }
+
+    pub fn price_after(&self) -> f64 {
+        match self.venue_prices() {
+            Some((_, after)) => after,
+            None => self.price_in_sol(&self.reserves_after),
+        }
+    }
+
+    fn venue_prices(&self) -> Option<(f64, f64)> {
+        self.pool_price?.sol_prices(self.token_decimals)
+    }
+
+    /// Price move caused by the swap, in percent (negative for sells).
+    pub fn price_impact_pct(&self) -> f64 {
+        let before = self.price_before();
+        if before == 0.0 {
+            return 0.0;
+        }
+        (self.price_after() - before) * 100.0 / before
+    }
+
+    /// SOL that left the pool, in SOL (negative when SOL came in).
+    pub fn sol_amount(&self) -> f64 {
+        (self.reserves_before.sol as f64 - self.reserves_after.sol as f64) / LAMPORTS_PER_SOL as f64
+    }
+
+    /// Pool SOL reserve after the swap, in SOL.
+    pub fn pool_sol_reserve(&self) -> f64 {
+        self.reserves_after.sol as f64 / LAMPORTS_PER_SOL as f64
+    }
+
+    /// Pool token reserve after the swap, in UI units.
+    pub fn pool_token_reserve(&self) -> f64 {
+        self.reserves_after.token as f64 / 10f64.powi(self.token_decimals as i32)
+    }
+
+    /// Both sides of the pool valued in USD.
+    pub fn liquidity_usd(&self, sol_price: f64) -> f64 {
+        2.0 * self.pool_sol_reserve() * sol_price
+    }
+}
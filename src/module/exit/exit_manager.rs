@@ -0,0 +1,391 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    future::Future,
+    sync::{mpsc, Arc, OnceLock},
+    thread,
+    time::Duration,
+};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    execute_intent, held_position, load_token_info, log, now_ms, sol_to_lamports, trade_account,
+    with_trade_store, ConfirmationService, Dex, ExecutionContext, ExitReason, ExitState,
+    HeldPosition, IntentKind, LiveConfig, LiveConfigRx, PoolStateCache, PriorityFeeEstimator,
+    Reserves, RiskManager, TokenListInfos, TradeIntent, Urgency, EST_SWAP_NETWORK_FEE, NATIVE_MINT,
+};
+
+/// How often every listed pool is checked without reserve updates, so
+/// holding times run out on quiet pools too.
+pub const EXIT_TICK: Duration = Duration::from_secs(5);
+
+/// How long a submitted exit may take to show up in the position before the
+/// rules are free to order it again, in milliseconds.
+const EXIT_RETRY_MS: i64 = 15_000;
+
+/// An exit submitted for a pool whose fill was not booked yet.
+#[derive(Debug, Clone, Copy)]
+struct PendingExit {
+    /// Tokens held when the exit was ordered.
+    token_amount: u64,
+    since_ms: i64,
+    /// Scale-out levels done before the exit, restored if it never lands.
+    scale_outs_before: usize,
+}
+
+/// Sells open positions on stop-loss, trailing stop, holding time and
+/// scale-out levels (`[exit]` and their pool overrides).
+///
+/// Positions, as the position book holds them, are checked on every reserve
+/// update the pool cache applies and every `EXIT_TICK`. They are valued at the cached reserves, so price rules
+/// only cover constant-product pools; Whirlpool and DLMM positions still
+/// leave on holding time, and the handler doesn't open them while a price
+/// rule is on. Exits go through `execute_intent` like any trade.
+///
+/// Trailing peaks and scale-out levels are read from the trade store the
+/// first time a pool is checked and written back as they change, so they
+/// survive restarts.
+///
+/// Holding times run on the store's clock, so replays, which drive it, check
+/// positions with `check_pools` instead of `run`.
+pub struct ExitManager {
+    client: Arc<RpcClient>,
+    payer: Arc<Keypair>,
+    live: LiveConfigRx,
+    pool_cache: Arc<PoolStateCache>,
+    risk: Arc<RiskManager>,
+    confirmations: Arc<ConfirmationService>,
+    fees: Arc<PriorityFeeEstimator>,
+    pool_infos: HashMap<Pubkey, TokenListInfos>,
+    states: HashMap<Pubkey, ExitState>,
+    pending: HashMap<Pubkey, PendingExit>,
+}
+
+impl ExitManager {
+    /// Strategy name the exit intents are tagged with.
+    pub const NAME: &'static str = "exit_manager";
+
+    pub fn new(
+        client: Arc<RpcClient>,
+        payer: Arc<Keypair>,
+        live: LiveConfigRx,
+        pool_cache: Arc<PoolStateCache>,
+        risk: Arc<RiskManager>,
+        confirmations: Arc<ConfirmationService>,
+        fees: Arc<PriorityFeeEstimator>,
+    ) -> Self {
+        ExitManager {
+            client,
+            payer,
+            live,
+            pool_cache,
+            risk,
+            confirmations,
+            fees,
+            pool_infos: HashMap::new(),
+            states: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Checks positions until the pool cache goes away.
+    pub async fn run(mut self) {
+        let mut updates = self.pool_cache.subscribe_updates();
+        let mut tick = tokio::time::interval(EXIT_TICK);
+
+        loop {
+            let pools = tokio::select! {
+                update = updates.recv() => match update {
+                    Ok(pool) => {
+                        // One check per pool, however many of its accounts changed
+                        let mut pools = HashSet::from([pool]);
+                        while let Ok(pool) = updates.try_recv() {
+                            pools.insert(pool);
+                        }
+                        pools
+                    }
+                    Err(RecvError::Lagged(_)) => self.listed_pools(),
+                    Err(RecvError::Closed) => break,
+                },
+                _ = tick.tick() => self.listed_pools(),
+            };
+
+            for pool in pools {
+                if let Some((intent, live)) = self.check_pool(pool) {
+                    tokio::spawn(self.exit(intent, live));
+                }
+            }
+        }
+    }
+
+    /// Checks `pools` in the given order, executing each exit before the
+    /// next pool is checked.
+    pub async fn check_pools(&mut self, pools: impl IntoIterator<Item = Pubkey>) {
+        for pool in pools {
+            if let Some((intent, live)) = self.check_pool(pool) {
+                self.exit(intent, live).await;
+            }
+        }
+    }
+
+    /// Pools of the pool list, which every tick checks.
+    pub fn listed_pools(&self) -> HashSet<Pubkey> {
+        self.live
+            .borrow()
+            .pools
+            .keys()
+            .filter_map(|pool| pool.parse().ok())
+            .collect()
+    }
+
+    /// Applies the exit rules to `pool`'s position; returns the exit they
+    /// order, with the config it was checked against.
+    fn check_pool(&mut self, pool: Pubkey) -> Option<(TradeIntent, Arc<LiveConfig>)> {
+        let live = Arc::clone(&self.live.borrow());
+        let pool_addr = pool.to_string();
+        let settings = live.pool_settings(&pool_addr);
+        if !settings.enable_sell || !settings.exit.is_enabled() {
+            return None;
+        }
+
+        let position = match held_position(&pool_addr) {
+            Ok(position) => position,
+            Err(e) => {
+                log!(format!("🔴 {}", e), "error");
+                return None;
+            }
+        };
+        let Some(opened_at_ms) = position.opened_at_ms() else {
+            self.states.remove(&pool);
+            self.pending.remove(&pool);
+            return None;
+        };
+        let token_amount = position.token_amount();
+        let now_ms = now_ms();
+
+        if let Some(pending) = self.pending.get(&pool).copied() {
+            let landed = pending.token_amount != token_amount;
+            if !landed && now_ms - pending.since_ms < EXIT_RETRY_MS {
+                return None;
+            }
+            if !landed {
+                if let Some(state) = self.states.get_mut(&pool) {
+                    state.scale_outs_done = pending.scale_outs_before;
+                    save_exit_state(&pool_addr, state);
+                }
+            }
+            self.pending.remove(&pool);
+        }
+
+        let info = recorded_pool_info(&mut self.pool_infos, &pool)?;
+        let dex = Dex::from_recorded(&info.dex)?;
+        let mint = if info.base_mint == NATIVE_MINT {
+            info.quote_mint.parse()
+        } else {
+            info.base_mint.parse()
+        };
+        let mint = mint.ok()?;
+        // Constant-product exits are bounded by a quote against these
+        let snapshot = self.pool_cache.snapshot(&pool);
+        let reserves = match (dex, snapshot) {
+            (Dex::OrcaWhirlpool | Dex::MeteoraDlmm, _) => Reserves::default(),
+            (_, Some(snapshot)) => snapshot.reserves,
+            (_, None) => return None,
+        };
+
+        let tip = dex.sell_tip();
+        let exit_costs = sol_to_lamports(tip) + EST_SWAP_NETWORK_FEE;
+        let held = HeldPosition {
+            token_amount,
+            cost_lamports: position.cost_lamports(),
+            exit_lamports: snapshot
+                .and_then(|snapshot| exit_value(info, &snapshot.reserves, token_amount))
+                .map(|lamports| lamports.saturating_sub(exit_costs)),
+            held_secs: (now_ms - opened_at_ms).max(0) as u64 / 1000,
+        };
+
+        let state = match self.states.entry(pool) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(stored_exit_state(&pool_addr)),
+        };
+        let before = state.clone();
+        let order = state.check(&settings.exit, &held);
+        if *state != before {
+            save_exit_state(&pool_addr, state);
+        }
+        let scale_outs_before = before.scale_outs_done;
+        let order = order?;
+
+        log!(
+            format!(
+                "{} {} : {} , Sell {} of {} tokens (Unrealized PnL {} %, held {} s)",
+                dex,
+                pool,
+                order.reason,
+                order.token_amount,
+                token_amount,
+                held.unrealized_pct()
+                    .map_or("unknown".to_string(), |pct| pct.to_string()),
+                held.held_secs
+            ),
+            "result"
+        );
+        self.pending.insert(
+            pool,
+            PendingExit {
+                token_amount,
+                since_ms: now_ms,
+                scale_outs_before,
+            },
+        );
+
+        let intent = TradeIntent {
+            strategy: Self::NAME,
+            dex,
+            pool,
+            mint,
+            kind: IntentKind::Sell {
+                token_amount: order.token_amount,
+            },
+            tip,
+            urgency: match order.reason {
+                ExitReason::StopLoss | ExitReason::TrailingStop => Urgency::Critical,
+                ExitReason::MaxHoldTime | ExitReason::ScaleOut(_) => Urgency::Medium,
+            },
+            reserves,
+            slippage_pct: settings.slippage.sell_pct,
+        };
+
+        Some((intent, live))
+    }
+
+    /// Executes an exit `check_pool` ordered.
+    fn exit(
+        &self,
+        intent: TradeIntent,
+        live: Arc<LiveConfig>,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let client = Arc::clone(&self.client);
+        let payer = Arc::clone(&self.payer);
+        let risk = Arc::clone(&self.risk);
+        let pool_cache = Arc::clone(&self.pool_cache);
+        let confirmations = Arc::clone(&self.confirmations);
+        let fees = Arc::clone(&self.fees);
+
+        async move {
+            // Paper fills sign nothing
+            let recent_blockhash = if trade_account().is_simulated() {
+                Hash::default()
+            } else {
+                match client.get_latest_blockhash().await {
+                    Ok(recent_blockhash) => recent_blockhash,
+                    Err(e) => {
+                        log!(
+                            format!("🔴 Failed to get a blockhash for the exit: {}", e),
+                            "error"
+                        );
+                        return;
+                    }
+                }
+            };
+            let ctx = ExecutionContext {
+                client,
+                payer,
+                config: &live.config,
+                risk: &risk,
+                pool_cache: &pool_cache,
+                confirmations: &confirmations,
+                fees: &fees,
+                senders: &live.senders,
+                recent_blockhash,
+                timestamp: now_ms() as u64,
+                trigger_slot: None,
+            };
+            execute_intent(&intent, &ctx).await;
+        }
+    }
+}
+
+/// Keys pre_start recorded for `pool`, read from disk once, or on every pass
+/// while a transfer fee change is pending.
+fn recorded_pool_info<'a>(
+    pool_infos: &'a mut HashMap<Pubkey, TokenListInfos>,
+    pool: &Pubkey,
+) -> Option<&'a TokenListInfos> {
+    let stale = pool_infos
+        .get(pool)
+        .is_none_or(|info| info.transfer_fee_change_epoch.is_some());
+    if stale {
+        if let Ok(info) = load_token_info(&pool.to_string()) {
+            pool_infos.insert(*pool, info);
+        }
+    }
+    pool_infos.get(pool)
+}
+
+/// What the exit rules remembered about `pool_id`'s position, as stored.
+/// Blocks on the store, once per pool.
+fn stored_exit_state(pool_id: &str) -> ExitState {
+    with_trade_store(|store| store.exit_state(pool_id)).unwrap_or_else(|e| {
+        log!(
+            format!("🔴 Failed to read the exit state of {}: {}", pool_id, e),
+            "error"
+        );
+        ExitState::default()
+    })
+}
+
+/// Hands `state` to the exit state writer, a thread started on first use that
+/// stores states in the order they changed.
+fn save_exit_state(pool_id: &str, state: &ExitState) {
+    static WRITER: OnceLock<Option<mpsc::Sender<(String, ExitState)>>> = OnceLock::new();
+
+    let writer = WRITER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<(String, ExitState)>();
+        let started = thread::Builder::new()
+            .name("exit-state".to_string())
+            .spawn(move || {
+                for (pool_id, state) in receiver {
+                    if let Err(e) =
+                        with_trade_store(|store| store.save_exit_state(&pool_id, &state))
+                    {
+                        log!(
+                            format!("🔴 Failed to save the exit state of {}: {}", pool_id, e),
+                            "error"
+                        );
+                    }
+                }
+            });
+        match started {
+            Ok(_) => Some(sender),
+            Err(e) => {
+                log!(
+                    format!("🔴 Failed to start the exit state writer: {}", e),
+                    "error"
+                );
+                None
+            }
+        }
+    });
+
+    if let Some(writer) = writer {
+        // The writer runs as long as the process
+        let _ = writer.send((pool_id.to_string(), state.clone()));
+    }
+}
+
+/// Lamports selling `token_amount` raw tokens into `reserves` would return,
+/// after the Token-2022 transfer fee and the pool's own fees.
+fn exit_value(info: &TokenListInfos, reserves: &Reserves, token_amount: u64) -> Option<u64> {
+    let token_amount = match info.transfer_fee {
+        Some(fee) => fee.amount_after_fee(token_amount),
+        None => token_amount,
+    };
+
+    info.fees?
+        .quote_exact_in(reserves, token_amount, false)
+        .ok()
+        .map(|quote| quote.amount_out)
+}
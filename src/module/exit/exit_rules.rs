@@ -0,0 +1,269 @@
+use std::fmt;
+
+use crate::ExitConfig;
+
+/// Why an exit was triggered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    StopLoss,
+    TrailingStop,
+    MaxHoldTime,
+    /// Index of the scale-out level reached.
+    ScaleOut(usize),
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitReason::StopLoss => write!(f, "stop-loss"),
+            ExitReason::TrailingStop => write!(f, "trailing stop"),
+            ExitReason::MaxHoldTime => write!(f, "max holding time"),
+            ExitReason::ScaleOut(level) => write!(f, "scale-out level {}", level + 1),
+        }
+    }
+}
+
+/// An open position as the exit rules see it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeldPosition {
+    /// Raw tokens held across the open lots.
+    pub token_amount: u64,
+    /// Cost basis of the open lots, in lamports.
+    pub cost_lamports: u64,
+    /// Lamports selling everything now would bring in, net of the exit's own
+    /// costs; `None` when the pool can't be quoted.
+    pub exit_lamports: Option<u64>,
+    /// Seconds since the oldest open lot was bought.
+    pub held_secs: u64,
+}
+
+impl HeldPosition {
+    /// Unrealized PnL in percent of the cost basis, when the position is valued.
+    pub fn unrealized_pct(&self) -> Option<f64> {
+        let exit_lamports = self.exit_lamports?;
+        if self.cost_lamports == 0 {
+            return Some(0.0);
+        }
+        Some((exit_lamports as f64 - self.cost_lamports as f64) * 100.0 / self.cost_lamports as f64)
+    }
+}
+
+/// Sell `token_amount` raw tokens because of `reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitOrder {
+    pub reason: ExitReason,
+    pub token_amount: u64,
+}
+
+/// What the exit rules remember about a position between checks, kept in
+/// the trade store next to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExitState {
+    /// Highest exit value seen, scaled to `peak_token_amount` tokens.
+    pub peak_lamports: u64,
+    pub peak_token_amount: u64,
+    /// Scale-out levels already sold into.
+    pub scale_outs_done: usize,
+}
+
+impl ExitState {
+    /// Checks `position` against the rules, most protective first: holding
+    /// time, stop-loss, trailing stop, then the next scale-out level.
+    ///
+    /// The trailing peak is kept per token, so partial sells and added lots
+    /// don't move the stop. A scale-out is counted as done once ordered.
+    pub fn check(&mut self, config: &ExitConfig, position: &HeldPosition) -> Option<ExitOrder> {
+        let sell_all = |reason| {
+            Some(ExitOrder {
+                reason,
+                token_amount: position.token_amount,
+            })
+        };
+
+        if config.max_hold_secs > 0 && position.held_secs >= config.max_hold_secs {
+            return sell_all(ExitReason::MaxHoldTime);
+        }
+
+        let exit_lamports = position.exit_lamports?;
+        let pnl_pct = position.unrealized_pct()?;
+        self.track_peak(exit_lamports, position.token_amount);
+
+        if config.stop_loss_pct > 0.0 && pnl_pct <= -config.stop_loss_pct {
+            return sell_all(ExitReason::StopLoss);
+        }
+        if config.trailing_stop_pct > 0.0 && self.peak_lamports > 0 {
+            let drawdown_pct =
+                (self.peak_lamports - exit_lamports) as f64 * 100.0 / self.peak_lamports as f64;
+            if drawdown_pct >= config.trailing_stop_pct {
+                return sell_all(ExitReason::TrailingStop);
+            }
+        }
+
+        let level = config.scale_out.get(self.scale_outs_done)?;
+        if pnl_pct < level.at_pct {
+            return None;
+        }
+        let reason = ExitReason::ScaleOut(self.scale_outs_done);
+        self.scale_outs_done += 1;
+
+        let token_amount = if level.sell_pct >= 100.0 {
+            position.token_amount
+        } else {
+            (position.token_amount as f64 * level.sell_pct / 100.0) as u64
+        };
+        (token_amount > 0).then_some(ExitOrder {
+            reason,
+            token_amount,
+        })
+    }
+
+    fn track_peak(&mut self, exit_lamports: u64, token_amount: u64) {
+        if self.peak_token_amount != token_amount {
+            self.peak_lamports = if self.peak_token_amount == 0 {
+                0
+            } else {
+                (self.peak_lamports as u128 * token_amount as u128 / self.peak_token_amount as u128)
+                    as u64
+            };
+            self.peak_token_amount = token_amount;
+        }
+        self.peak_lamports = self.peak_lamports.max(exit_lamports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScaleOutLevel;
+
+    fn rules() -> ExitConfig {
+        ExitConfig {
+            stop_loss_pct: 0.0,
+            trailing_stop_pct: 0.0,
+            max_hold_secs: 0,
+            scale_out: vec![],
+        }
+    }
+
+    fn held(token_amount: u64, exit_lamports: Option<u64>) -> HeldPosition {
+        HeldPosition {
+            token_amount,
+            cost_lamports: 1_000,
+            exit_lamports,
+            held_secs: 10,
+        }
+    }
+
+    #[test]
+    fn holding_time_sells_unvalued_positions() {
+        let config = ExitConfig {
+            max_hold_secs: 10,
+            stop_loss_pct: 5.0,
+            ..rules()
+        };
+
+        let order = ExitState::default().check(&config, &held(500, None));
+        assert_eq!(
+            order,
+            Some(ExitOrder {
+                reason: ExitReason::MaxHoldTime,
+                token_amount: 500
+            })
+        );
+
+        let young = HeldPosition {
+            held_secs: 9,
+            ..held(500, None)
+        };
+        assert_eq!(ExitState::default().check(&config, &young), None);
+    }
+
+    #[test]
+    fn stop_loss_sells_everything_at_its_threshold() {
+        let config = ExitConfig {
+            stop_loss_pct: 20.0,
+            ..rules()
+        };
+        let mut state = ExitState::default();
+
+        assert_eq!(state.check(&config, &held(500, Some(801))), None);
+        let order = state.check(&config, &held(500, Some(800)));
+        assert_eq!(order.map(|order| order.reason), Some(ExitReason::StopLoss));
+        assert_eq!(order.map(|order| order.token_amount), Some(500));
+    }
+
+    #[test]
+    fn trailing_stop_follows_the_peak_per_token() {
+        let config = ExitConfig {
+            trailing_stop_pct: 10.0,
+            ..rules()
+        };
+        let mut state = ExitState::default();
+
+        assert_eq!(state.check(&config, &held(1_000, Some(1_500))), None);
+        assert_eq!(state.check(&config, &held(1_000, Some(2_000))), None);
+        // Half sold: the peak scales to 1000 lamports for the 500 tokens left
+        assert_eq!(state.check(&config, &held(500, Some(910))), None);
+        assert_eq!(state.peak_lamports, 1_000);
+        let order = state.check(&config, &held(500, Some(900)));
+        assert_eq!(
+            order,
+            Some(ExitOrder {
+                reason: ExitReason::TrailingStop,
+                token_amount: 500
+            })
+        );
+    }
+
+    #[test]
+    fn scale_out_levels_are_sold_into_once_each() {
+        let config = ExitConfig {
+            scale_out: vec![
+                ScaleOutLevel {
+                    at_pct: 25.0,
+                    sell_pct: 50.0,
+                },
+                ScaleOutLevel {
+                    at_pct: 60.0,
+                    sell_pct: 100.0,
+                },
+            ],
+            ..rules()
+        };
+        let mut state = ExitState::default();
+
+        assert_eq!(state.check(&config, &held(1_000, Some(1_249))), None);
+        assert_eq!(
+            state.check(&config, &held(1_000, Some(1_300))),
+            Some(ExitOrder {
+                reason: ExitReason::ScaleOut(0),
+                token_amount: 500
+            })
+        );
+        assert_eq!(state.scale_outs_done, 1);
+        assert_eq!(state.check(&config, &held(500, Some(700))), None);
+        assert_eq!(
+            state.check(&config, &held(500, Some(1_600))),
+            Some(ExitOrder {
+                reason: ExitReason::ScaleOut(1),
+                token_amount: 500
+            })
+        );
+        assert_eq!(state.check(&config, &held(500, Some(5_000))), None);
+    }
+
+    #[test]
+    fn holding_time_comes_before_price_rules() {
+        let config = ExitConfig {
+            max_hold_secs: 5,
+            stop_loss_pct: 10.0,
+            ..rules()
+        };
+
+        let order = ExitState::default().check(&config, &held(500, Some(100)));
+        assert_eq!(
+            order.map(|order| order.reason),
+            Some(ExitReason::MaxHoldTime)
+        );
+    }
+}
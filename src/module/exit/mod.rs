@@ -0,0 +1,5 @@
+pub mod exit_manager;
+pub mod exit_rules;
+
+pub use exit_manager::*;
+pub use exit_rules::*;
@@ -0,0 +1,3 @@
+pub mod priority_fee;
+
+pub use priority_fee::*;
@@ -0,0 +1,253 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{load_token_info, log, PoolConfig, PriorityFeeConfig};
+
+/// Slots of fee history a suggestion looks at, as `getRecentPrioritizationFees`
+/// returns.
+const FEE_WINDOW_SLOTS: u64 = 150;
+
+/// Stream samples kept per pool, whatever their slots.
+const MAX_OBSERVED: usize = 1_000;
+
+/// How fast a trade must land; picks the percentile of recent fees its
+/// compute-unit price is set to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Urgency {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Urgency {
+    /// Percentile of recent compute-unit prices the trade pays.
+    pub fn percentile(self) -> f64 {
+        match self {
+            Urgency::Low => 25.0,
+            Urgency::Medium => 50.0,
+            Urgency::High => 75.0,
+            Urgency::Critical => 95.0,
+        }
+    }
+}
+
+impl fmt::Display for Urgency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Urgency::Low => write!(f, "low"),
+            Urgency::Medium => write!(f, "medium"),
+            Urgency::High => write!(f, "high"),
+            Urgency::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Compute-unit prices recently paid on one pool, as (slot, micro-lamports).
+#[derive(Debug, Default)]
+struct PoolFees {
+    /// Writable accounts `getRecentPrioritizationFees` is asked about.
+    accounts: Vec<Pubkey>,
+    /// Prices of the pool's swaps seen on the stream.
+    observed: VecDeque<(u64, u64)>,
+    /// Last `getRecentPrioritizationFees` answer for the accounts.
+    recent: Vec<(u64, u64)>,
+}
+
+impl PoolFees {
+    /// `pct` percentile of the prices paid over the last `FEE_WINDOW_SLOTS`
+    /// slots. The RPC answers per-slot minimums, often zero, which would drag
+    /// down the prices swaps actually paid, so each source gets its own
+    /// percentile and the higher one wins.
+    fn percentile(&self, pct: f64) -> Option<u64> {
+        let newest = self
+            .observed
+            .iter()
+            .chain(&self.recent)
+            .map(|(slot, _)| *slot)
+            .max()?;
+        let in_window = |samples: &mut dyn Iterator<Item = &(u64, u64)>| -> Vec<u64> {
+            samples
+                .filter(|(slot, _)| slot + FEE_WINDOW_SLOTS > newest)
+                .map(|(_, price)| *price)
+                .collect()
+        };
+
+        let observed = percentile_of(in_window(&mut self.observed.iter()), pct);
+        let recent = percentile_of(in_window(&mut self.recent.iter()), pct);
+        observed.max(recent)
+    }
+}
+
+/// `pct` percentile of `samples`, nearest rank.
+fn percentile_of(mut samples: Vec<u64>, pct: f64) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+
+    let rank = (pct.clamp(0.0, 100.0) / 100.0 * samples.len() as f64).ceil() as usize;
+    Some(samples[rank.saturating_sub(1)])
+}
+
+/// Tracks the compute-unit prices recently paid to write-lock each listed
+/// pool and its vaults: the prices of the swaps the transaction stream shows
+/// on the pool, plus `getRecentPrioritizationFees` for its accounts.
+///
+/// Strategies name an `Urgency` and get a percentile of those prices,
+/// capped by `priority_fee.max_micro_lamports`.
+#[derive(Debug, Default)]
+pub struct PriorityFeeEstimator {
+    pools: Mutex<HashMap<Pubkey, PoolFees>>,
+}
+
+impl PriorityFeeEstimator {
+    /// Tracks `pools`, with the vaults pre_start recorded for them.
+    pub fn new(pools: &[PoolConfig]) -> Self {
+        let pools = pools
+            .iter()
+            .filter_map(|pool| {
+                let pool_id: Pubkey = pool.address.parse().ok()?;
+                let mut accounts = vec![pool_id];
+                if let Ok(info) = load_token_info(&pool.address) {
+                    accounts.extend(info.base_vault_b64.parse::<Pubkey>().ok());
+                    accounts.extend(info.quote_vault_b64.parse::<Pubkey>().ok());
+                }
+                Some((
+                    pool_id,
+                    PoolFees {
+                        accounts,
+                        ..Default::default()
+                    },
+                ))
+            })
+            .collect();
+
+        Self {
+            pools: Mutex::new(pools),
+        }
+    }
+
+    /// Books the compute-unit price of a swap seen on `pool` in `slot`.
+    pub fn observe(&self, pool: Pubkey, slot: u64, micro_lamports: u64) {
+        let mut pools = self.pools.lock().unwrap_or_else(PoisonError::into_inner);
+        let observed = &mut pools.entry(pool).or_default().observed;
+        observed.push_back((slot, micro_lamports));
+        while observed.len() > MAX_OBSERVED {
+            observed.pop_front();
+        }
+    }
+
+    /// `pct` percentile of the prices paid on `pool` over the last
+    /// `FEE_WINDOW_SLOTS` slots, if any was seen.
+    pub fn percentile(&self, pool: &Pubkey, pct: f64) -> Option<u64> {
+        self.pools
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(pool)?
+            .percentile(pct)
+    }
+
+    /// Compute-unit price, in micro-lamports, of a trade on `pool` that must
+    /// land with `urgency`.
+    pub fn suggest(&self, config: &PriorityFeeConfig, pool: &Pubkey, urgency: Urgency) -> u64 {
+        self.percentile(pool, urgency.percentile())
+            .unwrap_or(config.fallback_micro_lamports)
+            .min(config.max_micro_lamports)
+    }
+
+    /// Refreshes every pool's `getRecentPrioritizationFees` every `every`.
+    pub fn spawn_refresh(self: &Arc<Self>, client: Arc<RpcClient>, every: Duration) {
+        let estimator = Arc::clone(self);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                let tracked: Vec<(Pubkey, Vec<Pubkey>)> = estimator
+                    .pools
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .iter()
+                    .filter(|(_, fees)| !fees.accounts.is_empty())
+                    .map(|(pool, fees)| (*pool, fees.accounts.clone()))
+                    .collect();
+
+                for (pool, accounts) in tracked {
+                    match client.get_recent_prioritization_fees(&accounts).await {
+                        Ok(fees) => {
+                            let mut pools = estimator
+                                .pools
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner);
+                            if let Some(pool_fees) = pools.get_mut(&pool) {
+                                pool_fees.recent = fees
+                                    .iter()
+                                    .map(|fee| (fee.slot, fee.prioritization_fee))
+                                    .collect();
+                            }
+                        }
+                        Err(e) => log!(
+                            format!("🔴 Failed to refresh priority fees of {}: {}", pool, e),
+                            "error"
+                        ),
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_fees(observed: &[(u64, u64)], recent: &[(u64, u64)]) -> PoolFees {
+        PoolFees {
+            observed: observed.iter().copied().collect(),
+            recent: recent.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn zero_slot_minimums_dont_drag_observed_prices_down() {
+        let recent: Vec<(u64, u64)> = (0..150).map(|slot| (slot, 0)).collect();
+        let fees = pool_fees(&[(140, 50_000), (145, 80_000), (149, 120_000)], &recent);
+
+        assert_eq!(fees.percentile(50.0), Some(80_000));
+        assert_eq!(fees.percentile(95.0), Some(120_000));
+    }
+
+    #[test]
+    fn the_higher_source_wins() {
+        let fees = pool_fees(&[(100, 10_000)], &[(99, 40_000), (100, 60_000)]);
+
+        assert_eq!(fees.percentile(50.0), Some(40_000));
+        assert_eq!(fees.percentile(100.0), Some(60_000));
+    }
+
+    #[test]
+    fn samples_past_the_window_are_dropped() {
+        let fees = pool_fees(&[(10, 900_000), (500, 20_000)], &[(300, 70_000)]);
+
+        assert_eq!(fees.percentile(100.0), Some(20_000));
+        assert_eq!(pool_fees(&[], &[]).percentile(50.0), None);
+    }
+
+    #[test]
+    fn nearest_rank() {
+        assert_eq!(percentile_of(vec![4, 1, 3, 2], 0.0), Some(1));
+        assert_eq!(percentile_of(vec![4, 1, 3, 2], 50.0), Some(2));
+        assert_eq!(percentile_of(vec![4, 1, 3, 2], 75.0), Some(3));
+        assert_eq!(percentile_of(vec![4, 1, 3, 2], 150.0), Some(4));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{DumpConfig, SizingTier, SlippageConfig};
+
+/// Buy into one dump, as the sizing table decides it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntrySize {
+    /// Index of the matching row of the table.
+    pub tier: usize,
+    pub sol_amount: f64,
+    /// Tip in SOL paid to the landing service.
+    pub tip: f64,
+    pub slippage_pct: f64,
+}
+
+/// Sizes a buy into a sell of `sell_sol` SOL that dropped the price by
+/// `drop_pct` % on a pool holding `liquidity_usd`, from the first row of
+/// `tiers` it reaches; `None` when no row matches or the buy comes to nothing.
+///
+/// `tiers` is `dump.sizing_tiers(slippage)`, taken once by callers sizing
+/// many dumps against the same settings.
+pub fn dump_sizing(
+    drop_pct: f64,
+    liquidity_usd: f64,
+    sell_sol: f64,
+    tiers: &[SizingTier],
+    dump: &DumpConfig,
+    slippage: &SlippageConfig,
+) -> Option<EntrySize> {
+    let (tier, row) = tiers
+        .iter()
+        .enumerate()
+        .find(|(_, row)| drop_pct >= row.min_drop_pct && liquidity_usd >= row.min_liquidity_usd)?;
+
+    // `min` would turn a NaN amount into `max_amount`, so check it first
+    let sol_amount = sell_sol * row.amount_pct / 100.0;
+    if sol_amount.is_nan() || sol_amount <= 0.0 {
+        return None;
+    }
+    let sol_amount = sol_amount.min(dump.max_amount);
+
+    Some(EntrySize {
+        tier,
+        sol_amount,
+        tip: (sol_amount * row.tip_pct / 100.0).max(dump.tip_min),
+        slippage_pct: (drop_pct * row.slippage_factor).clamp(slippage.min_pct, slippage.max_pct),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(min_drop_pct: f64, min_liquidity_usd: f64, amount_pct: f64) -> SizingTier {
+        SizingTier {
+            min_drop_pct,
+            min_liquidity_usd,
+            amount_pct,
+            tip_pct: 1.0,
+            slippage_factor: 0.25,
+        }
+    }
+
+    fn tiers() -> Vec<SizingTier> {
+        vec![
+            tier(30.0, 200_000.0, 20.0),
+            tier(20.0, 80_000.0, 10.0),
+            tier(10.0, 0.0, 5.0),
+        ]
+    }
+
+    fn dump() -> DumpConfig {
+        DumpConfig {
+            tiers: None,
+            max_amount: 5.0,
+            amount_in_factor_low: 0.0,
+            amount_in_factor_median: 0.0,
+            amount_in_factor_high: 0.0,
+            tip_min: 0.001,
+            tip_factor_low: 0.0,
+            tip_factor_median: 0.0,
+            tip_factor_high: 0.0,
+            tip_factor_ultra: 0.0,
+        }
+    }
+
+    fn slippage() -> SlippageConfig {
+        SlippageConfig {
+            factor_low: 0.0,
+            factor_median: 0.0,
+            factor_high: 0.0,
+            min_pct: 3.0,
+            max_pct: 8.0,
+            sell_pct: 1.0,
+        }
+    }
+
+    fn size(drop_pct: f64, liquidity_usd: f64, sell_sol: f64) -> Option<EntrySize> {
+        dump_sizing(
+            drop_pct,
+            liquidity_usd,
+            sell_sol,
+            &tiers(),
+            &dump(),
+            &slippage(),
+        )
+    }
+
+    #[test]
+    fn tier_boundaries() {
+        // (drop %, liquidity, tier)
+        let cases = [
+            (30.0, 200_000.0, Some(0)),
+            (29.99, 200_000.0, Some(1)),
+            (30.0, 199_999.0, Some(1)),
+            (20.0, 80_000.0, Some(1)),
+            (19.99, 80_000.0, Some(2)),
+            (20.0, 79_999.0, Some(2)),
+            (10.0, 0.0, Some(2)),
+            (9.99, 1_000_000.0, None),
+            (f64::NAN, 1_000_000.0, None),
+        ];
+        for (drop_pct, liquidity_usd, expected) in cases {
+            assert_eq!(
+                size(drop_pct, liquidity_usd, 1.0).map(|size| size.tier),
+                expected,
+                "drop {} % on {} USD",
+                drop_pct,
+                liquidity_usd
+            );
+        }
+    }
+
+    #[test]
+    fn amount_tip_and_slippage_of_the_tier() {
+        let entry = size(30.0, 500_000.0, 10.0).unwrap();
+        assert_eq!(entry.sol_amount, 2.0);
+        assert_eq!(entry.tip, 0.02);
+        assert_eq!(entry.slippage_pct, 7.5);
+
+        // Slippage is clamped to the configured range
+        assert_eq!(size(50.0, 500_000.0, 1.0).unwrap().slippage_pct, 8.0);
+        assert_eq!(size(10.0, 0.0, 1.0).unwrap().slippage_pct, 3.0);
+        assert_eq!(size(16.0, 0.0, 1.0).unwrap().slippage_pct, 4.0);
+    }
+
+    #[test]
+    fn amount_is_capped_at_max_amount() {
+        let capped = size(30.0, 500_000.0, 100.0).unwrap();
+        assert_eq!(capped.sol_amount, 5.0);
+        assert_eq!(capped.tip, 0.05);
+
+        let at_cap = size(30.0, 500_000.0, 25.0).unwrap();
+        assert_eq!(at_cap.sol_amount, 5.0);
+    }
+
+    #[test]
+    fn tip_is_floored_at_tip_min() {
+        // 5 % of 0.01 SOL tips 1 % of 0.0005 SOL, under the floor
+        let small = size(10.0, 0.0, 0.01).unwrap();
+        assert_eq!(small.sol_amount, 0.0005);
+        assert_eq!(small.tip, 0.001);
+
+        let large = size(10.0, 0.0, 40.0).unwrap();
+        assert_eq!(large.tip, 0.02);
+    }
+
+    #[test]
+    fn nothing_to_buy() {
+        assert_eq!(size(30.0, 500_000.0, 0.0), None);
+        assert_eq!(size(30.0, 500_000.0, f64::NAN), None);
+        assert_eq!(
+            dump_sizing(30.0, 500_000.0, 10.0, &[], &dump(), &slippage()),
+            None
+        );
+    }
+}
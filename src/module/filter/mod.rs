@@ -1,5 +1,3 @@
-pub mod dump_setup;
-pub mod slippage_setup;
+pub mod dump_sizing;
 
-pub use dump_setup::*;
-pub use slippage_setup::*;
+pub use dump_sizing::*;
@@ -0,0 +1,726 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
+
+use crate::*;
+
+/// What is needed to land a trade, shared by every intent of one observed transaction.
+pub struct ExecutionContext<'a> {
+    pub client: Arc<RpcClient>,
+    pub payer: Arc<Keypair>,
+    pub config: &'a BotConfig,
+    pub risk: &'a Arc<RiskManager>,
+    /// Live reserves of the listed pools, paper fills price against them.
+    pub pool_cache: &'a PoolStateCache,
+    /// Follows the submitted transactions until they land or expire.
+    pub confirmations: &'a Arc<ConfirmationService>,
+    /// Recent compute-unit prices per pool, priced by the intent's urgency.
+    pub fees: &'a PriorityFeeEstimator,
+    /// Gateways built from `[senders]`, in the order of `senders.enabled`.
+    pub senders: &'a [SenderGroup],
+    pub recent_blockhash: Hash,
+    /// Per-trade id used by the landing services to tag the submission.
+    pub timestamp: u64,
+    /// Slot of the observed transaction, for the landing slot delay.
+    pub trigger_slot: Option<u64>,
+}
+
+/// Builds the swap instruction for `intent` and submits it (racing the
+/// enabled gateways, or through the first one alone), unless `mode.submit_tx` is off, the risk
+/// manager holds it back, a circuit breaker is open or the pre-flight
+/// simulation (`[preflight]`) says it would fail. Returns once the gateways
+/// took it: what became of the submission is fed back to the breakers and
+/// the risk manager, and recorded for the landing stats, in the background.
+///
+/// On the paper and replay accounts the intent is filled by `paper_execute`
+/// instead, behind the same risk limits, whatever `mode.submit_tx` says.
+pub async fn execute_intent(intent: &TradeIntent, ctx: &ExecutionContext<'_>) {
+    let mode = &ctx.config.mode;
+
+    if trade_account().is_simulated() {
+        return execute_on_paper(intent, ctx).await;
+    }
+    if !mode.submit_tx {
+        return;
+    }
+
+    let is_buy = !is_sell(&intent.kind);
+    let (swap_ix, check) = match plan_swap(intent, ctx).await {
+        Ok(plan) => plan,
+        Err(e) => {
+            log!(
+                format!("🔴 Failed to build swap on {}: {:#}", intent.pool, e),
+                "error"
+            );
+            return;
+        }
+    };
+
+    let breaker = &ctx.config.breaker;
+    let breakers = ctx.risk.breakers();
+    if !breakers.allow_pool(breaker, intent.pool) {
+        log!(
+            format!(
+                "⛔ {} trade on {} held back: circuit breaker open",
+                intent.strategy, intent.pool
+            ),
+            "error"
+        );
+        return;
+    }
+    let count = if mode.is_racing { ctx.senders.len() } else { 1 };
+    let groups: Vec<&SenderGroup> = ctx
+        .senders
+        .iter()
+        .take(count)
+        .filter(|group| breakers.allow_gateway(breaker, group.gateway))
+        .collect();
+    if groups.is_empty() {
+        log!(
+            format!(
+                "⛔ {} trade on {} held back: every gateway's circuit breaker is open",
+                intent.strategy, intent.pool
+            ),
+            "error"
+        );
+        return;
+    }
+
+    let race = mode.is_racing.then_some(ctx.timestamp);
+    let unit_price = ctx
+        .fees
+        .suggest(&ctx.config.priority_fee, &intent.pool, intent.urgency);
+    let preflight = &ctx.config.preflight;
+    let unit_limit = if preflight.simulate {
+        match simulate_swap(
+            &ctx.client,
+            &ctx.payer,
+            &swap_ix,
+            unit_price,
+            ctx.recent_blockhash,
+            race,
+            &check,
+            preflight.cu_headroom_pct,
+        )
+        .await
+        {
+            Ok(unit_limit) => unit_limit,
+            Err(e @ PreflightError::Rpc(_)) => {
+                log!(
+                    format!("🔴 Pre-flight skipped on {}: {}", intent.pool, e),
+                    "error"
+                );
+                compute_unit_limit(intent.dex, preflight.cu_headroom_pct)
+            }
+            Err(e) => {
+                log!(
+                    format!(
+                        "⛔ {} trade on {} dropped by pre-flight: {}",
+                        intent.strategy, intent.pool, e
+                    ),
+                    "error"
+                );
+                return;
+            }
+        }
+    } else {
+        compute_unit_limit(intent.dex, preflight.cu_headroom_pct)
+    };
+
+    log!(
+        format!(
+            "Compute budget : {} units at {} micro-lamports ({} urgency)",
+            unit_limit, unit_price, intent.urgency
+        ),
+        "info"
+    );
+
+    let admission = match ctx.risk.admit(&ctx.config.risk, intent) {
+        Ok(admission) => admission,
+        Err(e) => {
+            log!(
+                format!(
+                    "🛑 {} entry on {} held back: {}",
+                    intent.strategy, intent.pool, e
+                ),
+                "error"
+            );
+            return;
+        }
+    };
+
+    let landings = multi_submit(
+        &groups,
+        ctx.confirmations,
+        &ctx.payer,
+        &swap_ix,
+        ComputeBudget {
+            unit_limit,
+            unit_price,
+        },
+        intent.tip,
+        ctx.recent_blockhash,
+        &IntentTag {
+            tag: ctx.timestamp,
+            pool: intent.pool,
+            trigger_slot: ctx.trigger_slot,
+        },
+        mode.is_racing,
+        is_buy,
+    )
+    .await;
+
+    let (risk, breaker, intent) = (Arc::clone(ctx.risk), breaker.clone(), intent.clone());
+    tokio::spawn(async move {
+        let outcomes = landings.await;
+        risk.settle(&breaker, &intent, admission, &outcomes);
+    });
+}
+
+async fn execute_on_paper(intent: &TradeIntent, ctx: &ExecutionContext<'_>) {
+    let budget = ComputeBudget {
+        unit_limit: compute_unit_limit(intent.dex, ctx.config.preflight.cu_headroom_pct),
+        unit_price: ctx
+            .fees
+            .suggest(&ctx.config.priority_fee, &intent.pool, intent.urgency),
+    };
+
+    let replaying = trade_account() == TradeAccount::Replay;
+    if replaying && matches!(intent.dex, Dex::OrcaWhirlpool | Dex::MeteoraDlmm) {
+        // Their quotes read tick and bin arrays the recording doesn't have
+        log!(
+            format!(
+                "📝 {} trade on {} skipped: {} pools can't be priced from a recording",
+                intent.strategy, intent.pool, intent.dex
+            ),
+            "info"
+        );
+        return;
+    }
+
+    let admission = match ctx.risk.admit(&ctx.config.risk, intent) {
+        Ok(admission) => admission,
+        Err(e) => {
+            log!(
+                format!(
+                    "🛑 {} entry on {} held back: {}",
+                    intent.strategy, intent.pool, e
+                ),
+                "error"
+            );
+            return;
+        }
+    };
+
+    if replaying {
+        // Filled and settled once the replay feeds the next slot
+        match place_replayed_order(intent, ctx, budget, admission).await {
+            Ok(()) => return,
+            Err(e) => log!(
+                format!("🔴 Paper fill on {} failed: {:#}", intent.pool, e),
+                "error"
+            ),
+        }
+    } else if let Err(e) = paper_execute(intent, ctx, budget).await {
+        log!(
+            format!("🔴 Paper fill on {} failed: {:#}", intent.pool, e),
+            "error"
+        );
+    }
+    ctx.risk.settle(&ctx.config.breaker, intent, admission, &[]);
+}
+
+/// Builds the swap of `intent` and what the pre-flight simulation must see
+/// it pay into the wallet.
+async fn plan_swap(
+    intent: &TradeIntent,
+    ctx: &ExecutionContext<'_>,
+) -> Result<(Instruction, PreflightCheck)> {
+    let pool_info = recorded_pool_info(&intent.pool)?;
+    let (ix, min_out) = build_intent_ix(intent, &pool_info, ctx).await?;
+
+    let payer = ctx.payer.pubkey();
+    let output_account = if is_sell(&intent.kind) {
+        get_associated_token_address(&payer, &Pubkey::from_str_const(NATIVE_MINT))
+    } else {
+        let (base_token_program, quote_token_program) = pool_info.mint_token_programs();
+        let token_program = if pool_info.base_mint == intent.mint.to_string() {
+            base_token_program
+        } else {
+            quote_token_program
+        };
+        get_associated_token_address_with_program_id(&payer, &intent.mint, &token_program)
+    };
+
+    Ok((
+        ix,
+        PreflightCheck {
+            dex: intent.dex,
+            output_account,
+            min_out,
+        },
+    ))
+}
+
+/// The swap instruction of `intent` and the least it pays into the wallet.
+async fn build_intent_ix(
+    intent: &TradeIntent,
+    pool_info: &TokenListInfos,
+    ctx: &ExecutionContext<'_>,
+) -> Result<(Instruction, u64)> {
+    let native_mint = Pubkey::from_str_const(NATIVE_MINT);
+    let payer = &ctx.payer.pubkey();
+    let (base_token_program, quote_token_program) = pool_info.mint_token_programs();
+
+    let (amount_in, amount_out) = match intent.dex {
+        Dex::OrcaWhirlpool => return whirlpool_swap_ix(intent, pool_info, ctx).await,
+        Dex::MeteoraDlmm => return dlmm_swap_ix(intent, pool_info, ctx).await,
+        Dex::RaydiumAmm | Dex::RaydiumCpmm | Dex::PumpSwap => {
+            let quote = constant_product_quote(intent, pool_info)?;
+            swap_limits(intent, &quote, pool_info)
+        }
+    };
+
+    let ix = match (intent.dex, &intent.kind) {
+        (Dex::RaydiumAmm, IntentKind::BuyExactOut { .. }) => {
+            let (coin_vault, pc_vault) = get_swap_keys(&intent.pool);
+
+            build_amm_swap_base_out(RayAMMSwapBaseOutParams {
+                max_amount_in: amount_in,
+                amount_out,
+                pool_id: intent.pool,
+                coin_vault,
+                pc_vault,
+                input_mint: native_mint,
+                output_mint: intent.mint,
+                payer: *payer,
+            })
+        }
+        (Dex::RaydiumAmm, IntentKind::Buy { .. }) => raydium_swap_base_in(
+            intent,
+            payer,
+            (amount_in, amount_out),
+            native_mint,
+            intent.mint,
+        ),
+        (Dex::RaydiumAmm, IntentKind::Sell { .. }) => raydium_swap_base_in(
+            intent,
+            payer,
+            (amount_in, amount_out),
+            intent.mint,
+            native_mint,
+        ),
+        // PumpSwap `buy` is exact-out: a plain `Buy` asks for its minimum
+        // token amount (as sent by the pool) and caps the spend at its size
+        (Dex::PumpSwap, IntentKind::Buy { .. } | IntentKind::BuyExactOut { .. }) => {
+            let base_amount_out = match (&intent.kind, &pool_info.transfer_fee) {
+                (IntentKind::Buy { .. }, Some(fee)) => fee.amount_before_fee(amount_out),
+                _ => amount_out,
+            };
+
+            build_pumpswap_buy(PumpSwapBuyParams {
+                max_quote_amount_in: amount_in,
+                base_amount_out,
+                pool_id: intent.pool,
+                base_mint: intent.mint,
+                quote_mint: native_mint,
+                base_token_program,
+                quote_token_program,
+                payer: *payer,
+            })
+        }
+        (Dex::PumpSwap, IntentKind::Sell { .. }) => build_pumpswap_sell(PumpSwapSellParams {
+            base_amount_in: amount_in,
+            min_quote_amount_out: amount_out,
+            pool_id: intent.pool,
+            base_mint: intent.mint,
+            quote_mint: native_mint,
+            base_token_program,
+            quote_token_program,
+            payer: *payer,
+        }),
+        (Dex::RaydiumCpmm, kind) => {
+            return cpmm_swap_ix(intent, kind, (amount_in, amount_out), pool_info, payer)
+                .map(|ix| (ix, amount_out))
+        }
+        (Dex::OrcaWhirlpool | Dex::MeteoraDlmm, _) => unreachable!("built above"),
+    };
+
+    Ok((ix, amount_out))
+}
+
+fn raydium_swap_base_in(
+    intent: &TradeIntent,
+    payer: &Pubkey,
+    (amount_in, minimum_amount_out): (u64, u64),
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+) -> Instruction {
+    let (coin_vault, pc_vault) = get_swap_keys(&intent.pool);
+
+    build_amm_swap_base_in(RayAMMSwapBaseInParams {
+        amount_in,
+        minimum_amount_out,
+        pool_id: intent.pool,
+        coin_vault,
+        pc_vault,
+        input_mint,
+        output_mint,
+        payer: *payer,
+    })
+}
+
+/// CPMM swap from the keys recorded by pre_start: exact-in for `Buy` / `Sell`,
+/// exact-out for `BuyExactOut`.
+fn cpmm_swap_ix(
+    intent: &TradeIntent,
+    kind: &IntentKind,
+    (amount_in, amount_out): (u64, u64),
+    pool_info: &TokenListInfos,
+    payer: &Pubkey,
+) -> Result<Instruction> {
+    let cpmm = pool_info
+        .cpmm
+        .as_ref()
+        .context("no CPMM keys recorded, run pre_start for this pool")?;
+
+    let native_mint = Pubkey::from_str_const(NATIVE_MINT);
+    let base_mint: Pubkey = pool_info.base_mint.parse()?;
+    let base = (
+        pool_info.base_vault_b64.parse()?,
+        base_mint,
+        cpmm.base_token_program.parse()?,
+    );
+    let quote = (
+        pool_info.quote_vault_b64.parse()?,
+        pool_info.quote_mint.parse()?,
+        cpmm.quote_token_program.parse()?,
+    );
+
+    // (vault, mint, token program) of the side we pay with, then the side we receive
+    let (
+        (input_vault, input_mint, input_token_program),
+        (output_vault, output_mint, output_token_program),
+    ) = if (base_mint == native_mint) != is_sell(kind) {
+        (base, quote)
+    } else {
+        (quote, base)
+    };
+
+    let param = RayCpmmSwapParams {
+        amount_in,
+        amount_out,
+        pool_id: intent.pool,
+        amm_config: cpmm.amm_config.parse()?,
+        observation_state: cpmm.observation_state.parse()?,
+        input_vault,
+        output_vault,
+        input_mint,
+        output_mint,
+        input_token_program,
+        output_token_program,
+        payer: *payer,
+    };
+
+    Ok(match kind {
+        IntentKind::BuyExactOut { .. } => build_cpmm_swap_base_output(param),
+        _ => build_cpmm_swap_base_input(param),
+    })
+}
+
+/// Whirlpool swap against the live pool: the tick arrays follow the current
+/// tick, and the quote rejects trades that would run past them.
+async fn whirlpool_swap_ix(
+    intent: &TradeIntent,
+    pool_info: &TokenListInfos,
+    ctx: &ExecutionContext<'_>,
+) -> Result<(Instruction, u64)> {
+    let keys = pool_info
+        .whirlpool
+        .as_ref()
+        .context("no Whirlpool keys recorded, run pre_start for this pool")?;
+
+    let token_mint_a: Pubkey = pool_info.base_mint.parse()?;
+    let a_to_b = (token_mint_a == Pubkey::from_str_const(NATIVE_MINT)) != is_sell(&intent.kind);
+    let amount_specified_is_input = !matches!(intent.kind, IntentKind::BuyExactOut { .. });
+
+    let (state, quote) = whirlpool_quote(intent, pool_info, &ctx.client).await?;
+    let (amount_in, amount_out) = swap_limits(intent, &quote, pool_info);
+    let (amount, other_amount_threshold) = if amount_specified_is_input {
+        (amount_in, amount_out)
+    } else {
+        (amount_out, amount_in)
+    };
+    let (token_program_a, token_program_b) = pool_info.mint_token_programs();
+
+    let ix = build_whirlpool_swap(WhirlpoolSwapParams {
+        amount,
+        other_amount_threshold,
+        sqrt_price_limit: if a_to_b {
+            WHIRLPOOL_MIN_SQRT_PRICE
+        } else {
+            WHIRLPOOL_MAX_SQRT_PRICE
+        },
+        amount_specified_is_input,
+        a_to_b,
+        pool_id: intent.pool,
+        token_mint_a,
+        token_mint_b: pool_info.quote_mint.parse()?,
+        token_vault_a: pool_info.base_vault_b64.parse()?,
+        token_vault_b: pool_info.quote_vault_b64.parse()?,
+        token_program_a,
+        token_program_b,
+        tick_arrays: state.tick_array_addresses,
+        oracle: keys.oracle.parse()?,
+        payer: ctx.payer.pubkey(),
+    });
+
+    Ok((ix, amount_out))
+}
+
+/// DLMM swap against the live pair: the bin arrays follow the active bin, and
+/// the quote rejects trades that would run past them.
+async fn dlmm_swap_ix(
+    intent: &TradeIntent,
+    pool_info: &TokenListInfos,
+    ctx: &ExecutionContext<'_>,
+) -> Result<(Instruction, u64)> {
+    let keys = pool_info
+        .dlmm
+        .as_ref()
+        .context("no DLMM keys recorded, run pre_start for this pool")?;
+
+    let token_x_mint: Pubkey = pool_info.base_mint.parse()?;
+    let swap_for_y = (token_x_mint == Pubkey::from_str_const(NATIVE_MINT)) != is_sell(&intent.kind);
+    let exact_in = !matches!(intent.kind, IntentKind::BuyExactOut { .. });
+
+    let (state, quote) = dlmm_quote(intent, pool_info, &ctx.client).await?;
+    let (amount_in, amount_out) = swap_limits(intent, &quote, pool_info);
+    let (token_x_program, token_y_program) = pool_info.mint_token_programs();
+
+    let param = DlmmSwapParams {
+        amount_in,
+        amount_out,
+        swap_for_y,
+        lb_pair: intent.pool,
+        reserve_x: pool_info.base_vault_b64.parse()?,
+        reserve_y: pool_info.quote_vault_b64.parse()?,
+        token_x_mint,
+        token_y_mint: pool_info.quote_mint.parse()?,
+        token_x_program,
+        token_y_program,
+        oracle: keys.oracle.parse()?,
+        bin_arrays: state.bin_array_addresses,
+        payer: ctx.payer.pubkey(),
+    };
+
+    let ix = if exact_in {
+        build_dlmm_swap(param)
+    } else {
+        build_dlmm_swap_exact_out(param)
+    };
+
+    Ok((ix, amount_out))
+}
+
+/// Quote of `intent` from the engine its swap is built with: against the
+/// reserves the intent expects on constant-product pools, against the live
+/// pool on Whirlpool and DLMM.
+pub(crate) async fn quote_intent(
+    intent: &TradeIntent,
+    pool_info: &TokenListInfos,
+    client: &RpcClient,
+) -> Result<SwapQuote> {
+    match intent.dex {
+        Dex::OrcaWhirlpool => Ok(whirlpool_quote(intent, pool_info, client).await?.1),
+        Dex::MeteoraDlmm => Ok(dlmm_quote(intent, pool_info, client).await?.1),
+        Dex::RaydiumAmm | Dex::RaydiumCpmm | Dex::PumpSwap => {
+            constant_product_quote(intent, pool_info)
+        }
+    }
+}
+
+async fn whirlpool_quote(
+    intent: &TradeIntent,
+    pool_info: &TokenListInfos,
+    client: &RpcClient,
+) -> Result<(WhirlpoolSwapState, SwapQuote)> {
+    let kind = &intent.kind;
+    let token_mint_a: Pubkey = pool_info.base_mint.parse()?;
+    let a_to_b = (token_mint_a == Pubkey::from_str_const(NATIVE_MINT)) != is_sell(kind);
+
+    let state = fetch_whirlpool_swap_state(client, &intent.pool, a_to_b).await?;
+    let quote = quote_whirlpool_swap(
+        &state.pool,
+        &state.tick_arrays,
+        pool_side_amount(kind, pool_info, specified_amount(kind)),
+        !matches!(kind, IntentKind::BuyExactOut { .. }),
+        a_to_b,
+    )?;
+    log_quote(intent, &quote, pool_info);
+
+    Ok((state, quote))
+}
+
+async fn dlmm_quote(
+    intent: &TradeIntent,
+    pool_info: &TokenListInfos,
+    client: &RpcClient,
+) -> Result<(DlmmSwapState, SwapQuote)> {
+    let kind = &intent.kind;
+    let token_x_mint: Pubkey = pool_info.base_mint.parse()?;
+    let swap_for_y = (token_x_mint == Pubkey::from_str_const(NATIVE_MINT)) != is_sell(kind);
+
+    let state = fetch_dlmm_swap_state(client, &intent.pool, swap_for_y).await?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default();
+    let quote = quote_dlmm_swap(
+        &state.lb_pair,
+        &state.bin_arrays,
+        pool_side_amount(kind, pool_info, specified_amount(kind)),
+        !matches!(kind, IntentKind::BuyExactOut { .. }),
+        swap_for_y,
+        now,
+    )?;
+    log_quote(intent, &quote, pool_info);
+
+    Ok((state, quote))
+}
+
+pub(crate) fn recorded_pool_info(pool: &Pubkey) -> Result<TokenListInfos> {
+    load_token_info(&pool.to_string())
+        .map_err(|e| anyhow!("no keys recorded ({}), run pre_start for this pool", e))
+}
+
+/// The token amount the pool itself swaps once Token-2022 transfer fees are
+/// taken: less than what we send on sells, more than what we receive on
+/// `BuyExactOut`.
+fn pool_side_amount(kind: &IntentKind, pool_info: &TokenListInfos, amount: u64) -> u64 {
+    match (kind, &pool_info.transfer_fee) {
+        (IntentKind::Sell { .. }, Some(fee)) => fee.amount_after_fee(amount),
+        (IntentKind::BuyExactOut { .. }, Some(fee)) => fee.amount_before_fee(amount),
+        _ => amount,
+    }
+}
+
+/// Quote of a constant-product intent against the reserves it expects, with
+/// the fees recorded by pre_start.
+pub(crate) fn constant_product_quote(
+    intent: &TradeIntent,
+    pool_info: &TokenListInfos,
+) -> Result<SwapQuote> {
+    let fees = pool_info
+        .fees
+        .context("no pool fees recorded, run pre_start for this pool")?;
+    let amount = pool_side_amount(&intent.kind, pool_info, specified_amount(&intent.kind));
+
+    let quote = match intent.kind {
+        IntentKind::Buy { .. } => fees.quote_exact_in(&intent.reserves, amount, true),
+        IntentKind::Sell { .. } => fees.quote_exact_in(&intent.reserves, amount, false),
+        IntentKind::BuyExactOut { .. } => fees.quote_exact_out(&intent.reserves, amount, true),
+    }?;
+    log_quote(intent, &quote, pool_info);
+
+    Ok(quote)
+}
+
+fn log_quote(intent: &TradeIntent, quote: &SwapQuote, pool_info: &TokenListInfos) {
+    let received = match (&intent.kind, &pool_info.transfer_fee) {
+        (IntentKind::Sell { .. }, _) | (_, None) => String::new(),
+        (_, Some(fee)) => format!(
+            " ({} after transfer fee)",
+            fee.amount_after_fee(quote.amount_out)
+        ),
+    };
+    log!(
+        format!(
+            "{} quote : {} in, {} out{}, {} fee",
+            intent.dex, quote.amount_in, quote.amount_out, received, quote.fee_amount
+        ),
+        "info"
+    );
+}
+
+pub(crate) fn is_sell(kind: &IntentKind) -> bool {
+    matches!(kind, IntentKind::Sell { .. })
+}
+
+/// Raw amount an intent fixes: lamports spent by `Buy`, tokens sold by `Sell`,
+/// tokens received by `BuyExactOut`.
+fn specified_amount(kind: &IntentKind) -> u64 {
+    match *kind {
+        IntentKind::Buy { sol_amount, .. } => sol_to_lamports(sol_amount),
+        IntentKind::BuyExactOut { token_amount, .. } | IntentKind::Sell { token_amount } => {
+            token_amount
+        }
+    }
+}
+
+/// Raw (input, output) bounds the swap is submitted with: the amount in and
+/// the quoted output less the intent's slippage for exact-in swaps, the
+/// quoted input plus slippage (capped by the intent) and amount out for
+/// `BuyExactOut`. Outputs are what reaches the wallet after transfer fees.
+pub(crate) fn swap_limits(
+    intent: &TradeIntent,
+    quote: &SwapQuote,
+    pool_info: &TokenListInfos,
+) -> (u64, u64) {
+    let slippage_pct = intent.slippage_pct;
+
+    let (amount_in, amount_out, bound) = match intent.kind {
+        IntentKind::Buy { sol_amount, .. } => {
+            let received = match &pool_info.transfer_fee {
+                Some(fee) => fee.amount_after_fee(quote.amount_out),
+                None => quote.amount_out,
+            };
+            let min_amount_out = calc_min_amount_out(received, slippage_pct);
+            (
+                sol_to_lamports(sol_amount),
+                min_amount_out,
+                format!("min out {}", min_amount_out),
+            )
+        }
+        IntentKind::Sell { token_amount } => {
+            let min_amount_out = calc_min_amount_out(quote.amount_out, slippage_pct);
+            (
+                token_amount,
+                min_amount_out,
+                format!("min out {}", min_amount_out),
+            )
+        }
+        IntentKind::BuyExactOut {
+            token_amount,
+            max_sol_amount,
+        } => {
+            let max_amount_in = calc_max_amount_in(quote.amount_in, slippage_pct)
+                .min(sol_to_lamports(max_sol_amount));
+            (
+                max_amount_in,
+                token_amount,
+                format!("max in {}", max_amount_in),
+            )
+        }
+    };
+
+    log!(
+        format!(
+            "{} {} : {} (slippage {}%)",
+            intent.dex, intent.pool, bound, slippage_pct
+        ),
+        "info"
+    );
+
+    (amount_in, amount_out)
+}
@@ -1,722 +1,192 @@
-use std::{collections::HashSet, sync::Arc};
+use std::sync::Arc;
 
-use crate::{RAY_AMM_ID_PUBKEY, PUMP_SWAP_ID_PUBKEY, RAY_AMM_AUTH_PUBKEY};
 use crate::*;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    hash::Hash, native_token::sol_to_lamports, pubkey::Pubkey, signature::Keypair, signer::Signer,
-};
+use solana_sdk::{hash::Hash, signature::Keypair};
 use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
 
+/// Decodes the swaps of a streamed transaction, runs the enabled strategies on
+/// each of them and executes the resulting intents.
+#[allow(clippy::too_many_arguments)]
 pub async fn swap_handler(
     non_blocking_client: Arc<RpcClient>,
-    enermy_list: Vec<String>,
+    live: Arc<LiveConfig>,
+    payer_keypair: Arc<Keypair>,
+    pool_cache: Arc<PoolStateCache>,
+    risk: Arc<RiskManager>,
+    confirmations: Arc<ConfirmationService>,
+    fees: Arc<PriorityFeeEstimator>,
     transaction_update: &SubscribeUpdateTransaction,
-    signer_key: String,
-    next_key: &str,
-    nozomi_key: &str,
-    blox_auth_header: &str,
-    zero_slot_key: &str,
     sol_price: f64,
-    show_buy: bool,
-    show_sell: bool,
-    env_max_amount: f64,
-    env_amount_in_factor_low: f64,
-    env_amount_in_factor_median: f64,
-    env_amount_in_factor_high: f64,
-    env_tip_min: f64,
-    env_tip_factor_low: f64,
-    env_tip_factor_median: f64,
-    env_tip_factor_high: f64,
-    env_tip_factor_ultra: f64,
-    max_sol_amount: f64,
-    is_submit_tx: bool,
-    on_debug: bool,
-    is_racing: bool,
-    env_acceptable_liquidity: u64,
-    take_profit_pcnt: f64,
 ) {
-    // Convert enemy list to HashSet for O(1) lookups
-    let enemy_set: HashSet<String> = enermy_list.into_iter().collect();
-    
-    // Cache Keypair parsing to avoid repeated base58 decoding
-    let payer_keypair = Arc::new(Keypair::from_base58_string(&signer_key));
-    let payer_pubkey = payer_keypair.pubkey();
-    
-    if let Some(transaction) = &transaction_update.transaction {
-        if let Some(transaction_message) = &transaction.transaction {
-            if let Some(message) = &transaction_message.message {
-                let mut is_enermy: bool = false;
-                let mut enemy_pubkey: Option<Pubkey> = None;
+    let config = &live.config;
+    let mode = &config.mode;
 
-                if let Some(first_key) = message.account_keys.first() {
-                    if let Ok(pubkey) = Pubkey::try_from(first_key.as_slice()) {
-                        // Check if pubkey is in enemy_set (O(1) lookup)
-                        if enemy_set.contains(&pubkey.to_string()) {
-                            is_enermy = true;
-                            enemy_pubkey = Some(pubkey);
-                        }
-                    }
-                }
-
-                for (_, instruction) in message.instructions.iter().enumerate() {
-                    match message
-                        .account_keys
-                        .get(instruction.program_id_index as usize)
-                    {
-                        Some(program_key) => {
-                            if let Ok(pubkey) = Pubkey::try_from(program_key.as_slice()) {
-                                if pubkey == RAY_AMM_ID_PUBKEY {
-                                    if !(instruction.data[0] == 9 || instruction.data[0] == 11) {
-                                        return;
-                                    }
-
-                                    if is_enermy {
-                                        log!("Enermy Raydium Trading Detected ... ", "enermy");
-                                        if let Some(enemy) = enemy_pubkey {
-                                            log!(format!("Enermy Address : {}", enemy), "enermy");
-                                        }
-                                    } else {
-                                        log!("Raydium Trading Detected ... ", "info");
-                                    }
-
-                                    if let Some(meta) = &transaction.meta {
-                                        log!(
-                                            format!(
-                                                "Signature: {}",
-                                                scan!(
-                                                    bs58::encode(&transaction.signature)
-                                                        .into_string(),
-                                                    "solscan"
-                                                )
-                                            ),
-                                            "info"
-                                        );
-
-                                        let (
-                                            ray_auth_pre_token_balance,
-                                            ray_auth_post_token_balance,
-                                        ) = get_pre_post_token_balance(&meta, RAY_AMM_AUTH);
-
-                                        if on_debug {
-                                            log!(format!("Program: {}", pubkey), "info");
-                                            display_balance_change(
-                                                &meta,
-                                                &ray_auth_pre_token_balance,
-                                                &ray_auth_post_token_balance,
-                                                RAY_AMM_AUTH,
-                                            );
-                                        }
-
-                                        for i in 0..meta
-                                            .pre_token_balances
-                                            .len()
-                                            .min(ray_auth_post_token_balance.len())
-                                        {
-                                            let pre_token_amount = ray_auth_pre_token_balance[i]
-                                                .ui_token_amount
-                                                .as_ref()
-                                                .map(|amt| amt.ui_amount)
-                                                .unwrap_or(0.0);
-                                            let pre_token_owner =
-                                                &ray_auth_pre_token_balance[i].owner;
-                                            let post_token_amount = ray_auth_post_token_balance[i]
-                                                .ui_token_amount
-                                                .as_ref()
-                                                .map(|amt| amt.ui_amount)
-                                                .unwrap_or(0.0);
-                                            let token_mint = &ray_auth_post_token_balance[i].mint;
-
-                                            if pre_token_amount != post_token_amount
-                                                && pre_token_owner == RAY_AMM_AUTH
-                                                && token_mint == NATIVE_MINT
-                                            {
-                                                //  Proceed Buy Transaction upon massive sell on Raydium
-                                                let second_account_idx = instruction.accounts[1];
-                                                if let Some(key) = message
-                                                    .account_keys
-                                                    .get(second_account_idx as usize)
-                                                {
-                                                    if let Ok(pool_id) =
-                                                        Pubkey::try_from(key.as_slice())
-                                                    {
-                                                        let (coin_vault, pc_vault) =
-                                                            get_swap_keys(&pool_id);
-
-                                                        let (
-                                                            price_impact_pct,
-                                                            current_liquidity,
-                                                            sol_amount,
-                                                            mint_addr,
-                                                            _,
-                                                            pool_ui_amount_in,
-                                                            pool_ui_token_amount_out,
-                                                        ) = get_price_impact(
-                                                            &ray_auth_pre_token_balance,
-                                                            &ray_auth_post_token_balance,
-                                                            RAY_AMM_AUTH,
-                                                            sol_price,
-                                                        );
-                                                        if (post_token_amount as f64)
-                                                            - (pre_token_amount as f64)
-                                                            > 0.0
-                                                        {
-                                                            if show_buy {
-                                                                log!("\t==> BUY Trade ==", "info");
-
-                                                                let (pnl, sell_token_amount_dec , token_inventory) =
-                                                                    calc_pnl(
-                                                                        &pool_id.to_string(),
-                                                                        pool_ui_amount_in,
-                                                                        pool_ui_token_amount_out,
-                                                                    );
-
-                                                                if take_profit_pcnt > pnl || token_inventory <= 0 {
-                                                                    return;
-                                                                }
-
-                                                                if is_submit_tx {
-                                                                    let buy_param = RayAMMSwapBaseInParams {
-                                                                            amount_in: sell_token_amount_dec,
-                                                                            minimum_amount_out: 1_u64,
-                                                                            pool_id: pool_id,
-                                                                            coin_vault: coin_vault,
-                                                                            pc_vault: pc_vault,
-                                                                            input_mint: Pubkey::from_str_const(
-                                                                                mint_addr.as_str(),
-                                                                            ),
-                                                                            output_mint: Pubkey::from_str_const(
-                                                                                NATIVE_MINT,
-                                                                            ),
-                                                                            payer: payer_pubkey,
-                                                                        };
-
-                                                                    let buy_ix =
-                                                                        build_amm_swap_base_in(
-                                                                            buy_param,
-                                                                        );
-
-                                                                    let payer_key = Arc::clone(&payer_keypair);
-
-                                                                    // Decode from base58
-                                                                    let decoded: Vec<u8> =
-                                                                        bs58::encode(
-                                                                            &transaction.signature,
-                                                                        )
-                                                                        .into_vec();
-
-                                                                    // Take the first 8 bytes and convert to u64
-                                                                    let num = u64::from_le_bytes(
-                                                                            decoded[..8].try_into().expect(
-                                                                                "Slice with incorrect length",
-                                                                            ),
-                                                                        );
-
-                                                                    if is_racing {
-                                                                        let _ = multi_submit(
-                                                                                non_blocking_client.clone(),
-                                                                                payer_key.clone(),
-                                                                                buy_ix,
-                                                                                0.0005,
-                                                                                Hash::new(
-                                                                                    &message.recent_blockhash,
-                                                                                ),
-                                                                                num,
-                                                                                next_key,
-                                                                                nozomi_key,
-                                                                                blox_auth_header,
-                                                                                zero_slot_key,
-                                                                                false
-                                                                            )
-                                                                            .await;
-                                                                    } else {
-                                                                        build_and_submit_pure_nozomi(
-                                                                                &payer_key,
-                                                                                &buy_ix,
-                                                                                0.0005,
-                                                                                &nozomi_key,
-                                                                                Hash::new(
-                                                                                    &message.recent_blockhash,
-                                                                                ),
-                                                                                non_blocking_client.clone(),
-                                                                                false
-                                                                            )
-                                                                            .await
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                return;
-                                                            }
-                                                        } else {
-                                                            if show_sell {
-                                                                log!("\t==> SELL Trade ==", "info");
-
-                                                                let (amount_factor, tip_factor, _) =
-                                                                    dump_setup(
-                                                                        price_impact_pct,
-                                                                        current_liquidity,
-                                                                        sol_price,
-                                                                        env_max_amount,
-                                                                        env_amount_in_factor_low,
-                                                                        env_amount_in_factor_median,
-                                                                        env_amount_in_factor_high,
-                                                                        env_tip_min,
-                                                                        env_tip_factor_low,
-                                                                        env_tip_factor_median,
-                                                                        env_tip_factor_high,
-                                                                        env_tip_factor_ultra,
-                                                                        on_debug,
-                                                                    );
-                                                                if amount_factor > 0.0 {
-                                                                    let buy_amount =
-                                                                        if (amount_factor
-                                                                            * sol_amount)
-                                                                            / 100.0
-                                                                            > max_sol_amount
-                                                                        {
-                                                                            max_sol_amount
-                                                                        } else {
-                                                                            (amount_factor
-                                                                                * sol_amount)
-                                                                                / 100.0
-                                                                        };
-
-                                                                    let tip_amount = (buy_amount
-                                                                        * tip_factor)
-                                                                        / 100.0;
-
-                                                                    log!(
-                                                                            format!(
-                                                                                "RAY_AMM Buy Sol Amount {} , Tip Sol Amount {}",
-                                                                                buy_amount, tip_amount
-                                                                            ),
-                                                                            "result"
-                                                                        );
-
-                                                                    if is_submit_tx {
-                                                                        let buy_param = RayAMMSwapBaseInParams {
-                                                                                amount_in: sol_to_lamports(buy_amount),
-                                                                                minimum_amount_out: 1_u64,
-                                                                                pool_id: pool_id,
-                                                                                coin_vault: coin_vault,
-                                                                                pc_vault: pc_vault,
-                                                                                input_mint: Pubkey::from_str_const(
-                                                                                    NATIVE_MINT,
-                                                                                ),
-                                                                                output_mint: Pubkey::from_str_const(
-                                                                                    mint_addr.as_str(),
-                                                                                ),
-                                                                                payer: payer_pubkey,
-                                                                            };
-
-                                                                        let buy_ix =
-                                                                            build_amm_swap_base_in(
-                                                                                buy_param,
-                                                                            );
-
-                                                                        let payer_key = Arc::clone(&payer_keypair);
-
-                                                                        // Decode from base58
-                                                                        let decoded: Vec<u8> =
-                                                                            bs58::encode(
-                                                                                &transaction
-                                                                                    .signature,
-                                                                            )
-                                                                            .into_vec();
-
-                                                                        // Take the first 8 bytes and convert to u64
-                                                                        let num = u64::from_le_bytes(
-                                                                                decoded[..8].try_into().expect(
-                                                                                    "Slice with incorrect length",
-                                                                                ),
-                                                                            );
-
-                                                                        if is_racing {
-                                                                            let _ = multi_submit(
-                                                                                    non_blocking_client.clone(),
-                                                                                    payer_key.clone(),
-                                                                                    buy_ix,
-                                                                                    tip_amount,
-                                                                                    Hash::new(
-                                                                                        &message.recent_blockhash,
-                                                                                    ),
-                                                                                    num,
-                                                                                    next_key,
-                                                                                    nozomi_key,
-                                                                                    blox_auth_header,
-                                                                                    zero_slot_key,
-                                                                                    true
-                                                                                )
-                                                                                .await;
-                                                                        } else {
-                                                                            build_and_submit_pure_nozomi(
-                                                                                    &payer_key,
-                                                                                    &buy_ix,
-                                                                                    tip_amount,
-                                                                                    &nozomi_key,
-                                                                                    Hash::new(
-                                                                                        &message.recent_blockhash,
-                                                                                    ),
-                                                                                    non_blocking_client.clone(),
-                                                                                    true
-                                                                                )
-                                                                                .await
-                                                                        }
-                                                                    }
-                                                                };
-                                                            } else {
-                                                                log!(
-                                                                    format!(
-                                                                        "  Second Account: {}",
-                                                                        hex::encode(key)
-                                                                    ),
-                                                                    "info"
-                                                                )
-                                                            }
-                                                        }
-                                                    } else {
-                                                        return;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                } else if pubkey == PUMP_SWAP_ID_PUBKEY {
-                                    if !(instruction.data[0] == 102 || instruction.data[0] == 51) {
-                                        return;
-                                    }
-
-                                    if is_enermy {
-                                        log!("Enermy Pumpswap Trading Detected ... ", "enermy");
-                                        if let Some(enemy) = enemy_pubkey {
-                                            log!(format!("Enermy Address : {}", enemy), "enermy");
-                                        }
-                                    } else {
-                                        log!("Pumpswap Trading Detected ... ", "info");
-                                    }
-
-                                    let extracted_keys = get_pumpswap_keys(transaction);
-
-                                    if let Some(meta) = &transaction.meta {
-                                        log!(
-                                            format!(
-                                                "Signature: {}",
-                                                scan!(
-                                                    bs58::encode(&transaction.signature)
-                                                        .into_string(),
-                                                    "solscan"
-                                                )
-                                            ),
-                                            "info"
-                                        );
-
-                                        let (
-                                            pumpswap_pool_pre_token_balance,
-                                            pumpswap_pool_post_token_balance,
-                                        ) = get_pre_post_token_balance(
-                                            &meta,
-                                            &extracted_keys[0].to_string(),
-                                        );
-
-                                        if on_debug {
-                                            log!(format!("Program: {}", pubkey), "info");
-                                            display_balance_change(
-                                                &meta,
-                                                &pumpswap_pool_pre_token_balance,
-                                                &pumpswap_pool_post_token_balance,
-                                                &extracted_keys[0].to_string(),
-                                            );
-                                        }
-                                        for i in 0..meta
-                                            .pre_token_balances
-                                            .len()
-                                            .min(pumpswap_pool_post_token_balance.len())
-                                        {
-                                            let pre_token_amount = pumpswap_pool_pre_token_balance
-                                                [i]
-                                                .ui_token_amount
-                                                .as_ref()
-                                                .map(|amt| amt.ui_amount)
-                                                .unwrap_or(0.0);
-                                            let pre_token_owner =
-                                                &pumpswap_pool_pre_token_balance[i].owner;
-                                            let post_token_amount =
-                                                pumpswap_pool_post_token_balance[i]
-                                                    .ui_token_amount
-                                                    .as_ref()
-                                                    .map(|amt| amt.ui_amount)
-                                                    .unwrap_or(0.0);
-                                            let token_mint =
-                                                &pumpswap_pool_post_token_balance[i].mint;
-
-                                            if pre_token_amount != post_token_amount
-                                                && pre_token_owner == &extracted_keys[0].to_string()
-                                                && token_mint == NATIVE_MINT
-                                            {
-                                                //  Proceed Buy Transaction upon massive sell on Pumpswap
-                                                let second_account_idx = instruction.accounts[0];
-                                                if let Some(key) = message
-                                                    .account_keys
-                                                    .get(second_account_idx as usize)
-                                                {
-                                                    if let Ok(pool_id) =
-                                                        Pubkey::try_from(key.as_slice())
-                                                    {
-                                                        let (
-                                                            price_impact_pct,
-                                                            current_liquidity,
-                                                            sol_amount,
-                                                            mint_addr,
-                                                            post_token_price_in_sol,
-                                                            pool_ui_amount_in,
-                                                            pool_ui_token_amount_out,
-                                                        ) = get_price_impact(
-                                                            &pumpswap_pool_pre_token_balance,
-                                                            &pumpswap_pool_post_token_balance,
-                                                            &extracted_keys[0].to_string(),
-                                                            sol_price,
-                                                        );
-                                                        if (post_token_amount as f64)
-                                                            - (pre_token_amount as f64)
-                                                            > 0.0
-                                                        {
-                                                            if show_buy {
-                                                                log!("\t==> BUY Trade ==", "info");
-
-                                                                let (pnl, sell_token_amount_dec , token_inventory) =
-                                                                    calc_pnl(
-                                                                        &pool_id.to_string(),
-                                                                        pool_ui_amount_in,
-                                                                        pool_ui_token_amount_out,
-                                                                    );
-
-                                                                if take_profit_pcnt > pnl || token_inventory <= 0{
-                                                                    return;
-                                                                }
-
-                                                                log!(
-                                                                    format!(
-                                                                        "Sell Token Amount {}",
-                                                                        sell_token_amount_dec
-                                                                    ),
-                                                                    "result"
-                                                                );
-
-                                                                if is_submit_tx {
-                                                                    let sell_param = PumpSwapSellParams {
-                                                                            base_amount_in: sell_token_amount_dec,
-                                                                            min_quote_amount_out: 1,
-                                                                            pool_id: pool_id,
-                                                                            base_mint: Pubkey::from_str_const(
-                                                                                mint_addr.as_str(),
-                                                                            ),
-                                                                            quote_mint: Pubkey::from_str_const(
-                                                                                NATIVE_MINT,
-                                                                            ),
-                                                                            base_token_program: spl_token::ID,
-                                                                            quote_token_program: spl_token::ID,
-                                                                            payer: payer_pubkey,
-                                                                        };
-
-                                                                    let sell_ix =
-                                                                        build_pumpswap_sell(
-                                                                            sell_param,
-                                                                        );
-
-                                                                    let payer_key = Arc::clone(&payer_keypair);
-
-                                                                    // Decode from base58
-                                                                    let decoded: Vec<u8> =
-                                                                        bs58::encode(
-                                                                            &transaction.signature,
-                                                                        )
-                                                                        .into_vec();
-
-                                                                    // Take the first 8 bytes and convert to u64
-                                                                    let num = u64::from_le_bytes(
-                                                                            decoded[..8].try_into().expect(
-                                                                                "Slice with incorrect length",
-                                                                            ),
-                                                                        );
-
-                                                                    if is_racing {
-                                                                        let _ = multi_submit(
-                                                                                non_blocking_client.clone(),
-                                                                                payer_key.clone(),
-                                                                                sell_ix,
-                                                                                0.0025,
-                                                                                Hash::new(
-                                                                                    &message.recent_blockhash,
-                                                                                ),
-                                                                                num,
-                                                                                next_key,
-                                                                                nozomi_key,
-                                                                                blox_auth_header,
-                                                                                zero_slot_key,
-                                                                                false
-                                                                            )
-                                                                            .await;
-                                                                    } else {
-                                                                        build_and_submit_pure_nozomi(
-                                                                                &payer_key,
-                                                                                &sell_ix,
-                                                                                0.0025,
-                                                                                &nozomi_key,
-                                                                                Hash::new(
-                                                                                    &message.recent_blockhash,
-                                                                                ),
-                                                                                non_blocking_client.clone(),
-                                                                                false
-                                                                            )
-                                                                            .await
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                return;
-                                                            }
-                                                        } else {
-                                                            if show_sell {
-                                                                log!("\t==> SELL Trade ==", "info");
-
-                                                                let (amount_factor, tip_factor, _) =
-                                                                    dump_setup(
-                                                                        price_impact_pct,
-                                                                        current_liquidity,
-                                                                        sol_price,
-                                                                        env_max_amount,
-                                                                        env_amount_in_factor_low,
-                                                                        env_amount_in_factor_median,
-                                                                        env_amount_in_factor_high,
-                                                                        env_tip_min,
-                                                                        env_tip_factor_low,
-                                                                        env_tip_factor_median,
-                                                                        env_tip_factor_high,
-                                                                        env_tip_factor_ultra,
-                                                                        on_debug,
-                                                                    );
-
-                                                                if amount_factor > 0.0 {
-                                                                    let buy_amount =
-                                                                        if (amount_factor
-                                                                            * sol_amount)
-                                                                            / 100.0
-                                                                            > max_sol_amount
-                                                                        {
-                                                                            max_sol_amount
-                                                                        } else {
-                                                                            (amount_factor
-                                                                                * sol_amount)
-                                                                                / 100.0
-                                                                        };
-
-                                                                    let tip_amount = (buy_amount
-                                                                        * tip_factor)
-                                                                        / 100.0;
-
-                                                                    let expected_token_amount =
-                                                                        buy_amount / (post_token_price_in_sol);
-
-                                                                    log!(
-                                                                        format!(
-                                                                            "Buy Sol Amount {} , Tip Sol Amount {}",
-                                                                            buy_amount, tip_amount
-                                                                        ),
-                                                                        "result"
-                                                                    );
-
-                                                                    if is_submit_tx {
-                                                                        let buy_param = PumpSwapBuyParams {
-                                                                            max_quote_amount_in: sol_to_lamports(
-                                                                                buy_amount * 1.01,
-                                                                            ),
-                                                                            base_amount_out: (expected_token_amount
-                                                                                * 1_000_000.0
-                                                                                / 1.01)
-                                                                                as u64,
-                                                                            pool_id: pool_id,
-                                                                            base_mint: Pubkey::from_str_const(
-                                                                                mint_addr.as_str(),
-                                                                            ),
-                                                                            quote_mint: Pubkey::from_str_const(
-                                                                                NATIVE_MINT,
-                                                                            ),
-                                                                            base_token_program: spl_token::ID,
-                                                                            quote_token_program: spl_token::ID,
-                                                                            payer: payer_pubkey,
-                                                                        };
-
-                                                                        let buy_ix =
-                                                                            build_pumpswap_buy(
-                                                                                buy_param,
-                                                                            );
+    let swaps = decode_swaps(transaction_update);
+    if swaps.is_empty() {
+        return;
+    }
 
-                                                                        let payer_key = Arc::clone(&payer_keypair);
+    let Some(recent_blockhash) = transaction_update
+        .transaction
+        .as_ref()
+        .and_then(|tx| tx.transaction.as_ref())
+        .and_then(|tx| tx.message.as_ref())
+        .and_then(|message| <[u8; 32]>::try_from(message.recent_blockhash.as_slice()).ok())
+        .map(Hash::new_from_array)
+    else {
+        return;
+    };
+
+    let exec_ctx = ExecutionContext {
+        client: non_blocking_client,
+        payer: payer_keypair,
+        config,
+        risk: &risk,
+        pool_cache: &pool_cache,
+        confirmations: &confirmations,
+        fees: &fees,
+        senders: &live.senders,
+        recent_blockhash,
+        // The first 8 bytes of the base58 signature tag our submissions
+        timestamp: swaps[0]
+            .signature
+            .as_bytes()
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .unwrap_or_default(),
+        trigger_slot: Some(transaction_update.slot),
+    };
+
+    let unit_price = observed_compute_unit_price(transaction_update);
+    for mut swap in swaps {
+        // Routed transactions can touch pools that were never prepared by pre_start
+        if !live.pools.contains_key(&swap.pool.to_string()) {
+            continue;
+        }
+        if let Some(unit_price) = unit_price {
+            fees.observe(swap.pool, swap.slot, unit_price);
+        }
+        note_mint_decimals(swap.mint, swap.token_decimals);
+
+        let token_info = load_token_info(&swap.pool.to_string()).ok();
+
+        // DLMM swap events carry bin ids only, the bin step was recorded by pre_start
+        if let Some(PoolPrice::ActiveBin {
+            bin_step: bin_step @ None,
+            ..
+        }) = &mut swap.pool_price
+        {
+            *bin_step = token_info
+                .as_ref()
+                .and_then(|info| info.dlmm.as_ref())
+                .map(|dlmm| dlmm.bin_step);
+        }
 
-                                                                        // Decode from base58
-                                                                        let decoded: Vec<u8> =
-                                                                            bs58::encode(
-                                                                                &transaction
-                                                                                    .signature,
-                                                                            )
-                                                                            .into_vec();
+        // Log the signal without holding up the strategies
+        let signal = swap.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = with_trade_store(|store| store.record_signal(&signal)) {
+                log!(format!("🔴 Failed to record signal: {}", e), "error");
+            }
+        });
+
+        // Check if the signer is in enemy_set (O(1) lookup)
+        let is_enermy = swap
+            .signer
+            .is_some_and(|signer| live.enemy_set.contains(&signer.to_string()));
+        let route = match (swap.routed, swap.is_exact_out()) {
+            (false, false) => "",
+            (false, true) => " (exact out)",
+            (true, false) => " (routed)",
+            (true, true) => " (routed, exact out)",
+        };
+
+        if is_enermy {
+            log!(
+                format!("Enermy {} Trading Detected{} ... ", swap.dex, route),
+                "enermy"
+            );
+            if let Some(enemy) = swap.signer {
+                log!(format!("Enermy Address : {}", enemy), "enermy");
+            }
+        } else {
+            log!(
+                format!("{} Trading Detected{} ... ", swap.dex, route),
+                "info"
+            );
+        }
 
-                                                                        // Take the first 8 bytes and convert to u64
-                                                                        let num = u64::from_le_bytes(
-                                                                            decoded[..8].try_into().expect(
-                                                                                "Slice with incorrect length",
-                                                                            ),
-                                                                        );
+        log!(
+            format!("Signature: {}", scan!(swap.signature, "solscan")),
+            "info"
+        );
+        log!(
+            format!(
+                "\t\tToken Mint Addr \t{}\n\t\tPre Token Price \t{} SOL\n\t\tPost Token Price \t{} SOL\n\t\tPrice Change Percent \t{} %\n\t\tCurrent Liquidity \t$ {}",
+                swap.mint,
+                swap.price_before(),
+                swap.price_after(),
+                swap.price_impact_pct(),
+                swap.liquidity_usd(sol_price)
+            ),
+            "info"
+        );
+        if mode.on_debug {
+            log!(format!("{:#?}", swap), "info");
+        }
 
-                                                                        if is_racing {
-                                                                            let _ = multi_submit(
-                                                                                non_blocking_client.clone(),
-                                                                                payer_key.clone(),
-                                                                                buy_ix,
-                                                                                tip_amount,
-                                                                                Hash::new(
-                                                                                    &message.recent_blockhash,
-                                                                                ),
-                                                                                num,
-                                                                                next_key,
-                                                                                nozomi_key,
-                                                                                blox_auth_header,
-                                                                                zero_slot_key,
-                                                                                true
-                                                                            )
-                                                                            .await;
-                                                                        } else {
-                                                                            build_and_submit_pure_nozomi(
-                                                                                &payer_key,
-                                                                                &buy_ix,
-                                                                                tip_amount,
-                                                                                &nozomi_key,
-                                                                                Hash::new(
-                                                                                    &message.recent_blockhash,
-                                                                                ),
-                                                                                non_blocking_client.clone(),
-                                                                                true
-                                                                            )
-                                                                            .await
-                                                                        }
-                                                                    }
-                                                                };
-                                                            }
-                                                        }
-                                                    } else {
-                                                        return;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            } else {
-                                log!(format!("  Program: {}", hex::encode(program_key)), "info");
-                            }
-                        }
-                        _ => (),
-                    }
+        // Account updates may already be past the observed transaction
+        let reserves = match pool_cache.snapshot(&swap.pool) {
+            Some(snapshot) if snapshot.slot >= swap.slot => {
+                if mode.on_debug {
+                    log!(
+                        format!(
+                            "Cached reserves at slot {} : {:?}",
+                            snapshot.slot, snapshot.reserves
+                        ),
+                        "info"
+                    );
+                }
+                snapshot.reserves
+            }
+            _ => swap.reserves_after,
+        };
+
+        let pool_addr = swap.pool.to_string();
+        let pool = live.pool_settings(&pool_addr);
+        let position = held_position(&pool_addr).unwrap_or_else(|e| {
+            log!(format!("🔴 {}", e), "error");
+            OpenPosition::default()
+        });
+        let ctx = StrategyContext {
+            config,
+            pool: &pool,
+            token: token_info.as_ref(),
+            reserves,
+            position: &position,
+            sol_price,
+        };
+
+        for strategy in live.strategies.iter() {
+            for intent in strategy.on_swap(&swap, &ctx) {
+                // The exit manager can't value these pools, its price rules would never fire
+                if !is_sell(&intent.kind)
+                    && matches!(intent.dex, Dex::OrcaWhirlpool | Dex::MeteoraDlmm)
+                    && pool.enable_sell
+                    && pool.exit.prices_positions()
+                {
+                    log!(
+                        format!(
+                            "⛔ {} entry on {} held back: {} positions can't be priced for the stop-loss, trailing stop or scale-out",
+                            intent.strategy, intent.pool, intent.dex
+                        ),
+                        "error"
+                    );
+                    continue;
                 }
+                execute_intent(&intent, &exec_ctx).await;
             }
         }
     }
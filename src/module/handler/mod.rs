@@ -1,3 +1,6 @@
+pub mod execute_intent;
+#[allow(clippy::module_inception)]
 pub mod handler;
 
+pub use execute_intent::*;
 pub use handler::*;
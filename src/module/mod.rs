@@ -1,8 +1,28 @@
+pub mod decode;
+pub mod exit;
+pub mod fees;
 pub mod filter;
 pub mod handler;
 pub mod monitor;
+pub mod paper;
+pub mod position;
+pub mod recording;
+pub mod risk;
+pub mod state;
+pub mod strategy;
+pub mod telemetry;
 pub mod tx_confirm;
+pub use decode::*;
+pub use exit::*;
+pub use fees::*;
 pub use filter::*;
 pub use handler::*;
 pub use monitor::*;
+pub use paper::*;
+pub use position::*;
+pub use recording::*;
+pub use risk::*;
+pub use state::*;
+pub use strategy::*;
+pub use telemetry::*;
 pub use tx_confirm::*;
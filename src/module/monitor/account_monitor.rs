@@ -1,8 +1,7 @@
 use {
-    crate::log,
+    crate::{log, PoolStateCache, StreamRecorder},
     anyhow::Result,
     futures::{sink::SinkExt, stream::StreamExt},
-    hex,
     log::error,
     solana_sdk::pubkey::Pubkey,
     std::{sync::Arc, time::Duration},
@@ -19,6 +18,8 @@ use {
 /// Manager for handling gRPC stream connections and account updates
 pub struct GrpcAccountStreamManager {
     client: GeyserGrpcClient<InterceptorXToken>,
+    pool_cache: Arc<PoolStateCache>,
+    recorder: Option<Arc<StreamRecorder>>,
     is_connected: bool,
     reconnect_attempts: u32,
     max_reconnect_attempts: u32,
@@ -26,33 +27,25 @@ pub struct GrpcAccountStreamManager {
 }
 
 impl GrpcAccountStreamManager {
-    /// Handles account update messages from the gRPC stream
-    /// This function can be customized based on your requirements:
-    /// - Store updates in a database
-    /// - Trigger specific actions based on account changes
-    /// - Filter for specific types of updates
-    /// - Transform data into your required format
+    /// Handles account update messages from the gRPC stream by decoding them
+    /// into the pool state cache
     ///
     /// # Arguments
     /// * `slot` - The slot number when the update occurred
-    /// * `account_info` - The account information containing all update details
+    /// * `account_info` - The account information containing all update details,
+    ///   its write version ordering the writes of one slot
     fn handle_account_update(
         &self,
         slot: u64,
         account_info: &yellowstone_grpc_proto::geyser::SubscribeUpdateAccountInfo,
     ) {
-        println!(
-            "ACCOUNT UPDATE RECEIVED:\nSlot: {}\nPubkey: {}\nLamports: {}\nOwner: {}\nData length: {}\nExecutable: {}\nWrite version: {}\n",
-            slot,
-            Pubkey::try_from(account_info.pubkey.clone()).expect("valid pubkey"),
-            account_info.lamports,
-            Pubkey::try_from(account_info.owner.clone()).expect("valid pubkey"),
-            account_info.data.len(),
-            account_info.executable,
-            account_info.write_version
-        );
-        if !account_info.data.is_empty() {
-            println!("Data (hex): {}", hex::encode(&account_info.data));
+        if let Ok(pubkey) = Pubkey::try_from(account_info.pubkey.as_slice()) {
+            self.pool_cache.apply(
+                &pubkey,
+                &account_info.data,
+                slot,
+                account_info.write_version,
+            );
         }
     }
 
@@ -61,9 +54,13 @@ impl GrpcAccountStreamManager {
     /// # Arguments
     /// * `endpoint` - The gRPC endpoint URL
     /// * `x_token` - Authentication token for the endpoint
+    /// * `pool_cache` - Cache the account updates are decoded into
+    /// * `recorder` - Keeps every received update for `replay`, when recording is on
     pub async fn new(
         endpoint: &str,
         x_token: &str,
+        pool_cache: Arc<PoolStateCache>,
+        recorder: Option<Arc<StreamRecorder>>,
     ) -> Result<Arc<Mutex<GrpcAccountStreamManager>>> {
         let interceptor = InterceptorXToken {
             x_token: Some(
@@ -88,6 +85,8 @@ impl GrpcAccountStreamManager {
 
         Ok(Arc::new(Mutex::new(GrpcAccountStreamManager {
             client,
+            pool_cache,
+            recorder,
             is_connected: false,
             reconnect_attempts: 0,
             max_reconnect_attempts: 10,
@@ -112,6 +111,9 @@ impl GrpcAccountStreamManager {
         while let Some(message) = stream.next().await {
             match message {
                 Ok(msg) => {
+                    if let Some(recorder) = &self.recorder {
+                        recorder.record(&msg);
+                    }
                     match msg.update_oneof {
                         Some(UpdateOneof::Account(account)) => {
                             if let Some(account_info) = account.account {
@@ -128,7 +130,7 @@ impl GrpcAccountStreamManager {
                         }
                         Some(UpdateOneof::Pong(_)) => {} // Ignore pong responses
                         _ => {
-                            println!("Other update received: {:?}", msg);
+                            log!(format!("Other update received: {:?}", msg), "info");
                         }
                     }
                 }
@@ -151,12 +153,15 @@ impl GrpcAccountStreamManager {
     /// * `request` - The original subscription request to reestablish the connection
     async fn reconnect(&mut self, request: SubscribeRequest) -> Result<()> {
         if self.reconnect_attempts >= self.max_reconnect_attempts {
-            println!("Max reconnection attempts reached");
+            log!("Max reconnection attempts reached", "error");
             return Ok(());
         }
 
         self.reconnect_attempts += 1;
-        println!("Reconnecting... Attempt {}", self.reconnect_attempts);
+        log!(
+            format!("Reconnecting... Attempt {}", self.reconnect_attempts),
+            "info"
+        );
 
         let backoff = self.reconnect_interval * std::cmp::min(self.reconnect_attempts, 5);
         tokio::time::sleep(backoff).await;
@@ -1,25 +1,20 @@
 use {
     crate::{
-        get_sol_price, load_auth_key, load_dump_setting, load_env_file, load_filter_setting,
-        load_is_submit_tx, load_key, load_max_sol_amount, log, swap_handler,
+        get_sol_price, log, only_own_transaction, swap_handler, ConfirmationService, LiveConfigRx,
+        PoolStateCache, PriorityFeeEstimator, RiskManager, StreamRecorder, OWN_TRANSACTIONS_FILTER,
     },
     anyhow::Result,
-    bs58,
     futures::{sink::SinkExt, stream::StreamExt},
-    hex,
     log::error,
     solana_client::nonblocking::rpc_client::RpcClient,
-    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    solana_sdk::commitment_config::CommitmentConfig,
     std::{sync::Arc, time::Duration},
     tokio::sync::{mpsc, Mutex},
     tonic::{metadata::errors::InvalidMetadataValue, transport::Endpoint},
     tonic_health::pb::health_client::HealthClient,
     yellowstone_grpc_client::{GeyserGrpcClient, InterceptorXToken},
     yellowstone_grpc_proto::{
-        geyser::{
-            geyser_client::GeyserClient, subscribe_update::UpdateOneof, SubscribeRequest,
-            SubscribeUpdateTransaction,
-        },
+        geyser::{geyser_client::GeyserClient, subscribe_update::UpdateOneof, SubscribeRequest},
         prelude::SubscribeRequestPing,
     },
 };
@@ -27,6 +22,11 @@ use {
 pub struct TxGrpcStreamManager {
     client: GeyserGrpcClient<InterceptorXToken>,
     nonblocking_client: Arc<RpcClient>,
+    pool_cache: Arc<PoolStateCache>,
+    risk: Arc<RiskManager>,
+    confirmations: Arc<ConfirmationService>,
+    fees: Arc<PriorityFeeEstimator>,
+    recorder: Option<Arc<StreamRecorder>>,
     is_connected: bool,
     reconnect_attempts: u32,
     max_reconnect_attempts: u32,
@@ -40,11 +40,22 @@ impl TxGrpcStreamManager {
     /// # Arguments
     /// * `endpoint` - The gRPC endpoint URL
     /// * `x_token` - Authentication token for the endpoint
+    /// * `pool_cache` - Live pool reserves handed to every swap handler
+    /// * `risk` - Risk manager every submission goes through
+    /// * `confirmations` - Follows every submission until it lands or expires
+    /// * `fees` - Recent priority fees, fed with every observed swap
+    /// * `recorder` - Keeps every received update for `replay`, when recording is on
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         endpoint: &str,
         rpc_endpoint: &str,
         x_token: &str,
         sol_price: f64,
+        pool_cache: Arc<PoolStateCache>,
+        risk: Arc<RiskManager>,
+        confirmations: Arc<ConfirmationService>,
+        fees: Arc<PriorityFeeEstimator>,
+        recorder: Option<Arc<StreamRecorder>>,
     ) -> Result<Arc<Mutex<TxGrpcStreamManager>>> {
         let interceptor = InterceptorXToken {
             x_token: Some(
@@ -74,6 +85,11 @@ impl TxGrpcStreamManager {
         Ok(Arc::new(Mutex::new(TxGrpcStreamManager {
             client,
             nonblocking_client,
+            pool_cache,
+            risk,
+            confirmations,
+            fees,
+            recorder,
             is_connected: false,
             reconnect_attempts: 0,
             max_reconnect_attempts: 10,
@@ -86,39 +102,17 @@ impl TxGrpcStreamManager {
     ///
     /// # Arguments
     /// * `request` - The subscription request containing transaction filters and other parameters
+    /// * `live` - Hot-reloaded config; each spawned handler gets the latest snapshot
     pub async fn connect(
         &mut self,
-        request: SubscribeRequest,
-        enermy_list: Vec<String>,
+        mut request: SubscribeRequest,
+        mut live: LiveConfigRx,
     ) -> Result<()> {
-        let (_, _, _, _, show_buy, show_sell, on_debug, is_racing) = load_env_file();
-        let signer_key = load_key();
-        let (
-            env_max_amount,
-            env_amount_in_factor_low,
-            env_amount_in_factor_median,
-            env_amount_in_factor_high,
-            env_tip_min,
-            env_tip_factor_low,
-            env_tip_factor_median,
-            env_tip_factor_high,
-            env_tip_factor_ultra,
-        ) = load_dump_setting();
-
-        let (env_acceptable_liquidity, take_profit_pcnt) = load_filter_setting();
-        let max_sol_amount = load_max_sol_amount();
-        let is_submit_tx = load_is_submit_tx();
-        let (next_key, nozomi_key, blox_auth_header, zero_slot_key) = load_auth_key();
-        
-        // Clone keys once for reuse in the loop
-        let next_key_clone = next_key.clone();
-        let nozomi_key_clone = nozomi_key.clone();
-        let blox_auth_header_clone = blox_auth_header.clone();
-        let zero_slot_key_clone = zero_slot_key.clone();
+        // Endpoints and keypair are pinned by the watcher, so the payer never changes.
+        let payer = Arc::new(live.borrow_and_update().config.payer());
 
         let sol_price_clone = Arc::clone(&self.sol_price);
 
-        let request = request.clone();
         let (mut subscribe_tx, mut stream) = self
             .client
             .subscribe_with_request(Some(request.clone()))
@@ -143,11 +137,11 @@ impl TxGrpcStreamManager {
             }
         });
 
-        let _ = tokio::spawn(async move {
+        tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(60));
             loop {
                 interval.tick().await;
-                if let Some(new_price) = get_sol_price().await.flatten() {
+                if let Some(new_price) = get_sol_price().await.ok().flatten() {
                     let mut price = sol_price_clone.lock().await;
                     *price = new_price;
                 }
@@ -160,48 +154,41 @@ impl TxGrpcStreamManager {
                 Some(message) = stream.next() => {
                     match message {
                         Ok(msg) => {
+                            if let Some(recorder) = &self.recorder {
+                                recorder.record(&msg);
+                            }
                             match msg.update_oneof {
                                 Some(UpdateOneof::Transaction(transaction)) => {
+                                    if msg.filters.iter().any(|filter| filter == OWN_TRANSACTIONS_FILTER) {
+                                        self.confirmations.on_transaction(transaction.clone());
+                                    }
+                                    if only_own_transaction(&msg.filters) {
+                                        continue;
+                                    }
                                     let nonblocking_client = self.nonblocking_client.clone();
 
                                     let sol_price = *self.sol_price.lock().await; // Extract the f64 value safely
 
-                                    let signer_string = signer_key.to_base58_string();
-                                    let enermy_list_clone = enermy_list.clone();
-                                    let next_key = next_key_clone.clone();
-                                    let nozomi_key = nozomi_key_clone.clone();
-                                    let blox_auth_header = blox_auth_header_clone.clone();
-                                    let zero_slot_key = zero_slot_key_clone.clone();
+                                    let snapshot = Arc::clone(&live.borrow());
+                                    let payer = Arc::clone(&payer);
+                                    let pool_cache = Arc::clone(&self.pool_cache);
+                                    let risk = Arc::clone(&self.risk);
+                                    let confirmations = Arc::clone(&self.confirmations);
+                                    let fees = Arc::clone(&self.fees);
 
-                                    let _ = tokio::spawn(async move {
-                                        let _ = swap_handler(
+                                    tokio::spawn(async move {
+                                        swap_handler(
                                             nonblocking_client,
-                                            enermy_list_clone,
+                                            snapshot,
+                                            payer,
+                                            pool_cache,
+                                            risk,
+                                            confirmations,
+                                            fees,
                                             &transaction,
-                                            signer_string,
-                                            next_key,
-                                            nozomi_key,
-                                            blox_auth_header,
-                                            zero_slot_key,
                                             sol_price,
-                                            show_buy,
-                                            show_sell,
-                                            env_max_amount,
-                                            env_amount_in_factor_low,
-                                            env_amount_in_factor_median,
-                                            env_amount_in_factor_high,
-                                            env_tip_min,
-                                            env_tip_factor_low,
-                                            env_tip_factor_median,
-                                            env_tip_factor_high,
-                                            env_tip_factor_ultra,
-                                            max_sol_amount,
-                                            is_submit_tx,
-                                            on_debug,
-                                            is_racing,
-                                            env_acceptable_liquidity,
-                                            take_profit_pcnt
-                                        ).await;
+                                        )
+                                        .await;
                                     });
                                 }
                                 Some(UpdateOneof::Ping(_)) => {
@@ -212,6 +199,9 @@ impl TxGrpcStreamManager {
                                         })
                                         .await?;
                                 }
+                                Some(UpdateOneof::BlockMeta(block_meta)) => {
+                                    self.confirmations.on_block_meta(&block_meta);
+                                }
                                 Some(UpdateOneof::Pong(_)) => {} // Ignore pong responses
                                 _ => {
                                     log!(format!("Other update received: {:?}", msg) , "info");
@@ -223,11 +213,36 @@ impl TxGrpcStreamManager {
                             error!("Error: {:?}", err);
                             self.is_connected = false;
                             ping_handle.abort(); // Cleanup ping task
-                            Box::pin(self.reconnect(request.clone() , enermy_list)).await?;
+                            Box::pin(self.reconnect(request.clone(), live)).await?;
                             return Ok(());
                         }
                     }
                 }
+                Ok(()) = live.changed() => {
+                    let black_list = live.borrow_and_update().black_list.clone();
+                    // Re-sending the request swaps the filters on the open stream.
+                    let mut changed = false;
+                    for (name, filter) in request.transactions.iter_mut() {
+                        // Our own transactions are followed wherever they trade
+                        if name == OWN_TRANSACTIONS_FILTER {
+                            continue;
+                        }
+                        if filter.account_exclude != black_list {
+                            filter.account_exclude = black_list.clone();
+                            changed = true;
+                        }
+                    }
+                    if changed {
+                        if let Err(e) = subscribe_tx.send(request.clone()).await {
+                            log!(format!("Failed to update subscription filters: {:?}", e), "error");
+                        } else {
+                            log!(
+                                format!("✅ Subscription black list updated ({} addresses)", black_list.len()),
+                                "update"
+                            );
+                        }
+                    }
+                }
                 Some(_) = ping_receiver.recv() => {
                     // Send ping when requested by the ping task
                     if let Err(e) = subscribe_tx
@@ -254,11 +269,7 @@ impl TxGrpcStreamManager {
     ///
     /// # Arguments
     /// * `request` - The original subscription request to reestablish the connection
-    async fn reconnect(
-        &mut self,
-        request: SubscribeRequest,
-        enermy_list: Vec<String>,
-    ) -> Result<()> {
+    async fn reconnect(&mut self, request: SubscribeRequest, live: LiveConfigRx) -> Result<()> {
         if self.reconnect_attempts >= self.max_reconnect_attempts {
             log!("Max reconnection attempts reached", "error");
             return Ok(());
@@ -273,6 +284,6 @@ impl TxGrpcStreamManager {
         let backoff = self.reconnect_interval * std::cmp::min(self.reconnect_attempts, 5);
         tokio::time::sleep(backoff).await;
 
-        Box::pin(self.connect(request, enermy_list)).await
+        Box::pin(self.connect(request, live)).await
     }
 }
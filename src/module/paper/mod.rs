@@ -0,0 +1,3 @@
+pub mod paper_executor;
+
+pub use paper_executor::*;
@@ -0,0 +1,617 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use tokio::{
+    sync::broadcast::error::RecvError,
+    time::{timeout_at, Instant},
+};
+
+use crate::*;
+
+/// Longest a paper fill waits for the pool state of a slot past its trigger.
+const NEXT_SLOT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Base fee of the transaction's one signature.
+const SIGNATURE_FEE: u64 = 5_000;
+
+/// Rent-exempt minimum per byte of account data, the 128-byte account
+/// header included, in lamports.
+const RENT_PER_BYTE: u64 = 6_960;
+
+/// Size of an SPL Token account; Token-2022 adds its account type and the
+/// immutable owner extension, plus the withheld fee of transfer fee mints.
+const TOKEN_ACCOUNT_LEN: u64 = 165;
+const TOKEN_2022_ACCOUNT_EXTRA_LEN: u64 = 5;
+const TRANSFER_FEE_AMOUNT_LEN: u64 = 12;
+
+/// Numbers the synthetic signatures of this process's paper fills.
+static PAPER_FILLS: AtomicU64 = AtomicU64::new(0);
+
+/// Decimals of the mints seen in observed swaps or read from the RPC, so
+/// fills look each one up at most once and replays never do.
+static MINT_DECIMALS: Mutex<Option<HashMap<Pubkey, u8>>> = Mutex::new(None);
+
+/// Orders placed during a replay, oldest first, until the replay feeds the
+/// pool state they fill at.
+static REPLAYED_ORDERS: Mutex<Vec<ReplayedOrder>> = Mutex::new(Vec::new());
+
+/// A paper swap with the bounds and fees it would have been submitted with.
+struct PaperOrder {
+    intent: TradeIntent,
+    pool_info: TokenListInfos,
+    amount_in: u64,
+    amount_out: u64,
+    network_fee: u64,
+    tip: u64,
+    /// Rent of the token account the first buy of the pool opens.
+    token_account_rent: u64,
+    token_decimals: u8,
+}
+
+/// A replay's paper order, waiting for the pool state of a slot past
+/// `after_slot` or for `deadline_ms` on the replay clock.
+struct ReplayedOrder {
+    order: PaperOrder,
+    breaker: BreakerConfig,
+    admission: Admission,
+    after_slot: u64,
+    deadline_ms: i64,
+}
+
+/// Fills `intent` on paper and books it in the store's simulated account
+/// (paper or replay), as the confirmation of a landed swap would.
+///
+/// The swap keeps the bounds it would have been submitted with, quoted
+/// against the reserves the intent expects, and fills at the pool state of
+/// the next observed slot with the same quote engine. PumpSwap buys fill
+/// exact-out like the live instruction: the pool sends the minimum out and
+/// takes what it costs, up to the spend. A fill past its bounds fails like
+/// the swap would and books nothing. Fills pay the network fee of `budget`
+/// and the tip of the first enabled gateway, and are booked off the runtime.
+///
+/// Paper wallets start without token accounts: the first buy of a pool also
+/// pays the rent of its token account, which pre_start pays on the live
+/// wallet.
+///
+/// Whirlpool and DLMM fills are approximate: the cache doesn't follow their
+/// tick and bin arrays, so they are quoted at the live state read from the
+/// RPC when the fill is priced, which can be past the next slot.
+pub async fn paper_execute(
+    intent: &TradeIntent,
+    ctx: &ExecutionContext<'_>,
+    budget: ComputeBudget,
+) -> Result<()> {
+    let order = PaperOrder::place(intent, ctx, budget).await?;
+
+    let at_fill = TradeIntent {
+        reserves: next_slot_reserves(intent, ctx).await,
+        ..order.intent.clone()
+    };
+    if matches!(intent.dex, Dex::OrcaWhirlpool | Dex::MeteoraDlmm) {
+        log!(
+            format!(
+                "📝 Paper {} fill on {} is approximate: {} pools are quoted at their live state, not the next slot's",
+                intent.strategy, intent.pool, intent.dex
+            ),
+            "info"
+        );
+    }
+    let quote = quote_intent(&at_fill, &order.pool_info, &ctx.client).await?;
+
+    let Some(fill) = order.fill(&quote) else {
+        return Ok(());
+    };
+    let pool_id = intent.pool.to_string();
+    let rent = order.token_account_rent;
+    tokio::task::spawn_blocking(move || book_paper_fill(&pool_id, fill, rent)).await?
+}
+
+/// Places `intent` as `paper_execute` would during a replay, which can't
+/// wait for the next slot while it handles the current one:
+/// `fill_replayed_orders` fills it once the replay fed that slot, and settles
+/// it with the risk manager then. Only constant-product pools are priced
+/// from the recording.
+pub async fn place_replayed_order(
+    intent: &TradeIntent,
+    ctx: &ExecutionContext<'_>,
+    budget: ComputeBudget,
+    admission: Admission,
+) -> Result<()> {
+    let order = PaperOrder::place(intent, ctx, budget).await?;
+
+    let cached_slot = ctx
+        .pool_cache
+        .snapshot(&intent.pool)
+        .map_or(0, |snapshot| snapshot.slot);
+    REPLAYED_ORDERS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(ReplayedOrder {
+            order,
+            breaker: ctx.config.breaker.clone(),
+            admission,
+            after_slot: ctx.trigger_slot.unwrap_or(cached_slot),
+            deadline_ms: now_ms() + NEXT_SLOT_TIMEOUT.as_millis() as i64,
+        });
+
+    Ok(())
+}
+
+/// Fills the replay's waiting orders whose pool state came or whose wait
+/// ran out by the replay clock, every one of them when `flush`, oldest
+/// first, and settles each with `risk`. Blocks on the store.
+pub fn fill_replayed_orders(pool_cache: &PoolStateCache, risk: &RiskManager, flush: bool) {
+    let now_ms = now_ms();
+    let due: Vec<ReplayedOrder> = {
+        let mut orders = REPLAYED_ORDERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (due, waiting) = std::mem::take(&mut *orders)
+            .into_iter()
+            .partition(|waiting| {
+                flush
+                    || now_ms >= waiting.deadline_ms
+                    || pool_cache
+                        .snapshot(&waiting.order.intent.pool)
+                        .is_none_or(|snapshot| snapshot.slot > waiting.after_slot)
+            });
+        *orders = waiting;
+        due
+    };
+
+    for ReplayedOrder {
+        order,
+        breaker,
+        admission,
+        after_slot,
+        ..
+    } in due
+    {
+        let intent = &order.intent;
+        let reserves = match pool_cache.snapshot(&intent.pool) {
+            Some(snapshot) => {
+                if snapshot.slot <= after_slot {
+                    log!(
+                        format!(
+                            "📝 No pool state past slot {} on {}, paper fill at the latest one",
+                            after_slot, intent.pool
+                        ),
+                        "info"
+                    );
+                }
+                snapshot.reserves
+            }
+            None => intent.reserves,
+        };
+        let at_fill = TradeIntent {
+            reserves,
+            ..intent.clone()
+        };
+
+        if let Err(e) = constant_product_quote(&at_fill, &order.pool_info).and_then(|quote| {
+            match order.fill(&quote) {
+                Some(fill) => {
+                    book_paper_fill(&intent.pool.to_string(), fill, order.token_account_rent)
+                }
+                None => Ok(()),
+            }
+        }) {
+            log!(
+                format!("🔴 Paper fill on {} failed: {:#}", intent.pool, e),
+                "error"
+            );
+        }
+        risk.settle(&breaker, intent, admission, &[]);
+    }
+}
+
+/// Remembers the decimals of `mint` as an observed swap reported them.
+pub fn note_mint_decimals(mint: Pubkey, decimals: u8) {
+    MINT_DECIMALS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(HashMap::new)
+        .insert(mint, decimals);
+}
+
+/// Decimals of `mint`, read from the RPC the first time outside replays.
+async fn mint_decimals(mint: &Pubkey, client: &RpcClient) -> Result<u8> {
+    let known = MINT_DECIMALS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .and_then(|decimals| decimals.get(mint).copied());
+    if let Some(decimals) = known {
+        return Ok(decimals);
+    }
+    if trade_account() == TradeAccount::Replay {
+        bail!("no swap of {} in the recording gave its decimals", mint);
+    }
+
+    let decimals = client.get_token_supply(mint).await?.decimals;
+    note_mint_decimals(*mint, decimals);
+    Ok(decimals)
+}
+
+impl PaperOrder {
+    /// Quotes `intent` against the reserves it expects and prices its fees.
+    async fn place(
+        intent: &TradeIntent,
+        ctx: &ExecutionContext<'_>,
+        budget: ComputeBudget,
+    ) -> Result<Self> {
+        let pool_info = recorded_pool_info(&intent.pool)?;
+        let expected = quote_intent(intent, &pool_info, &ctx.client).await?;
+        let (amount_in, amount_out) = swap_limits(intent, &expected, &pool_info);
+
+        let intent = as_submitted(intent, amount_out);
+        let token_account_rent = if is_sell(&intent.kind) {
+            0
+        } else {
+            token_account_rent(&pool_info)
+        };
+
+        let priority_fee =
+            (budget.unit_price as u128 * budget.unit_limit as u128).div_ceil(1_000_000);
+        let tip = ctx
+            .senders
+            .first()
+            .filter(|group| group.gateway.tip_account().is_some())
+            .map_or(0, |group| sol_to_lamports(intent.tip.max(group.min_tip)));
+
+        Ok(PaperOrder {
+            token_decimals: mint_decimals(&intent.mint, &ctx.client).await?,
+            intent,
+            pool_info,
+            amount_in,
+            amount_out,
+            network_fee: SIGNATURE_FEE + priority_fee as u64,
+            tip,
+            token_account_rent,
+        })
+    }
+
+    /// The fill of the order at `quote`, none when that is past its bounds.
+    fn fill(&self, quote: &SwapQuote) -> Option<ConfirmedFill> {
+        let intent = &self.intent;
+        let (amount_in, amount_out) = (self.amount_in, self.amount_out);
+
+        // (side, tokens in or out of the wallet, SOL swapped, within the bounds)
+        let (side, token_amount, swap_lamports, filled) = match intent.kind {
+            IntentKind::Buy { .. } => {
+                let received = match &self.pool_info.transfer_fee {
+                    Some(fee) => fee.amount_after_fee(quote.amount_out),
+                    None => quote.amount_out,
+                };
+                (SwapSide::Buy, received, amount_in, received >= amount_out)
+            }
+            IntentKind::Sell { .. } => (
+                SwapSide::Sell,
+                amount_in,
+                quote.amount_out,
+                quote.amount_out >= amount_out,
+            ),
+            IntentKind::BuyExactOut { .. } => (
+                SwapSide::Buy,
+                amount_out,
+                quote.amount_in,
+                quote.amount_in <= amount_in,
+            ),
+        };
+        if !filled {
+            log!(
+                format!(
+                    "📝 Paper {} trade on {} failed: the pool moved past its slippage bound",
+                    intent.strategy, intent.pool
+                ),
+                "error"
+            );
+            return None;
+        }
+
+        let swap_lamports = swap_lamports as i64;
+        let wallet_lamports = match side {
+            SwapSide::Buy => -swap_lamports,
+            SwapSide::Sell => swap_lamports,
+        } - (self.network_fee + self.tip) as i64;
+
+        let fill = ConfirmedFill {
+            signature: format!(
+                "paper-{}-{}",
+                now_ms(),
+                PAPER_FILLS.fetch_add(1, Ordering::Relaxed)
+            ),
+            side,
+            token_amount,
+            token_decimals: self.token_decimals,
+            wallet_lamports,
+            network_fee: self.network_fee,
+        };
+        log!(
+            format!(
+                "📝 Paper {} fill on {} : {} tokens, SOL Δ{} (network fee {}, tip {})",
+                intent.strategy,
+                intent.pool,
+                token_amount,
+                wallet_lamports as f64 / LAMPORTS_PER_SOL as f64,
+                lamports_to_sol(self.network_fee),
+                lamports_to_sol(self.tip)
+            ),
+            "update"
+        );
+
+        Some(fill)
+    }
+}
+
+/// `intent` as its swap instruction executes it, given the minimum out it is
+/// submitted with: live PumpSwap buys ask for exactly that amount and cap
+/// the spend at their size, see `build_intent_ix`.
+fn as_submitted(intent: &TradeIntent, min_amount_out: u64) -> TradeIntent {
+    match intent.kind {
+        IntentKind::Buy { sol_amount, .. } if intent.dex == Dex::PumpSwap => TradeIntent {
+            kind: IntentKind::BuyExactOut {
+                token_amount: min_amount_out,
+                max_sol_amount: sol_amount,
+            },
+            ..intent.clone()
+        },
+        _ => intent.clone(),
+    }
+}
+
+/// Rent of the wallet's token account for the traded mint of `pool_info`.
+fn token_account_rent(pool_info: &TokenListInfos) -> u64 {
+    let mut len = TOKEN_ACCOUNT_LEN;
+    if pool_info.token_program == TOKEN_2022_PRO {
+        len += TOKEN_2022_ACCOUNT_EXTRA_LEN;
+        if pool_info.transfer_fee.is_some() {
+            len += TRANSFER_FEE_AMOUNT_LEN;
+        }
+    }
+    (len + 128) * RENT_PER_BYTE
+}
+
+/// Books a paper fill of `pool_id` in the store's simulated account, adding
+/// `token_account_rent` to the pool's first buy. Blocks on the store.
+fn book_paper_fill(pool_id: &str, mut fill: ConfirmedFill, token_account_rent: u64) -> Result<()> {
+    if fill.side == SwapSide::Buy && !with_trade_store(|store| store.has_fills(pool_id))? {
+        fill.wallet_lamports -= token_account_rent as i64;
+        log!(
+            format!(
+                "📝 First paper buy on {} opens its token account : {} SOL rent",
+                pool_id,
+                lamports_to_sol(token_account_rent)
+            ),
+            "info"
+        );
+    }
+
+    if let Some(realized) = book_fill(pool_id, &fill)? {
+        if fill.side == SwapSide::Sell {
+            log!(
+                format!(
+                    "Paper realized PnL on {} : {} SOL",
+                    pool_id,
+                    realized as f64 / LAMPORTS_PER_SOL as f64
+                ),
+                "success"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Reserves of the first cached pool state past the intent's trigger slot
+/// (past the state cached now for exits), waiting up to `NEXT_SLOT_TIMEOUT`
+/// for it; the latest state when none comes, the intent's own reserves for
+/// pools the cache does not follow.
+async fn next_slot_reserves(intent: &TradeIntent, ctx: &ExecutionContext<'_>) -> Reserves {
+    let mut updates = ctx.pool_cache.subscribe_updates();
+    let Some(current) = ctx.pool_cache.snapshot(&intent.pool) else {
+        return intent.reserves;
+    };
+    let after = ctx.trigger_slot.unwrap_or(current.slot);
+    if current.slot > after {
+        return current.reserves;
+    }
+
+    let deadline = Instant::now() + NEXT_SLOT_TIMEOUT;
+    loop {
+        match timeout_at(deadline, updates.recv()).await {
+            Ok(Ok(pool)) if pool != intent.pool => {}
+            Ok(Ok(_) | Err(RecvError::Lagged(_))) => {
+                if let Some(snapshot) = ctx.pool_cache.snapshot(&intent.pool) {
+                    if snapshot.slot > after {
+                        return snapshot.reserves;
+                    }
+                }
+            }
+            Ok(Err(RecvError::Closed)) | Err(_) => {
+                log!(
+                    format!(
+                        "📝 No pool state past slot {} on {}, paper fill at the latest one",
+                        after, intent.pool
+                    ),
+                    "info"
+                );
+                return ctx
+                    .pool_cache
+                    .snapshot(&intent.pool)
+                    .map_or(intent.reserves, |snapshot| snapshot.reserves);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn pool_info(token_program: &str, transfer_fee: Option<TokenTransferFee>) -> TokenListInfos {
+        TokenListInfos {
+            id_bs64: String::new(),
+            base_vault_b64: String::new(),
+            quote_vault_b64: String::new(),
+            base_mint: Pubkey::new_unique().to_string(),
+            quote_mint: NATIVE_MINT.to_string(),
+            clean_symbol: "TEST".to_string(),
+            ata: String::new(),
+            dex: "pumpswap".to_string(),
+            cpmm: None,
+            whirlpool: None,
+            dlmm: None,
+            token_program: token_program.to_string(),
+            transfer_fee,
+            transfer_fee_change_epoch: None,
+            fees: Some(ConstantProductFees::PumpSwap {
+                lp_fee_basis_points: 20,
+                protocol_fee_basis_points: 5,
+                coin_creator_fee_basis_points: 5,
+            }),
+        }
+    }
+
+    fn pumpswap_buy(sol_amount: f64, reserves: Reserves) -> TradeIntent {
+        TradeIntent {
+            strategy: "test",
+            dex: Dex::PumpSwap,
+            pool: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            kind: IntentKind::Buy {
+                sol_amount,
+                expected_token_amount: 0.0,
+            },
+            tip: 0.0,
+            urgency: Urgency::default(),
+            reserves,
+            slippage_pct: 5.0,
+        }
+    }
+
+    fn order(intent: &TradeIntent, pool_info: TokenListInfos) -> PaperOrder {
+        let expected = constant_product_quote(intent, &pool_info).unwrap();
+        let (amount_in, amount_out) = swap_limits(intent, &expected, &pool_info);
+        PaperOrder {
+            intent: as_submitted(intent, amount_out),
+            pool_info,
+            amount_in,
+            amount_out,
+            network_fee: SIGNATURE_FEE,
+            tip: 0,
+            token_account_rent: 0,
+            token_decimals: 6,
+        }
+    }
+
+    #[test]
+    fn pumpswap_buys_fill_their_minimum_out() {
+        let reserves = Reserves {
+            sol: 100 * SOL,
+            token: 1_000_000_000_000,
+        };
+        let intent = pumpswap_buy(1.0, reserves);
+        let order = order(&intent, pool_info(TOKEN_PRO, None));
+        assert!(matches!(order.intent.kind, IntentKind::BuyExactOut { .. }));
+
+        // At the expected reserves the minimum out costs less than the buy's size
+        let quote = constant_product_quote(&order.intent, &order.pool_info).unwrap();
+        let fill = order.fill(&quote).unwrap();
+        assert_eq!(fill.token_amount, order.amount_out);
+        assert_eq!(
+            fill.wallet_lamports,
+            -(quote.amount_in as i64) - SIGNATURE_FEE as i64
+        );
+        assert!(quote.amount_in < SOL);
+
+        // The pool moved past the slippage: the minimum out costs more than the size
+        let moved = TradeIntent {
+            reserves: Reserves {
+                sol: 110 * SOL,
+                token: 900_000_000_000,
+            },
+            ..order.intent.clone()
+        };
+        let quote = constant_product_quote(&moved, &order.pool_info).unwrap();
+        assert!(quote.amount_in > SOL);
+        assert_eq!(order.fill(&quote), None);
+    }
+
+    #[test]
+    fn other_intents_fill_as_placed() {
+        let intent = TradeIntent {
+            dex: Dex::RaydiumAmm,
+            ..pumpswap_buy(1.0, Reserves::default())
+        };
+        assert_eq!(as_submitted(&intent, 10), intent);
+    }
+
+    #[test]
+    fn token_account_rent_follows_the_account_size() {
+        assert_eq!(token_account_rent(&pool_info(TOKEN_PRO, None)), 2_039_280);
+        assert_eq!(
+            token_account_rent(&pool_info(TOKEN_2022_PRO, None)),
+            2_074_080
+        );
+        let fee = TokenTransferFee {
+            basis_points: 100,
+            maximum_fee: 1_000,
+        };
+        assert_eq!(
+            token_account_rent(&pool_info(TOKEN_2022_PRO, Some(fee))),
+            2_157_600
+        );
+    }
+
+    #[test]
+    fn first_paper_buy_of_a_pool_pays_the_rent() {
+        let _store = use_test_trade_store();
+        let pool = Pubkey::new_unique().to_string();
+        with_trade_store(|store| {
+            store.save_position(&BuyHistoryInfo {
+                pool_id: pool.clone(),
+                base_mint: Pubkey::new_unique().to_string(),
+                quote_mint: NATIVE_MINT.to_string(),
+                base_vault: String::new(),
+                quote_vault: String::new(),
+                token_ata: String::new(),
+                symbol: String::new(),
+                total_amount_in: 0,
+                total_ui_amount_in: 0.0,
+                total_token_amount_out: 0,
+                total_ui_token_amount_out: 0.0,
+                take_profit: 0.0,
+                transactions: vec![],
+                dex: "pumpswap".to_string(),
+            })
+        })
+        .unwrap();
+        let buy = |signature: &str| ConfirmedFill {
+            signature: signature.to_string(),
+            side: SwapSide::Buy,
+            token_amount: 1_000,
+            token_decimals: 6,
+            wallet_lamports: -(SOL as i64),
+            network_fee: 0,
+        };
+
+        book_paper_fill(&pool, buy("first"), 2_039_280).unwrap();
+        book_paper_fill(&pool, buy("second"), 2_039_280).unwrap();
+        assert_eq!(
+            held_position(&pool).unwrap().cost_lamports(),
+            2 * SOL + 2_039_280
+        );
+    }
+}
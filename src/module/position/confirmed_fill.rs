@@ -0,0 +1,24 @@
+use crate::SwapSide;
+
+/// One of our swaps as its confirmed transaction changed the wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmedFill {
+    pub signature: String,
+    /// `Buy` spent SOL for tokens, `Sell` the reverse.
+    pub side: SwapSide,
+    /// Raw tokens the wallet received (buy) or sent (sell).
+    pub token_amount: u64,
+    pub token_decimals: u8,
+    /// Change of the fee payer's SOL balance: the swap itself plus the
+    /// network fee, tips and any rent, all together.
+    pub wallet_lamports: i64,
+    /// Signature and priority fee charged by the network, part of `wallet_lamports`.
+    pub network_fee: u64,
+}
+
+impl ConfirmedFill {
+    /// Lamports the fill cost (buy) or returned (sell), everything included.
+    pub fn lamports(&self) -> u64 {
+        self.wallet_lamports.unsigned_abs()
+    }
+}
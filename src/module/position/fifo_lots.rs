@@ -0,0 +1,112 @@
+/// Open part of one buy: the tokens still held from it and what they cost,
+/// swap input, network fee, tips and rent included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionLot {
+    pub signature: String,
+    pub token_amount: u64,
+    pub cost_lamports: u64,
+    /// When the buy was booked, in Unix milliseconds.
+    pub opened_at_ms: i64,
+}
+
+/// Open lots of a pool, oldest first, and the PnL its past sells locked in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpenPosition {
+    pub lots: Vec<PositionLot>,
+    pub realized_pnl_lamports: i64,
+}
+
+impl OpenPosition {
+    /// Raw tokens held across the open lots.
+    pub fn token_amount(&self) -> u64 {
+        self.lots.iter().map(|lot| lot.token_amount).sum()
+    }
+
+    /// Cost basis of the open lots, in lamports.
+    pub fn cost_lamports(&self) -> u64 {
+        self.lots.iter().map(|lot| lot.cost_lamports).sum()
+    }
+
+    /// When the oldest open lot was bought, in Unix milliseconds.
+    pub fn opened_at_ms(&self) -> Option<i64> {
+        self.lots.iter().map(|lot| lot.opened_at_ms).min()
+    }
+}
+
+/// Takes `token_amount` out of `lots` oldest first and returns the cost basis
+/// of what was taken; a partly sold lot keeps the rest of its cost pro rata.
+///
+/// Tokens beyond the lots (received outside the bot) carry no cost.
+pub fn consume_fifo(lots: &mut [PositionLot], mut token_amount: u64) -> u64 {
+    let mut cost = 0u64;
+
+    for lot in lots.iter_mut().filter(|lot| lot.token_amount > 0) {
+        if token_amount == 0 {
+            break;
+        }
+
+        let taken = token_amount.min(lot.token_amount);
+        let taken_cost = if taken == lot.token_amount {
+            lot.cost_lamports
+        } else {
+            (lot.cost_lamports as u128 * taken as u128 / lot.token_amount as u128) as u64
+        };
+
+        lot.token_amount -= taken;
+        lot.cost_lamports -= taken_cost;
+        token_amount -= taken;
+        cost += taken_cost;
+    }
+
+    cost
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lot(signature: &str, token_amount: u64, cost_lamports: u64) -> PositionLot {
+        PositionLot {
+            signature: signature.to_string(),
+            token_amount,
+            cost_lamports,
+            opened_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn oldest_lots_go_first() {
+        let mut lots = vec![lot("a", 100, 1_000), lot("b", 100, 3_000)];
+
+        assert_eq!(consume_fifo(&mut lots, 150), 1_000 + 1_500);
+        assert_eq!(lots, [lot("a", 0, 0), lot("b", 50, 1_500)]);
+    }
+
+    #[test]
+    fn partial_sells_keep_the_rest_of_the_cost() {
+        let mut lots = vec![lot("a", 3, 1_000)];
+
+        // Rounded down each time, the last sale takes what is left
+        assert_eq!(consume_fifo(&mut lots, 1), 333);
+        assert_eq!(consume_fifo(&mut lots, 1), 333);
+        assert_eq!(consume_fifo(&mut lots, 1), 334);
+        assert_eq!(lots, [lot("a", 0, 0)]);
+    }
+
+    #[test]
+    fn tokens_beyond_the_lots_cost_nothing() {
+        let mut lots = vec![lot("a", 0, 0), lot("b", 10, 500)];
+
+        assert_eq!(consume_fifo(&mut lots, 25), 500);
+        assert_eq!(consume_fifo(&mut lots, 5), 0);
+        assert_eq!(consume_fifo(&mut [], 5), 0);
+    }
+
+    #[test]
+    fn large_lots_dont_overflow() {
+        let mut lots = vec![lot("a", u64::MAX, u64::MAX)];
+
+        assert_eq!(consume_fifo(&mut lots, u64::MAX / 2), u64::MAX / 2);
+        assert_eq!(lots[0].cost_lamports, u64::MAX - u64::MAX / 2);
+    }
+}
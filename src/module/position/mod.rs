@@ -0,0 +1,9 @@
+pub mod confirmed_fill;
+pub mod fifo_lots;
+pub mod position_book;
+pub mod position_pnl;
+
+pub use confirmed_fill::*;
+pub use fifo_lots::*;
+pub use position_book::*;
+pub use position_pnl::*;
@@ -0,0 +1,139 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{now_ms, with_trade_store, ConfirmedFill, OpenExposure, OpenPosition, StoreError};
+
+/// How far back the book keeps realized PnL: the risk manager's loss window.
+pub const REALIZED_HISTORY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Open position of one recorded pool and the mint it trades.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolPosition {
+    pub pool_id: String,
+    pub token_mint: String,
+    pub position: OpenPosition,
+}
+
+/// The store's open positions and recently realized PnL, kept in memory so
+/// exit checks, strategies and risk checks never wait on SQLite.
+///
+/// It is read from the store once, by `load_position_book` at startup or on
+/// first use, and kept current by `book_fill`, which every fill goes through.
+/// Pools the store had no position for when it was read count as flat.
+#[derive(Debug, Default)]
+struct PositionBook {
+    positions: HashMap<String, PoolPosition>,
+    /// Booking time and PnL of the fills that realized any, oldest first.
+    realized: VecDeque<(i64, i64)>,
+}
+
+/// Process-wide book of the store's account, read on first use.
+static POSITION_BOOK: Mutex<Option<PositionBook>> = Mutex::new(None);
+
+fn lock_book() -> MutexGuard<'static, Option<PositionBook>> {
+    POSITION_BOOK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Reads the open positions from the store unless the book has them
+/// already. Blocks on the store; call it before the stream starts.
+pub fn load_position_book() -> Result<(), StoreError> {
+    if lock_book().is_some() {
+        return Ok(());
+    }
+
+    // Store first, then the book, as `book_fill` takes them: no fill can
+    // land between the read and the book taking it
+    with_trade_store(|store| {
+        let positions = store.pool_positions()?;
+        let realized = store.realized_fills_since(now_ms() - REALIZED_HISTORY_MS)?;
+        lock_book().get_or_insert_with(|| PositionBook {
+            positions: positions
+                .into_iter()
+                .map(|position| (position.pool_id.clone(), position))
+                .collect(),
+            realized: realized.into(),
+        });
+        Ok(())
+    })
+}
+
+/// Drops the book, to be read again from a store tests swapped in.
+#[cfg(test)]
+pub(crate) fn clear_position_book() {
+    *lock_book() = None;
+}
+
+/// Runs `f` on the book, reading it from the store first if needed.
+fn with_position_book<T>(f: impl FnOnce(&mut PositionBook) -> T) -> Result<T, StoreError> {
+    load_position_book()?;
+    Ok(f(lock_book().as_mut().expect("book loaded above")))
+}
+
+/// Open lots and realized PnL of `pool_id`; empty when nothing is held.
+pub fn held_position(pool_id: &str) -> Result<OpenPosition, StoreError> {
+    with_position_book(|book| {
+        book.positions
+            .get(pool_id)
+            .map(|held| held.position.clone())
+            .unwrap_or_default()
+    })
+}
+
+/// Cost basis of every pool still holding tokens.
+pub fn held_exposures() -> Result<Vec<OpenExposure>, StoreError> {
+    with_position_book(|book| {
+        book.positions
+            .values()
+            .filter(|held| held.position.token_amount() != 0)
+            .map(|held| OpenExposure {
+                pool_id: held.pool_id.clone(),
+                token_mint: held.token_mint.clone(),
+                cost_lamports: held.position.cost_lamports(),
+            })
+            .collect()
+    })
+}
+
+/// PnL realized by the fills booked since `since_ms`, at most
+/// `REALIZED_HISTORY_MS` back, in lamports.
+pub fn booked_pnl_since(since_ms: i64) -> Result<i64, StoreError> {
+    with_position_book(|book| {
+        let horizon = now_ms() - REALIZED_HISTORY_MS;
+        while book
+            .realized
+            .front()
+            .is_some_and(|(booked_at_ms, _)| *booked_at_ms < horizon)
+        {
+            book.realized.pop_front();
+        }
+
+        book.realized
+            .iter()
+            .filter(|(booked_at_ms, _)| *booked_at_ms >= since_ms)
+            .map(|(_, realized)| realized)
+            .sum()
+    })
+}
+
+/// Books `fill` in the store and brings the pool's position in the book up
+/// to date, with what it realized; returns as `TradeStore::record_fill` does. Blocks on the store;
+/// keep it off the async runtime.
+pub fn book_fill(pool_id: &str, fill: &ConfirmedFill) -> Result<Option<i64>, StoreError> {
+    with_trade_store(|store| {
+        let realized = store.record_fill(pool_id, fill)?;
+        if let Some(realized) = realized {
+            let position = store.pool_position(pool_id)?;
+            // Updated with the store still held, so fills land in the book in order
+            if let Some(book) = lock_book().as_mut() {
+                book.positions.insert(pool_id.to_string(), position);
+                if realized != 0 {
+                    book.realized.push_back((now_ms(), realized));
+                }
+            }
+        }
+
+        Ok(realized)
+    })
+}
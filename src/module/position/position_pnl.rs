@@ -0,0 +1,60 @@
+use crate::{log, OpenPosition};
+
+/// A pool's open position valued at a prospective exit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionPnl {
+    /// Raw tokens held across the open lots.
+    pub token_amount: u64,
+    /// Cost basis of the open lots, in lamports.
+    pub cost_lamports: u64,
+    /// PnL past sells locked in, in lamports.
+    pub realized_lamports: i64,
+    /// What selling every held token now would add, in lamports.
+    pub unrealized_lamports: i64,
+}
+
+impl PositionPnl {
+    /// Unrealized PnL in percent of the open cost basis.
+    pub fn unrealized_pct(&self) -> f64 {
+        if self.cost_lamports == 0 {
+            return 0.0;
+        }
+        self.unrealized_lamports as f64 * 100.0 / self.cost_lamports as f64
+    }
+}
+
+/// Values the open lots of `position`; `exit_proceeds` gives the lamports a
+/// sale of the given raw token amount would bring in, net of its own costs.
+/// `None` when nothing is held.
+pub fn position_pnl(
+    position: &OpenPosition,
+    exit_proceeds: impl FnOnce(u64) -> u64,
+) -> Option<PositionPnl> {
+    let token_amount = position.token_amount();
+    if token_amount == 0 {
+        return None;
+    }
+    let cost_lamports = position.cost_lamports();
+    let proceeds = exit_proceeds(token_amount);
+
+    let pnl = PositionPnl {
+        token_amount,
+        cost_lamports,
+        realized_lamports: position.realized_pnl_lamports,
+        unrealized_lamports: proceeds as i64 - cost_lamports as i64,
+    };
+    log!(
+        format!(
+            "Inventory {} tokens in {} lots, Cost {} lamports, Exit {} lamports, Unrealized PnL {} %, Realized {} lamports",
+            token_amount,
+            position.lots.len(),
+            cost_lamports,
+            proceeds,
+            pnl.unrealized_pct(),
+            pnl.realized_lamports
+        ),
+        "info"
+    );
+
+    Some(pnl)
+}
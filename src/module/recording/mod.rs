@@ -0,0 +1,5 @@
+pub mod stream_recorder;
+pub mod stream_replay;
+
+pub use stream_recorder::*;
+pub use stream_replay::*;
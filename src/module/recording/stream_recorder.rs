@@ -0,0 +1,142 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use prost::Message;
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+use zstd::stream::write::Encoder;
+
+use crate::log;
+
+/// Extension of recording files.
+pub const RECORDING_EXTENSION: &str = "zst";
+
+/// Receive time (u64) and update length (u32) ahead of each update, little-endian.
+pub const FRAME_HEADER_LEN: usize = 12;
+
+/// zstd level; cheap enough to keep up with the stream on one thread.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Longest recorded updates wait in the compressor before reaching the disk.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Writes the raw Geyser updates the bot receives to zstd-compressed files,
+/// as frames of the receive time in microseconds since the epoch, the length
+/// of the encoded update and the `SubscribeUpdate` protobuf itself.
+///
+/// Updates are encoded where they are received and handed to a writer
+/// thread, which moves on to a new file every `rotate`. A rotated file is a
+/// complete zstd stream; the file of an interrupted run reads up to its last
+/// flush, at most `FLUSH_INTERVAL` behind.
+pub struct StreamRecorder {
+    sender: mpsc::Sender<Vec<u8>>,
+}
+
+impl StreamRecorder {
+    /// Opens the first file in `dir` and starts the writer thread.
+    pub fn start(dir: impl AsRef<Path>, rotate: Duration) -> io::Result<Arc<Self>> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let encoder = create_recording(&dir)?;
+
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("stream-recorder".to_string())
+            .spawn(move || write_frames(&dir, rotate, &receiver, encoder))?;
+
+        Ok(Arc::new(Self { sender }))
+    }
+
+    /// Queues `update`, received now, for the recording.
+    pub fn record(&self, update: &SubscribeUpdate) {
+        let received_at_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_micros() as u64)
+            .unwrap_or_default();
+        let encoded = update.encode_to_vec();
+
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + encoded.len());
+        frame.extend_from_slice(&received_at_us.to_le_bytes());
+        frame.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&encoded);
+        // The writer only stops once every recorder is dropped
+        let _ = self.sender.send(frame);
+    }
+}
+
+type RecordingEncoder = Encoder<'static, BufWriter<File>>;
+
+/// New recording file in `dir`, named after the current UTC time to the
+/// millisecond.
+fn create_recording(dir: &Path) -> io::Result<RecordingEncoder> {
+    let path: PathBuf = dir.join(format!(
+        "stream-{}.{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f"),
+        RECORDING_EXTENSION
+    ));
+    log!(
+        format!("Recording the stream to {}", path.display()),
+        "info"
+    );
+
+    Encoder::new(BufWriter::new(File::create(path)?), COMPRESSION_LEVEL)
+}
+
+fn write_frames(
+    dir: &Path,
+    rotate: Duration,
+    receiver: &mpsc::Receiver<Vec<u8>>,
+    mut encoder: RecordingEncoder,
+) {
+    let mut opened = Instant::now();
+    let mut flushed = Instant::now();
+
+    loop {
+        match receiver.recv_timeout(FLUSH_INTERVAL.saturating_sub(flushed.elapsed())) {
+            Ok(frame) => {
+                if let Err(e) = encoder.write_all(&frame) {
+                    log!(format!("🔴 Failed to record an update: {}", e), "error");
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if flushed.elapsed() >= FLUSH_INTERVAL {
+            if let Err(e) = encoder.flush() {
+                log!(format!("🔴 Failed to flush the recording: {}", e), "error");
+            }
+            flushed = Instant::now();
+        }
+
+        if opened.elapsed() >= rotate {
+            match create_recording(dir) {
+                Ok(next) => finish_recording(std::mem::replace(&mut encoder, next)),
+                Err(e) => log!(
+                    format!(
+                        "🔴 Failed to rotate the recording, keeping the current file: {}",
+                        e
+                    ),
+                    "error"
+                ),
+            }
+            opened = Instant::now();
+        }
+    }
+
+    finish_recording(encoder);
+}
+
+fn finish_recording(encoder: RecordingEncoder) {
+    if let Err(e) = encoder.finish().and_then(|mut file| file.flush()) {
+        log!(format!("🔴 Failed to close the recording: {}", e), "error");
+    }
+}
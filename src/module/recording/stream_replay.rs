@@ -0,0 +1,246 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, ErrorKind, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use prost::Message;
+use tokio::time::{sleep_until, Instant};
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+use zstd::stream::read::Decoder;
+
+use crate::{FRAME_HEADER_LEN, RECORDING_EXTENSION};
+
+/// An update read back from a recording.
+#[derive(Debug, Clone)]
+pub struct RecordedUpdate {
+    /// When the bot received it, in microseconds since the epoch.
+    pub received_at_us: u64,
+    pub update: SubscribeUpdate,
+}
+
+/// Reads the updates of one recording file in the order they were received.
+///
+/// Iteration ends at the end of the file; a frame cut short by an interrupted
+/// recording ends it with an `UnexpectedEof` error.
+pub struct RecordingReader {
+    decoder: Decoder<'static, BufReader<File>>,
+    /// Set once the file is exhausted or failed to read.
+    done: bool,
+}
+
+impl RecordingReader {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            decoder: Decoder::new(File::open(path)?)?,
+            done: false,
+        })
+    }
+
+    fn read_update(&mut self) -> io::Result<Option<RecordedUpdate>> {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        let mut read = 0;
+        while read < FRAME_HEADER_LEN {
+            match self.decoder.read(&mut header[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let received_at_us = u64::from_le_bytes(header[..8].try_into().expect("8 bytes"));
+        let len = u32::from_le_bytes(header[8..].try_into().expect("4 bytes"));
+        let mut encoded = vec![0; len as usize];
+        self.decoder.read_exact(&mut encoded)?;
+        let update = SubscribeUpdate::decode(encoded.as_slice())
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+        Ok(Some(RecordedUpdate {
+            received_at_us,
+            update,
+        }))
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = io::Result<RecordedUpdate>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.read_update().transpose();
+        self.done = !matches!(next, Some(Ok(_)));
+        next
+    }
+}
+
+/// The recording files `paths` name, directories expanded to the recordings
+/// they hold; file names carry their start time, so sorting them orders them.
+pub fn recording_files(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+
+        let mut recordings: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        recordings.retain(|file| {
+            file.extension()
+                .is_some_and(|extension| extension == RECORDING_EXTENSION)
+        });
+        recordings.sort();
+        files.extend(recordings);
+    }
+
+    Ok(files)
+}
+
+/// Paces a replay: each update is due the recorded gap after the first one,
+/// divided by the speed. A speed of 0 replays as fast as updates are read.
+pub struct ReplayClock {
+    speed: f64,
+    /// Receive time of the first update and when it was replayed.
+    origin: Option<(u64, Instant)>,
+}
+
+impl ReplayClock {
+    pub fn new(speed: f64) -> Self {
+        Self {
+            speed,
+            origin: None,
+        }
+    }
+
+    /// Waits until the update received at `received_at_us` is due.
+    pub async fn wait(&mut self, received_at_us: u64) {
+        if self.speed <= 0.0 {
+            return;
+        }
+        let (first_us, started) = *self
+            .origin
+            .get_or_insert_with(|| (received_at_us, Instant::now()));
+
+        let gap = Duration::from_micros(received_at_us.saturating_sub(first_us));
+        sleep_until(started + gap.div_f64(self.speed)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, thread, time::Instant};
+
+    use yellowstone_grpc_proto::geyser::{subscribe_update::UpdateOneof, SubscribeUpdatePing};
+    use zstd::stream::write::Encoder;
+
+    use super::*;
+    use crate::StreamRecorder;
+
+    fn update(filter: &str) -> SubscribeUpdate {
+        SubscribeUpdate {
+            filters: vec![filter.to_string()],
+            update_oneof: Some(UpdateOneof::Ping(SubscribeUpdatePing {})),
+            ..Default::default()
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Every update recorded under `dir`, files in order.
+    fn read_back(dir: &Path) -> io::Result<Vec<RecordedUpdate>> {
+        let mut updates = Vec::new();
+        for file in recording_files(&[dir.to_path_buf()])? {
+            for recorded in RecordingReader::open(file)? {
+                updates.push(recorded?);
+            }
+        }
+        Ok(updates)
+    }
+
+    #[test]
+    fn recordings_read_back_across_rotations() {
+        let dir = temp_dir("stream-recording");
+        let recorder = StreamRecorder::start(&dir, Duration::from_millis(50)).unwrap();
+        let filters = ["a", "b", "c", "d", "e"];
+        for filter in &filters[..3] {
+            recorder.record(&update(filter));
+        }
+        thread::sleep(Duration::from_millis(100));
+        for filter in &filters[3..] {
+            recorder.record(&update(filter));
+        }
+        // The writer closes the last file once the recorder is gone
+        drop(recorder);
+        fs::write(dir.join("notes.txt"), "not a recording").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let updates = loop {
+            match read_back(&dir) {
+                Ok(updates) if updates.len() == filters.len() => break updates,
+                _ if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+                result => panic!("recording not read back: {:?}", result.map(|u| u.len())),
+            }
+        };
+
+        assert!(recording_files(std::slice::from_ref(&dir)).unwrap().len() > 1);
+        let read: Vec<_> = updates
+            .iter()
+            .map(|recorded| recorded.update.filters[0].as_str())
+            .collect();
+        assert_eq!(read, filters);
+        assert!(updates
+            .iter()
+            .all(|recorded| recorded.update == update(&recorded.update.filters[0])));
+        assert!(updates
+            .windows(2)
+            .all(|pair| pair[0].received_at_us <= pair[1].received_at_us));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cut_frames_end_the_recording_with_an_error() {
+        let dir = temp_dir("stream-cut");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stream-cut.zst");
+
+        let encoded = update("a").encode_to_vec();
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&7u64.to_le_bytes());
+        frame.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&encoded);
+        let mut encoder = Encoder::new(File::create(&path).unwrap(), 3).unwrap();
+        encoder.write_all(&frame).unwrap();
+        // An interrupted run stops within the next frame
+        encoder.write_all(&frame[..FRAME_HEADER_LEN + 1]).unwrap();
+        encoder.finish().unwrap();
+
+        let mut reader = RecordingReader::open(&path).unwrap();
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.received_at_us, 7);
+        assert_eq!(first.update, update("a"));
+        let cut = reader.next().unwrap().unwrap_err();
+        assert_eq!(cut.kind(), ErrorKind::UnexpectedEof);
+        assert!(reader.next().is_none());
+
+        // A run killed after a flush leaves a zstd stream that never finishes
+        let mut encoder = Encoder::new(File::create(&path).unwrap(), 3).unwrap();
+        encoder.write_all(&frame).unwrap();
+        encoder.flush().unwrap();
+        drop(encoder);
+
+        let mut reader = RecordingReader::open(&path).unwrap();
+        assert_eq!(reader.next().unwrap().unwrap().update, update("a"));
+        assert!(reader.next().is_none_or(|end| end.is_err()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
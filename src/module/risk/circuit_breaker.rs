@@ -0,0 +1,264 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    hash::Hash,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{log, BreakerConfig, Gateway, TxOutcome};
+
+/// How long a probe may take to report back before another one is let
+/// through; longer than a submission takes to land or expire.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Consecutive failures of one gateway or pool and the backoff they caused.
+#[derive(Debug, Default)]
+struct Breaker {
+    failures: u32,
+    /// Times the breaker opened since it last saw a success.
+    trips: u32,
+    open_until: Option<Instant>,
+    probe_since: Option<Instant>,
+}
+
+impl Breaker {
+    /// Whether a submission may go through: always while closed, never while
+    /// open, and once per `PROBE_TIMEOUT` after the cooldown ran out.
+    fn allow(&mut self, now: Instant) -> bool {
+        match self.open_until {
+            None => true,
+            Some(until) if now < until => false,
+            Some(_) => {
+                if self
+                    .probe_since
+                    .is_some_and(|since| now.duration_since(since) < PROBE_TIMEOUT)
+                {
+                    return false;
+                }
+                self.probe_since = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Books an outcome; returns the cooldown when a failure opened the breaker.
+    fn record(
+        &mut self,
+        threshold: u32,
+        config: &BreakerConfig,
+        succeeded: bool,
+        now: Instant,
+    ) -> Option<Duration> {
+        if succeeded {
+            *self = Breaker::default();
+            return None;
+        }
+
+        self.failures += 1;
+        self.probe_since = None;
+        // Late outcomes of submissions made before the breaker opened only count
+        let probing = self.open_until.is_some_and(|until| now >= until);
+        if self.failures < threshold || (self.open_until.is_some() && !probing) {
+            return None;
+        }
+
+        self.trips += 1;
+        let cooldown = config
+            .cooldown_secs
+            .saturating_mul(1 << (self.trips - 1).min(32))
+            .min(config.max_cooldown_secs);
+        let cooldown = Duration::from_secs(cooldown);
+        self.open_until = Some(now + cooldown);
+        Some(cooldown)
+    }
+}
+
+/// Breakers of one kind, keyed by what they guard.
+#[derive(Debug)]
+struct BreakerSet<K> {
+    kind: &'static str,
+    breakers: Mutex<HashMap<K, Breaker>>,
+}
+
+impl<K: Copy + Eq + Hash + Display> BreakerSet<K> {
+    fn new(kind: &'static str) -> Self {
+        BreakerSet {
+            kind,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn allow(&self, threshold: u32, key: K) -> bool {
+        if threshold == 0 {
+            return true;
+        }
+        let mut breakers = self.breakers.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(breaker) = breakers.get_mut(&key) else {
+            return true;
+        };
+
+        let was_probing = breaker.probe_since.is_some();
+        let allowed = breaker.allow(Instant::now());
+        if allowed && breaker.open_until.is_some() && !was_probing {
+            log!(
+                format!("🔁 Probing {} {} after its cooldown", self.kind, key),
+                "info"
+            );
+        }
+        allowed
+    }
+
+    fn record(&self, threshold: u32, config: &BreakerConfig, key: K, succeeded: bool) {
+        if threshold == 0 {
+            return;
+        }
+        let mut breakers = self.breakers.lock().unwrap_or_else(PoisonError::into_inner);
+        let breaker = breakers.entry(key).or_default();
+
+        let was_open = breaker.open_until.is_some();
+        let failures = breaker.failures + 1;
+        match breaker.record(threshold, config, succeeded, Instant::now()) {
+            Some(cooldown) => log!(
+                format!(
+                    "⛔ Circuit breaker on {} {} open for {} s after {} consecutive failures",
+                    self.kind,
+                    key,
+                    cooldown.as_secs(),
+                    failures
+                ),
+                "error"
+            ),
+            None if succeeded && was_open => log!(
+                format!("✅ Circuit breaker on {} {} closed", self.kind, key),
+                "success"
+            ),
+            None => {}
+        }
+    }
+}
+
+/// Per-gateway and per-pool circuit breakers (`[breaker]`), fed by the
+/// confirmation outcome of every submission.
+///
+/// A gateway fails when it rejects a transaction or never lands it; one that
+/// lands a transaction did its job, even if the swap failed on-chain. When a
+/// race landed through one gateway, the others had nothing left to land:
+/// their outcomes count neither way. A pool
+/// fails when none of a submission's transactions landed successfully and at
+/// least one failed on-chain or expired, so gateway outages don't close pools.
+#[derive(Debug)]
+pub struct CircuitBreakers {
+    gateways: BreakerSet<Gateway>,
+    pools: BreakerSet<Pubkey>,
+}
+
+impl Default for CircuitBreakers {
+    fn default() -> Self {
+        CircuitBreakers {
+            gateways: BreakerSet::new("gateway"),
+            pools: BreakerSet::new("pool"),
+        }
+    }
+}
+
+impl CircuitBreakers {
+    /// Whether submissions may go through `gateway`.
+    pub fn allow_gateway(&self, config: &BreakerConfig, gateway: Gateway) -> bool {
+        self.gateways.allow(config.gateway_failures, gateway)
+    }
+
+    /// Whether submissions on `pool` may go out.
+    pub fn allow_pool(&self, config: &BreakerConfig, pool: Pubkey) -> bool {
+        self.pools.allow(config.pool_failures, pool)
+    }
+
+    /// Books what became of a submission on `pool`, per gateway it went through.
+    pub fn record(&self, config: &BreakerConfig, pool: Pubkey, outcomes: &[(Gateway, TxOutcome)]) {
+        let race_landed = outcomes.iter().any(|(_, outcome)| outcome.reached_chain());
+        for (gateway, outcome) in outcomes {
+            if race_landed && !outcome.reached_chain() {
+                continue;
+            }
+            self.gateways.record(
+                config.gateway_failures,
+                config,
+                *gateway,
+                outcome.reached_chain(),
+            );
+        }
+
+        match TxOutcome::best(outcomes.iter().map(|(_, outcome)| outcome.clone())) {
+            Some(TxOutcome::Landed { .. }) => {
+                self.pools.record(config.pool_failures, config, pool, true)
+            }
+            Some(TxOutcome::Failed { .. } | TxOutcome::Expired) => {
+                self.pools.record(config.pool_failures, config, pool, false)
+            }
+            Some(TxOutcome::Rejected(_)) | None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BreakerConfig {
+        BreakerConfig {
+            gateway_failures: 1,
+            pool_failures: 1,
+            cooldown_secs: 30,
+            max_cooldown_secs: 600,
+        }
+    }
+
+    #[test]
+    fn siblings_of_a_landed_race_are_neutral() {
+        let (breakers, pool) = (CircuitBreakers::default(), Pubkey::new_unique());
+
+        breakers.record(
+            &config(),
+            pool,
+            &[
+                (
+                    Gateway::Jito,
+                    TxOutcome::Landed {
+                        slot: 1,
+                        fee: 5_000,
+                    },
+                ),
+                (Gateway::Nozomi, TxOutcome::Expired),
+                (
+                    Gateway::Rpc,
+                    TxOutcome::Rejected("rate limited".to_string()),
+                ),
+            ],
+        );
+
+        assert!(breakers.allow_gateway(&config(), Gateway::Jito));
+        assert!(breakers.allow_gateway(&config(), Gateway::Nozomi));
+        assert!(breakers.allow_gateway(&config(), Gateway::Rpc));
+        assert!(breakers.allow_pool(&config(), pool));
+    }
+
+    #[test]
+    fn races_nothing_landed_fail_every_gateway() {
+        let (breakers, pool) = (CircuitBreakers::default(), Pubkey::new_unique());
+
+        breakers.record(
+            &config(),
+            pool,
+            &[
+                (Gateway::Jito, TxOutcome::Expired),
+                (Gateway::Nozomi, TxOutcome::Expired),
+            ],
+        );
+
+        assert!(!breakers.allow_gateway(&config(), Gateway::Jito));
+        assert!(!breakers.allow_gateway(&config(), Gateway::Nozomi));
+        assert!(!breakers.allow_pool(&config(), pool));
+    }
+}
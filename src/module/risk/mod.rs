@@ -0,0 +1,5 @@
+pub mod circuit_breaker;
+pub mod risk_manager;
+
+pub use circuit_breaker::*;
+pub use risk_manager::*;
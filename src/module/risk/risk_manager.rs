@@ -0,0 +1,823 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::Duration,
+};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::{
+    booked_pnl_since, held_exposures, lamports_to_sol, log, now_ms, sol_to_lamports,
+    with_trade_store, BreakerConfig, CircuitBreakers, Gateway, IntentKind, RiskConfig, StoreError,
+    TradeIntent, TxOutcome, REALIZED_HISTORY_MS,
+};
+
+/// Window of the trade rate limit, in milliseconds.
+const RATE_WINDOW_MS: i64 = 60_000;
+
+/// Window of the realized loss limit, all of the PnL the position book keeps.
+const LOSS_WINDOW_MS: i64 = REALIZED_HISTORY_MS;
+
+/// How long an admitted entry counts towards positions, exposure and the
+/// wallet reserve on top of the booked lots when it is never settled; longer
+/// than a submission can take to land or expire, in milliseconds.
+const INFLIGHT_TTL_MS: i64 = 120_000;
+
+/// How often the wallet balance is refreshed for the reserve check.
+const BALANCE_REFRESH: Duration = Duration::from_secs(5);
+
+/// How often the kill switch is read back from the store, where `make halt`
+/// and `make resume` change it.
+const KILL_SWITCH_REFRESH: Duration = Duration::from_secs(5);
+
+/// A tripped kill switch, as persisted in the trade store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillSwitch {
+    pub reason: String,
+    pub tripped_at_ms: i64,
+}
+
+/// Open cost basis of one pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenExposure {
+    pub pool_id: String,
+    pub token_mint: String,
+    pub cost_lamports: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum RiskError {
+    #[error("kill switch tripped: {0}")]
+    KillSwitch(String),
+
+    #[error("{open} positions open, the limit is {limit}")]
+    MaxOpenPositions { open: usize, limit: u32 },
+
+    #[error("exposure to {mint} would reach {exposure} SOL, the limit is {limit} SOL")]
+    MintExposure {
+        mint: String,
+        exposure: f64,
+        limit: f64,
+    },
+
+    #[error("total exposure would reach {exposure} SOL, the limit is {limit} SOL")]
+    TotalExposure { exposure: f64, limit: f64 },
+
+    #[error("{trades} trades in the last minute, the limit is {limit}")]
+    TradeRate { trades: usize, limit: u32 },
+
+    #[error("{loss} SOL realized loss over the last 24 hours, the limit is {limit} SOL")]
+    DailyLoss { loss: f64, limit: f64 },
+
+    #[error("wallet holds {balance} SOL, below its {reserve} SOL reserve")]
+    BelowReserve { balance: f64, reserve: f64 },
+
+    #[error("wallet would keep {remaining} SOL, below its {reserve} SOL reserve")]
+    WalletReserve { remaining: f64, reserve: f64 },
+
+    #[error("wallet balance unknown, the reserve can't be checked")]
+    BalanceUnknown,
+
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl RiskError {
+    /// Whether this is a broken limit, which halts entries until an operator
+    /// resets the kill switch; a tripped switch, an unknown balance or a
+    /// store failure only hold back the entry at hand.
+    pub fn trips_kill_switch(&self) -> bool {
+        !matches!(
+            self,
+            RiskError::KillSwitch(_) | RiskError::BalanceUnknown | RiskError::Store(_)
+        )
+    }
+}
+
+/// What `RiskManager::admit` let through, handed back to `settle` once the
+/// submission is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Admission {
+    /// Key of the in-flight entry; exits have none.
+    entry: Option<u64>,
+}
+
+/// An admitted entry whose fill may not be booked yet.
+#[derive(Debug)]
+struct InflightEntry {
+    id: u64,
+    since_ms: i64,
+    pool: String,
+    mint: String,
+    lamports: u64,
+}
+
+#[derive(Debug, Default)]
+struct RiskState {
+    /// Times of the recent submissions, oldest first.
+    submissions: VecDeque<i64>,
+    inflight: Vec<InflightEntry>,
+    /// Key of the next admitted entry.
+    next_id: u64,
+}
+
+impl RiskState {
+    fn prune(&mut self, now_ms: i64) {
+        while self
+            .submissions
+            .front()
+            .is_some_and(|at_ms| now_ms - at_ms >= RATE_WINDOW_MS)
+        {
+            self.submissions.pop_front();
+        }
+        self.inflight
+            .retain(|entry| now_ms - entry.since_ms < INFLIGHT_TTL_MS);
+    }
+}
+
+/// Gate every submission goes through (`[risk]`).
+///
+/// Exits are always let through, only counted towards the trade rate.
+/// Entries are checked against the persistent kill switch and every limit;
+/// breaking any limit trips the kill switch, which holds entries back until
+/// `make resume`. It also keeps the circuit breakers of gateways and pools.
+///
+/// Entries are checked in memory: positions and realized PnL come from the
+/// position book, and the kill switch is a copy of the stored one, tripped
+/// here or refreshed by `spawn_kill_switch_watch`.
+#[derive(Debug, Default)]
+pub struct RiskManager {
+    state: Mutex<RiskState>,
+    wallet_lamports: Mutex<Option<u64>>,
+    kill_switch: Arc<KillSwitchCopy>,
+    breakers: CircuitBreakers,
+}
+
+/// The risk manager's copy of the stored kill switch.
+#[derive(Debug, Default)]
+struct KillSwitchCopy {
+    tripped: Mutex<Option<KillSwitch>>,
+    /// Trips held in the copy whose store write hasn't run yet.
+    unsaved_trips: AtomicUsize,
+}
+
+impl KillSwitchCopy {
+    fn lock(&self) -> MutexGuard<'_, Option<KillSwitch>> {
+        self.tripped.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Writes a trip held in the copy to the store. Blocks on the store.
+    fn save(&self, reason: &str) {
+        // Under the store lock, so a refresh reads either the unsaved trip or the stored one
+        let tripped = with_trade_store(|store| {
+            let tripped = store.trip_kill_switch(reason)?;
+            *self.lock() = store.kill_switch()?;
+            Ok(tripped)
+        });
+        self.unsaved_trips.fetch_sub(1, Ordering::SeqCst);
+
+        match tripped {
+            Ok(true) => log!(
+                format!("🛑 Kill switch tripped, entries halted : {}", reason),
+                "error"
+            ),
+            Ok(false) => {}
+            Err(e) => log!(format!("🔴 Failed to trip the kill switch: {}", e), "error"),
+        }
+    }
+}
+
+impl RiskManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn breakers(&self) -> &CircuitBreakers {
+        &self.breakers
+    }
+
+    /// Admits `intent` for submission, booking it towards the limits, or
+    /// says which limit holds it back.
+    pub fn admit(&self, limits: &RiskConfig, intent: &TradeIntent) -> Result<Admission, RiskError> {
+        // On the store's clock, which replays drive
+        let now_ms = now_ms();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.prune(now_ms);
+
+        let lamports = match intent.kind {
+            IntentKind::Buy { sol_amount, .. } => sol_to_lamports(sol_amount),
+            IntentKind::BuyExactOut { max_sol_amount, .. } => sol_to_lamports(max_sol_amount),
+            IntentKind::Sell { .. } => {
+                state.submissions.push_back(now_ms);
+                return Ok(Admission { entry: None });
+            }
+        } + sol_to_lamports(intent.tip);
+        let entry = InflightEntry {
+            id: state.next_id,
+            since_ms: now_ms,
+            pool: intent.pool.to_string(),
+            mint: intent.mint.to_string(),
+            lamports,
+        };
+
+        if let Err(e) = self.check_entry(limits, &state, &entry) {
+            drop(state);
+            if e.trips_kill_switch() {
+                self.trip_kill_switch(&e);
+            }
+            return Err(e);
+        }
+
+        let admission = Admission {
+            entry: Some(entry.id),
+        };
+        state.next_id += 1;
+        state.submissions.push_back(now_ms);
+        state.inflight.push(entry);
+
+        Ok(admission)
+    }
+
+    /// Books what became of an admitted intent: each gateway's outcome feeds
+    /// the circuit breakers, and its entry stops counting as in flight, the
+    /// fill being booked by now if it landed.
+    pub fn settle(
+        &self,
+        breaker: &BreakerConfig,
+        intent: &TradeIntent,
+        admission: Admission,
+        outcomes: &[(Gateway, TxOutcome)],
+    ) {
+        self.breakers.record(breaker, intent.pool, outcomes);
+
+        let Some(id) = admission.entry else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.inflight.retain(|entry| entry.id != id);
+    }
+
+    fn check_entry(
+        &self,
+        limits: &RiskConfig,
+        state: &RiskState,
+        entry: &InflightEntry,
+    ) -> Result<(), RiskError> {
+        if let Some(kill_switch) = self.kill_switch.lock().as_ref() {
+            return Err(RiskError::KillSwitch(kill_switch.reason.clone()));
+        }
+
+        if limits.daily_loss_limit_sol > 0.0 {
+            let realized = booked_pnl_since(now_ms() - LOSS_WINDOW_MS)?;
+            let loss = lamports_to_sol(realized.min(0).unsigned_abs());
+            if loss >= limits.daily_loss_limit_sol {
+                return Err(RiskError::DailyLoss {
+                    loss,
+                    limit: limits.daily_loss_limit_sol,
+                });
+            }
+        }
+
+        if limits.min_wallet_reserve_sol > 0.0 {
+            let reserve = limits.min_wallet_reserve_sol;
+            let balance = self
+                .wallet_lamports
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .ok_or(RiskError::BalanceUnknown)?;
+            if lamports_to_sol(balance) < reserve {
+                return Err(RiskError::BelowReserve {
+                    balance: lamports_to_sol(balance),
+                    reserve,
+                });
+            }
+            let inflight: u64 = state.inflight.iter().map(|entry| entry.lamports).sum();
+            let remaining = lamports_to_sol(balance.saturating_sub(inflight + entry.lamports));
+            if remaining < reserve {
+                return Err(RiskError::WalletReserve { remaining, reserve });
+            }
+        }
+
+        if limits.max_trades_per_minute > 0
+            && state.submissions.len() >= limits.max_trades_per_minute as usize
+        {
+            return Err(RiskError::TradeRate {
+                trades: state.submissions.len(),
+                limit: limits.max_trades_per_minute,
+            });
+        }
+
+        if limits.max_open_positions == 0
+            && limits.max_mint_exposure_sol <= 0.0
+            && limits.max_total_exposure_sol <= 0.0
+        {
+            return Ok(());
+        }
+        let exposures = held_exposures()?;
+
+        if limits.max_open_positions > 0 {
+            let open: HashSet<&str> = exposures
+                .iter()
+                .map(|exposure| exposure.pool_id.as_str())
+                .chain(state.inflight.iter().map(|entry| entry.pool.as_str()))
+                .collect();
+            if !open.contains(entry.pool.as_str())
+                && open.len() >= limits.max_open_positions as usize
+            {
+                return Err(RiskError::MaxOpenPositions {
+                    open: open.len(),
+                    limit: limits.max_open_positions,
+                });
+            }
+        }
+
+        let booked = exposures
+            .iter()
+            .map(|exposure| (exposure.token_mint.as_str(), exposure.cost_lamports));
+        let inflight = state
+            .inflight
+            .iter()
+            .map(|entry| (entry.mint.as_str(), entry.lamports));
+        let (mint_lamports, total_lamports) = booked.chain(inflight).fold(
+            (entry.lamports, entry.lamports),
+            |(mint_lamports, total_lamports), (mint, lamports)| {
+                let mint_lamports = if mint == entry.mint {
+                    mint_lamports + lamports
+                } else {
+                    mint_lamports
+                };
+                (mint_lamports, total_lamports + lamports)
+            },
+        );
+
+        let mint_exposure = lamports_to_sol(mint_lamports);
+        if limits.max_mint_exposure_sol > 0.0 && mint_exposure > limits.max_mint_exposure_sol {
+            return Err(RiskError::MintExposure {
+                mint: entry.mint.clone(),
+                exposure: mint_exposure,
+                limit: limits.max_mint_exposure_sol,
+            });
+        }
+        let total_exposure = lamports_to_sol(total_lamports);
+        if limits.max_total_exposure_sol > 0.0 && total_exposure > limits.max_total_exposure_sol {
+            return Err(RiskError::TotalExposure {
+                exposure: total_exposure,
+                limit: limits.max_total_exposure_sol,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Holds entries back from now on for `breach`, and persists the kill
+    /// switch off the async runtime (inline outside of one). Only the first
+    /// breach writes to the store: later entries stop at the copy.
+    fn trip_kill_switch(&self, breach: &RiskError) {
+        let reason = breach.to_string();
+        {
+            let mut tripped = self.kill_switch.lock();
+            if tripped.is_some() {
+                return;
+            }
+            *tripped = Some(KillSwitch {
+                reason: reason.clone(),
+                tripped_at_ms: now_ms(),
+            });
+            // Before the copy is unlocked, so no refresh can clear it meanwhile
+            self.kill_switch
+                .unsaved_trips
+                .fetch_add(1, Ordering::SeqCst);
+        }
+
+        let kill_switch = Arc::clone(&self.kill_switch);
+        let save = move || kill_switch.save(&reason);
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(save)),
+            Err(_) => save(),
+        }
+    }
+
+    /// Reads the kill switch back from the store into the copy entries are
+    /// checked against, and returns it; a trip not written yet stays in the
+    /// copy. Blocks on the store.
+    pub fn load_kill_switch(&self) -> Result<Option<KillSwitch>, StoreError> {
+        with_trade_store(|store| {
+            let stored = store.kill_switch()?;
+            let mut tripped = self.kill_switch.lock();
+            if stored.is_some() || self.kill_switch.unsaved_trips.load(Ordering::SeqCst) == 0 {
+                *tripped = stored;
+            }
+            Ok(tripped.clone())
+        })
+    }
+
+    /// Follows the kill switch other processes trip or reset, off the runtime.
+    pub fn spawn_kill_switch_watch(self: &Arc<Self>) {
+        let risk = Arc::clone(self);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(KILL_SWITCH_REFRESH);
+            loop {
+                interval.tick().await;
+                let watched = Arc::clone(&risk);
+                let refreshed = tokio::task::spawn_blocking(move || watched.load_kill_switch())
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|loaded| loaded.map_err(|e| e.to_string()));
+                if let Err(e) = refreshed {
+                    log!(
+                        format!("🔴 Failed to refresh the kill switch: {}", e),
+                        "error"
+                    );
+                }
+            }
+        });
+    }
+
+    /// Sets the wallet balance the reserve check sees, for replays, which
+    /// don't watch the wallet.
+    pub fn set_wallet_balance(&self, lamports: u64) {
+        *self
+            .wallet_lamports
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(lamports);
+    }
+
+    /// Keeps the wallet balance used by the reserve check up to date.
+    pub fn spawn_balance_watch(self: &Arc<Self>, client: Arc<RpcClient>, wallet: Pubkey) {
+        let risk = Arc::clone(self);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(BALANCE_REFRESH);
+            loop {
+                interval.tick().await;
+                match client.get_balance(&wallet).await {
+                    Ok(lamports) => {
+                        *risk
+                            .wallet_lamports
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner) = Some(lamports);
+                    }
+                    Err(e) => log!(
+                        format!("🔴 Failed to refresh the wallet balance: {}", e),
+                        "error"
+                    ),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{book_fill, use_test_trade_store, BuyHistoryInfo, ConfirmedFill, Dex, SwapSide};
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn no_limits() -> RiskConfig {
+        RiskConfig {
+            max_open_positions: 0,
+            max_mint_exposure_sol: 0.0,
+            max_total_exposure_sol: 0.0,
+            max_trades_per_minute: 0,
+            daily_loss_limit_sol: 0.0,
+            min_wallet_reserve_sol: 0.0,
+        }
+    }
+
+    fn no_breakers() -> BreakerConfig {
+        BreakerConfig {
+            gateway_failures: 0,
+            pool_failures: 0,
+            cooldown_secs: 0,
+            max_cooldown_secs: 0,
+        }
+    }
+
+    fn buy(pool: Pubkey, mint: Pubkey, sol_amount: f64) -> TradeIntent {
+        TradeIntent {
+            strategy: "test",
+            dex: Dex::RaydiumAmm,
+            pool,
+            mint,
+            kind: IntentKind::Buy {
+                sol_amount,
+                expected_token_amount: 0.0,
+            },
+            tip: 0.0,
+            urgency: Default::default(),
+            reserves: Default::default(),
+            slippage_pct: 1.0,
+        }
+    }
+
+    fn sell(pool: Pubkey, mint: Pubkey) -> TradeIntent {
+        TradeIntent {
+            kind: IntentKind::Sell { token_amount: 1 },
+            ..buy(pool, mint, 0.0)
+        }
+    }
+
+    /// Records `pool` in the store and books a fill on it.
+    fn book(pool: Pubkey, mint: Pubkey, side: SwapSide, wallet_lamports: i64) {
+        with_trade_store(|store| {
+            store.save_position(&BuyHistoryInfo {
+                pool_id: pool.to_string(),
+                base_mint: mint.to_string(),
+                quote_mint: crate::NATIVE_MINT.to_string(),
+                base_vault: String::new(),
+                quote_vault: String::new(),
+                token_ata: String::new(),
+                symbol: String::new(),
+                total_amount_in: 0,
+                total_ui_amount_in: 0.0,
+                total_token_amount_out: 0,
+                total_ui_token_amount_out: 0.0,
+                take_profit: 0.0,
+                transactions: vec![],
+                dex: Dex::RaydiumAmm.to_string(),
+            })
+        })
+        .unwrap();
+        let fill = ConfirmedFill {
+            signature: Pubkey::new_unique().to_string(),
+            side,
+            token_amount: 1_000,
+            token_decimals: 6,
+            wallet_lamports,
+            network_fee: 0,
+        };
+        book_fill(&pool.to_string(), &fill).unwrap();
+    }
+
+    fn stored_kill_switch() -> Option<KillSwitch> {
+        with_trade_store(|store| store.kill_switch()).unwrap()
+    }
+
+    #[test]
+    fn entries_pass_without_limits() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+
+        for _ in 0..10 {
+            let intent = buy(Pubkey::new_unique(), Pubkey::new_unique(), 5.0);
+            risk.admit(&no_limits(), &intent).unwrap();
+        }
+        assert_eq!(stored_kill_switch(), None);
+    }
+
+    #[test]
+    fn trade_rate_counts_exits_and_trips_the_kill_switch() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            max_trades_per_minute: 2,
+            ..no_limits()
+        };
+        let (pool, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        risk.admit(&limits, &sell(pool, mint)).unwrap();
+        risk.admit(&limits, &buy(pool, mint, 0.1)).unwrap();
+        // Exits are let through past the limit
+        risk.admit(&limits, &sell(pool, mint)).unwrap();
+
+        let held = risk.admit(&limits, &buy(pool, mint, 0.1));
+        assert!(matches!(
+            held,
+            Err(RiskError::TradeRate {
+                trades: 3,
+                limit: 2
+            })
+        ));
+        assert!(stored_kill_switch().is_some_and(|tripped| tripped.reason.contains("trades")));
+    }
+
+    #[test]
+    fn open_positions_count_booked_and_inflight_pools() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            max_open_positions: 2,
+            ..no_limits()
+        };
+        let held = (Pubkey::new_unique(), Pubkey::new_unique());
+        book(held.0, held.1, SwapSide::Buy, -(SOL as i64));
+
+        let inflight = buy(Pubkey::new_unique(), Pubkey::new_unique(), 0.1);
+        risk.admit(&limits, &inflight).unwrap();
+        // Adding to a pool already open takes no new slot
+        risk.admit(&limits, &buy(held.0, held.1, 0.1)).unwrap();
+
+        let third = buy(Pubkey::new_unique(), Pubkey::new_unique(), 0.1);
+        assert!(matches!(
+            risk.admit(&limits, &third),
+            Err(RiskError::MaxOpenPositions { open: 2, limit: 2 })
+        ));
+        assert!(stored_kill_switch().is_some());
+    }
+
+    #[test]
+    fn mint_exposure_adds_booked_inflight_and_tip() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            max_mint_exposure_sol: 2.0,
+            ..no_limits()
+        };
+        let (pool, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        book(pool, mint, SwapSide::Buy, -(SOL as i64));
+
+        // Another mint doesn't count
+        risk.admit(
+            &limits,
+            &buy(Pubkey::new_unique(), Pubkey::new_unique(), 1.5),
+        )
+        .unwrap();
+        risk.admit(&limits, &buy(pool, mint, 0.5)).unwrap();
+
+        let tipped = TradeIntent {
+            tip: 0.1,
+            ..buy(pool, mint, 0.45)
+        };
+        assert!(matches!(
+            risk.admit(&limits, &tipped),
+            Err(RiskError::MintExposure { .. })
+        ));
+        assert!(stored_kill_switch().is_some());
+    }
+
+    #[test]
+    fn total_exposure_adds_every_mint() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            max_total_exposure_sol: 2.0,
+            ..no_limits()
+        };
+        book(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            SwapSide::Buy,
+            -(SOL as i64),
+        );
+
+        risk.admit(
+            &limits,
+            &buy(Pubkey::new_unique(), Pubkey::new_unique(), 0.5),
+        )
+        .unwrap();
+        assert!(matches!(
+            risk.admit(
+                &limits,
+                &buy(Pubkey::new_unique(), Pubkey::new_unique(), 0.6)
+            ),
+            Err(RiskError::TotalExposure { .. })
+        ));
+        assert!(stored_kill_switch().is_some());
+    }
+
+    #[test]
+    fn daily_loss_counts_realized_pnl() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            daily_loss_limit_sol: 0.5,
+            ..no_limits()
+        };
+        let (pool, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        book(pool, mint, SwapSide::Buy, -(SOL as i64));
+        risk.admit(&limits, &buy(pool, mint, 0.1)).unwrap();
+
+        // Sells the whole lot back for 0.4 SOL: 0.6 SOL realized loss
+        book(pool, mint, SwapSide::Sell, 400_000_000);
+        assert!(matches!(
+            risk.admit(&limits, &buy(pool, mint, 0.1)),
+            Err(RiskError::DailyLoss { .. })
+        ));
+        assert!(stored_kill_switch().is_some_and(|tripped| tripped.reason.contains("loss")));
+    }
+
+    #[test]
+    fn wallet_reserve_needs_a_balance_and_counts_inflight_entries() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            min_wallet_reserve_sol: 1.0,
+            ..no_limits()
+        };
+        let (pool, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // An unknown balance holds the entry back without tripping anything
+        assert!(matches!(
+            risk.admit(&limits, &buy(pool, mint, 0.1)),
+            Err(RiskError::BalanceUnknown)
+        ));
+        assert_eq!(stored_kill_switch(), None);
+
+        risk.set_wallet_balance(2 * SOL);
+        risk.admit(&limits, &buy(pool, mint, 0.6)).unwrap();
+        assert!(matches!(
+            risk.admit(&limits, &buy(pool, mint, 0.6)),
+            Err(RiskError::WalletReserve { .. })
+        ));
+        assert!(stored_kill_switch().is_some());
+    }
+
+    #[test]
+    fn wallet_below_reserve_trips_the_kill_switch() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            min_wallet_reserve_sol: 1.0,
+            ..no_limits()
+        };
+        risk.set_wallet_balance(SOL / 2);
+
+        let intent = buy(Pubkey::new_unique(), Pubkey::new_unique(), 0.1);
+        assert!(matches!(
+            risk.admit(&limits, &intent),
+            Err(RiskError::BelowReserve { .. })
+        ));
+        assert!(stored_kill_switch().is_some_and(|tripped| tripped.reason.contains("reserve")));
+    }
+
+    #[test]
+    fn settle_frees_the_entry_it_admitted() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+        let limits = RiskConfig {
+            max_total_exposure_sol: 1.0,
+            ..no_limits()
+        };
+        let (pool, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let small = buy(pool, mint, 0.2);
+        let large = buy(pool, mint, 0.7);
+        let _small = risk.admit(&limits, &small).unwrap();
+        let large_admission = risk.admit(&limits, &large).unwrap();
+
+        // Settling the later, larger entry of the same pool frees its 0.7 SOL
+        risk.settle(&no_breakers(), &large, large_admission, &[]);
+        risk.admit(&limits, &buy(pool, mint, 0.7)).unwrap();
+        assert_eq!(stored_kill_switch(), None);
+    }
+
+    #[test]
+    fn kill_switch_persists_until_reset() {
+        let _store = use_test_trade_store();
+        let limits = RiskConfig {
+            max_trades_per_minute: 1,
+            ..no_limits()
+        };
+        let (pool, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let risk = RiskManager::new();
+        risk.admit(&limits, &buy(pool, mint, 0.1)).unwrap();
+        assert!(risk.admit(&limits, &buy(pool, mint, 0.1)).is_err());
+        let tripped = stored_kill_switch().expect("tripped by the trade rate");
+
+        // A restart reads the switch back and holds entries under any limits
+        let restarted = RiskManager::new();
+        assert_eq!(restarted.load_kill_switch().unwrap(), Some(tripped.clone()));
+        assert!(matches!(
+            restarted.admit(&no_limits(), &buy(pool, mint, 0.1)),
+            Err(RiskError::KillSwitch(reason)) if reason == tripped.reason
+        ));
+
+        // Later breaches keep the first reason
+        assert!(risk.admit(&limits, &buy(pool, mint, 0.1)).is_err());
+        assert_eq!(stored_kill_switch(), Some(tripped));
+
+        // `make resume` clears it, and the next refresh lets entries through
+        assert!(with_trade_store(|store| store.reset_kill_switch()).unwrap());
+        assert_eq!(restarted.load_kill_switch().unwrap(), None);
+        restarted
+            .admit(&no_limits(), &buy(pool, mint, 0.1))
+            .unwrap();
+    }
+
+    #[test]
+    fn unsaved_trips_survive_a_refresh() {
+        let _store = use_test_trade_store();
+        let risk = RiskManager::new();
+
+        *risk.kill_switch.lock() = Some(KillSwitch {
+            reason: "test".to_string(),
+            tripped_at_ms: 1,
+        });
+        risk.kill_switch
+            .unsaved_trips
+            .fetch_add(1, Ordering::SeqCst);
+        assert!(risk.load_kill_switch().unwrap().is_some());
+
+        risk.kill_switch.save("test");
+        assert_eq!(risk.load_kill_switch().unwrap(), stored_kill_switch());
+        assert!(stored_kill_switch().is_some());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod pool_state_cache;
+
+pub use pool_state_cache::*;
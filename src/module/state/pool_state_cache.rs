@@ -0,0 +1,322 @@
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use tokio::sync::broadcast;
+use yellowstone_grpc_proto::geyser::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+};
+
+use crate::{
+    cpmm_reserves, decode_cpmm_pool_state, load_token_info, log, raydium_amm_reserves,
+    CpmmPoolState, LiquidityStateLayoutV4, PoolConfig, Reserves, NATIVE_MINT,
+};
+
+/// `getMultipleAccounts` accepts at most this many keys per call.
+const SEED_BATCH_SIZE: usize = 100;
+
+/// Pool updates a slow subscriber may fall behind by before missing some.
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+/// Reserves of a pool as its subscribed accounts last reported them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSnapshot {
+    pub reserves: Reserves,
+    /// Oldest slot among the accounts the reserves were derived from.
+    pub slot: u64,
+}
+
+/// Constant-product venues whose vault balances price the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CachedVenue {
+    RaydiumAmm,
+    RaydiumCpmm,
+    PumpSwap,
+}
+
+/// What a subscribed account is to the pool it belongs to.
+#[derive(Debug, Clone, Copy)]
+enum WatchedAccount {
+    BaseVault(Pubkey),
+    QuoteVault(Pubkey),
+    PoolState(Pubkey),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slotted<T> {
+    value: T,
+    slot: u64,
+    /// Orders the writes of one slot; 0 for accounts read from the RPC.
+    write_version: u64,
+}
+
+/// Latest decoded accounts of one pool.
+#[derive(Debug)]
+struct PoolAccounts {
+    venue: CachedVenue,
+    sol_is_base: bool,
+    base_vault: Option<Slotted<u64>>,
+    quote_vault: Option<Slotted<u64>>,
+    /// Pool state, for the venues whose vaults hold more than the liquidity.
+    state: Option<Slotted<PoolStateAccount>>,
+}
+
+#[derive(Debug)]
+enum PoolStateAccount {
+    RaydiumAmm(Box<LiquidityStateLayoutV4>),
+    RaydiumCpmm(Box<CpmmPoolState>),
+}
+
+/// Live reserves of every constant-product pool in the pool list, fed by the
+/// account stream so quotes don't depend on the one transaction just observed.
+///
+/// Whirlpool and DLMM pools are not cached: their vaults don't price the
+/// token, and their swaps are quoted against live tick / bin arrays.
+pub struct PoolStateCache {
+    watched: HashMap<Pubkey, WatchedAccount>,
+    pools: RwLock<HashMap<Pubkey, PoolAccounts>>,
+    updates: broadcast::Sender<Pubkey>,
+}
+
+impl PoolStateCache {
+    /// Watches the vaults of every listed pool prepared by pre_start, plus the
+    /// pool account on Raydium AMM (PnL not taken yet) and CPMM (protocol and
+    /// fund fees), whose vaults hold more than the swappable liquidity.
+    pub fn new(pools: &[PoolConfig]) -> Self {
+        let mut watched = HashMap::new();
+        let mut states = HashMap::new();
+
+        for pool in pools {
+            let (Ok(pool_id), Ok(info)) = (pool.address.parse(), load_token_info(&pool.address))
+            else {
+                log!(
+                    format!(
+                        "No keys recorded for {}, its reserves are not cached",
+                        pool.address
+                    ),
+                    "error"
+                );
+                continue;
+            };
+            let venue = match info.dex.as_str() {
+                "RAYDIUM_AMM" => CachedVenue::RaydiumAmm,
+                "RAYDIUM_CPMM" => CachedVenue::RaydiumCpmm,
+                "PUMPSWAP" => CachedVenue::PumpSwap,
+                _ => continue,
+            };
+            let (Ok(base_vault), Ok(quote_vault)) =
+                (info.base_vault_b64.parse(), info.quote_vault_b64.parse())
+            else {
+                continue;
+            };
+
+            watched.insert(base_vault, WatchedAccount::BaseVault(pool_id));
+            watched.insert(quote_vault, WatchedAccount::QuoteVault(pool_id));
+            if venue != CachedVenue::PumpSwap {
+                watched.insert(pool_id, WatchedAccount::PoolState(pool_id));
+            }
+            states.insert(
+                pool_id,
+                PoolAccounts {
+                    venue,
+                    sol_is_base: info.base_mint == NATIVE_MINT,
+                    base_vault: None,
+                    quote_vault: None,
+                    state: None,
+                },
+            );
+        }
+
+        PoolStateCache {
+            watched,
+            pools: RwLock::new(states),
+            updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Pools whose cached accounts changed, one message per applied update.
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<Pubkey> {
+        self.updates.subscribe()
+    }
+
+    /// Whether `pool`'s reserves are cached at all.
+    pub fn is_cached(&self, pool: &Pubkey) -> bool {
+        self.pools
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(pool)
+    }
+
+    /// Account subscription covering every watched account.
+    pub fn subscribe_request(&self) -> SubscribeRequest {
+        SubscribeRequest {
+            accounts: HashMap::from([(
+                "pool_state".to_string(),
+                SubscribeRequestFilterAccounts {
+                    account: self.watched.keys().map(Pubkey::to_string).collect(),
+                    ..Default::default()
+                },
+            )]),
+            commitment: Some(CommitmentLevel::Processed as i32),
+            ..Default::default()
+        }
+    }
+
+    /// Loads the current state of every watched account, since the stream only
+    /// reports accounts once they change.
+    pub async fn seed(&self, client: &RpcClient) -> Result<()> {
+        let keys: Vec<Pubkey> = self.watched.keys().copied().collect();
+
+        for batch in keys.chunks(SEED_BATCH_SIZE) {
+            let response = client
+                .get_multiple_accounts_with_commitment(batch, CommitmentConfig::processed())
+                .await?;
+            for (key, account) in batch.iter().zip(response.value) {
+                if let Some(account) = account {
+                    // Any write the stream reports for the slot is newer
+                    self.apply(key, &account.data, response.context.slot, 0);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes an update of a watched account and notifies update subscribers;
+    /// writes older than the cached one by `(slot, write_version)` and accounts
+    /// that fail to decode are ignored.
+    pub fn apply(&self, key: &Pubkey, data: &[u8], slot: u64, write_version: u64) {
+        let Some(watched) = self.watched.get(key) else {
+            return;
+        };
+        let mut pools = self.pools.write().unwrap_or_else(PoisonError::into_inner);
+
+        let (pool, applied) = match *watched {
+            WatchedAccount::BaseVault(pool) | WatchedAccount::QuoteVault(pool) => {
+                let Some(accounts) = pools.get_mut(&pool) else {
+                    return;
+                };
+                let vault = match watched {
+                    WatchedAccount::BaseVault(_) => &mut accounts.base_vault,
+                    _ => &mut accounts.quote_vault,
+                };
+                let applied = token_account_amount(data)
+                    .is_some_and(|amount| update(vault, amount, slot, write_version));
+                (pool, applied)
+            }
+            WatchedAccount::PoolState(pool) => {
+                let Some(accounts) = pools.get_mut(&pool) else {
+                    return;
+                };
+                let state = match accounts.venue {
+                    CachedVenue::RaydiumAmm => LiquidityStateLayoutV4::deserialize(&mut &data[..])
+                        .ok()
+                        .map(|state| PoolStateAccount::RaydiumAmm(Box::new(state))),
+                    CachedVenue::RaydiumCpmm => decode_cpmm_pool_state(data)
+                        .ok()
+                        .map(|state| PoolStateAccount::RaydiumCpmm(Box::new(state))),
+                    CachedVenue::PumpSwap => None,
+                };
+                let applied = state
+                    .is_some_and(|state| update(&mut accounts.state, state, slot, write_version));
+                (pool, applied)
+            }
+        };
+        drop(pools);
+
+        if applied {
+            // Nobody listening is fine, the cache is read on demand too
+            let _ = self.updates.send(pool);
+        }
+    }
+
+    /// Current reserves of `pool`, once every account they depend on was seen.
+    pub fn snapshot(&self, pool: &Pubkey) -> Option<PoolSnapshot> {
+        let pools = self.pools.read().unwrap_or_else(PoisonError::into_inner);
+        let accounts = pools.get(pool)?;
+
+        let base = accounts.base_vault?;
+        let quote = accounts.quote_vault?;
+        let mut slot = base.slot.min(quote.slot);
+
+        let (base_amount, quote_amount) = match (accounts.venue, &accounts.state) {
+            (CachedVenue::PumpSwap, _) => (base.value, quote.value),
+            (_, None) => return None,
+            (_, Some(state)) => {
+                slot = slot.min(state.slot);
+                match &state.value {
+                    PoolStateAccount::RaydiumAmm(state) => {
+                        raydium_amm_reserves(state, base.value, quote.value)
+                    }
+                    PoolStateAccount::RaydiumCpmm(state) => {
+                        cpmm_reserves(state, base.value, quote.value)
+                    }
+                }
+            }
+        };
+
+        let reserves = if accounts.sol_is_base {
+            Reserves {
+                sol: base_amount,
+                token: quote_amount,
+            }
+        } else {
+            Reserves {
+                sol: quote_amount,
+                token: base_amount,
+            }
+        };
+
+        Some(PoolSnapshot { reserves, slot })
+    }
+}
+
+/// Stores `value` unless the cached one is a later write; returns whether it
+/// did.
+fn update<T>(cached: &mut Option<Slotted<T>>, value: T, slot: u64, write_version: u64) -> bool {
+    let is_newer = cached
+        .as_ref()
+        .is_none_or(|cached| (cached.slot, cached.write_version) <= (slot, write_version));
+    if is_newer {
+        *cached = Some(Slotted {
+            value,
+            slot,
+            write_version,
+        });
+    }
+    is_newer
+}
+
+/// Balance of an SPL Token or Token-2022 account; extensions come after it.
+pub fn token_account_amount(data: &[u8]) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(64..72)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_writes_of_a_slot_replace_earlier_ones() {
+        let mut cached = None;
+        assert!(update(&mut cached, 1, 100, 7));
+        assert!(!update(&mut cached, 2, 100, 6));
+        assert!(update(&mut cached, 3, 100, 8));
+        assert!(!update(&mut cached, 4, 99, 9));
+        assert!(update(&mut cached, 5, 101, 0));
+        assert_eq!(cached.map(|cached| cached.value), Some(5));
+    }
+
+    #[test]
+    fn seeded_accounts_yield_to_streamed_writes_of_their_slot() {
+        let mut cached = None;
+        assert!(update(&mut cached, 1, 100, 3));
+        assert!(!update(&mut cached, 2, 100, 0));
+        assert_eq!(cached.map(|cached| cached.value), Some(1));
+    }
+}
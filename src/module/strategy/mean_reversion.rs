@@ -0,0 +1,160 @@
+use crate::{
+    dump_sizing, lamports_to_sol, log, position_pnl, sol_to_lamports, IntentKind, ObservedSwap,
+    Strategy, StrategyContext, SwapSide, TradeIntent, Urgency, EST_SWAP_NETWORK_FEE,
+};
+
+/// Buys into large sells and takes profit on the following buys.
+///
+/// Entry size, tip and slippage come from the pool's sizing table by price
+/// drop and liquidity (see `dump_sizing`); exits sell every open lot once their unrealized PnL, net of
+/// entry and exit costs, reaches `take_profit`.
+#[derive(Debug, Default)]
+pub struct MeanReversion;
+
+impl MeanReversion {
+    pub const NAME: &'static str = "mean_reversion";
+
+    fn take_profit(&self, swap: &ObservedSwap, ctx: &StrategyContext) -> Option<TradeIntent> {
+        if !ctx.config.mode.show_buy || !ctx.pool.enable_sell {
+            return None;
+        }
+        log!("\t==> BUY Trade ==", "info");
+
+        let tip = swap.dex.sell_tip();
+        // Entry costs are in the lots, the exit's own tip and fee come off its proceeds
+        let exit_costs = sol_to_lamports(tip) + EST_SWAP_NETWORK_FEE;
+        let position = position_pnl(ctx.position, |token_amount| {
+            sol_to_lamports(expected_sell_out(swap, ctx, token_amount)).saturating_sub(exit_costs)
+        })?;
+        if ctx.pool.take_profit > position.unrealized_pct() {
+            return None;
+        }
+
+        log!(
+            format!("Sell Token Amount {}", position.token_amount),
+            "result"
+        );
+
+        Some(TradeIntent {
+            strategy: Self::NAME,
+            dex: swap.dex,
+            pool: swap.pool,
+            mint: swap.mint,
+            kind: IntentKind::Sell {
+                token_amount: position.token_amount,
+            },
+            tip,
+            urgency: Urgency::Medium,
+            reserves: ctx.reserves,
+            slippage_pct: ctx.pool.slippage.sell_pct,
+        })
+    }
+
+    fn buy_the_dump(&self, swap: &ObservedSwap, ctx: &StrategyContext) -> Option<TradeIntent> {
+        if !ctx.config.mode.show_sell
+            || !ctx.pool.enable_buy
+            || swap.price_impact_pct().abs() < ctx.pool.min_price_impact
+        {
+            return None;
+        }
+        log!("\t==> SELL Trade ==", "info");
+
+        let size = dump_sizing(
+            swap.price_impact_pct().abs(),
+            swap.liquidity_usd(ctx.sol_price),
+            swap.sol_amount(),
+            &ctx.pool.dump.sizing_tiers(&ctx.pool.slippage),
+            &ctx.pool.dump,
+            &ctx.pool.slippage,
+        )?;
+        if ctx.config.mode.on_debug {
+            log!(format!("( Debug Mode ) {:?}", size), "info");
+        }
+
+        let buy_amount = size.sol_amount;
+        let tip_amount = size.tip;
+        let expected_token_amount = expected_buy_out(swap, ctx, sol_to_lamports(buy_amount));
+
+        log!(
+            format!(
+                "{} Buy Sol Amount {} , Tip Sol Amount {}",
+                swap.dex, buy_amount, tip_amount
+            ),
+            "result"
+        );
+
+        Some(TradeIntent {
+            strategy: Self::NAME,
+            dex: swap.dex,
+            pool: swap.pool,
+            mint: swap.mint,
+            kind: IntentKind::Buy {
+                sol_amount: buy_amount,
+                expected_token_amount,
+            },
+            tip: tip_amount,
+            // The dump is bought back by others within a few slots
+            urgency: Urgency::High,
+            reserves: ctx.reserves,
+            slippage_pct: size.slippage_pct,
+        })
+    }
+}
+
+/// Tokens (UI units) `lamports` would buy at the pool's freshest reserves, net of any
+/// Token-2022 transfer fee; quoted like `expected_sell_out`.
+fn expected_buy_out(swap: &ObservedSwap, ctx: &StrategyContext, lamports: u64) -> f64 {
+    let token_amount = match ctx
+        .token
+        .and_then(|token| token.fees)
+        .map(|fees| fees.quote_exact_in(&ctx.reserves, lamports, true))
+    {
+        Some(Ok(quote)) => quote.amount_out,
+        _ => {
+            (lamports_to_sol(lamports) / swap.price_after()
+                * 10f64.powi(swap.token_decimals as i32)) as u64
+        }
+    };
+    let token_amount = match ctx.token.and_then(|token| token.transfer_fee) {
+        Some(fee) => fee.amount_after_fee(token_amount),
+        None => token_amount,
+    };
+
+    token_amount as f64 / 10f64.powi(swap.token_decimals as i32)
+}
+
+/// SOL a sale of `token_amount` raw units would return at the pool's
+/// freshest reserves:
+/// quoted exactly on constant-product pools with recorded fees, at the venue
+/// price otherwise.
+fn expected_sell_out(swap: &ObservedSwap, ctx: &StrategyContext, token_amount: u64) -> f64 {
+    // Only what is left after the Token-2022 transfer fee reaches the pool
+    let token_amount = match ctx.token.and_then(|token| token.transfer_fee) {
+        Some(fee) => fee.amount_after_fee(token_amount),
+        None => token_amount,
+    };
+
+    match ctx
+        .token
+        .and_then(|token| token.fees)
+        .map(|fees| fees.quote_exact_in(&ctx.reserves, token_amount, false))
+    {
+        Some(Ok(quote)) => lamports_to_sol(quote.amount_out),
+        _ => swap.price_after() * token_amount as f64 / 10f64.powi(swap.token_decimals as i32),
+    }
+}
+
+impl Strategy for MeanReversion {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn on_swap(&self, swap: &ObservedSwap, ctx: &StrategyContext) -> Vec<TradeIntent> {
+        let intent = match swap.side {
+            SwapSide::Buy => self.take_profit(swap, ctx),
+            SwapSide::Sell => self.buy_the_dump(swap, ctx),
+        };
+
+        intent.into_iter().collect()
+    }
+}
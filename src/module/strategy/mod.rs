@@ -0,0 +1,7 @@
+pub mod mean_reversion;
+pub mod registry;
+pub mod types;
+
+pub use mean_reversion::*;
+pub use registry::*;
+pub use types::*;
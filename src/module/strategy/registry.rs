@@ -0,0 +1,31 @@
+use std::{fmt, sync::Arc};
+
+use crate::{MeanReversion, ObservedSwap, StrategyContext, TradeIntent};
+
+/// Decision logic run on every observed swap.
+///
+/// Implementations must be cheap and non-blocking: they run inline in the
+/// swap handler, and all I/O for placing trades happens after they return.
+pub trait Strategy: fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn on_swap(&self, swap: &ObservedSwap, ctx: &StrategyContext) -> Vec<TradeIntent>;
+}
+
+/// Names accepted in `strategy.enabled`.
+pub const STRATEGY_NAMES: &[&str] = &[MeanReversion::NAME];
+
+pub fn build_strategy(name: &str) -> Option<Arc<dyn Strategy>> {
+    match name {
+        MeanReversion::NAME => Some(Arc::new(MeanReversion)),
+        _ => None,
+    }
+}
+
+/// Instantiates the configured strategies; names were checked when the config was validated.
+pub fn build_strategies(names: &[String]) -> Vec<Arc<dyn Strategy>> {
+    names
+        .iter()
+        .filter_map(|name| build_strategy(name))
+        .collect()
+}
@@ -0,0 +1,54 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{BotConfig, Dex, OpenPosition, PoolSettings, Reserves, TokenListInfos, Urgency};
+
+/// Everything a strategy may read besides the swap itself.
+pub struct StrategyContext<'a> {
+    pub config: &'a BotConfig,
+    pub pool: &'a PoolSettings,
+    /// Keys recorded by pre_start for the swap's pool, if it was prepared.
+    pub token: Option<&'a TokenListInfos>,
+    /// Freshest known reserves of the swap's pool: the account stream's view
+    /// when it is at least as recent as the swap, else the swap's own.
+    pub reserves: Reserves,
+    /// Open lots of the swap's pool, as the position book holds them.
+    pub position: &'a OpenPosition,
+    pub sol_price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntentKind {
+    /// Spend `sol_amount` SOL, expecting about `expected_token_amount` tokens (UI units).
+    Buy {
+        sol_amount: f64,
+        expected_token_amount: f64,
+    },
+    /// Receive exactly `token_amount` raw token units, spending at most `max_sol_amount` SOL.
+    BuyExactOut {
+        token_amount: u64,
+        max_sol_amount: f64,
+    },
+    /// Sell `token_amount` raw token units for SOL.
+    Sell { token_amount: u64 },
+}
+
+/// A trade a strategy wants to place; the handler turns it into a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeIntent {
+    pub strategy: &'static str,
+    pub dex: Dex,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub kind: IntentKind,
+    /// Tip in SOL paid to the landing service.
+    pub tip: f64,
+    /// How fast the trade must land; sets its compute-unit price from the
+    /// fees recently paid on the pool.
+    pub urgency: Urgency,
+    /// Pool reserves the trade is expected to execute against, usually the
+    /// ones the observed swap left behind.
+    pub reserves: Reserves,
+    /// Tolerance (in %) between the quote against `reserves` and the bound
+    /// the swap is submitted with.
+    pub slippage_pct: f64,
+}
@@ -0,0 +1,149 @@
+use std::{
+    sync::{mpsc, OnceLock},
+    thread,
+};
+
+use crate::{log, with_trade_store, Gateway, StoreError, TxOutcome};
+
+/// A transaction handed to one gateway, shared by the regions it is sent to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub signature: String,
+    /// Tag of the intent the transaction carries out; every gateway's copy
+    /// of one intent shares it.
+    pub intent_tag: u64,
+    pub pool_id: String,
+    pub gateway: Gateway,
+    pub tip_lamports: u64,
+    /// Slot of the transaction that triggered the intent, if any.
+    pub trigger_slot: Option<u64>,
+    pub submitted_at_ms: i64,
+}
+
+/// One region's answer to a submission.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionSend {
+    pub signature: String,
+    pub region: String,
+    pub sent_at_ms: i64,
+    /// Time until the region answered, accepted or not.
+    pub latency_ms: u64,
+    /// Why the region refused the transaction, if it did.
+    pub error: Option<String>,
+}
+
+/// Landing record of one gateway.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GatewayStats {
+    pub gateway: String,
+    pub submissions: u64,
+    pub landed: u64,
+    pub failed: u64,
+    pub expired: u64,
+    pub rejected: u64,
+    /// Average slots between the trigger transaction and our landed one.
+    pub avg_slot_delay: Option<f64>,
+    /// Network fees of every transaction that reached the chain plus the
+    /// tips of those that succeeded, in lamports.
+    pub cost_lamports: u64,
+}
+
+impl GatewayStats {
+    /// Share of the submissions that landed and succeeded, in percent.
+    pub fn landing_rate_pct(&self) -> f64 {
+        if self.submissions == 0 {
+            return 0.0;
+        }
+        self.landed as f64 * 100.0 / self.submissions as f64
+    }
+
+    /// Lamports spent per successful landing.
+    pub fn cost_per_landing(&self) -> Option<u64> {
+        (self.landed > 0).then(|| self.cost_lamports / self.landed)
+    }
+}
+
+/// Answers of one gateway region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionStats {
+    pub gateway: String,
+    pub region: String,
+    pub sends: u64,
+    pub accepted: u64,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: u64,
+}
+
+/// Queues a submission for the store; telemetry never holds a trade back.
+pub fn record_submission(submission: &Submission) {
+    queue(TelemetryRecord::Submission(submission.clone()));
+}
+
+/// Queues one region's answer to a recorded submission.
+pub fn record_region_send(send: &RegionSend) {
+    queue(TelemetryRecord::RegionSend(send.clone()));
+}
+
+/// Queues what became of a recorded submission.
+pub fn record_landing(signature: &str, outcome: &TxOutcome) {
+    queue(TelemetryRecord::Landing(
+        signature.to_string(),
+        outcome.clone(),
+    ));
+}
+
+enum TelemetryRecord {
+    Submission(Submission),
+    RegionSend(RegionSend),
+    Landing(String, TxOutcome),
+}
+
+/// Hands `record` to the telemetry writer, a thread started on first use
+/// that stores records one at a time in the order they were queued, so a
+/// submission is in before the answers and landing that refer to it.
+fn queue(record: TelemetryRecord) {
+    static WRITER: OnceLock<Option<mpsc::Sender<TelemetryRecord>>> = OnceLock::new();
+
+    let writer = WRITER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        let started = thread::Builder::new()
+            .name("landing-telemetry".to_string())
+            .spawn(move || {
+                for record in receiver {
+                    report(with_trade_store(|store| match &record {
+                        TelemetryRecord::Submission(submission) => {
+                            store.record_submission(submission)
+                        }
+                        TelemetryRecord::RegionSend(send) => store.record_region_send(send),
+                        TelemetryRecord::Landing(signature, outcome) => {
+                            store.record_landing(signature, outcome)
+                        }
+                    }));
+                }
+            });
+        match started {
+            Ok(_) => Some(sender),
+            Err(e) => {
+                log!(
+                    format!("🔴 Failed to start the landing telemetry writer: {}", e),
+                    "error"
+                );
+                None
+            }
+        }
+    });
+
+    if let Some(writer) = writer {
+        // The writer runs as long as the process
+        let _ = writer.send(record);
+    }
+}
+
+fn report(result: Result<(), StoreError>) {
+    if let Err(e) = result {
+        log!(
+            format!("🔴 Failed to record landing telemetry: {}", e),
+            "error"
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod landing_telemetry;
+
+pub use landing_telemetry::*;
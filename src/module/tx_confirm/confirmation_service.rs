@@ -0,0 +1,311 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{
+    clock::MAX_PROCESSING_AGE, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+    signature::Signature,
+};
+use solana_transaction_status::{
+    ConfirmedTransactionWithStatusMeta, EncodedConfirmedTransactionWithStatusMeta,
+    UiTransactionEncoding,
+};
+use tokio::{sync::oneshot, time::timeout};
+use yellowstone_grpc_proto::{
+    convert_from::create_tx_with_meta,
+    geyser::{
+        SubscribeRequest, SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterTransactions,
+        SubscribeUpdateBlockMeta, SubscribeUpdateTransaction,
+    },
+};
+
+use crate::{get_ui_token_balance_change, log, TxOutcome};
+
+/// Recent blockhashes kept with their block height, to tell when ours expire.
+const BLOCKHASH_HISTORY: usize = 2 * MAX_PROCESSING_AGE;
+
+/// Longest wait for an outcome when the stream stalls and no block height
+/// comes to expire the transaction.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Name of the swap stream's filter on the payer's transactions.
+pub const OWN_TRANSACTIONS_FILTER: &str = "own_transactions";
+
+/// Name of the swap stream's filter on block heights.
+const BLOCKS_META_FILTER: &str = "blocks_meta";
+
+/// A submitted transaction waiting for its outcome.
+struct Pending {
+    blockhash: Hash,
+    /// Block height when it was submitted, for blockhashes not seen yet.
+    submitted_height: Option<u64>,
+    is_buy: bool,
+    sender: oneshot::Sender<TxOutcome>,
+}
+
+#[derive(Default)]
+struct ConfirmationState {
+    pending: HashMap<Signature, Pending>,
+    block_height: Option<u64>,
+    blockhashes: VecDeque<(Hash, u64)>,
+}
+
+impl ConfirmationState {
+    /// Last block height `pending` can land at.
+    fn last_valid_height(&self, pending: &Pending) -> Option<u64> {
+        self.blockhashes
+            .iter()
+            .find(|(blockhash, _)| *blockhash == pending.blockhash)
+            .map(|(_, height)| *height)
+            .or(pending.submitted_height)
+            .map(|height| height + MAX_PROCESSING_AGE as u64)
+    }
+}
+
+/// Follows our own transactions on the swap stream, which also carries the
+/// payer's transactions and block heights, instead of polling the RPC.
+///
+/// Each expected signature resolves to landed or failed as soon as the
+/// stream shows it, or to expired once the chain passes the last block
+/// height its blockhash is valid for. Landed swaps are booked on their
+/// position before the outcome is handed back. The stream is at processed
+/// commitment, so a landing reported there may still be dropped by a fork.
+pub struct ConfirmationService {
+    client: Arc<RpcClient>,
+    payer: Pubkey,
+    state: Mutex<ConfirmationState>,
+}
+
+/// Handle on an expected signature; dropping it stops the wait.
+pub struct PendingConfirmation {
+    service: Arc<ConfirmationService>,
+    signature: Signature,
+    receiver: Option<oneshot::Receiver<TxOutcome>>,
+}
+
+impl PendingConfirmation {
+    /// Waits for the outcome of the transaction.
+    pub async fn outcome(mut self) -> TxOutcome {
+        let mut receiver = self.receiver.take().expect("outcome taken once");
+        if let Ok(outcome) = timeout(CONFIRM_TIMEOUT, &mut receiver).await {
+            return outcome.unwrap_or(TxOutcome::Expired);
+        }
+        match self.service.forget(&self.signature) {
+            // The stream fell silent, ask the RPC before giving up
+            Some(is_buy) => lookup(&self.service.client, self.signature, is_buy).await,
+            // Already resolving, its lookup is under way
+            None => receiver.await.unwrap_or(TxOutcome::Expired),
+        }
+    }
+}
+
+impl Drop for PendingConfirmation {
+    fn drop(&mut self) {
+        self.service.forget(&self.signature);
+    }
+}
+
+impl ConfirmationService {
+    /// `client` is only asked about transactions the stream did not show
+    /// before their blockhash expired.
+    pub fn new(client: Arc<RpcClient>, payer: Pubkey) -> Arc<Self> {
+        Arc::new(Self {
+            client,
+            payer,
+            state: Mutex::default(),
+        })
+    }
+
+    /// Starts following `signature`, sent with `blockhash`. Call it before
+    /// sending so a fast landing is not missed.
+    pub fn expect(
+        self: &Arc<Self>,
+        signature: Signature,
+        blockhash: Hash,
+        is_buy: bool,
+    ) -> PendingConfirmation {
+        let (sender, receiver) = oneshot::channel();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let submitted_height = state.block_height;
+        state.pending.insert(
+            signature,
+            Pending {
+                blockhash,
+                submitted_height,
+                is_buy,
+                sender,
+            },
+        );
+
+        PendingConfirmation {
+            service: Arc::clone(self),
+            signature,
+            receiver: Some(receiver),
+        }
+    }
+
+    /// Stops following `signature`, returning its side if it was pending.
+    fn forget(&self, signature: &Signature) -> Option<bool> {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pending
+            .remove(signature)
+            .map(|pending| pending.is_buy)
+    }
+
+    /// Adds the payer's transactions, failed ones included, and block
+    /// heights to the swap stream's `request`, so the service follows them
+    /// on that stream rather than a subscription of its own.
+    pub fn add_filters(&self, request: &mut SubscribeRequest) {
+        request.transactions.insert(
+            OWN_TRANSACTIONS_FILTER.to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: None,
+                signature: None,
+                account_include: vec![],
+                account_exclude: vec![],
+                account_required: vec![self.payer.to_string()],
+            },
+        );
+        request.blocks_meta.insert(
+            BLOCKS_META_FILTER.to_string(),
+            SubscribeRequestFilterBlocksMeta {},
+        );
+    }
+
+    /// Resolves a pending signature the stream shows.
+    pub fn on_transaction(&self, update: SubscribeUpdateTransaction) {
+        let Some(info) = update.transaction else {
+            return;
+        };
+        let Ok(signature) = Signature::try_from(info.signature.as_slice()) else {
+            return;
+        };
+        let Some(pending) = self
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pending
+            .remove(&signature)
+        else {
+            return;
+        };
+
+        // Decoding and booking stay off the stream loop
+        let client = Arc::clone(&self.client);
+        let slot = update.slot;
+        tokio::spawn(async move {
+            let encoded = create_tx_with_meta(info)
+                .map_err(|e| e.to_string())
+                .and_then(|tx_with_meta| {
+                    ConfirmedTransactionWithStatusMeta {
+                        slot,
+                        tx_with_meta,
+                        block_time: None,
+                    }
+                    .encode(UiTransactionEncoding::JsonParsed, Some(0))
+                    .map_err(|e| e.to_string())
+                });
+            let outcome = match encoded {
+                Ok(encoded) => settle(encoded, pending.is_buy).await,
+                Err(e) => {
+                    log!(
+                        format!("🔴 Failed to decode streamed {}: {}", signature, e),
+                        "error"
+                    );
+                    lookup(&client, signature, pending.is_buy).await
+                }
+            };
+            let _ = pending.sender.send(outcome);
+        });
+    }
+
+    /// Advances the block height and expires the signatures whose blockhash
+    /// it passed.
+    pub fn on_block_meta(&self, block_meta: &SubscribeUpdateBlockMeta) {
+        let (Some(height), Ok(blockhash)) = (
+            block_meta.block_height.map(|height| height.block_height),
+            Hash::from_str(&block_meta.blockhash),
+        ) else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.block_height = Some(state.block_height.map_or(height, |known| known.max(height)));
+        state.blockhashes.push_back((blockhash, height));
+        while state.blockhashes.len() > BLOCKHASH_HISTORY {
+            state.blockhashes.pop_front();
+        }
+
+        let expired: Vec<Signature> = state
+            .pending
+            .iter()
+            .filter(|(_, pending)| {
+                state
+                    .last_valid_height(pending)
+                    .is_some_and(|last_valid| height > last_valid)
+            })
+            .map(|(signature, _)| *signature)
+            .collect();
+        for signature in expired {
+            let Some(pending) = state.pending.remove(&signature) else {
+                continue;
+            };
+            // The stream may have dropped it while reconnecting, the RPC has the last word
+            let client = Arc::clone(&self.client);
+            tokio::spawn(async move {
+                let outcome = lookup(&client, signature, pending.is_buy).await;
+                let _ = pending.sender.send(outcome);
+            });
+        }
+    }
+}
+
+/// Whether a streamed transaction only matched the payer's filter, so it
+/// touches none of the followed pools or failed.
+pub fn only_own_transaction(filters: &[String]) -> bool {
+    !filters.is_empty()
+        && filters
+            .iter()
+            .all(|filter| filter == OWN_TRANSACTIONS_FILTER)
+}
+
+/// Books a confirmed transaction on its position, on the blocking pool as
+/// it writes to the store, and says what became of it.
+async fn settle(tx: EncodedConfirmedTransactionWithStatusMeta, is_buy: bool) -> TxOutcome {
+    let outcome = TxOutcome::from_confirmed(&tx);
+    if let TxOutcome::Landed { .. } = outcome {
+        if let Err(e) =
+            tokio::task::spawn_blocking(move || get_ui_token_balance_change(&tx, is_buy)).await
+        {
+            log!(format!("🔴 Failed to book a landed swap: {}", e), "error");
+        }
+    }
+    outcome
+}
+
+/// Fetches a transaction the stream never showed, expired if it is not
+/// on-chain either.
+async fn lookup(client: &RpcClient, signature: Signature, is_buy: bool) -> TxOutcome {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::JsonParsed),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+    match client.get_transaction_with_config(&signature, config).await {
+        Ok(tx) => settle(tx, is_buy).await,
+        Err(_e) => {
+            log!(
+                format!("Transaction {} never confirmed ⏳", signature),
+                "error"
+            );
+            TxOutcome::Expired
+        }
+    }
+}
@@ -1,57 +1,108 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
 use solana_transaction_status::{
-    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction, UiMessage,
-    UiParsedInstruction,
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiMessage,
 };
 
 use crate::{
-    display_and_save_ui_balance_change, get_pre_post_ui_token_balance, log, PUMP_SWAP_ID,
-    RAY_AMM_AUTH, RAY_AMM_ID,
+    book_fill, lamports_to_sol, log, owner_token_balance_change, with_trade_store, ConfirmedFill,
+    SwapSide, NATIVE_MINT,
 };
 
-pub fn get_ui_token_balance_change(transaction_meta: &EncodedConfirmedTransactionWithStatusMeta , is_buy : bool) {
-    let encoded_transaction: &EncodedTransaction = &transaction_meta.transaction.transaction;
-
-    if let EncodedTransaction::Json(transaction_message) = encoded_transaction {
-        if let UiMessage::Parsed(message) = &transaction_message.message {
-            for (_, instruction) in message.instructions.iter().enumerate() {
-                if let UiInstruction::Parsed(ui_parsed_ix) = instruction {
-                    if let UiParsedInstruction::PartiallyDecoded(partially_decoded_ix) =
-                        ui_parsed_ix
-                    {
-                        if let Some(meta) = &transaction_meta.transaction.meta {
-                            if partially_decoded_ix.program_id == RAY_AMM_ID {
-                                let (ray_auth_pre_token_balance, ray_auth_post_token_balance) =
-                                    get_pre_post_ui_token_balance(meta, RAY_AMM_AUTH);
+/// Books one of our confirmed swaps on the position of the pool it traded:
+/// the tokens the wallet gained or lost and every lamport it paid or got,
+/// network fee, tips and rent included.
+pub fn get_ui_token_balance_change(
+    transaction_meta: &EncodedConfirmedTransactionWithStatusMeta,
+    is_buy: bool,
+) {
+    let EncodedTransaction::Json(transaction_message) = &transaction_meta.transaction.transaction
+    else {
+        log!("No transaction message found.", "error");
+        return;
+    };
+    let UiMessage::Parsed(message) = &transaction_message.message else {
+        log!("No transaction message found.", "error");
+        return;
+    };
+    let Some(meta) = &transaction_meta.transaction.meta else {
+        return;
+    };
+    let signature = transaction_message.signatures[0].as_str();
+    if meta.err.is_some() {
+        log!(
+            format!("Transaction {} failed, nothing to book", signature),
+            "error"
+        );
+        return;
+    }
 
-                                display_and_save_ui_balance_change(
-                                    &transaction_message.signatures[0].as_str(),
-                                    partially_decoded_ix.accounts[1].as_str(),
-                                    &ray_auth_pre_token_balance,
-                                    &ray_auth_post_token_balance,
-                                    RAY_AMM_AUTH,
-                                    is_buy
-                                );
-                            } else if partially_decoded_ix.program_id == PUMP_SWAP_ID {
-                                let pool_id = &partially_decoded_ix.accounts[0];
+    let accounts: Vec<&str> = message
+        .account_keys
+        .iter()
+        .map(|account| account.pubkey.as_str())
+        .collect();
+    let (pool_id, token_mint) = match with_trade_store(|store| store.position_among(&accounts)) {
+        Ok(Some(position)) => position,
+        Ok(None) => {
+            log!(
+                format!("Transaction {} touches no recorded pool", signature),
+                "error"
+            );
+            return;
+        }
+        Err(e) => {
+            log!(format!("🔴 {}", e), "error");
+            return;
+        }
+    };
 
-                                let (ray_auth_pre_token_balance, ray_auth_post_token_balance) =
-                                    get_pre_post_ui_token_balance(meta, pool_id);
+    // The fee payer is our wallet
+    let wallet = accounts[0];
+    let (pre_tokens, post_tokens, token_decimals) =
+        owner_token_balance_change(meta, wallet, &token_mint);
+    // Swaps settle in the wallet's wrapped SOL account, fees and tips in its lamports
+    let (pre_wsol, post_wsol, _) = owner_token_balance_change(meta, wallet, NATIVE_MINT);
+    let wallet_lamports = (meta.post_balances[0] as i64 - meta.pre_balances[0] as i64)
+        + (post_wsol as i64 - pre_wsol as i64);
 
-                                display_and_save_ui_balance_change(
-                                    &transaction_message.signatures[0].as_str(),
-                                    pool_id,
-                                    &ray_auth_pre_token_balance,
-                                    &ray_auth_post_token_balance,
-                                    pool_id,
-                                    is_buy
-                                );
-                            }
-                        }
-                    }
-                }
-            }
+    let fill = ConfirmedFill {
+        signature: signature.to_string(),
+        side: if is_buy {
+            SwapSide::Buy
         } else {
-            log!("No transaction message found.", "error");
-        }
+            SwapSide::Sell
+        },
+        token_amount: pre_tokens.abs_diff(post_tokens),
+        token_decimals,
+        wallet_lamports,
+        network_fee: meta.fee,
+    };
+    log!(
+        format!(
+            "\tToken [ {} ]\t{} -> \t{}\t, SOL Δ{} (network fee {})",
+            token_mint,
+            pre_tokens,
+            post_tokens,
+            wallet_lamports as f64 / LAMPORTS_PER_SOL as f64,
+            lamports_to_sol(meta.fee)
+        ),
+        "update"
+    );
+
+    match book_fill(&pool_id, &fill) {
+        Ok(Some(realized)) if !is_buy => log!(
+            format!(
+                "Realized PnL on {} : {} SOL",
+                pool_id,
+                realized as f64 / LAMPORTS_PER_SOL as f64
+            ),
+            "success"
+        ),
+        Ok(Some(_)) => {}
+        Ok(None) => log!(format!("Fill {} already recorded", signature), "info"),
+        Err(e) => log!(
+            format!("🔴 Failed to record trade on {}: {}", pool_id, e),
+            "error"
+        ),
     }
 }
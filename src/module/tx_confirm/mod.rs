@@ -1,5 +1,7 @@
-pub mod confirm_tx_get_token_balance;
+pub mod confirmation_service;
 pub mod get_token_balance_change;
+pub mod tx_outcome;
 
-pub use confirm_tx_get_token_balance::*;
+pub use confirmation_service::*;
 pub use get_token_balance_change::*;
+pub use tx_outcome::*;
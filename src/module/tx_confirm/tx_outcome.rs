@@ -0,0 +1,111 @@
+use std::fmt;
+
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+
+/// What became of one submitted transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxOutcome {
+    /// Landed in `slot` and succeeded, paying `fee` lamports of network fee.
+    Landed { slot: u64, fee: u64 },
+    /// Landed in `slot` but failed on-chain (slippage, lost race, ...).
+    Failed {
+        error: TransactionError,
+        slot: u64,
+        fee: u64,
+    },
+    /// Accepted but never confirmed, e.g. on a stale blockhash.
+    Expired,
+    /// The gateway refused the submission.
+    Rejected(String),
+}
+
+impl TxOutcome {
+    /// Outcome of a transaction fetched once confirmed.
+    pub fn from_confirmed(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Self {
+        let slot = tx.slot;
+        match &tx.transaction.meta {
+            Some(meta) => match &meta.err {
+                Some(error) => TxOutcome::Failed {
+                    error: error.clone(),
+                    slot,
+                    fee: meta.fee,
+                },
+                None => TxOutcome::Landed {
+                    slot,
+                    fee: meta.fee,
+                },
+            },
+            None => TxOutcome::Landed { slot, fee: 0 },
+        }
+    }
+
+    /// Whether the gateway delivered the transaction, whatever it did on-chain.
+    pub fn reached_chain(&self) -> bool {
+        matches!(self, TxOutcome::Landed { .. } | TxOutcome::Failed { .. })
+    }
+
+    /// Slot the transaction landed in, failed or not.
+    pub fn slot(&self) -> Option<u64> {
+        match self {
+            TxOutcome::Landed { slot, .. } | TxOutcome::Failed { slot, .. } => Some(*slot),
+            TxOutcome::Expired | TxOutcome::Rejected(_) => None,
+        }
+    }
+
+    /// Network fee paid, failed or not.
+    pub fn fee(&self) -> Option<u64> {
+        match self {
+            TxOutcome::Landed { fee, .. } | TxOutcome::Failed { fee, .. } => Some(*fee),
+            TxOutcome::Expired | TxOutcome::Rejected(_) => None,
+        }
+    }
+
+    /// Name the outcome is stored under.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TxOutcome::Landed { .. } => "landed",
+            TxOutcome::Failed { .. } => "failed",
+            TxOutcome::Expired => "expired",
+            TxOutcome::Rejected(_) => "rejected",
+        }
+    }
+
+    /// Why the transaction did not go through, if it did not.
+    pub fn error(&self) -> Option<String> {
+        match self {
+            TxOutcome::Landed { .. } => None,
+            TxOutcome::Failed { error, .. } => Some(error.to_string()),
+            TxOutcome::Expired => Some("never confirmed".to_string()),
+            TxOutcome::Rejected(e) => Some(e.clone()),
+        }
+    }
+
+    /// The most successful of `outcomes`: the same transaction sent to
+    /// several regions of a gateway landed if any region landed it.
+    pub fn best(outcomes: impl IntoIterator<Item = TxOutcome>) -> Option<TxOutcome> {
+        outcomes.into_iter().max_by_key(TxOutcome::rank)
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            TxOutcome::Landed { .. } => 3,
+            TxOutcome::Failed { .. } => 2,
+            TxOutcome::Expired => 1,
+            TxOutcome::Rejected(_) => 0,
+        }
+    }
+}
+
+impl fmt::Display for TxOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxOutcome::Landed { slot, .. } => write!(f, "landed in slot {}", slot),
+            TxOutcome::Failed { error, slot, .. } => {
+                write!(f, "failed on-chain in slot {}: {}", slot, error)
+            }
+            TxOutcome::Expired => write!(f, "never confirmed"),
+            TxOutcome::Rejected(e) => write!(f, "rejected: {}", e),
+        }
+    }
+}
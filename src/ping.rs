@@ -1,19 +1,25 @@
-use std::{env, str::FromStr};
+use std::env;
 
-use raydium_trade_bot::load_env_file;
+use raydium_trade_bot::BotConfig;
 use tokio::time::Instant;
 use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
 
 #[tokio::main]
 async fn main() {
     /* Initial Settings */
-    let (_, rpc, grpc, token, _, _, _, _) = load_env_file();
+    let config = match BotConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
 
-    let yellowstone_grpc_http = grpc;
-    let yellowstone_grpc_token = token;
+    let yellowstone_grpc_http = config.endpoints.grpc_endpoint;
+    let yellowstone_grpc_token = config.endpoints.grpc_token;
 
     // Get command line arguments
-    let args: Vec<String> = env::args().collect();
+    let _args: Vec<String> = env::args().collect();
 
     // Check if an argument is provided
     println!("Running ping test...");
@@ -55,13 +61,3 @@ pub async fn ping_test(
     }
     Ok(())
 }
-
-pub fn import_env_var(key: &str) -> String {
-    match env::var(key) {
-        Ok(res) => res,
-        Err(e) => {
-            println!("{}", format!("{}: {}", e, key));
-            loop {}
-        }
-    }
-}
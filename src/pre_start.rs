@@ -1,9 +1,13 @@
 use borsh::BorshDeserialize;
-use colored::Colorize;
 use raydium_trade_bot::{
-    decode_pumpswap_pool_info, ensure_ata_created, get_onchain_metadata, load_env_file,
-    load_pool_info, log, save_token_info, save_token_trade_info, BuyHistoryInfo,
-    LiquidityStateLayoutV4, PoolKeys, TokenListInfos, NATIVE_MINT, PUMP_SWAP_ID,
+    decode_cpmm_amm_config, decode_cpmm_pool_state, decode_dlmm_lb_pair,
+    decode_mint_metadata_symbol, decode_mint_transfer_fee, decode_pumpswap_fees,
+    decode_pumpswap_pool_info, decode_whirlpool_state, ensure_ata_created, get_onchain_metadata,
+    load_pool_info, log, pumpswap_global_config_address, resolve_pools, save_token_info,
+    scheduled_transfer_fee_change, whirlpool_oracle_address, with_trade_store, BotConfig,
+    BuyHistoryInfo, ConstantProductFees, CpmmPoolKeys, DlmmPoolKeys, LiquidityStateLayoutV4,
+    PoolKeys, TokenListInfos, WhirlpoolPoolKeys, METEORA_DLMM_ID, NATIVE_MINT, ORCA_WHIRLPOOL_ID,
+    PUMP_SWAP_ID, RAY_CPMM_ID,
 };
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signer::Signer};
@@ -16,8 +20,18 @@ async fn main() {
         "info"
     );
 
-    let (payer, rpc, _, _, _, _, _, _) = load_env_file();
-    let client = RpcClient::new_with_commitment(rpc.to_string(), CommitmentConfig::confirmed());
+    let config = match BotConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            log!(format!("🔴 {}", e), "error");
+            return;
+        }
+    };
+    let payer = config.payer();
+    let client = RpcClient::new_with_commitment(
+        config.endpoints.rpc_endpoint.clone(),
+        CommitmentConfig::confirmed(),
+    );
 
     log!(
         format!("My Address : {}", payer.pubkey().to_string()),
@@ -37,164 +51,280 @@ async fn main() {
         }
     }
 
-    let pool_infos = load_pool_info();
+    let pool_infos = match load_pool_info(&config) {
+        Ok(pool_infos) => pool_infos,
+        Err(e) => {
+            log!(format!("🔴 {:#}", e), "error");
+            return;
+        }
+    };
+
+    let pool_settings = match resolve_pools(&config, &pool_infos) {
+        Ok(pool_settings) => pool_settings,
+        Err(e) => {
+            log!(format!("🔴 {}", e), "error");
+            return;
+        }
+    };
 
     log!(format!("Loaded Pool Keys : {:#?}", pool_infos), "info");
 
-    let pool_pubkeys: Vec<Pubkey> = pool_infos
-        .iter()
-        .map(|pool_addr| Pubkey::from_str_const(pool_addr))
-        .collect();
-
-    for pool_key in &pool_pubkeys {
-        match client.get_account(pool_key) {
-            Ok(data) => {
-                if data.owner.to_string() == PUMP_SWAP_ID {
-                    let info = decode_pumpswap_pool_info(data.data.clone());
-                    let dex = "PUMPSWAP";
-                    let id_bs64 = pool_key;
-                    let base_vault_b64 = info.pool_base_token_account;
-                    let quote_vault_b64 = info.pool_quote_token_account;
-                    let base_mint = info.base_mint;
-                    let quote_mint = info.quote_mint;
-
-                    let token_mint: String;
-                    if base_mint == NATIVE_MINT {
-                        token_mint = quote_mint.clone()
-                    } else {
-                        token_mint = base_mint.clone()
-                    }
-
-                    let token_mint_key = &Pubkey::from_str_const(token_mint.as_str());
-                    let metadata = get_onchain_metadata(&client, token_mint_key).await.unwrap();
-
-                    let symbol = metadata.unwrap().symbol;
-                    let clean_symbol = symbol.trim_end_matches('\0');
-
-                    let pool_keys = PoolKeys {
-                        base_mint: Pubkey::from_str_const(base_mint.clone().as_ref()),
-                        quote_mint: Pubkey::from_str_const(quote_mint.clone().as_ref()),
-                    };
-
-                    match ensure_ata_created(&client, &payer.pubkey(), &pool_keys, &payer).await {
-                        Ok(ata) => {
-                            let token_info = TokenListInfos {
-                                id_bs64: id_bs64.to_string(),
-                                base_vault_b64: base_vault_b64.clone(),
-                                quote_vault_b64: quote_vault_b64.clone(),
-                                base_mint: base_mint.clone(),
-                                quote_mint: quote_mint.clone(),
-                                clean_symbol: clean_symbol.to_string(),
-                                ata: ata.to_string(),
-                                dex: dex.to_string(),
-                            };
-
-                            let buy_history_info = BuyHistoryInfo {
-                                pool_id: id_bs64.to_string(),
-                                base_mint: base_mint.to_string(),
-                                base_vault: base_vault_b64,
-                                quote_mint: quote_mint.to_string(),
-                                quote_vault: quote_vault_b64,
-                                symbol: clean_symbol.to_string(),
-                                token_ata: ata.to_string(),
-                                total_ui_amount_in: 0.0,
-                                total_amount_in: 0,
-                                total_ui_token_amount_out: 0.0,
-                                total_token_amount_out: 0,
-                                take_profit: 5,
-                                transactions: vec![],
-                                dex: dex.to_string(),
-                            };
-
-                            log!(
-                                format!("Token Info saved in file : {:#?}", token_info),
-                                "result"
-                            );
-
-                            save_token_trade_info(&buy_history_info, &id_bs64.to_string());
-                            save_token_info(&token_info, &id_bs64.to_string());
-                        }
-                        Err(_error) => {}
-                    };
-                } else {
-                    match LiquidityStateLayoutV4::deserialize(&mut data.data.as_slice()) {
-                        Ok(info) => {
-                            let dex = "RAYDIUM_AMM";
-                            let id_bs64 = pool_key;
-                            let base_vault_b64 = info.base_vault;
-                            let quote_vault_b64 = info.quote_vault;
-                            let base_mint = info.base_mint;
-                            let quote_mint = info.quote_mint;
-
-                            let token_mint: String;
-                            if info.base_mint.to_string() == NATIVE_MINT {
-                                token_mint = info.quote_mint.to_string()
-                            } else {
-                                token_mint = info.base_mint.to_string()
-                            }
-
-                            let token_mint_key = &Pubkey::from_str_const(token_mint.as_str());
-                            let metadata =
-                                get_onchain_metadata(&client, token_mint_key).await.unwrap();
-
-                            let symbol = metadata.unwrap().symbol;
-                            let clean_symbol = symbol.trim_end_matches('\0');
-
-                            let pool_keys = PoolKeys {
-                                base_mint: base_mint,
-                                quote_mint: quote_mint,
-                            };
-
-                            match ensure_ata_created(&client, &payer.pubkey(), &pool_keys, &payer)
-                                .await
-                            {
-                                Ok(ata) => {
-                                    let token_info = TokenListInfos {
-                                        id_bs64: id_bs64.to_string(),
-                                        base_vault_b64: base_vault_b64.to_string(),
-                                        quote_vault_b64: quote_vault_b64.to_string(),
-                                        base_mint: info.base_mint.to_string(),
-                                        quote_mint: info.quote_mint.to_string(),
-                                        clean_symbol: clean_symbol.to_string(),
-                                        ata: ata.to_string(),
-                                        dex: dex.to_string(),
-                                    };
-
-                                    let buy_history_info = BuyHistoryInfo {
-                                        pool_id: id_bs64.to_string(),
-                                        base_mint: base_mint.to_string(),
-                                        base_vault: base_vault_b64.to_string(),
-                                        quote_mint: quote_mint.to_string(),
-                                        quote_vault: quote_vault_b64.to_string(),
-                                        symbol: clean_symbol.to_string(),
-                                        token_ata: ata.to_string(),
-                                        total_ui_amount_in: 0.0,
-                                        total_amount_in: 0,
-                                        total_ui_token_amount_out: 0.0,
-                                        total_token_amount_out: 0,
-                                        take_profit: 5,
-                                        transactions: vec![],
-                                        dex: dex.to_string(),
-                                    };
-
-                                    log!(
-                                        format!("Token Info saved in file : {:#?}", token_info),
-                                        "result"
-                                    );
-
-                                    save_token_trade_info(&buy_history_info, &id_bs64.to_string());
-                                    save_token_info(&token_info, &id_bs64.to_string());
-                                }
-                                Err(_error) => {}
-                            };
-                        }
-                        Err(e) => {
-                            log!(format!("Failed to deserialize pool info: {:?}", e), "error")
-                        }
-                    }
+    for pool_info in &pool_infos {
+        let pool_key = &Pubkey::from_str_const(&pool_info.address);
+        let take_profit = pool_settings[&pool_info.address].take_profit;
+
+        let data = match client.get_account(pool_key) {
+            Ok(data) => data,
+            Err(error) => {
+                log!(format!("Error fetching pool account: {:?}", error), "error");
+                continue;
+            }
+        };
+
+        let pool = if data.owner.to_string() == PUMP_SWAP_ID {
+            let info = decode_pumpswap_pool_info(data.data.clone());
+            let fees = match client.get_account_data(&pumpswap_global_config_address()) {
+                Ok(global_config) => decode_pumpswap_fees(&global_config, &data.data),
+                Err(e) => {
+                    log!(
+                        format!("Failed to fetch PumpSwap global config: {}", e),
+                        "error"
+                    );
+                    continue;
+                }
+            };
+            PreparedPool {
+                dex: "PUMPSWAP",
+                base_vault: info.pool_base_token_account,
+                quote_vault: info.pool_quote_token_account,
+                base_mint: info.base_mint,
+                quote_mint: info.quote_mint,
+                cpmm: None,
+                whirlpool: None,
+                fees,
+                dlmm: None,
+            }
+        } else if data.owner.to_string() == RAY_CPMM_ID {
+            let info = match decode_cpmm_pool_state(&data.data) {
+                Ok(info) => info,
+                Err(e) => {
+                    log!(format!("Failed to deserialize pool info: {:?}", e), "error");
+                    continue;
+                }
+            };
+            let amm_config = match client
+                .get_account_data(&info.amm_config)
+                .map_err(|e| e.to_string())
+                .and_then(|data| decode_cpmm_amm_config(&data).map_err(|e| e.to_string()))
+            {
+                Ok(amm_config) => amm_config,
+                Err(e) => {
+                    log!(format!("Failed to fetch CPMM amm config: {}", e), "error");
+                    continue;
+                }
+            };
+            PreparedPool {
+                dex: "RAYDIUM_CPMM",
+                base_vault: info.token_0_vault.to_string(),
+                quote_vault: info.token_1_vault.to_string(),
+                base_mint: info.token_0_mint.to_string(),
+                quote_mint: info.token_1_mint.to_string(),
+                cpmm: Some(CpmmPoolKeys {
+                    amm_config: info.amm_config.to_string(),
+                    observation_state: info.observation_key.to_string(),
+                    base_token_program: info.token_0_program.to_string(),
+                    quote_token_program: info.token_1_program.to_string(),
+                    trade_fee_rate: amm_config.trade_fee_rate,
+                }),
+                whirlpool: None,
+                fees: Some(ConstantProductFees::RaydiumCpmm {
+                    trade_fee_rate: amm_config.trade_fee_rate,
+                }),
+                dlmm: None,
+            }
+        } else if data.owner.to_string() == ORCA_WHIRLPOOL_ID {
+            let info = match decode_whirlpool_state(&data.data) {
+                Ok(info) => info,
+                Err(e) => {
+                    log!(format!("Failed to deserialize pool info: {:?}", e), "error");
+                    continue;
+                }
+            };
+            PreparedPool {
+                dex: "ORCA_WHIRLPOOL",
+                base_vault: info.token_vault_a.to_string(),
+                quote_vault: info.token_vault_b.to_string(),
+                base_mint: info.token_mint_a.to_string(),
+                quote_mint: info.token_mint_b.to_string(),
+                cpmm: None,
+                whirlpool: Some(WhirlpoolPoolKeys {
+                    tick_spacing: info.tick_spacing,
+                    oracle: whirlpool_oracle_address(pool_key).to_string(),
+                }),
+                fees: None,
+                dlmm: None,
+            }
+        } else if data.owner.to_string() == METEORA_DLMM_ID {
+            let info = match decode_dlmm_lb_pair(&data.data) {
+                Ok(info) => info,
+                Err(e) => {
+                    log!(format!("Failed to deserialize pool info: {:?}", e), "error");
+                    continue;
+                }
+            };
+            PreparedPool {
+                dex: "METEORA_DLMM",
+                base_vault: info.reserve_x.to_string(),
+                quote_vault: info.reserve_y.to_string(),
+                base_mint: info.token_x_mint.to_string(),
+                quote_mint: info.token_y_mint.to_string(),
+                cpmm: None,
+                whirlpool: None,
+                fees: None,
+                dlmm: Some(DlmmPoolKeys {
+                    bin_step: info.bin_step,
+                    oracle: info.oracle.to_string(),
+                }),
+            }
+        } else {
+            match LiquidityStateLayoutV4::deserialize(&mut data.data.as_slice()) {
+                Ok(info) => PreparedPool {
+                    dex: "RAYDIUM_AMM",
+                    base_vault: info.base_vault.to_string(),
+                    quote_vault: info.quote_vault.to_string(),
+                    base_mint: info.base_mint.to_string(),
+                    quote_mint: info.quote_mint.to_string(),
+                    cpmm: None,
+                    whirlpool: None,
+                    fees: Some(ConstantProductFees::RaydiumAmm {
+                        swap_fee_numerator: info.swap_fee_numerator,
+                        swap_fee_denominator: info.swap_fee_denominator,
+                    }),
+                    dlmm: None,
+                },
+                Err(e) => {
+                    log!(format!("Failed to deserialize pool info: {:?}", e), "error");
+                    continue;
                 }
             }
-            Err(error) => log!(format!("Error fetching pool account: {:?}", error), "error"),
+        };
+
+        let id_bs64 = pool_key.to_string();
+        let token_mint = if pool.base_mint == NATIVE_MINT {
+            &pool.quote_mint
+        } else {
+            &pool.base_mint
+        };
+
+        let token_mint_key = &Pubkey::from_str_const(token_mint);
+        let mint_account = match client.get_account(token_mint_key) {
+            Ok(mint_account) => mint_account,
+            Err(error) => {
+                log!(format!("Error fetching mint account: {:?}", error), "error");
+                continue;
+            }
+        };
+        let token_program = mint_account.owner;
+        let epoch = client
+            .get_epoch_info()
+            .map(|info| info.epoch)
+            .unwrap_or_default();
+        let transfer_fee = decode_mint_transfer_fee(&mint_account.data, epoch);
+        if let Some(fee) = &transfer_fee {
+            log!(
+                format!(
+                    "Token-2022 transfer fee : {} bps, max {}",
+                    fee.basis_points, fee.maximum_fee
+                ),
+                "info"
+            );
+        }
+        let transfer_fee_change_epoch = scheduled_transfer_fee_change(&mint_account.data, epoch);
+        if let Some(change_epoch) = transfer_fee_change_epoch {
+            log!(
+                format!(
+                    "⚠️ Transfer fee of {} changes at epoch {}, the bot re-reads it then",
+                    token_mint, change_epoch
+                ),
+                "update"
+            );
+        }
+
+        // Token-2022 mints usually carry their metadata in a mint extension
+        let symbol = match get_onchain_metadata(&client, token_mint_key).await {
+            Ok(Some(metadata)) => metadata.symbol,
+            _ => decode_mint_metadata_symbol(&mint_account.data).unwrap_or_default(),
+        };
+        let clean_symbol = symbol.trim_end_matches('\0');
+
+        let pool_keys = PoolKeys {
+            base_mint: Pubkey::from_str_const(&pool.base_mint),
+            quote_mint: Pubkey::from_str_const(&pool.quote_mint),
+        };
+
+        match ensure_ata_created(&client, &payer.pubkey(), &pool_keys, &token_program, &payer).await
+        {
+            Ok(ata) => {
+                let token_info = TokenListInfos {
+                    id_bs64: id_bs64.clone(),
+                    base_vault_b64: pool.base_vault.clone(),
+                    quote_vault_b64: pool.quote_vault.clone(),
+                    base_mint: pool.base_mint.clone(),
+                    quote_mint: pool.quote_mint.clone(),
+                    clean_symbol: clean_symbol.to_string(),
+                    ata: ata.to_string(),
+                    dex: pool.dex.to_string(),
+                    cpmm: pool.cpmm,
+                    whirlpool: pool.whirlpool,
+                    dlmm: pool.dlmm,
+                    token_program: token_program.to_string(),
+                    transfer_fee,
+                    transfer_fee_change_epoch,
+                    fees: pool.fees,
+                };
+
+                let buy_history_info = BuyHistoryInfo {
+                    pool_id: id_bs64.clone(),
+                    base_mint: pool.base_mint,
+                    base_vault: pool.base_vault,
+                    quote_mint: pool.quote_mint,
+                    quote_vault: pool.quote_vault,
+                    symbol: clean_symbol.to_string(),
+                    token_ata: ata.to_string(),
+                    total_ui_amount_in: 0.0,
+                    total_amount_in: 0,
+                    total_ui_token_amount_out: 0.0,
+                    total_token_amount_out: 0,
+                    take_profit,
+                    transactions: vec![],
+                    dex: pool.dex.to_string(),
+                };
+
+                log!(
+                    format!("Token Info saved in file : {:#?}", token_info),
+                    "result"
+                );
+
+                if let Err(e) = with_trade_store(|store| store.save_position(&buy_history_info)) {
+                    log!(format!("🔴 Failed to record position: {}", e), "error");
+                    continue;
+                }
+                save_token_info(&token_info, &id_bs64);
+            }
+            Err(_error) => {}
         };
     }
 }
+
+/// Pool accounts decoded into the fields pre_start persists, whatever the dex.
+struct PreparedPool {
+    dex: &'static str,
+    base_vault: String,
+    quote_vault: String,
+    base_mint: String,
+    quote_mint: String,
+    cpmm: Option<CpmmPoolKeys>,
+    whirlpool: Option<WhirlpoolPoolKeys>,
+    dlmm: Option<DlmmPoolKeys>,
+    fees: Option<ConstantProductFees>,
+}
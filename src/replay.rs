@@ -0,0 +1,216 @@
+use std::{collections::BTreeSet, env, fs, io::ErrorKind, path::PathBuf, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use raydium_trade_bot::{
+    fill_replayed_orders, get_sol_price, lamports_to_sol, load_pool_info, load_position_book, log,
+    only_own_transaction, recording_files, select_trade_account, set_replay_time_ms,
+    sol_to_lamports, swap_handler, with_trade_store, BotConfig, ConfirmationService, ExitManager,
+    LiveConfig, PoolStateCache, PriorityFeeEstimator, RecordingReader, ReplayClock, RiskManager,
+    TradeAccount, EXIT_TICK, REPLAY_TRADE_DB_PATH,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    signer::Signer,
+};
+use tokio::sync::watch;
+use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+
+/// Feeds recorded stream updates back into the pool cache, the strategies and
+/// the exit manager as `main` would, with every intent filled on paper into
+/// the replay account. The account is emptied at the start of each replay.
+///
+/// Updates are handled one at a time on the recording's clock: holding
+/// times, fill times and the risk windows read the receive time of the
+/// update being replayed, and paper fills wait for the pool state the
+/// replay feeds next. Nothing reads the live chain, so Whirlpool and DLMM
+/// trades, which can't be priced from the recording, are skipped.
+///
+/// Usage: `replay [--speed <x>] [--balance <SOL>] [--sol-price <USD>]
+/// [<recording or directory>...]`; the `recorder.dir` recordings by default,
+/// at the recorded pace (`--speed 10` for ten times faster, `--speed 0` for
+/// as fast as they can be read). `--balance` is the wallet balance the
+/// reserve check sees, unknown otherwise; `--sol-price` the price sizing
+/// uses, fetched once otherwise. Pass both for repeatable replays.
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut speed = 1.0;
+    let mut balance = None;
+    let mut sol_price = None;
+    let mut paths = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--speed" | "--balance" | "--sol-price" => {
+                let value = args
+                    .next()
+                    .with_context(|| format!("{} needs a value", arg))?;
+                let value = value
+                    .parse::<f64>()
+                    .with_context(|| format!("{:?} is not a number", value))?;
+                if !value.is_finite() || value < 0.0 {
+                    bail!("{} must be >= 0, got {}", arg, value);
+                }
+                match arg.as_str() {
+                    "--speed" => speed = value,
+                    "--balance" => balance = Some(value),
+                    _ => sol_price = Some(value),
+                }
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    // Replays book on their own account, from scratch every time
+    select_trade_account(TradeAccount::Replay);
+    for suffix in ["", "-wal", "-shm"] {
+        match fs::remove_file(format!("{}{}", REPLAY_TRADE_DB_PATH, suffix)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    load_position_book()?;
+
+    let config = Arc::new(BotConfig::load()?);
+    if paths.is_empty() {
+        paths.push(PathBuf::from(&config.recorder.dir));
+    }
+    let files = recording_files(&paths)?;
+    if files.is_empty() {
+        log!(format!("No recordings found in {:?}", paths), "info");
+        return Ok(());
+    }
+
+    let payer = Arc::new(config.payer());
+    let pool_info_list = load_pool_info(&config)?;
+    let live_config = LiveConfig::load(Arc::clone(&config))?;
+    let sol_price = match sol_price {
+        Some(sol_price) => sol_price,
+        None => {
+            let sol_price = get_sol_price().await.ok().flatten().unwrap_or(0.0);
+            log!(
+                format!("Sizing at a SOL price of {} USD", sol_price),
+                "info"
+            );
+            sol_price
+        }
+    };
+    // Only handed to the handlers, nothing replayed calls it
+    let client = Arc::new(RpcClient::new_with_commitment(
+        config.endpoints.rpc_endpoint.clone(),
+        CommitmentConfig::processed(),
+    ));
+
+    // Pools are priced from the recorded account updates alone
+    let pool_cache = Arc::new(PoolStateCache::new(&pool_info_list));
+    let risk = Arc::new(RiskManager::new());
+    if let Some(balance) = balance {
+        risk.set_wallet_balance(sol_to_lamports(balance));
+    }
+    // Nothing is submitted, so nothing waits for a confirmation
+    let confirmations = ConfirmationService::new(Arc::clone(&client), payer.pubkey());
+    let fees = Arc::new(PriorityFeeEstimator::new(&pool_info_list));
+    // The config the replay starts with holds for all of it
+    let (_config_sender, live) = watch::channel(Arc::new(live_config));
+    let mut exits = ExitManager::new(
+        Arc::clone(&client),
+        Arc::clone(&payer),
+        live.clone(),
+        Arc::clone(&pool_cache),
+        Arc::clone(&risk),
+        Arc::clone(&confirmations),
+        Arc::clone(&fees),
+    );
+    let mut updated_pools = pool_cache.subscribe_updates();
+
+    let mut clock = ReplayClock::new(speed);
+    let (mut started_ms, mut next_tick_ms) = (None, i64::MIN);
+    let (mut transactions, mut accounts) = (0u64, 0u64);
+    for file in &files {
+        log!(format!("Replaying {}", file.display()), "info");
+        let reader = RecordingReader::open(file)
+            .with_context(|| format!("failed to open {}", file.display()))?;
+
+        for recorded in reader {
+            let recorded = match recorded {
+                Ok(recorded) => recorded,
+                Err(e) => {
+                    log!(format!("🔴 {} ends early: {}", file.display(), e), "error");
+                    break;
+                }
+            };
+            clock.wait(recorded.received_at_us).await;
+            let replayed_at_ms = (recorded.received_at_us / 1000) as i64;
+            set_replay_time_ms(replayed_at_ms);
+            started_ms.get_or_insert(replayed_at_ms);
+
+            // Fills whose next slot never came, then the exit tick
+            fill_replayed_orders(&pool_cache, &risk, false);
+            if replayed_at_ms >= next_tick_ms {
+                let listed: BTreeSet<Pubkey> = exits.listed_pools().into_iter().collect();
+                exits.check_pools(listed).await;
+                next_tick_ms = replayed_at_ms + EXIT_TICK.as_millis() as i64;
+            }
+
+            match recorded.update.update_oneof {
+                Some(UpdateOneof::Account(account)) => {
+                    let Some(info) = account.account else {
+                        continue;
+                    };
+                    if let Ok(pubkey) = Pubkey::try_from(info.pubkey.as_slice()) {
+                        pool_cache.apply(&pubkey, &info.data, account.slot, info.write_version);
+                        accounts += 1;
+                    }
+
+                    let mut pools = BTreeSet::new();
+                    while let Ok(pool) = updated_pools.try_recv() {
+                        pools.insert(pool);
+                    }
+                    fill_replayed_orders(&pool_cache, &risk, false);
+                    exits.check_pools(pools).await;
+                }
+                // Our own recorded submissions are no signal
+                Some(UpdateOneof::Transaction(_))
+                    if only_own_transaction(&recorded.update.filters) => {}
+                Some(UpdateOneof::Transaction(transaction)) => {
+                    transactions += 1;
+                    swap_handler(
+                        Arc::clone(&client),
+                        Arc::clone(&live.borrow()),
+                        Arc::clone(&payer),
+                        Arc::clone(&pool_cache),
+                        Arc::clone(&risk),
+                        Arc::clone(&confirmations),
+                        Arc::clone(&fees),
+                        &transaction,
+                        sol_price,
+                    )
+                    .await;
+                }
+                _ => {}
+            }
+        }
+    }
+    // Orders still waiting for a next slot fill at the last one
+    fill_replayed_orders(&pool_cache, &risk, true);
+
+    let realized =
+        with_trade_store(|store| store.realized_pnl_since(started_ms.unwrap_or_default()))?;
+    let open = with_trade_store(|store| store.open_exposures())?;
+    log!(
+        format!(
+            "Replayed {} transactions and {} account updates from {} file(s)\n\tRealized PnL : {} SOL\n\tOpen positions : {} ({} SOL at cost)\n\tFills : {}",
+            transactions,
+            accounts,
+            files.len(),
+            realized as f64 / LAMPORTS_PER_SOL as f64,
+            open.len(),
+            lamports_to_sol(open.iter().map(|exposure| exposure.cost_lamports).sum()),
+            REPLAY_TRADE_DB_PATH
+        ),
+        "result"
+    );
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
-use std::{env, str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc};
 
-use raydium_trade_bot::{get_ui_token_balance_change, load_env_file};
+use raydium_trade_bot::{get_ui_token_balance_change, BotConfig};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
 use solana_transaction_status::UiTransactionEncoding;
@@ -8,9 +8,15 @@ use solana_transaction_status::UiTransactionEncoding;
 #[tokio::main]
 async fn main() {
     /* Initial Settings */
-    let (_, rpc, grpc, token, _, _, _, _) = load_env_file();
+    let config = match BotConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
     let client = Arc::new(RpcClient::new_with_commitment(
-        rpc.to_string(),
+        config.endpoints.rpc_endpoint,
         CommitmentConfig::processed(),
     ));
 
@@ -26,7 +32,7 @@ async fn main() {
 
     match client.get_transaction_with_config(&sig, config).await {
         Ok(parsed_tx) => {
-            get_ui_token_balance_change(&parsed_tx , false);
+            get_ui_token_balance_change(&parsed_tx, false);
         }
         Err(e) => {
             println!("Error: {:#?}", e);
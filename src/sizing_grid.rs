@@ -0,0 +1,117 @@
+use std::env;
+
+use anyhow::{anyhow, Context, Result};
+use raydium_trade_bot::{
+    dump_sizing, load_pool_info, resolve_pools, BotConfig, EntrySize, PoolSettings,
+};
+
+/// Price drops (%) and pool liquidities (USD) the grid is laid out on.
+const DROPS_PCT: &[f64] = &[5.0, 8.0, 10.0, 12.0, 15.0, 18.0, 20.0, 25.0, 30.0, 40.0];
+const LIQUIDITIES_USD: &[f64] = &[
+    50_000.0,
+    80_000.0,
+    100_000.0,
+    200_000.0,
+    500_000.0,
+    800_000.0,
+    2_000_000.0,
+    5_000_000.0,
+];
+
+/// Formats one field of a sizing decision for a grid cell.
+type CellFormat = fn(&EntrySize) -> String;
+
+/// SOL sold by the dump sized when no amount is given.
+const DEFAULT_SELL_SOL: f64 = 10.0;
+
+/// Prints the sizing table in effect and the buys it decides across a grid of
+/// price drops and liquidities, without connecting to anything.
+///
+/// Usage: `sizing_grid [<dump size in SOL>] [<pool address>]`; with a pool,
+/// its overrides from the pool file apply.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let sell_sol = match args.next() {
+        Some(amount) => amount
+            .parse::<f64>()
+            .with_context(|| format!("{:?} is not a SOL amount", amount))?,
+        None => DEFAULT_SELL_SOL,
+    };
+    let pool = args.next();
+
+    let config = BotConfig::load()?;
+    let settings = match &pool {
+        Some(pool) => resolve_pools(&config, &load_pool_info(&config)?)?
+            .remove(pool)
+            .ok_or_else(|| anyhow!("{} is not in {}", pool, config.paths.pool_addr))?,
+        None => PoolSettings::defaults(&config),
+    };
+    let tiers = settings.dump.sizing_tiers(&settings.slippage);
+
+    println!(
+        "Sizing table ({}), max {} SOL, min tip {} SOL, slippage {}..={} %",
+        match (&pool, &settings.dump.tiers) {
+            (Some(pool), _) => format!("pool {}", pool),
+            (None, Some(_)) => "dump.tiers".to_string(),
+            (None, None) => "built from the legacy factors".to_string(),
+        },
+        settings.dump.max_amount,
+        settings.dump.tip_min,
+        settings.slippage.min_pct,
+        settings.slippage.max_pct
+    );
+    println!(
+        "{:>5} {:>10} {:>14} {:>8} {:>8} {:>10}",
+        "tier", "drop >= %", "liquidity >= $", "amount %", "tip %", "slip x"
+    );
+    for (i, tier) in tiers.iter().enumerate() {
+        println!(
+            "{:>5} {:>10} {:>14} {:>8} {:>8} {:>10}",
+            i,
+            tier.min_drop_pct,
+            tier.min_liquidity_usd,
+            tier.amount_pct,
+            tier.tip_pct,
+            tier.slippage_factor
+        );
+    }
+
+    let size = |drop_pct: f64, liquidity_usd: f64| {
+        dump_sizing(
+            drop_pct,
+            liquidity_usd,
+            sell_sol,
+            &tiers,
+            &settings.dump,
+            &settings.slippage,
+        )
+    };
+    let grids: [(&str, CellFormat); 4] = [
+        ("Tier", |size| size.tier.to_string()),
+        ("Buy (SOL)", |size| format!("{:.3}", size.sol_amount)),
+        ("Tip (SOL)", |size| format!("{:.4}", size.tip)),
+        ("Slippage (%)", |size| format!("{:.2}", size.slippage_pct)),
+    ];
+
+    for (title, cell) in grids {
+        println!(
+            "\n{} for a {} SOL dump, by drop % (rows) and liquidity $ (columns)",
+            title, sell_sol
+        );
+        print!("{:>6}", "");
+        for liquidity in LIQUIDITIES_USD {
+            print!(" {:>10}", liquidity);
+        }
+        println!();
+        for &drop in DROPS_PCT {
+            print!("{:>6}", drop);
+            for &liquidity in LIQUIDITIES_USD {
+                let value = size(drop, liquidity).map_or("-".to_string(), |size| cell(&size));
+                print!(" {:>10}", value);
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
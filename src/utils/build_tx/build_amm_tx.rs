@@ -1,4 +1,7 @@
-use crate::{RayAMMSwapBaseInParams, RAY_AMM_AUTH, RAY_AMM_ID};
+use crate::{
+    RayAMMSwapBaseInParams, RayAMMSwapBaseOutParams, SwapBaseOut, RAY_AMM_AUTH, RAY_AMM_ID,
+    RAY_AMM_SWAP_BASE_OUT,
+};
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
@@ -6,44 +9,86 @@ use solana_sdk::{
 use spl_associated_token_account::get_associated_token_address;
 
 pub fn build_amm_swap_base_in(amm_swap_base_in_param: RayAMMSwapBaseInParams) -> Instruction {
-    let user_token_source_mint = amm_swap_base_in_param.input_mint;
-    let user_token_destination_mint = amm_swap_base_in_param.output_mint;
-    let amm_program_id = Pubkey::from_str_const(RAY_AMM_ID);
-    let amm_pool = amm_swap_base_in_param.pool_id;
-    let amm_authority = Pubkey::from_str_const(RAY_AMM_AUTH);
-
-    let user_token_source =
-        get_associated_token_address(&amm_swap_base_in_param.payer, &user_token_source_mint);
-    let user_token_destination =
-        get_associated_token_address(&amm_swap_base_in_param.payer, &user_token_destination_mint);
-
     let mut data = Vec::new();
     data.extend_from_slice(&9_u8.to_le_bytes());
     data.extend_from_slice(&amm_swap_base_in_param.amount_in.to_le_bytes());
     data.extend_from_slice(&amm_swap_base_in_param.minimum_amount_out.to_le_bytes());
 
     Instruction {
-        program_id: amm_program_id,
-        accounts: vec![
-            AccountMeta::new_readonly(spl_token::id(), false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new_readonly(amm_authority, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new(amm_swap_base_in_param.coin_vault, false),
-            AccountMeta::new(amm_swap_base_in_param.pc_vault, false),
-            AccountMeta::new_readonly(amm_pool, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new(amm_pool, false),
-            AccountMeta::new_readonly(amm_pool, false),
-            AccountMeta::new(user_token_source, false),
-            AccountMeta::new(user_token_destination, false),
-            AccountMeta::new_readonly(amm_swap_base_in_param.payer, true),
-        ],
+        program_id: Pubkey::from_str_const(RAY_AMM_ID),
+        accounts: amm_swap_accounts(
+            &amm_swap_base_in_param.pool_id,
+            &amm_swap_base_in_param.coin_vault,
+            &amm_swap_base_in_param.pc_vault,
+            &amm_swap_base_in_param.input_mint,
+            &amm_swap_base_in_param.output_mint,
+            &amm_swap_base_in_param.payer,
+        ),
         data,
     }
 }
+
+/// Exact-out swap: receive exactly `amount_out`, spending at most `max_amount_in`.
+pub fn build_amm_swap_base_out(amm_swap_base_out_param: RayAMMSwapBaseOutParams) -> Instruction {
+    let args = SwapBaseOut {
+        discriminator: RAY_AMM_SWAP_BASE_OUT,
+        max_amount_in: amm_swap_base_out_param.max_amount_in,
+        amount_out: amm_swap_base_out_param.amount_out,
+    };
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&args.discriminator.to_le_bytes());
+    data.extend_from_slice(&args.max_amount_in.to_le_bytes());
+    data.extend_from_slice(&args.amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: Pubkey::from_str_const(RAY_AMM_ID),
+        accounts: amm_swap_accounts(
+            &amm_swap_base_out_param.pool_id,
+            &amm_swap_base_out_param.coin_vault,
+            &amm_swap_base_out_param.pc_vault,
+            &amm_swap_base_out_param.input_mint,
+            &amm_swap_base_out_param.output_mint,
+            &amm_swap_base_out_param.payer,
+        ),
+        data,
+    }
+}
+
+/// Account list shared by both swap instructions (18-account form, the unused
+/// OpenBook accounts are filled with the pool).
+fn amm_swap_accounts(
+    amm_pool: &Pubkey,
+    coin_vault: &Pubkey,
+    pc_vault: &Pubkey,
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
+    payer: &Pubkey,
+) -> Vec<AccountMeta> {
+    let amm_pool = *amm_pool;
+    let amm_authority = Pubkey::from_str_const(RAY_AMM_AUTH);
+
+    let user_token_source = get_associated_token_address(payer, input_mint);
+    let user_token_destination = get_associated_token_address(payer, output_mint);
+
+    vec![
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new_readonly(amm_authority, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new(*coin_vault, false),
+        AccountMeta::new(*pc_vault, false),
+        AccountMeta::new_readonly(amm_pool, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new(amm_pool, false),
+        AccountMeta::new_readonly(amm_pool, false),
+        AccountMeta::new(user_token_source, false),
+        AccountMeta::new(user_token_destination, false),
+        AccountMeta::new_readonly(*payer, true),
+    ]
+}
@@ -0,0 +1,57 @@
+use crate::{RayCpmmSwapParams, RAY_CPMM_AUTH, RAY_CPMM_ID};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub const RAY_CPMM_SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+pub const RAY_CPMM_SWAP_BASE_OUTPUT: [u8; 8] = [55, 217, 98, 86, 163, 74, 180, 173];
+
+/// Exact-in swap: spend `amount_in`, receive at least `amount_out`.
+pub fn build_cpmm_swap_base_input(cpmm_swap_param: RayCpmmSwapParams) -> Instruction {
+    build_cpmm_swap(RAY_CPMM_SWAP_BASE_INPUT, cpmm_swap_param)
+}
+
+/// Exact-out swap: receive exactly `amount_out`, spending at most `amount_in`.
+pub fn build_cpmm_swap_base_output(cpmm_swap_param: RayCpmmSwapParams) -> Instruction {
+    build_cpmm_swap(RAY_CPMM_SWAP_BASE_OUTPUT, cpmm_swap_param)
+}
+
+fn build_cpmm_swap(discriminator: [u8; 8], param: RayCpmmSwapParams) -> Instruction {
+    let user_input_token = get_associated_token_address_with_program_id(
+        &param.payer,
+        &param.input_mint,
+        &param.input_token_program,
+    );
+    let user_output_token = get_associated_token_address_with_program_id(
+        &param.payer,
+        &param.output_mint,
+        &param.output_token_program,
+    );
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&discriminator);
+    data.extend_from_slice(&param.amount_in.to_le_bytes());
+    data.extend_from_slice(&param.amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: Pubkey::from_str_const(RAY_CPMM_ID),
+        accounts: vec![
+            AccountMeta::new_readonly(param.payer, true),
+            AccountMeta::new_readonly(Pubkey::from_str_const(RAY_CPMM_AUTH), false),
+            AccountMeta::new_readonly(param.amm_config, false),
+            AccountMeta::new(param.pool_id, false),
+            AccountMeta::new(user_input_token, false),
+            AccountMeta::new(user_output_token, false),
+            AccountMeta::new(param.input_vault, false),
+            AccountMeta::new(param.output_vault, false),
+            AccountMeta::new_readonly(param.input_token_program, false),
+            AccountMeta::new_readonly(param.output_token_program, false),
+            AccountMeta::new_readonly(param.input_mint, false),
+            AccountMeta::new_readonly(param.output_mint, false),
+            AccountMeta::new(param.observation_state, false),
+        ],
+        data,
+    }
+}
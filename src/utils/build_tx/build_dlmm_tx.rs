@@ -0,0 +1,105 @@
+use crate::{is_token_2022, DlmmSwapParams, MEMO_PRO, METEORA_DLMM_EVENT_AUTH, METEORA_DLMM_ID};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub const DLMM_SWAP: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+pub const DLMM_SWAP_EXACT_OUT: [u8; 8] = [250, 73, 101, 33, 38, 207, 75, 184];
+pub const DLMM_SWAP_WITH_PRICE_IMPACT: [u8; 8] = [56, 173, 230, 208, 173, 228, 156, 205];
+pub const DLMM_SWAP2: [u8; 8] = [65, 75, 63, 76, 235, 91, 91, 136];
+pub const DLMM_SWAP_EXACT_OUT2: [u8; 8] = [43, 215, 247, 132, 137, 60, 243, 81];
+pub const DLMM_SWAP_WITH_PRICE_IMPACT2: [u8; 8] = [74, 98, 192, 214, 177, 51, 75, 51];
+
+/// Exact-in swap: spend `amount_in`, receive at least `amount_out`.
+pub fn build_dlmm_swap(dlmm_swap_param: DlmmSwapParams) -> Instruction {
+    build_dlmm_swap_ix(DLMM_SWAP, DLMM_SWAP2, dlmm_swap_param)
+}
+
+/// Exact-out swap: receive exactly `amount_out`, spending at most `amount_in`.
+pub fn build_dlmm_swap_exact_out(dlmm_swap_param: DlmmSwapParams) -> Instruction {
+    build_dlmm_swap_ix(DLMM_SWAP_EXACT_OUT, DLMM_SWAP_EXACT_OUT2, dlmm_swap_param)
+}
+
+// The v2 instruction is used when either mint is Token-2022, the v1 one otherwise
+fn build_dlmm_swap_ix(
+    discriminator: [u8; 8],
+    discriminator_v2: [u8; 8],
+    param: DlmmSwapParams,
+) -> Instruction {
+    let program_id = Pubkey::from_str_const(METEORA_DLMM_ID);
+    let (input_mint, input_program, output_mint, output_program) = if param.swap_for_y {
+        (
+            param.token_x_mint,
+            param.token_x_program,
+            param.token_y_mint,
+            param.token_y_program,
+        )
+    } else {
+        (
+            param.token_y_mint,
+            param.token_y_program,
+            param.token_x_mint,
+            param.token_x_program,
+        )
+    };
+    let user_token_in =
+        get_associated_token_address_with_program_id(&param.payer, &input_mint, &input_program);
+    let user_token_out =
+        get_associated_token_address_with_program_id(&param.payer, &output_mint, &output_program);
+    let is_v2 = is_token_2022(&param.token_x_program) || is_token_2022(&param.token_y_program);
+
+    let mut data = Vec::with_capacity(28);
+    data.extend_from_slice(if is_v2 {
+        &discriminator_v2
+    } else {
+        &discriminator
+    });
+    data.extend_from_slice(&param.amount_in.to_le_bytes());
+    data.extend_from_slice(&param.amount_out.to_le_bytes());
+    if is_v2 {
+        // No transfer hook accounts (empty `remaining_accounts_info.slices`)
+        data.extend_from_slice(&0u32.to_le_bytes());
+    }
+
+    let mut accounts = vec![
+        AccountMeta::new(param.lb_pair, false),
+        // No bitmap extension, optional accounts are set to the program id
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new(param.reserve_x, false),
+        AccountMeta::new(param.reserve_y, false),
+        AccountMeta::new(user_token_in, false),
+        AccountMeta::new(user_token_out, false),
+        AccountMeta::new_readonly(param.token_x_mint, false),
+        AccountMeta::new_readonly(param.token_y_mint, false),
+        AccountMeta::new(param.oracle, false),
+        // No host fee
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new_readonly(param.payer, true),
+        AccountMeta::new_readonly(param.token_x_program, false),
+        AccountMeta::new_readonly(param.token_y_program, false),
+    ];
+    if is_v2 {
+        accounts.push(AccountMeta::new_readonly(
+            Pubkey::from_str_const(MEMO_PRO),
+            false,
+        ));
+    }
+    accounts.extend([
+        AccountMeta::new_readonly(Pubkey::from_str_const(METEORA_DLMM_EVENT_AUTH), false),
+        AccountMeta::new_readonly(program_id, false),
+    ]);
+    accounts.extend(
+        param
+            .bin_arrays
+            .iter()
+            .map(|bin_array| AccountMeta::new(*bin_array, false)),
+    );
+
+    Instruction {
+        program_id,
+        accounts,
+        data,
+    }
+}
@@ -1,12 +1,12 @@
 use crate::{
     PumpSwapBuyParams, PumpSwapSellParams, ASSOCIATED_TOKEN_PRO, PUMPSWAP_FEE, PUMP_SWAP_ID,
-    SYSTEM_PRO, TOKEN_PRO,
+    SYSTEM_PRO,
 };
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 const PUMPSWAP_PROGRAM_ID: Pubkey = Pubkey::from_str_const(PUMP_SWAP_ID);
 const SYSTEMP_PROGRAM_ID: Pubkey = Pubkey::from_str_const(SYSTEM_PRO);
@@ -15,17 +15,32 @@ const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = Pubkey::from_str_const(ASSOCIATED_TO
 pub fn build_pumpswap_buy(pumpswap_buy_param: PumpSwapBuyParams) -> Instruction {
     let (global_config, _) =
         Pubkey::find_program_address(&["global_config".as_bytes()], &PUMPSWAP_PROGRAM_ID);
-    let user_base_token_account =
-        get_associated_token_address(&pumpswap_buy_param.payer, &pumpswap_buy_param.base_mint);
-    let user_quote_token_account =
-        get_associated_token_address(&pumpswap_buy_param.payer, &pumpswap_buy_param.quote_mint);
-    let pool_base_token_account =
-        get_associated_token_address(&pumpswap_buy_param.pool_id, &pumpswap_buy_param.base_mint);
-    let pool_quote_token_account =
-        get_associated_token_address(&pumpswap_buy_param.pool_id, &pumpswap_buy_param.quote_mint);
+    let user_base_token_account = get_associated_token_address_with_program_id(
+        &pumpswap_buy_param.payer,
+        &pumpswap_buy_param.base_mint,
+        &pumpswap_buy_param.base_token_program,
+    );
+    let user_quote_token_account = get_associated_token_address_with_program_id(
+        &pumpswap_buy_param.payer,
+        &pumpswap_buy_param.quote_mint,
+        &pumpswap_buy_param.quote_token_program,
+    );
+    let pool_base_token_account = get_associated_token_address_with_program_id(
+        &pumpswap_buy_param.pool_id,
+        &pumpswap_buy_param.base_mint,
+        &pumpswap_buy_param.base_token_program,
+    );
+    let pool_quote_token_account = get_associated_token_address_with_program_id(
+        &pumpswap_buy_param.pool_id,
+        &pumpswap_buy_param.quote_mint,
+        &pumpswap_buy_param.quote_token_program,
+    );
     let protocol_fee_recipient = Pubkey::from_str_const(PUMPSWAP_FEE[0]);
-    let protocol_fee_recipient_token_account =
-        get_associated_token_address(&protocol_fee_recipient, &pumpswap_buy_param.quote_mint);
+    let protocol_fee_recipient_token_account = get_associated_token_address_with_program_id(
+        &protocol_fee_recipient,
+        &pumpswap_buy_param.quote_mint,
+        &pumpswap_buy_param.quote_token_program,
+    );
     let (event_authority, _) =
         Pubkey::find_program_address(&["__event_authority".as_bytes()], &PUMPSWAP_PROGRAM_ID);
 
@@ -63,19 +78,32 @@ pub fn build_pumpswap_buy(pumpswap_buy_param: PumpSwapBuyParams) -> Instruction
 pub fn build_pumpswap_sell(pumpswap_sell_param: PumpSwapSellParams) -> Instruction {
     let (global_config, _) =
         Pubkey::find_program_address(&["global_config".as_bytes()], &PUMPSWAP_PROGRAM_ID);
-    let user_base_token_account =
-        get_associated_token_address(&pumpswap_sell_param.payer, &pumpswap_sell_param.base_mint);
-    let user_quote_token_account =
-        get_associated_token_address(&pumpswap_sell_param.payer, &pumpswap_sell_param.quote_mint);
-    let pool_base_token_account =
-        get_associated_token_address(&pumpswap_sell_param.pool_id, &pumpswap_sell_param.base_mint);
-    let pool_quote_token_account = get_associated_token_address(
+    let user_base_token_account = get_associated_token_address_with_program_id(
+        &pumpswap_sell_param.payer,
+        &pumpswap_sell_param.base_mint,
+        &pumpswap_sell_param.base_token_program,
+    );
+    let user_quote_token_account = get_associated_token_address_with_program_id(
+        &pumpswap_sell_param.payer,
+        &pumpswap_sell_param.quote_mint,
+        &pumpswap_sell_param.quote_token_program,
+    );
+    let pool_base_token_account = get_associated_token_address_with_program_id(
+        &pumpswap_sell_param.pool_id,
+        &pumpswap_sell_param.base_mint,
+        &pumpswap_sell_param.base_token_program,
+    );
+    let pool_quote_token_account = get_associated_token_address_with_program_id(
         &pumpswap_sell_param.pool_id,
         &pumpswap_sell_param.quote_mint,
+        &pumpswap_sell_param.quote_token_program,
     );
     let protocol_fee_recipient = Pubkey::from_str_const(PUMPSWAP_FEE[0]);
-    let protocol_fee_recipient_token_account =
-        get_associated_token_address(&protocol_fee_recipient, &pumpswap_sell_param.quote_mint);
+    let protocol_fee_recipient_token_account = get_associated_token_address_with_program_id(
+        &protocol_fee_recipient,
+        &pumpswap_sell_param.quote_mint,
+        &pumpswap_sell_param.quote_token_program,
+    );
     let (event_authority, _) =
         Pubkey::find_program_address(&["__event_authority".as_bytes()], &PUMPSWAP_PROGRAM_ID);
 
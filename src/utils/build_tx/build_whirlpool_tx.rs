@@ -0,0 +1,83 @@
+use crate::{is_token_2022, WhirlpoolSwapParams, MEMO_PRO, ORCA_WHIRLPOOL_ID};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub const WHIRLPOOL_SWAP: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+pub const WHIRLPOOL_SWAP_V2: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// Whirlpool swap, exact-in or exact-out depending on `amount_specified_is_input`.
+/// `swap_v2` is used when either mint is Token-2022, the v1 `swap` otherwise.
+pub fn build_whirlpool_swap(param: WhirlpoolSwapParams) -> Instruction {
+    let user_token_a = get_associated_token_address_with_program_id(
+        &param.payer,
+        &param.token_mint_a,
+        &param.token_program_a,
+    );
+    let user_token_b = get_associated_token_address_with_program_id(
+        &param.payer,
+        &param.token_mint_b,
+        &param.token_program_b,
+    );
+    let is_v2 = is_token_2022(&param.token_program_a) || is_token_2022(&param.token_program_b);
+
+    let mut data = Vec::with_capacity(43);
+    data.extend_from_slice(if is_v2 {
+        &WHIRLPOOL_SWAP_V2
+    } else {
+        &WHIRLPOOL_SWAP
+    });
+    data.extend_from_slice(&param.amount.to_le_bytes());
+    data.extend_from_slice(&param.other_amount_threshold.to_le_bytes());
+    data.extend_from_slice(&param.sqrt_price_limit.to_le_bytes());
+    data.push(param.amount_specified_is_input as u8);
+    data.push(param.a_to_b as u8);
+
+    let tick_arrays = param
+        .tick_arrays
+        .iter()
+        .map(|tick_array| AccountMeta::new(*tick_array, false));
+
+    let accounts = if is_v2 {
+        // No transfer hook accounts (`remaining_accounts_info: None`)
+        data.push(0);
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(param.token_program_a, false),
+            AccountMeta::new_readonly(param.token_program_b, false),
+            AccountMeta::new_readonly(Pubkey::from_str_const(MEMO_PRO), false),
+            AccountMeta::new_readonly(param.payer, true),
+            AccountMeta::new(param.pool_id, false),
+            AccountMeta::new_readonly(param.token_mint_a, false),
+            AccountMeta::new_readonly(param.token_mint_b, false),
+            AccountMeta::new(user_token_a, false),
+            AccountMeta::new(param.token_vault_a, false),
+            AccountMeta::new(user_token_b, false),
+            AccountMeta::new(param.token_vault_b, false),
+        ];
+        accounts.extend(tick_arrays);
+        accounts.push(AccountMeta::new(param.oracle, false));
+        accounts
+    } else {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(param.payer, true),
+            AccountMeta::new(param.pool_id, false),
+            AccountMeta::new(user_token_a, false),
+            AccountMeta::new(param.token_vault_a, false),
+            AccountMeta::new(user_token_b, false),
+            AccountMeta::new(param.token_vault_b, false),
+        ];
+        accounts.extend(tick_arrays);
+        accounts.push(AccountMeta::new(param.oracle, false));
+        accounts
+    };
+
+    Instruction {
+        program_id: Pubkey::from_str_const(ORCA_WHIRLPOOL_ID),
+        accounts,
+        data,
+    }
+}
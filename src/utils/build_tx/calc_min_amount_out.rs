@@ -1,16 +1,25 @@
-use bigdecimal::BigDecimal;
+/// Basis points in a percent.
+const BPS_PER_PCT: f64 = 100.0;
+const BPS_DENOMINATOR: u128 = 10_000;
 
-pub fn calc_min_amount_out(
-    amount: BigDecimal,
-    current_price: BigDecimal,
-    slippage: BigDecimal,
-    currency_out_decimals: u32,
-) -> BigDecimal {
-    let slippage_factor = BigDecimal::from(1) - (slippage / BigDecimal::from(100));
-    let amount_out_without_slip = &amount / &current_price;
-    let min_amount_out = &amount_out_without_slip * &slippage_factor;
+/// Smallest acceptable output once `slippage_pct` is taken off the quoted
+/// `amount_out`, rounded down and never below one unit.
+pub fn calc_min_amount_out(amount_out: u64, slippage_pct: f64) -> u64 {
+    let bps = slippage_bps(slippage_pct);
+    let min_amount_out = amount_out as u128 * (BPS_DENOMINATOR - bps) / BPS_DENOMINATOR;
 
-    // Convert to integer unit of output token
-    let min_pow = min_amount_out * BigDecimal::from(10_u64.pow(currency_out_decimals));
-    min_pow.with_prec(0) // Rounds to integer
+    (min_amount_out as u64).max(1)
+}
+
+/// Largest acceptable input once `slippage_pct` is added to the quoted
+/// `amount_in`, rounded up.
+pub fn calc_max_amount_in(amount_in: u64, slippage_pct: f64) -> u64 {
+    let bps = slippage_bps(slippage_pct);
+    let max_amount_in = (amount_in as u128 * (BPS_DENOMINATOR + bps)).div_ceil(BPS_DENOMINATOR);
+
+    u64::try_from(max_amount_in).unwrap_or(u64::MAX)
+}
+
+fn slippage_bps(slippage_pct: f64) -> u128 {
+    ((slippage_pct * BPS_PER_PCT).round() as u128).min(BPS_DENOMINATOR)
 }
@@ -1,8 +1,12 @@
 pub mod build_amm_tx;
+pub mod build_cpmm_tx;
+pub mod build_dlmm_tx;
 pub mod build_pumpswap_tx;
+pub mod build_whirlpool_tx;
 pub mod calc_min_amount_out;
-pub mod swap_quote;
 pub use build_amm_tx::*;
+pub use build_cpmm_tx::*;
+pub use build_dlmm_tx::*;
 pub use build_pumpswap_tx::*;
+pub use build_whirlpool_tx::*;
 pub use calc_min_amount_out::*;
-pub use swap_quote::*;
@@ -1,16 +1,13 @@
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    hash::Hash, instruction::Instruction, native_token::sol_to_lamports, pubkey::Pubkey,
-    signature::Keypair, signer::Signer, system_instruction, transaction::Transaction,
-};
-use std::{str::FromStr, sync::Arc};
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
 use thiserror::Error;
 
-use crate::{get_race_ix, log, BLOXROUTE_MIN_TIP, BLOX_TIP};
+use super::{parse_signature, Gateway, SendError, SignedTx, TxSender};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum BloXRegion {
     UK,
     NY,
@@ -21,6 +18,26 @@ pub enum BloXRegion {
 }
 
 impl BloXRegion {
+    pub const ALL: [BloXRegion; 6] = [
+        BloXRegion::UK,
+        BloXRegion::NY,
+        BloXRegion::LA,
+        BloXRegion::Germany,
+        BloXRegion::Amsterdam,
+        BloXRegion::Tokyo,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BloXRegion::UK => "uk",
+            BloXRegion::NY => "ny",
+            BloXRegion::LA => "la",
+            BloXRegion::Germany => "germany",
+            BloXRegion::Amsterdam => "amsterdam",
+            BloXRegion::Tokyo => "tokyo",
+        }
+    }
+
     fn endpoint(&self) -> &'static str {
         match self {
             BloXRegion::UK => "https://uk.solana.dex.blxrbdn.com",
@@ -33,6 +50,17 @@ impl BloXRegion {
     }
 }
 
+impl FromStr for BloXRegion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|region| region.name() == s)
+            .ok_or_else(|| format!("unknown bloXroute region {:?}", s))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum BloXError {
     #[error("HTTP request failed: {0}")]
@@ -112,57 +140,44 @@ pub async fn submit_blox_paladin_tx(
     Ok(json)
 }
 
-pub async fn build_and_submit_blox(
-    payer: &Arc<Keypair>,
-    buy_ix: &Instruction,
-    tip_amount: f64,
-    auth_header: &str,
-    blockhash: &str,
-    region: BloXRegion,
-    client: Arc<RpcClient>,
-    timestamp: u64,
-) -> () {
-    log!(format!("[ BloXRoute ] {:#?}", region), "info");
-    let mut _ixs: Vec<Instruction> = vec![];
-    let modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(30000);
-
-    _ixs.insert(0, modify_compute_units);
-
-    let race_ix = get_race_ix(payer.pubkey(), timestamp);
-    _ixs.insert(1, race_ix);
-
-    _ixs.insert(2, buy_ix.clone());
-    let tip_fee_addr = Pubkey::from_str_const(BLOX_TIP[0]);
-
-    let tip: f64;
+/// bloXroute trader API, one region.
+#[derive(Debug)]
+pub struct BloXSender {
+    pub region: BloXRegion,
+    pub auth_header: String,
+}
 
-    if tip_amount < BLOXROUTE_MIN_TIP {
-        tip = BLOXROUTE_MIN_TIP
-    } else {
-        tip = tip_amount
+#[async_trait]
+impl TxSender for BloXSender {
+    fn gateway(&self) -> Gateway {
+        Gateway::BloXroute
     }
 
-    let tip_ix = system_instruction::transfer(&payer.pubkey(), &tip_fee_addr, sol_to_lamports(tip));
-
-    _ixs.insert(3, tip_ix);
-
-    let txn = Transaction::new_signed_with_payer(
-        &_ixs,
-        Some(&payer.pubkey()),
-        &[&payer.as_ref()],
-        Hash::from_str(&blockhash).expect("msg"),
-    );
-    let serialized_tx = bincode::serialize(&txn).expect("Failed to serialize transaction");
-
-    let encoded_tx = &bs64::encode(&serialized_tx);
+    fn region(&self) -> &'static str {
+        self.region.name()
+    }
 
-    match submit_blox_tx(&encoded_tx, auth_header, region, false, true, false).await {
-        Ok(sig) => {
-            log!(format!("[BloXRoute Confirm] {} ", sig), "success");
-        }
-        Err(e) => {
-            log!(format!("[BloXRoute Error] {}", e), "error");
+    async fn send(&self, tx: &SignedTx) -> Result<Signature, SendError> {
+        let response = submit_blox_tx(
+            &tx.encoded,
+            &self.auth_header,
+            self.region,
+            false,
+            true,
+            false,
+        )
+        .await
+        .map_err(|e| match e {
+            BloXError::ReqwestError(e) => SendError::Http(e),
+            BloXError::ApiError(e) => SendError::Api(Gateway::BloXroute, e),
+        })?;
+
+        match response.get("signature").and_then(|v| v.as_str()) {
+            Some(signature) => parse_signature(Gateway::BloXroute, signature),
+            None => Err(SendError::Api(
+                Gateway::BloXroute,
+                "Signature not found in response".to_string(),
+            )),
         }
     }
 }
@@ -1,50 +1,37 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    hash::Hash, instruction::Instruction, signature::Keypair, signer::Signer,
-    transaction::Transaction,
-};
+use solana_sdk::signature::Signature;
 
-use crate::{get_race_ix, get_token_balance_change_from_tx, log};
+use super::{Gateway, SendError, SignedTx, TxSender};
 
-pub async fn build_and_submit_general<'a>(
-    payer: &'a Arc<Keypair>,
-    buy_ix: &Instruction,
-    blockhash: Hash,
-    client: Arc<RpcClient>,
-    timestamp: u64,
-    is_buy : bool
-) {
-    log!(format!("[ RPC Confirm Building Tx ] "), "info");
-    let mut _ixs: Vec<Instruction> = vec![];
-    let modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(30000);
-
-    _ixs.insert(0, modify_compute_units);
-
-    let race_ix = get_race_ix(payer.pubkey(), timestamp);
-    _ixs.insert(1, race_ix);
-
-    _ixs.insert(2, buy_ix.clone());
+/// The plain RPC endpoint, with preflight; it takes no tip.
+pub struct RpcSender {
+    pub client: Arc<RpcClient>,
+}
 
-    let txn = Transaction::new_signed_with_payer(
-        &_ixs,
-        Some(&payer.pubkey()),
-        &[&payer.as_ref()],
-        blockhash,
-    );
+impl std::fmt::Debug for RpcSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The URL may carry an API key
+        f.debug_struct("RpcSender").finish_non_exhaustive()
+    }
+}
 
-    log!(format!("[ RPC Confirm Submiting Tx ] "), "info");
+#[async_trait]
+impl TxSender for RpcSender {
+    fn gateway(&self) -> Gateway {
+        Gateway::Rpc
+    }
 
-    match client.send_transaction(&txn).await {
-        Ok(signature) => {
-            log!(format!("[RPC Confirm] {} ", signature), "success");
+    fn region(&self) -> &'static str {
+        "rpc"
+    }
 
-            get_token_balance_change_from_tx(client, signature, is_buy).await;
-        }
-        Err(err) => {
-            log!(format!("[RPC Error] : {:?}", err), "error");
-        }
+    async fn send(&self, tx: &SignedTx) -> Result<Signature, SendError> {
+        self.client
+            .send_transaction(&tx.transaction)
+            .await
+            .map_err(|e| SendError::Rpc(Box::new(e)))
     }
 }
@@ -1,139 +1,202 @@
-use std::sync::Arc;
+use std::{future::Future, sync::Arc, time::Instant};
 
-use super::*;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{hash::Hash, instruction::Instruction, signature::Keypair};
+use futures::future::join_all;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, hash::Hash, instruction::Instruction, pubkey::Pubkey,
+    signature::Keypair, signer::Signer, transaction::Transaction,
+};
+use solana_system_interface::instruction::transfer;
 
+use super::{SenderGroup, SignedTx};
+use crate::{
+    get_race_ix, log, now_ms, record_landing, record_region_send, record_submission,
+    sol_to_lamports, ConfirmationService, Gateway, RegionSend, Submission, TxOutcome,
+};
+
+/// Compute budget every copy of a submission asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub unit_limit: u32,
+    /// Micro-lamports per compute unit.
+    pub unit_price: u64,
+}
+
+/// The intent a submission carries out, as landing telemetry records it.
+#[derive(Debug, Clone, Copy)]
+pub struct IntentTag {
+    /// Shared by every gateway's copy; also the race tag when racing.
+    pub tag: u64,
+    pub pool: Pubkey,
+    /// Slot of the transaction that triggered the intent, if any.
+    pub trigger_slot: Option<u64>,
+}
+
+/// Signs the swap once per gateway of `groups`, each tipping its own account,
+/// posts it to every region of the gateway and returns once they answered.
+/// The returned future follows each gateway's transaction until it lands or
+/// expires and resolves to what became of it per gateway; spawn it rather
+/// than hold up the caller.
+///
+/// With `race` on, the transactions carry the race instruction tagged with
+/// `intent.tag`, so only the first of them to land goes through. Every
+/// submission and region answer is queued for the landing stats once the
+/// regions have answered, every landing once it is known.
+#[allow(clippy::too_many_arguments)]
 pub async fn multi_submit(
-    client: Arc<RpcClient>,
-    payer: Arc<Keypair>,
-    buy_ix: Instruction,
+    groups: &[&SenderGroup],
+    confirmations: &Arc<ConfirmationService>,
+    payer: &Keypair,
+    swap_ix: &Instruction,
+    budget: ComputeBudget,
     tip_amount: f64,
     blockhash: Hash,
-    timestamp: u64,
-    next_key: &str,
-    nozomi_key: &str,
-    blox_auth_header: &str,
-    zero_slot_key: &str,
-    is_buy : bool
-) -> anyhow::Result<Vec<String>> {
-    let client_general = client.clone();
-    let blockhash_general = blockhash.clone();
-    let buy_ix_general = buy_ix.clone();
-    let payer_general = Arc::clone(&payer);
-    // Spawn each task concurrently
-    let general_handle = tokio::spawn(async move {
-        build_and_submit_general(
-            &payer_general,
-            &buy_ix_general,
-            blockhash_general,
-            client_general,
-            timestamp,
-            is_buy
-        )
-        .await;
-    });
+    intent: &IntentTag,
+    race: bool,
+    is_buy: bool,
+) -> impl Future<Output = Vec<(Gateway, TxOutcome)>> + Send + 'static {
+    let sent = join_all(groups.iter().map(|group| {
+        async move {
+            let gateway = group.gateway;
+            log!(format!("[ {} Building Tx ]", gateway), "info");
+            let tip = match gateway.tip_account() {
+                Some(_) => tip_amount.max(group.min_tip),
+                None => 0.0,
+            };
+            let tx = SignedTx::new(build_tx(
+                gateway,
+                payer,
+                swap_ix,
+                tip,
+                blockhash,
+                race.then_some(intent.tag),
+                budget,
+            ));
+            let signature = tx.transaction.signatures[0];
+            // Followed before it is sent, a fast landing can't slip by
+            let pending = confirmations.expect(signature, blockhash, is_buy);
+            let submitted_at_ms = now_ms();
 
-    let client_jito = client.clone();
-    let blockhash_jito = blockhash.clone();
-    let buy_ix_jito = buy_ix.clone();
-    let payer_jito = Arc::clone(&payer);
-    let jito_handle = tokio::spawn(async move {
-        build_and_submit_jito(
-            &payer_jito,
-            &buy_ix_jito,
-            tip_amount,
-            blockhash_jito,
-            client_jito,
-            timestamp,
-            is_buy
-        )
-        .await;
-    });
+            log!(format!("[ {} Submiting Tx ]", gateway), "info");
+            let answers = join_all(group.senders.iter().map(|sender| {
+                let tx = &tx;
+                async move {
+                    let sent_at_ms = now_ms();
+                    let started = Instant::now();
+                    let answer = sender.send(tx).await;
+                    let latency_ms = started.elapsed().as_millis() as u64;
 
-    let client_next = client.clone();
-    let blockhash_next = blockhash.clone();
-    let buy_ix_next = buy_ix.clone();
-    let payer_next = Arc::clone(&payer);
-    let next_key = next_key.to_string();
-    let next_handle = tokio::spawn(async move {
-        build_and_submit_next(
-            &payer_next,
-            &buy_ix_next,
-            tip_amount,
-            &next_key,
-            blockhash_next,
-            NextBlockRegion::Frankfurt,
-            client_next,
-            timestamp,
-            is_buy
-        )
-        .await;
-    });
+                    match &answer {
+                        Ok(signature) => log!(
+                            format!(
+                                "[{} {} Confirm] {} ({} ms)",
+                                gateway,
+                                sender.region(),
+                                signature,
+                                latency_ms
+                            ),
+                            "success"
+                        ),
+                        Err(e) => log!(
+                            format!("[{} {} Error] {}", gateway, sender.region(), e),
+                            "error"
+                        ),
+                    }
+                    let send = RegionSend {
+                        signature: tx.transaction.signatures[0].to_string(),
+                        region: sender.region().to_string(),
+                        sent_at_ms,
+                        latency_ms,
+                        error: answer.as_ref().err().map(ToString::to_string),
+                    };
+                    (answer, send)
+                }
+            }))
+            .await;
 
-    let client_nozomi = client.clone();
-    let blockhash_nozomi = blockhash.clone();
-    let buy_ix_nozomi = buy_ix.clone();
-    let payer_nozomi = Arc::clone(&payer);
-    let nozomi_key = nozomi_key.to_string();
-    let nozomi_handle = tokio::spawn(async move {
-        build_and_submit_nozomi(
-            &payer_nozomi,
-            &buy_ix_nozomi,
-            tip_amount,
-            &nozomi_key,
-            blockhash_nozomi,
-            NozomiRegion::AMS,
-            client_nozomi,
-            timestamp,
-            is_buy
-        )
-        .await;
-    });
+            // Stored only once every region has it, off the send path
+            record_submission(&Submission {
+                signature: signature.to_string(),
+                intent_tag: intent.tag,
+                pool_id: intent.pool.to_string(),
+                gateway,
+                tip_lamports: sol_to_lamports(tip),
+                trigger_slot: intent.trigger_slot,
+                submitted_at_ms,
+            });
+            let answers: Vec<_> = answers
+                .into_iter()
+                .map(|(answer, send)| {
+                    record_region_send(&send);
+                    answer
+                })
+                .collect();
 
-    // let blockhash_zslot = blockhash.clone();
-    // let buy_ix_zslot = buy_ix.clone();
-    // let payer_zslot = Arc::clone(&payer);
-    // let zslot_handle = tokio::spawn(async move {
-    //     build_and_submit_zslot(
-    //         &payer_zslot,
-    //         &buy_ix_zslot,
-    //         tip_amount,
-    //         &zero_slot_key,
-    //         blockhash_zslot,
-    //         ZeroSlotRegion::AMS,
-    //         timestamp,
-    //     )
-    //     .await;
-    // });
+            // Every region got the same transaction, so one confirmation covers them all
+            let landing = if answers.iter().any(Result::is_ok) {
+                Ok(pending)
+            } else {
+                Err(TxOutcome::Rejected(
+                    answers
+                        .into_iter()
+                        .find_map(Result::err)
+                        .map_or("no region enabled".to_string(), |e| e.to_string()),
+                ))
+            };
+            (gateway, signature, landing)
+        }
+    }))
+    .await;
 
-    // let blockhash_blox = blockhash.clone();
-    // let buy_ix_blox = buy_ix.clone();
-    // let payer_blox = Arc::clone(&payer);
-    // let blox_handle = tokio::spawn(async move {
-    //     build_and_submit_blox(
-    //         &payer_blox,
-    //         &buy_ix_blox,
-    //         tip_amount,
-    //         &blox_auth_header,
-    //         blockhash_blox,
-    //         BloXRegion::Amsterdam,
-    //         timestamp,
-    //     )
-    //     .await;
-    // });
+    let trigger_slot = intent.trigger_slot;
+    join_all(
+        sent.into_iter()
+            .map(move |(gateway, signature, landing)| async move {
+                let outcome = match landing {
+                    Ok(pending) => pending.outcome().await,
+                    Err(rejected) => rejected,
+                };
+                record_landing(&signature.to_string(), &outcome);
+                if let (Some(slot), Some(trigger_slot)) = (outcome.slot(), trigger_slot) {
+                    log!(
+                        format!(
+                            "[{}] {} {} slot(s) after its trigger",
+                            gateway,
+                            outcome,
+                            slot.saturating_sub(trigger_slot)
+                        ),
+                        "info"
+                    );
+                }
+                (gateway, outcome)
+            }),
+    )
+}
 
-    // Await the completion of all tasks
-    if let Err(err) = tokio::try_join!(
-        general_handle,
-        next_handle,
-        // zslot_handle,
-        jito_handle,
-        nozomi_handle,
-        // blox_handle
-    ) {
-        return Err(anyhow::anyhow!("{}", err));
+/// Compute unit limit and price, race tag, swap and tip, in that order.
+pub(super) fn build_tx(
+    gateway: Gateway,
+    payer: &Keypair,
+    swap_ix: &Instruction,
+    tip: f64,
+    blockhash: Hash,
+    race: Option<u64>,
+    budget: ComputeBudget,
+) -> Transaction {
+    let mut ixs = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(budget.unit_limit),
+        ComputeBudgetInstruction::set_compute_unit_price(budget.unit_price),
+    ];
+    if let Some(timestamp) = race {
+        ixs.push(get_race_ix(payer.pubkey(), timestamp));
+    }
+    ixs.push(swap_ix.clone());
+    if let Some(tip_account) = gateway.tip_account() {
+        ixs.push(transfer(
+            &payer.pubkey(),
+            &tip_account,
+            sol_to_lamports(tip),
+        ));
     }
 
-    Ok(Vec::<String>::new())
+    Transaction::new_signed_with_payer(&ixs, Some(&payer.pubkey()), &[payer], blockhash)
 }
@@ -1,23 +1,14 @@
-use std::sync::Arc;
+use std::str::FromStr;
 
-use crate::{get_race_ix, get_token_balance_change_from_tx, log, JITO_MIN_TIP, JITO_TIP};
-use futures::stream::{self, StreamExt};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    hash::Hash,
-    instruction::Instruction,
-    native_token::sol_to_lamports,
-    pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
-    system_instruction,
-    transaction::Transaction,
-};
+use solana_sdk::signature::Signature;
 use thiserror::Error;
 
-#[derive(Debug)]
+use super::{parse_signature, Gateway, SendError, SignedTx, TxSender};
+
+#[derive(Debug, Clone, Copy)]
 pub enum JitoRegion {
     Mainnet,
     Amsterdam,
@@ -27,6 +18,24 @@ pub enum JitoRegion {
 }
 
 impl JitoRegion {
+    pub const ALL: [JitoRegion; 5] = [
+        JitoRegion::Mainnet,
+        JitoRegion::Amsterdam,
+        JitoRegion::Frankfurt,
+        JitoRegion::NY,
+        JitoRegion::Tokyo,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JitoRegion::Mainnet => "mainnet",
+            JitoRegion::Amsterdam => "amsterdam",
+            JitoRegion::Frankfurt => "frankfurt",
+            JitoRegion::NY => "ny",
+            JitoRegion::Tokyo => "tokyo",
+        }
+    }
+
     fn endpoint(&self) -> &'static str {
         match self {
             JitoRegion::Mainnet => "https://mainnet.block-engine.jito.wtf/api/v1/transactions",
@@ -42,6 +51,17 @@ impl JitoRegion {
     }
 }
 
+impl FromStr for JitoRegion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|region| region.name() == s)
+            .ok_or_else(|| format!("unknown Jito region {:?}", s))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum JitoError {
     #[error("HTTP request failed: {0}")]
@@ -69,7 +89,7 @@ pub async fn send_tx_using_jito(
     });
 
     let response = client
-        .post(&format!("{}?bundleOnly=false", rpc_endpoint))
+        .post(format!("{}?bundleOnly=false", rpc_endpoint))
         .header("Content-Type", "application/json")
         .json(&payload)
         .send()
@@ -84,86 +104,36 @@ pub async fn send_tx_using_jito(
     Ok(json)
 }
 
-pub async fn build_and_submit_jito<'a>(
-    payer: &'a Arc<Keypair>,
-    buy_ix: &Instruction,
-    tip_amount: f64,
-    blockhash: Hash,
-    client: Arc<RpcClient>,
-    timestamp: u64,
-    is_buy : bool
-) -> () {
-    log!(format!("[ Jito Building Tx ]"), "info");
-
-    let mut _ixs: Vec<Instruction> = vec![];
-    let modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(30000);
-
-    _ixs.insert(0, modify_compute_units);
-
-    let race_ix = get_race_ix(payer.pubkey(), timestamp);
-    _ixs.insert(1, race_ix);
-
-    _ixs.insert(2, buy_ix.clone());
-    let tip_fee_addr = Pubkey::from_str_const(JITO_TIP[0]);
-
-    let tip: f64;
+/// Jito block engine, one region.
+#[derive(Debug)]
+pub struct JitoSender {
+    pub region: JitoRegion,
+}
 
-    if tip_amount < JITO_MIN_TIP {
-        tip = JITO_MIN_TIP
-    } else {
-        tip = tip_amount
+#[async_trait]
+impl TxSender for JitoSender {
+    fn gateway(&self) -> Gateway {
+        Gateway::Jito
     }
 
-    let tip_ix = system_instruction::transfer(&payer.pubkey(), &tip_fee_addr, sol_to_lamports(tip));
-
-    _ixs.insert(3, tip_ix);
-
-    let txn = Transaction::new_signed_with_payer(
-        &_ixs,
-        Some(&payer.pubkey()),
-        &[&payer.as_ref()],
-        blockhash,
-    );
-    let serialized_tx = bincode::serialize(&txn).expect("Failed to serialize transaction");
-
-    let encoded_tx = &bs64::encode(&serialized_tx);
-
-    let data = [
-        JitoRegion::Amsterdam,
-        JitoRegion::Frankfurt,
-        JitoRegion::Mainnet,
-        JitoRegion::NY,
-        JitoRegion::Tokyo,
-    ];
+    fn region(&self) -> &'static str {
+        self.region.name()
+    }
 
-    log!(format!("[ Jito Submiting Tx ]"), "info");
-
-    stream::iter(&data)
-        .for_each_concurrent(10, |each_region| {
-            // Clone `client` inside here so each task gets its own reference
-            let client = client.clone();
-            async move {
-                match send_tx_using_jito(&encoded_tx, each_region).await {
-                    Ok(sig) => {
-                        log!(
-                            format!("[JitoRegion Confirm] {} ", sig.get("result").unwrap()),
-                            "success"
-                        );
-
-                        if let Some(sig_str) = sig.get("result").and_then(|v| v.as_str()) {
-                            if let Ok(signature) = sig_str.parse::<Signature>() {
-                                get_token_balance_change_from_tx(client, signature, is_buy).await;
-                            } else {
-                                log!(format!("Invalid signature string: {}", sig_str), "error");
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log!(format!("[JitoRegion Error] {}", e), "error");
-                    }
-                }
-            }
-        })
-        .await;
+    async fn send(&self, tx: &SignedTx) -> Result<Signature, SendError> {
+        let response = send_tx_using_jito(&tx.encoded, &self.region)
+            .await
+            .map_err(|e| match e {
+                JitoError::ReqwestError(e) => SendError::Http(e),
+                JitoError::JitoApiError(e) => SendError::Api(Gateway::Jito, e),
+            })?;
+
+        match response.get("result").and_then(|v| v.as_str()) {
+            Some(signature) => parse_signature(Gateway::Jito, signature),
+            None => Err(SendError::Api(
+                Gateway::Jito,
+                "Signature not found in response".to_string(),
+            )),
+        }
+    }
 }
@@ -3,7 +3,9 @@ pub mod general_confirm;
 pub mod integrate_submit;
 pub mod jito_send_tx;
 pub mod next_block;
+pub mod preflight;
 pub mod temporal_nozomi;
+pub mod tx_sender;
 pub mod zero_slot;
 
 pub use bloxroute::*;
@@ -11,5 +13,7 @@ pub use general_confirm::*;
 pub use integrate_submit::*;
 pub use jito_send_tx::*;
 pub use next_block::*;
+pub use preflight::*;
 pub use temporal_nozomi::*;
+pub use tx_sender::*;
 pub use zero_slot::*;
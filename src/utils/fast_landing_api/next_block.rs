@@ -1,29 +1,29 @@
-use std::{str::FromStr, sync::Arc};
+use std::str::FromStr;
 
-use crate::{get_race_ix, get_token_balance_change_from_tx, log, NEXT_BLOCK_MIN_TIP, NEXT_FEE};
-use futures::stream::{self, StreamExt};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    hash::Hash,
-    instruction::Instruction,
-    native_token::sol_to_lamports,
-    pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
-    system_instruction,
-    transaction::Transaction,
-};
+use solana_sdk::signature::Signature;
 use thiserror::Error;
 
-#[derive(Debug)]
+use super::{parse_signature, Gateway, SendError, SignedTx, TxSender};
+
+#[derive(Debug, Clone, Copy)]
 pub enum NextBlockRegion {
     Frankfurt,
     NewYork,
 }
 
 impl NextBlockRegion {
+    pub const ALL: [NextBlockRegion; 2] = [NextBlockRegion::Frankfurt, NextBlockRegion::NewYork];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NextBlockRegion::Frankfurt => "frankfurt",
+            NextBlockRegion::NewYork => "new_york",
+        }
+    }
+
     fn endpoint(&self) -> &'static str {
         match self {
             NextBlockRegion::Frankfurt => "https://fra.nextblock.io/api/v2/submit",
@@ -32,6 +32,17 @@ impl NextBlockRegion {
     }
 }
 
+impl FromStr for NextBlockRegion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|region| region.name() == s)
+            .ok_or_else(|| format!("unknown NextBlock region {:?}", s))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum NextBlockError {
     #[error("HTTP request failed: {0}")]
@@ -76,75 +87,31 @@ pub async fn submit_next_transaction(
     }
 }
 
-pub async fn build_and_submit_next<'a>(
-    payer: &'a Arc<Keypair>,
-    buy_ix: &Instruction,
-    tip_amount: f64,
-    auth_header: &str,
-    blockhash: Hash,
-    region: NextBlockRegion,
-    client: Arc<RpcClient>,
-    timestamp: u64,
-    is_buy : bool
-) -> () {
-    log!(format!("[ NextBlock Building Tx ] {:#?}", region), "info");
-    let mut _ixs: Vec<Instruction> = vec![];
-    let modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(30000);
-
-    _ixs.insert(0, modify_compute_units);
+/// NextBlock, one region.
+#[derive(Debug)]
+pub struct NextBlockSender {
+    pub region: NextBlockRegion,
+    pub auth_header: String,
+}
 
-    let race_ix = get_race_ix(payer.pubkey(), timestamp);
-    _ixs.insert(1, race_ix);
+#[async_trait]
+impl TxSender for NextBlockSender {
+    fn gateway(&self) -> Gateway {
+        Gateway::NextBlock
+    }
 
-    _ixs.insert(2, buy_ix.clone());
-    let tip_fee_addr = Pubkey::from_str_const(NEXT_FEE[0]);
+    fn region(&self) -> &'static str {
+        self.region.name()
+    }
 
-    let tip: f64;
+    async fn send(&self, tx: &SignedTx) -> Result<Signature, SendError> {
+        let signature = submit_next_transaction(&tx.encoded, &self.auth_header, self.region, false)
+            .await
+            .map_err(|e| match e {
+                NextBlockError::ReqwestError(e) => SendError::Http(e),
+                NextBlockError::ApiError(e) => SendError::Api(Gateway::NextBlock, e),
+            })?;
 
-    if tip_amount < NEXT_BLOCK_MIN_TIP {
-        tip = NEXT_BLOCK_MIN_TIP
-    } else {
-        tip = tip_amount
+        parse_signature(Gateway::NextBlock, &signature)
     }
-
-    let tip_ix = system_instruction::transfer(&payer.pubkey(), &tip_fee_addr, sol_to_lamports(tip));
-
-    _ixs.insert(3, tip_ix);
-
-    let txn = Transaction::new_signed_with_payer(
-        &_ixs,
-        Some(&payer.pubkey()),
-        &[&payer.as_ref()],
-        blockhash,
-    );
-
-    let serialized_tx = bincode::serialize(&txn).expect("Failed to serialize transaction");
-
-    let encoded_tx = &bs64::encode(&serialized_tx);
-
-    let data: Vec<NextBlockRegion> = vec![NextBlockRegion::Frankfurt, NextBlockRegion::NewYork];
-
-    log!(format!("[ NextBlock Submiting Tx ] {:#?}", region), "info");
-
-    stream::iter(data)
-        .for_each_concurrent(None, |region| {
-            let client = client.clone(); // Clone client for async block
-            async move {
-                match submit_next_transaction(&encoded_tx, auth_header, region, false).await {
-                    Ok(sig) => {
-                        log!(format!("[NextBlockRegion Confirm] {} ", sig), "success");
-                        if let Ok(signature) = sig.parse::<Signature>() {
-                            get_token_balance_change_from_tx(client, signature, is_buy).await;
-                        } else {
-                            log!(format!("Invalid signature string: {}", sig), "error");
-                        }
-                    }
-                    Err(e) => {
-                        log!(format!("[NextBlockRegion Error] {}", e), "error");
-                    }
-                }
-            }
-        })
-        .await;
 }
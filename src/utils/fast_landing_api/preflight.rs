@@ -0,0 +1,185 @@
+use std::sync::{Mutex, PoisonError};
+
+use futures::future::join;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::ClientError,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, instruction::Instruction, pubkey::Pubkey,
+    signature::Keypair, transaction::TransactionError,
+};
+use thiserror::Error;
+
+use super::{build_tx, ComputeBudget};
+use crate::{token_account_amount, Dex, Gateway};
+
+/// Most compute units a transaction may ask for.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Most compute units measured per DEX, used instead of its estimate when a
+/// swap is sent without simulation. Swaps cost more when they cross ticks or
+/// bins or create an account, so the last measurement alone would undershoot.
+static MEASURED_COMPUTE_UNITS: Mutex<Vec<(Dex, u64)>> = Mutex::new(Vec::new());
+
+/// What a simulated swap must deliver.
+#[derive(Debug, Clone, Copy)]
+pub struct PreflightCheck {
+    pub dex: Dex,
+    /// Wallet token account the swap pays out to.
+    pub output_account: Pubkey,
+    /// Least the account must gain, in raw units of its mint.
+    pub min_out: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum PreflightError {
+    #[error("simulation unavailable: {0}")]
+    Rpc(Box<ClientError>),
+
+    #[error("would fail: {error} ({last_log})")]
+    WouldFail {
+        error: TransactionError,
+        last_log: String,
+    },
+
+    #[error("would receive {received}, below its {min_out} minimum")]
+    BelowMinOut { received: u64, min_out: u64 },
+}
+
+impl From<ClientError> for PreflightError {
+    fn from(e: ClientError) -> Self {
+        PreflightError::Rpc(Box::new(e))
+    }
+}
+
+/// Compute-unit limit of a swap on `dex` sent without simulation: the most
+/// units measured for the DEX, or its estimate, plus `headroom_pct`.
+pub fn compute_unit_limit(dex: Dex, headroom_pct: f64) -> u32 {
+    let units = MEASURED_COMPUTE_UNITS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(measured, _)| *measured == dex)
+        .map_or(dex.compute_unit_estimate() as u64, |(_, units)| *units);
+
+    with_headroom(units, headroom_pct)
+}
+
+/// Raises the high-water mark of `dex` to `units`.
+fn record_compute_units(dex: Dex, units: u64) {
+    let mut measured = MEASURED_COMPUTE_UNITS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    match measured.iter_mut().find(|(measured, _)| *measured == dex) {
+        Some((_, most)) => *most = (*most).max(units),
+        None => measured.push((dex, units)),
+    }
+}
+
+/// Simulates the swap as it would be submitted (tip aside) and returns the
+/// compute-unit limit to send it with: the measured units plus
+/// `headroom_pct`. Fails if the transaction would fail or pay less than
+/// `check.min_out` into the output account.
+#[allow(clippy::too_many_arguments)]
+pub async fn simulate_swap(
+    client: &RpcClient,
+    payer: &Keypair,
+    swap_ix: &Instruction,
+    unit_price: u64,
+    blockhash: Hash,
+    race: Option<u64>,
+    check: &PreflightCheck,
+    headroom_pct: f64,
+) -> Result<u32, PreflightError> {
+    let tx = build_tx(
+        Gateway::Rpc,
+        payer,
+        swap_ix,
+        0.0,
+        blockhash,
+        race,
+        ComputeBudget {
+            unit_limit: MAX_COMPUTE_UNIT_LIMIT,
+            unit_price,
+        },
+    );
+    let config = RpcSimulateTransactionConfig {
+        // A processed blockhash may not have reached the RPC yet
+        replace_recent_blockhash: true,
+        commitment: Some(CommitmentConfig::processed()),
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: vec![check.output_account.to_string()],
+        }),
+        ..Default::default()
+    };
+
+    let (simulation, before) = join(
+        client.simulate_transaction_with_config(&tx, config),
+        client.get_account_with_commitment(&check.output_account, CommitmentConfig::processed()),
+    )
+    .await;
+    let result = simulation?.value;
+    if let Some(error) = result.err {
+        return Err(PreflightError::WouldFail {
+            error,
+            last_log: result
+                .logs
+                .and_then(|logs| logs.last().cloned())
+                .unwrap_or_default(),
+        });
+    }
+
+    // A missing account holds nothing yet, the swap creates it
+    let before = before?
+        .value
+        .and_then(|account| token_account_amount(&account.data))
+        .unwrap_or(0);
+    let after = result
+        .accounts
+        .and_then(|accounts| accounts.into_iter().next().flatten())
+        .and_then(|account| account.data.decode())
+        .and_then(|data| token_account_amount(&data))
+        .unwrap_or(0);
+    let received = after.saturating_sub(before);
+    if received < check.min_out {
+        return Err(PreflightError::BelowMinOut {
+            received,
+            min_out: check.min_out,
+        });
+    }
+
+    let Some(units) = result.units_consumed else {
+        return Ok(compute_unit_limit(check.dex, headroom_pct));
+    };
+    record_compute_units(check.dex, units);
+
+    Ok(with_headroom(units, headroom_pct))
+}
+
+fn with_headroom(units: u64, headroom_pct: f64) -> u32 {
+    let limit = (units as f64 * (1.0 + headroom_pct / 100.0)).ceil() as u64;
+    limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_follows_the_most_units_measured() {
+        let dex = Dex::MeteoraDlmm;
+        assert_eq!(compute_unit_limit(dex, 0.0), dex.compute_unit_estimate());
+
+        record_compute_units(dex, 90_000);
+        record_compute_units(dex, 60_000);
+        assert_eq!(compute_unit_limit(dex, 0.0), 90_000);
+        assert_eq!(compute_unit_limit(dex, 20.0), 108_000);
+
+        record_compute_units(dex, 2_000_000);
+        assert_eq!(compute_unit_limit(dex, 20.0), MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
@@ -1,25 +1,14 @@
-use std::{str::FromStr, sync::Arc};
+use std::str::FromStr;
 
-use crate::{
-    get_race_ix, get_token_balance_change_from_tx, log, JsonRpcResponse, RayAMMSwapBaseInParams,
-    NOZOMI_MIN_TIP, NOZOMI_TIP,
-};
-use futures::stream::{self, StreamExt};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    hash::Hash,
-    instruction::Instruction,
-    native_token::sol_to_lamports,
-    pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
-    system_instruction,
-    transaction::Transaction,
-};
+use solana_sdk::signature::Signature;
 
-#[derive(Debug)]
+use super::{parse_signature, Gateway, SendError, SignedTx, TxSender};
+use crate::JsonRpcResponse;
+
+#[derive(Debug, Clone, Copy)]
 pub enum NozomiRegion {
     USEast,
     AMS,
@@ -27,6 +16,16 @@ pub enum NozomiRegion {
 }
 
 impl NozomiRegion {
+    pub const ALL: [NozomiRegion; 3] = [NozomiRegion::USEast, NozomiRegion::AMS, NozomiRegion::FRA];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NozomiRegion::USEast => "us_east",
+            NozomiRegion::AMS => "ams",
+            NozomiRegion::FRA => "fra",
+        }
+    }
+
     fn url(&self) -> &'static str {
         match self {
             NozomiRegion::USEast => "http://nozomi-preview-pit.temporal.xyz/?c=",
@@ -36,6 +35,17 @@ impl NozomiRegion {
     }
 }
 
+impl FromStr for NozomiRegion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|region| region.name() == s)
+            .ok_or_else(|| format!("unknown Nozomi region {:?}", s))
+    }
+}
+
 pub async fn submit_nozomi_tx(
     transaction_content: &str,
     region: NozomiRegion,
@@ -60,131 +70,28 @@ pub async fn submit_nozomi_tx(
     Ok(data)
 }
 
-pub async fn build_and_submit_nozomi<'a>(
-    payer: &'a Arc<Keypair>,
-    buy_ix: &Instruction,
-    tip_amount: f64,
-    auth_header: &str,
-    blockhash: Hash,
-    region: NozomiRegion,
-    client: Arc<RpcClient>,
-    timestamp: u64,
-    is_buy: bool,
-) -> () {
-    log!(format!("[ Nozomi Building Tx ] {:#?}", region), "info");
-    let mut _ixs: Vec<Instruction> = vec![];
-    let modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(30000);
-
-    _ixs.insert(0, modify_compute_units);
-
-    let race_ix = get_race_ix(payer.pubkey(), timestamp);
-    _ixs.insert(1, race_ix);
-
-    _ixs.insert(2, buy_ix.clone());
-    let tip_fee_addr = Pubkey::from_str_const(NOZOMI_TIP[0]);
-
-    let tip: f64;
-
-    if tip_amount < NOZOMI_MIN_TIP {
-        tip = NOZOMI_MIN_TIP
-    } else {
-        tip = tip_amount
-    }
-
-    let tip_ix = system_instruction::transfer(&payer.pubkey(), &tip_fee_addr, sol_to_lamports(tip));
-
-    _ixs.insert(3, tip_ix);
-
-    let txn = Transaction::new_signed_with_payer(
-        &_ixs,
-        Some(&payer.pubkey()),
-        &[&payer.as_ref()],
-        blockhash,
-    );
-    let serialized_tx = bincode::serialize(&txn).expect("Failed to serialize transaction");
-
-    let encoded_tx = &bs64::encode(&serialized_tx);
-
-    let data: Vec<NozomiRegion> = vec![NozomiRegion::AMS, NozomiRegion::FRA, NozomiRegion::USEast];
-
-    log!(format!("[ Nozomi Submiting Tx ] {:#?}", region), "info");
-    stream::iter(data)
-        .for_each_concurrent(None, |region| {
-            let client = client.clone();
-            async move {
-                match submit_nozomi_tx(&encoded_tx, region, auth_header).await {
-                    Ok(sig) => {
-                        log!(format!("[Nozomi Confirm] {} ", sig.result), "success");
-                        if let Ok(signature) = sig.result.parse::<Signature>() {
-                            get_token_balance_change_from_tx(client, signature, is_buy).await;
-                        } else {
-                            log!(format!("Invalid signature string: {}", sig.result), "error");
-                        }
-                    }
-                    Err(e) => {
-                        log!(format!("[Nozomi Error] {}", e), "error");
-                    }
-                }
-            }
-        })
-        .await;
+/// Temporal Nozomi, one region.
+#[derive(Debug)]
+pub struct NozomiSender {
+    pub region: NozomiRegion,
+    pub auth_key: String,
 }
 
-pub async fn build_and_submit_pure_nozomi<'a>(
-    payer: &'a Arc<Keypair>,
-    buy_ix: &Instruction,
-    tip_amount: f64,
-    auth_header: &str,
-    blockhash: Hash,
-    client: Arc<RpcClient>,
-    is_buy: bool,
-) -> () {
-    log!(format!("[ Nozomi Building Tx ]"), "info");
-    let mut _ixs: Vec<Instruction> = vec![];
-    let modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(30000);
-
-    _ixs.insert(0, modify_compute_units);
-
-    _ixs.insert(1, buy_ix.clone());
-    let tip_fee_addr = Pubkey::from_str_const(NOZOMI_TIP[0]);
-
-    let tip: f64;
-
-    if tip_amount < NOZOMI_MIN_TIP {
-        tip = NOZOMI_MIN_TIP
-    } else {
-        tip = tip_amount
+#[async_trait]
+impl TxSender for NozomiSender {
+    fn gateway(&self) -> Gateway {
+        Gateway::Nozomi
     }
 
-    let tip_ix = system_instruction::transfer(&payer.pubkey(), &tip_fee_addr, sol_to_lamports(tip));
-
-    _ixs.insert(2, tip_ix);
-
-    let txn = Transaction::new_signed_with_payer(
-        &_ixs,
-        Some(&payer.pubkey()),
-        &[&payer.as_ref()],
-        blockhash,
-    );
-    let serialized_tx = bincode::serialize(&txn).expect("Failed to serialize transaction");
-
-    let encoded_tx = &bs64::encode(&serialized_tx);
+    fn region(&self) -> &'static str {
+        self.region.name()
+    }
 
-    log!(format!("[ Nozomi Submiting Tx ]"), "info");
-    match submit_nozomi_tx(&encoded_tx, NozomiRegion::AMS, auth_header).await {
-        Ok(sig) => {
-            log!(format!("[Nozomi Confirm] {} ", sig.result), "success");
+    async fn send(&self, tx: &SignedTx) -> Result<Signature, SendError> {
+        let response = submit_nozomi_tx(&tx.encoded, self.region, &self.auth_key)
+            .await
+            .map_err(|e| SendError::Api(Gateway::Nozomi, format!("{:#}", e)))?;
 
-            if let Ok(signature) = sig.result.parse::<Signature>() {
-                get_token_balance_change_from_tx(client, signature, is_buy).await;
-            } else {
-                log!(format!("Invalid signature string: {}", sig.result), "error");
-            }
-        }
-        Err(e) => {
-            log!(format!("[Nozomi Error] {}", e), "error");
-        }
+        parse_signature(Gateway::Nozomi, &response.result)
     }
 }
@@ -0,0 +1,279 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::Transaction,
+};
+use thiserror::Error;
+
+use super::*;
+use crate::{
+    BotConfig, SenderRoute, BLOXROUTE_MIN_TIP, BLOX_TIP, JITO_MIN_TIP, JITO_TIP,
+    NEXT_BLOCK_MIN_TIP, NEXT_FEE, NOZOMI_MIN_TIP, NOZOMI_TIP, ZSLOT_MIN_TIP, ZSLOT_TIP,
+};
+
+/// Landing services a submission can go through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gateway {
+    Rpc,
+    Jito,
+    NextBlock,
+    Nozomi,
+    #[serde(rename = "bloxroute")]
+    BloXroute,
+    ZeroSlot,
+}
+
+/// Gateways in `senders.enabled` when it is not set: Nozomi leads, so it is
+/// the one used when `mode.is_racing` is off.
+pub const DEFAULT_SENDERS: &[&str] = &["nozomi", "jito", "next_block", "rpc"];
+
+impl Gateway {
+    pub const ALL: [Gateway; 6] = [
+        Gateway::Rpc,
+        Gateway::Jito,
+        Gateway::NextBlock,
+        Gateway::Nozomi,
+        Gateway::BloXroute,
+        Gateway::ZeroSlot,
+    ];
+
+    /// Name used in `senders.enabled` and as the prefix of its `[senders]` keys.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Gateway::Rpc => "rpc",
+            Gateway::Jito => "jito",
+            Gateway::NextBlock => "next_block",
+            Gateway::Nozomi => "nozomi",
+            Gateway::BloXroute => "bloxroute",
+            Gateway::ZeroSlot => "zero_slot",
+        }
+    }
+
+    /// Account the tip is paid to; `None` for the plain RPC, which takes none.
+    pub fn tip_account(&self) -> Option<Pubkey> {
+        let account = match self {
+            Gateway::Rpc => return None,
+            Gateway::Jito => JITO_TIP[0],
+            Gateway::NextBlock => NEXT_FEE[0],
+            Gateway::Nozomi => NOZOMI_TIP[0],
+            Gateway::BloXroute => BLOX_TIP[0],
+            Gateway::ZeroSlot => ZSLOT_TIP[0],
+        };
+        Some(Pubkey::from_str_const(account))
+    }
+
+    /// Smallest tip the gateway accepts, the default of its `min_tip` key.
+    pub fn default_min_tip(&self) -> f64 {
+        match self {
+            Gateway::Rpc => 0.0,
+            Gateway::Jito => JITO_MIN_TIP,
+            Gateway::NextBlock => NEXT_BLOCK_MIN_TIP,
+            Gateway::Nozomi => NOZOMI_MIN_TIP,
+            Gateway::BloXroute => BLOXROUTE_MIN_TIP,
+            Gateway::ZeroSlot => ZSLOT_MIN_TIP,
+        }
+    }
+
+    /// Regions the gateway can be reached in; empty for the plain RPC.
+    pub fn region_names(&self) -> Vec<&'static str> {
+        match self {
+            Gateway::Rpc => Vec::new(),
+            Gateway::Jito => JitoRegion::ALL.iter().map(JitoRegion::name).collect(),
+            Gateway::NextBlock => NextBlockRegion::ALL
+                .iter()
+                .map(NextBlockRegion::name)
+                .collect(),
+            Gateway::Nozomi => NozomiRegion::ALL.iter().map(NozomiRegion::name).collect(),
+            Gateway::BloXroute => BloXRegion::ALL.iter().map(BloXRegion::name).collect(),
+            Gateway::ZeroSlot => ZeroSlotRegion::ALL
+                .iter()
+                .map(ZeroSlotRegion::name)
+                .collect(),
+        }
+    }
+
+    /// Regions posted to when its `regions` key is not set.
+    pub fn default_regions(&self) -> &'static [&'static str] {
+        match self {
+            Gateway::Rpc => &[],
+            Gateway::Jito => &["amsterdam", "frankfurt", "mainnet", "ny", "tokyo"],
+            Gateway::NextBlock => &["frankfurt", "new_york"],
+            Gateway::Nozomi => &["ams", "fra", "us_east"],
+            Gateway::BloXroute => &["amsterdam"],
+            Gateway::ZeroSlot => &["ams"],
+        }
+    }
+
+    /// Key of `[gateways]` the gateway authenticates with, if any.
+    pub fn api_key<'a>(&self, config: &'a BotConfig) -> Option<(&'static str, &'a str)> {
+        let keys = &config.gateways;
+        match self {
+            Gateway::Rpc | Gateway::Jito => None,
+            Gateway::NextBlock => Some(("next_block_key", &keys.next_block_key)),
+            Gateway::Nozomi => Some(("nozomi_api_key", &keys.nozomi_api_key)),
+            Gateway::BloXroute => Some(("blox_auth_header", &keys.blox_auth_header)),
+            Gateway::ZeroSlot => Some(("zslot_api_key", &keys.zslot_api_key)),
+        }
+    }
+}
+
+impl fmt::Display for Gateway {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gateway::Rpc => write!(f, "RPC"),
+            Gateway::Jito => write!(f, "Jito"),
+            Gateway::NextBlock => write!(f, "NextBlock"),
+            Gateway::Nozomi => write!(f, "Nozomi"),
+            Gateway::BloXroute => write!(f, "bloXroute"),
+            Gateway::ZeroSlot => write!(f, "0slot"),
+        }
+    }
+}
+
+impl FromStr for Gateway {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Gateway::ALL
+            .into_iter()
+            .find(|gateway| gateway.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Gateway::ALL.iter().map(Gateway::name).collect();
+                format!("unknown gateway {:?} (available: {})", s, names.join(", "))
+            })
+    }
+}
+
+/// A signed transaction and its base64 wire form, shared by every region of
+/// a gateway.
+pub struct SignedTx {
+    pub transaction: Transaction,
+    pub encoded: String,
+}
+
+impl SignedTx {
+    pub fn new(transaction: Transaction) -> Self {
+        let serialized_tx =
+            bincode::serialize(&transaction).expect("Failed to serialize transaction");
+        SignedTx {
+            encoded: bs64::encode(&serialized_tx),
+            transaction,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SendError {
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("RPC error: {0}")]
+    Rpc(Box<solana_client::client_error::ClientError>),
+
+    #[error("{0} API returned an error: {1}")]
+    Api(Gateway, String),
+
+    #[error("{0} returned an invalid signature: {1:?}")]
+    InvalidSignature(Gateway, String),
+}
+
+/// Posts signed transactions to one region of a landing gateway.
+///
+/// Building and signing is shared (see `multi_submit`); an implementation
+/// only knows its endpoint and how the gateway wants the transaction.
+#[async_trait]
+pub trait TxSender: fmt::Debug + Send + Sync {
+    fn gateway(&self) -> Gateway;
+
+    /// Region posted to, for logs.
+    fn region(&self) -> &'static str;
+
+    /// Submits `tx`, returning the signature the gateway accepted.
+    async fn send(&self, tx: &SignedTx) -> Result<Signature, SendError>;
+}
+
+/// Parses the signature a gateway answered with.
+pub fn parse_signature(gateway: Gateway, signature: &str) -> Result<Signature, SendError> {
+    signature
+        .parse()
+        .map_err(|_| SendError::InvalidSignature(gateway, signature.to_string()))
+}
+
+/// The senders of one gateway: every region gets the same transaction, which
+/// tips at least `min_tip`.
+#[derive(Debug)]
+pub struct SenderGroup {
+    pub gateway: Gateway,
+    pub min_tip: f64,
+    pub senders: Vec<Arc<dyn TxSender>>,
+}
+
+/// Builds a sender per enabled gateway and region (`[senders]`), in the order
+/// of `senders.enabled`; names and regions were checked when the config was
+/// validated.
+pub fn build_senders(config: &BotConfig) -> Vec<SenderGroup> {
+    config
+        .senders
+        .routes
+        .iter()
+        .map(|route| SenderGroup {
+            gateway: route.gateway,
+            min_tip: route.min_tip,
+            senders: route_senders(config, route),
+        })
+        .collect()
+}
+
+fn route_senders(config: &BotConfig, route: &SenderRoute) -> Vec<Arc<dyn TxSender>> {
+    fn each<R: FromStr>(
+        route: &SenderRoute,
+        sender: impl Fn(R) -> Arc<dyn TxSender>,
+    ) -> Vec<Arc<dyn TxSender>> {
+        route
+            .regions
+            .iter()
+            .filter_map(|region| region.parse().ok())
+            .map(sender)
+            .collect()
+    }
+    let keys = &config.gateways;
+
+    match route.gateway {
+        Gateway::Rpc => vec![Arc::new(RpcSender {
+            client: Arc::new(RpcClient::new_with_commitment(
+                config.endpoints.rpc_endpoint.clone(),
+                CommitmentConfig::processed(),
+            )),
+        })],
+        Gateway::Jito => each(route, |region| Arc::new(JitoSender { region })),
+        Gateway::NextBlock => each(route, |region| {
+            Arc::new(NextBlockSender {
+                region,
+                auth_header: keys.next_block_key.clone(),
+            })
+        }),
+        Gateway::Nozomi => each(route, |region| {
+            Arc::new(NozomiSender {
+                region,
+                auth_key: keys.nozomi_api_key.clone(),
+            })
+        }),
+        Gateway::BloXroute => each(route, |region| {
+            Arc::new(BloXSender {
+                region,
+                auth_header: keys.blox_auth_header.clone(),
+            })
+        }),
+        Gateway::ZeroSlot => each(route, |region| {
+            Arc::new(ZeroSlotSender {
+                region,
+                api_key: keys.zslot_api_key.clone(),
+            })
+        }),
+    }
+}
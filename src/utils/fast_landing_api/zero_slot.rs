@@ -1,17 +1,14 @@
-use std::{str::FromStr, sync::Arc};
+use std::str::FromStr;
 
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    hash::Hash, instruction::Instruction, native_token::sol_to_lamports, pubkey::Pubkey,
-    signature::Keypair, signer::Signer, system_instruction, transaction::Transaction,
-};
+use solana_sdk::signature::Signature;
 use thiserror::Error;
 
-use crate::{get_race_ix, log, ZSLOT_MIN_TIP, ZSLOT_TIP};
+use super::{parse_signature, Gateway, SendError, SignedTx, TxSender};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum ZeroSlotRegion {
     Frankfurt,
     NewYork,
@@ -19,6 +16,20 @@ pub enum ZeroSlotRegion {
 }
 
 impl ZeroSlotRegion {
+    pub const ALL: [ZeroSlotRegion; 3] = [
+        ZeroSlotRegion::Frankfurt,
+        ZeroSlotRegion::NewYork,
+        ZeroSlotRegion::AMS,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ZeroSlotRegion::Frankfurt => "frankfurt",
+            ZeroSlotRegion::NewYork => "new_york",
+            ZeroSlotRegion::AMS => "ams",
+        }
+    }
+
     fn endpoint(&self) -> &'static str {
         match self {
             ZeroSlotRegion::NewYork => "https://ny.0slot.trade?api-key=",
@@ -28,6 +39,17 @@ impl ZeroSlotRegion {
     }
 }
 
+impl FromStr for ZeroSlotRegion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|region| region.name() == s)
+            .ok_or_else(|| format!("unknown 0slot region {:?}", s))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ZeroSlotError {
     #[error("HTTP request failed: {0}")]
@@ -37,25 +59,25 @@ pub enum ZeroSlotError {
     ApiError(String),
 }
 
+/// Sends a base64 transaction through 0slot's `sendTransaction`; the API key
+/// goes in the URL.
 pub async fn submit_zslot_transaction(
     transaction_content: &str,
-    auth_header: &str,
+    api_key: &str,
     region: ZeroSlotRegion,
-    front_running_protection: bool,
 ) -> Result<String, ZeroSlotError> {
     let client = Client::new();
-    let endpoint = region.endpoint();
+    let endpoint = format!("{}{}", region.endpoint(), api_key);
 
     let payload = json!({
-        "transaction": {
-            "content": transaction_content,
-        },
-        "frontRunningProtection": front_running_protection,
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "sendTransaction",
+        "params": [transaction_content, {"encoding": "base64"}]
     });
 
     let response = client
         .post(endpoint)
-        .header("Authorization", auth_header)
         .header("Content-Type", "application/json")
         .json(&payload)
         .send()
@@ -63,7 +85,10 @@ pub async fn submit_zslot_transaction(
 
     let json: serde_json::Value = response.json().await?;
 
-    if let Some(signature) = json.get("signature").and_then(|s| s.as_str()) {
+    if let Some(error) = json.get("error") {
+        return Err(ZeroSlotError::ApiError(error.to_string()));
+    }
+    if let Some(signature) = json.get("result").and_then(|s| s.as_str()) {
         Ok(signature.to_string())
     } else {
         Err(ZeroSlotError::ApiError(
@@ -72,57 +97,31 @@ pub async fn submit_zslot_transaction(
     }
 }
 
-pub async fn build_and_submit_zslot<'a>(
-    payer: &'a Arc<Keypair>,
-    buy_ix: &Instruction,
-    tip_amount: f64,
-    auth_header: &str,
-    blockhash: &'a str,
-    region: ZeroSlotRegion,
-    client: Arc<RpcClient>,
-    timestamp: u64,
-) -> () {
-    log!(format!("[ ZeroSlot ] {:#?}", region), "info");
-    let mut _ixs: Vec<Instruction> = vec![];
-    let modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(30000);
-
-    _ixs.insert(0, modify_compute_units);
-
-    let race_ix = get_race_ix(payer.pubkey(), timestamp);
-    _ixs.insert(1, race_ix);
-
-    _ixs.insert(2, buy_ix.clone());
-    let tip_fee_addr = Pubkey::from_str_const(ZSLOT_TIP[0]);
-
-    let tip: f64;
+/// 0slot, one region.
+#[derive(Debug)]
+pub struct ZeroSlotSender {
+    pub region: ZeroSlotRegion,
+    pub api_key: String,
+}
 
-    if tip_amount < ZSLOT_MIN_TIP {
-        tip = ZSLOT_MIN_TIP
-    } else {
-        tip = tip_amount
+#[async_trait]
+impl TxSender for ZeroSlotSender {
+    fn gateway(&self) -> Gateway {
+        Gateway::ZeroSlot
     }
 
-    let tip_ix = system_instruction::transfer(&payer.pubkey(), &tip_fee_addr, sol_to_lamports(tip));
-
-    _ixs.insert(3, tip_ix);
-
-    let txn = Transaction::new_signed_with_payer(
-        &_ixs,
-        Some(&payer.pubkey()),
-        &[&payer.as_ref()],
-        Hash::from_str(&blockhash).expect("msg"),
-    );
-    let serialized_tx = bincode::serialize(&txn).expect("Failed to serialize transaction");
+    fn region(&self) -> &'static str {
+        self.region.name()
+    }
 
-    let encoded_tx = &bs64::encode(&serialized_tx);
+    async fn send(&self, tx: &SignedTx) -> Result<Signature, SendError> {
+        let signature = submit_zslot_transaction(&tx.encoded, &self.api_key, self.region)
+            .await
+            .map_err(|e| match e {
+                ZeroSlotError::ReqwestError(e) => SendError::Http(e),
+                ZeroSlotError::ApiError(e) => SendError::Api(Gateway::ZeroSlot, e),
+            })?;
 
-    match submit_zslot_transaction(&encoded_tx, auth_header, region, false).await {
-        Ok(sig) => {
-            log!(format!("[ZeroSlot Confirm] {} ", sig), "success");
-        }
-        Err(e) => {
-            log!(format!("[ZeroSlot Error] {}", e), "error");
-        }
+        parse_signature(Gateway::ZeroSlot, &signature)
     }
 }
@@ -0,0 +1,44 @@
+use num_bigint::BigUint;
+
+/// `numerator / denominator` as `u128`, rounded up when `round_up`.
+/// `None` on division by zero or when the quotient does not fit.
+pub fn div_to_u128(numerator: &BigUint, denominator: &BigUint, round_up: bool) -> Option<u128> {
+    if *denominator == BigUint::ZERO {
+        return None;
+    }
+    let quotient = numerator / denominator;
+    let quotient = if round_up && (numerator % denominator) != BigUint::ZERO {
+        quotient + 1u32
+    } else {
+        quotient
+    };
+
+    u128::try_from(quotient).ok()
+}
+
+/// `a * b / denominator` without intermediate overflow.
+pub fn mul_div_u128(a: u128, b: u128, denominator: u128, round_up: bool) -> Option<u128> {
+    div_to_u128(
+        &(BigUint::from(a) * b),
+        &BigUint::from(denominator),
+        round_up,
+    )
+}
+
+/// `a * b >> shift` without intermediate overflow.
+pub fn mul_shr_u128(a: u128, b: u128, shift: u32, round_up: bool) -> Option<u128> {
+    div_to_u128(
+        &(BigUint::from(a) * b),
+        &(BigUint::from(1u32) << shift),
+        round_up,
+    )
+}
+
+/// `(a << shift) / denominator` without intermediate overflow.
+pub fn shl_div_u128(a: u128, shift: u32, denominator: u128, round_up: bool) -> Option<u128> {
+    div_to_u128(
+        &(BigUint::from(a) << shift),
+        &BigUint::from(denominator),
+        round_up,
+    )
+}
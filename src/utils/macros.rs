@@ -4,6 +4,11 @@ use std::io::{BufWriter, Write};
 use chrono::Local;
 
 pub fn log_to_file(message: &str) {
+    // Unit tests log to the console only
+    if cfg!(test) {
+        return;
+    }
+
     let filename = format!(
         "src/assets/logs/log_{}.txt",
         Local::now().format("%Y-%m-%d_%H")
@@ -0,0 +1,43 @@
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    skip_discriminator, DlmmBinArray, DlmmLbPair, DLMM_BINS_PER_ARRAY, METEORA_DLMM_ID_PUBKEY,
+};
+
+/// Bin array indexes covered by `LbPair::bin_array_bitmap`; the rest need the
+/// bitmap extension account.
+const BITMAP_MIN_INDEX: i64 = -512;
+const BITMAP_MAX_INDEX: i64 = 511;
+
+/// Decodes an `LbPair` account; trailing bytes are ignored.
+pub fn decode_dlmm_lb_pair(data: &[u8]) -> std::io::Result<DlmmLbPair> {
+    DlmmLbPair::deserialize(&mut skip_discriminator(data)?)
+}
+
+pub fn decode_dlmm_bin_array(data: &[u8]) -> std::io::Result<DlmmBinArray> {
+    DlmmBinArray::deserialize(&mut skip_discriminator(data)?)
+}
+
+/// Index of the bin array holding `bin_id`.
+pub fn dlmm_bin_array_index(bin_id: i32) -> i64 {
+    bin_id.div_euclid(DLMM_BINS_PER_ARRAY) as i64
+}
+
+pub fn dlmm_bin_array_address(lb_pair: &Pubkey, index: i64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"bin_array", lb_pair.as_ref(), &index.to_le_bytes()],
+        &METEORA_DLMM_ID_PUBKEY,
+    )
+    .0
+}
+
+/// Whether the pair's bitmap marks bin array `index` as holding liquidity.
+/// Indexes outside the bitmap are reported as empty.
+pub fn dlmm_bin_array_has_liquidity(lb_pair: &DlmmLbPair, index: i64) -> bool {
+    if !(BITMAP_MIN_INDEX..=BITMAP_MAX_INDEX).contains(&index) {
+        return false;
+    }
+    let bit = (index - BITMAP_MIN_INDEX) as usize;
+    lb_pair.bin_array_bitmap[bit / 64] & (1 << (bit % 64)) != 0
+}
@@ -0,0 +1,451 @@
+use crate::{
+    mul_div_u128, mul_shr_u128, shl_div_u128, DlmmBin, DlmmBinArray, DlmmLbPair, QuoteError,
+    SwapQuote,
+};
+
+pub const DLMM_BINS_PER_ARRAY: i32 = 70;
+
+const SCALE_OFFSET: u32 = 64;
+const ONE: u128 = 1 << SCALE_OFFSET;
+const BASIS_POINT_MAX: u128 = 10_000;
+/// Fee rates are in billionths.
+const FEE_PRECISION: u128 = 1_000_000_000;
+const MAX_FEE_RATE: u128 = 100_000_000;
+const MAX_EXPONENTIAL: u32 = 0x80000;
+
+/// Q64.64 price of bin `bin_id`, `(1 + bin_step / 10_000)^bin_id`, bit-exact with the program.
+pub fn dlmm_price_from_id(bin_id: i32, bin_step: u16) -> Option<u128> {
+    let base = ONE + ((bin_step as u128) << SCALE_OFFSET) / BASIS_POINT_MAX;
+    pow_q64(base, bin_id)
+}
+
+/// Simulates a swap the way the DLMM program executes it, bin by bin, with the
+/// variable fee growing as the active bin moves away from the reference.
+///
+/// `bin_arrays` are the arrays with liquidity in swap direction (gaps are
+/// jumped like the program does); the quote fails with `ArraysExhausted` when
+/// the swap would need more of them. `amount` is the input when `exact_in`,
+/// the output otherwise. `now` is the unix timestamp used to decay volatility.
+pub fn quote_dlmm_swap(
+    lb_pair: &DlmmLbPair,
+    bin_arrays: &[DlmmBinArray],
+    amount: u64,
+    exact_in: bool,
+    swap_for_y: bool,
+    now: i64,
+) -> Result<SwapQuote, QuoteError> {
+    if amount == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let mut arrays: Vec<&DlmmBinArray> = bin_arrays.iter().collect();
+    if swap_for_y {
+        arrays.sort_by_key(|array| std::cmp::Reverse(array.index));
+    } else {
+        arrays.sort_by_key(|array| array.index);
+    }
+
+    let mut fees = FeeState::new(lb_pair, now);
+    let mut active_id = lb_pair.active_id;
+    let mut remaining = amount;
+    let mut calculated: u64 = 0;
+    let mut fee_total: u64 = 0;
+
+    for array in arrays {
+        let lower = (array.index * DLMM_BINS_PER_ARRAY as i64) as i32;
+        let upper = lower + DLMM_BINS_PER_ARRAY - 1;
+        // Arrays behind the active bin were already swapped through
+        if (swap_for_y && lower > active_id) || (!swap_for_y && upper < active_id) {
+            continue;
+        }
+        // Jump over empty arrays to the first bin of this one
+        active_id = if swap_for_y {
+            active_id.min(upper)
+        } else {
+            active_id.max(lower)
+        };
+
+        while remaining > 0 && (lower..=upper).contains(&active_id) {
+            fees.update_volatility_accumulator(active_id);
+            let bin = &array.bins[(active_id - lower) as usize];
+            let price = if bin.price != 0 {
+                bin.price
+            } else {
+                dlmm_price_from_id(active_id, lb_pair.bin_step).ok_or(QuoteError::Overflow)?
+            };
+
+            let has_liquidity = if swap_for_y {
+                bin.amount_y > 0
+            } else {
+                bin.amount_x > 0
+            };
+            if has_liquidity {
+                let fee_rate = fees.total_fee_rate();
+                let step = if exact_in {
+                    swap_exact_in(bin, remaining, price, fee_rate, swap_for_y)?
+                } else {
+                    swap_exact_out(bin, remaining, price, fee_rate, swap_for_y)?
+                };
+
+                if exact_in {
+                    remaining -= step.amount_in;
+                    calculated += step.amount_out;
+                } else {
+                    remaining -= step.amount_out;
+                    calculated = calculated
+                        .checked_add(step.amount_in)
+                        .ok_or(QuoteError::Overflow)?;
+                }
+                fee_total += step.fee_amount;
+            }
+
+            if remaining > 0 {
+                active_id += if swap_for_y { -1 } else { 1 };
+            }
+        }
+
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    if remaining > 0 {
+        return Err(QuoteError::ArraysExhausted("bin arrays"));
+    }
+
+    let (amount_in, amount_out) = if exact_in {
+        (amount, calculated)
+    } else {
+        (calculated, amount)
+    };
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount: fee_total,
+    })
+}
+
+struct BinSwap {
+    /// Including fees
+    amount_in: u64,
+    amount_out: u64,
+    fee_amount: u64,
+}
+
+fn swap_exact_in(
+    bin: &DlmmBin,
+    amount_in: u64,
+    price: u128,
+    fee_rate: u128,
+    swap_for_y: bool,
+) -> Result<BinSwap, QuoteError> {
+    let max_amount_out = max_amount_out(bin, swap_for_y);
+    let max_amount_in = amount_in_for(max_amount_out, price, swap_for_y)?;
+    let max_fee = fee_on_amount(max_amount_in, fee_rate)?;
+    let max_amount_in_with_fee = max_amount_in
+        .checked_add(max_fee)
+        .ok_or(QuoteError::Overflow)?;
+
+    if amount_in >= max_amount_in_with_fee {
+        return Ok(BinSwap {
+            amount_in: max_amount_in_with_fee,
+            amount_out: max_amount_out,
+            fee_amount: max_fee,
+        });
+    }
+
+    // Fee included in the amount
+    let fee = mul_div_u128(amount_in as u128, fee_rate, FEE_PRECISION, true)
+        .ok_or(QuoteError::Overflow)? as u64;
+    let amount_in_after_fee = amount_in - fee;
+    let amount_out = if swap_for_y {
+        mul_shr_u128(amount_in_after_fee as u128, price, SCALE_OFFSET, false)
+    } else {
+        shl_div_u128(amount_in_after_fee as u128, SCALE_OFFSET, price, false)
+    }
+    .ok_or(QuoteError::Overflow)?;
+
+    Ok(BinSwap {
+        amount_in,
+        amount_out: (amount_out.min(max_amount_out as u128)) as u64,
+        fee_amount: fee,
+    })
+}
+
+fn swap_exact_out(
+    bin: &DlmmBin,
+    amount_out: u64,
+    price: u128,
+    fee_rate: u128,
+    swap_for_y: bool,
+) -> Result<BinSwap, QuoteError> {
+    let amount_out = amount_out.min(max_amount_out(bin, swap_for_y));
+    let amount_in = amount_in_for(amount_out, price, swap_for_y)?;
+    let fee = fee_on_amount(amount_in, fee_rate)?;
+
+    Ok(BinSwap {
+        amount_in: amount_in.checked_add(fee).ok_or(QuoteError::Overflow)?,
+        amount_out,
+        fee_amount: fee,
+    })
+}
+
+fn max_amount_out(bin: &DlmmBin, swap_for_y: bool) -> u64 {
+    if swap_for_y {
+        bin.amount_y
+    } else {
+        bin.amount_x
+    }
+}
+
+/// Input needed for `amount_out` at `price`, before fees, rounded up.
+fn amount_in_for(amount_out: u64, price: u128, swap_for_y: bool) -> Result<u64, QuoteError> {
+    let amount_in = if swap_for_y {
+        shl_div_u128(amount_out as u128, SCALE_OFFSET, price, true)
+    } else {
+        mul_shr_u128(amount_out as u128, price, SCALE_OFFSET, true)
+    }
+    .ok_or(QuoteError::Overflow)?;
+
+    u64::try_from(amount_in).map_err(|_| QuoteError::Overflow)
+}
+
+/// Fee charged on top of `amount`, rounded up.
+fn fee_on_amount(amount: u64, fee_rate: u128) -> Result<u64, QuoteError> {
+    let fee = mul_div_u128(amount as u128, fee_rate, FEE_PRECISION - fee_rate, true)
+        .ok_or(QuoteError::Overflow)?;
+
+    u64::try_from(fee).map_err(|_| QuoteError::Overflow)
+}
+
+/// The pair's fee parameters, with the volatility references refreshed for `now`.
+struct FeeState<'a> {
+    lb_pair: &'a DlmmLbPair,
+    volatility_accumulator: u32,
+    volatility_reference: u32,
+    index_reference: i32,
+}
+
+impl<'a> FeeState<'a> {
+    fn new(lb_pair: &'a DlmmLbPair, now: i64) -> Self {
+        let params = &lb_pair.parameters;
+        let v_params = &lb_pair.v_parameters;
+
+        let mut volatility_reference = v_params.volatility_reference;
+        let mut index_reference = v_params.index_reference;
+
+        let elapsed = now - v_params.last_update_timestamp;
+        if elapsed >= params.filter_period as i64 {
+            index_reference = lb_pair.active_id;
+            volatility_reference = if elapsed < params.decay_period as i64 {
+                (v_params.volatility_accumulator as u64 * params.reduction_factor as u64
+                    / BASIS_POINT_MAX as u64) as u32
+            } else {
+                0
+            };
+        }
+
+        FeeState {
+            lb_pair,
+            volatility_accumulator: v_params.volatility_accumulator,
+            volatility_reference,
+            index_reference,
+        }
+    }
+
+    fn update_volatility_accumulator(&mut self, active_id: i32) {
+        let delta_id = self.index_reference.abs_diff(active_id) as u64;
+        let accumulator = self.volatility_reference as u64 + delta_id * BASIS_POINT_MAX as u64;
+
+        self.volatility_accumulator =
+            accumulator.min(self.lb_pair.parameters.max_volatility_accumulator as u64) as u32;
+    }
+
+    fn total_fee_rate(&self) -> u128 {
+        let params = &self.lb_pair.parameters;
+        let bin_step = self.lb_pair.bin_step as u128;
+
+        let base_fee = params.base_factor as u128
+            * bin_step
+            * 10
+            * 10u128.pow(params.base_fee_power_factor as u32);
+        let variable_fee = if params.variable_fee_control > 0 {
+            let square_vfa_bin = (self.volatility_accumulator as u128 * bin_step).pow(2);
+            (params.variable_fee_control as u128 * square_vfa_bin).div_ceil(100_000_000_000)
+        } else {
+            0
+        };
+
+        (base_fee + variable_fee).min(MAX_FEE_RATE)
+    }
+}
+
+/// Q64.64 `base^exp`; bases above one are inverted first so every product fits.
+fn pow_q64(base: u128, exp: i32) -> Option<u128> {
+    if exp == 0 {
+        return Some(ONE);
+    }
+    let mut invert = exp.is_negative();
+    let exp = exp.unsigned_abs();
+    if exp >= MAX_EXPONENTIAL {
+        return None;
+    }
+
+    let mut squared_base = base;
+    let mut result = ONE;
+    if squared_base >= result {
+        squared_base = u128::MAX.checked_div(squared_base)?;
+        invert = !invert;
+    }
+
+    for bit in 0..19 {
+        if exp & (1 << bit) != 0 {
+            result = result.checked_mul(squared_base)? >> SCALE_OFFSET;
+        }
+        squared_base = squared_base.checked_mul(squared_base)? >> SCALE_OFFSET;
+    }
+
+    if result == 0 {
+        return None;
+    }
+    if invert {
+        result = u128::MAX.checked_div(result)?;
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshDeserialize;
+
+    use super::*;
+
+    /// 0.1% steps between bins.
+    const BIN_STEP: u16 = 10;
+    /// With `BIN_STEP`, a base fee of 0.1%: 1_000_000 billionths.
+    const BASE_FACTOR: u16 = 10_000;
+
+    fn zeroed<T: BorshDeserialize>() -> T {
+        T::deserialize(&mut [0u8; 16_384].as_slice()).unwrap()
+    }
+
+    /// Pair active at bin 0 with only the base fee.
+    fn lb_pair() -> DlmmLbPair {
+        let mut lb_pair: DlmmLbPair = zeroed();
+        lb_pair.bin_step = BIN_STEP;
+        lb_pair.parameters.base_factor = BASE_FACTOR;
+        lb_pair
+    }
+
+    /// Array `index` with `amount_y` in the given bins.
+    fn bin_array(index: i64, amounts_y: &[(i32, u64)]) -> DlmmBinArray {
+        let mut array: DlmmBinArray = zeroed();
+        array.index = index;
+        let lower = index as i32 * DLMM_BINS_PER_ARRAY;
+        for (bin_id, amount_y) in amounts_y {
+            array.bins[(bin_id - lower) as usize].amount_y = *amount_y;
+        }
+        array
+    }
+
+    /// 1 token Y in the active bin 0, plenty in bin -1 below it.
+    fn two_bins() -> Vec<DlmmBinArray> {
+        vec![
+            bin_array(0, &[(0, 1_000_000)]),
+            bin_array(-1, &[(-1, 1_000_000_000_000)]),
+        ]
+    }
+
+    #[test]
+    fn bin_prices_follow_the_bin_step() {
+        assert_eq!(dlmm_price_from_id(0, BIN_STEP), Some(ONE));
+
+        for bin_id in (-5_000..=5_000).step_by(250) {
+            let exact = 1.001f64.powi(bin_id) * 2f64.powi(64);
+            let price = dlmm_price_from_id(bin_id, BIN_STEP).unwrap() as f64;
+            assert!(
+                ((price - exact) / exact).abs() < 1e-9,
+                "bin {}: {} against {}",
+                bin_id,
+                price,
+                exact
+            );
+        }
+    }
+
+    // Bin 0 is drained at price 1: 1_000_000 out for 1_000_000 in plus a
+    // fee on top of ceil(1_000_000 * 1e6 / (1e9 - 1e6)) = 1_002. The
+    // 1_998_998 left pay a fee within of ceil(1_998.998) = 1_999 and swap
+    // their 1_996_999 at bin -1's price of 1 / 1.001.
+    #[test]
+    fn exact_in_swaps_cross_into_the_next_bin() {
+        let quote = quote_dlmm_swap(&lb_pair(), &two_bins(), 3_000_000, true, true, 0).unwrap();
+
+        assert_eq!(quote.amount_in, 3_000_000);
+        assert_eq!(quote.fee_amount, 1_002 + 1_999);
+        let second_bin = 1_996_999.0 / 1.001;
+        assert!((quote.amount_out as f64 - (1_000_000.0 + second_bin)).abs() <= 1.0);
+    }
+
+    #[test]
+    fn exact_out_swaps_cross_into_the_next_bin() {
+        let quote = quote_dlmm_swap(&lb_pair(), &two_bins(), 1_500_000, false, true, 0).unwrap();
+
+        assert_eq!(quote.amount_out, 1_500_000);
+        // 500_000 out of bin -1 take 500_500 in, plus the fee on top
+        let second_bin = 500_500.0 * (1.0 + 1.0 / 999.0);
+        let reference = 1_001_002.0 + second_bin;
+        assert!(
+            (quote.amount_in as f64 - reference).abs() <= 2.0,
+            "{} against {}",
+            quote.amount_in,
+            reference
+        );
+    }
+
+    #[test]
+    fn crossed_bins_raise_the_variable_fee() {
+        let mut lb_pair = lb_pair();
+        lb_pair.parameters.variable_fee_control = 40_000;
+        lb_pair.parameters.max_volatility_accumulator = 350_000;
+        let quote = quote_dlmm_swap(&lb_pair, &two_bins(), 3_000_000, true, true, 0).unwrap();
+
+        // One bin away, (10_000 * 10)^2 * 40_000 / 1e11 = 4_000 billionths
+        // join the base fee: ceil(1_998_998 * 1_004_000 / 1e9) = 2_007
+        assert_eq!(quote.fee_amount, 1_002 + 2_007);
+        let base_only = quote_dlmm_swap(&self::lb_pair(), &two_bins(), 3_000_000, true, true, 0);
+        assert!(quote.amount_out < base_only.unwrap().amount_out);
+    }
+
+    #[test]
+    fn empty_arrays_are_jumped() {
+        // Arrays -1 and -2 hold nothing and are not loaded; the swap resumes
+        // at the top bin of array -3
+        let arrays = vec![
+            bin_array(0, &[(0, 1_000_000)]),
+            bin_array(-3, &[(-141, 1_000_000_000_000)]),
+        ];
+        let quote = quote_dlmm_swap(&lb_pair(), &arrays, 3_000_000, true, true, 0).unwrap();
+
+        let far_bin = 1_996_999.0 * 1.001f64.powi(-141);
+        assert!((quote.amount_out as f64 - (1_000_000.0 + far_bin)).abs() <= 1.0);
+    }
+
+    #[test]
+    fn swaps_past_the_loaded_arrays_fail() {
+        assert!(matches!(
+            quote_dlmm_swap(&lb_pair(), &two_bins(), u64::MAX / 4, true, true, 0),
+            Err(QuoteError::ArraysExhausted(_))
+        ));
+        // Nothing on the X side
+        assert!(matches!(
+            quote_dlmm_swap(&lb_pair(), &two_bins(), 1_000, true, false, 0),
+            Err(QuoteError::ArraysExhausted(_))
+        ));
+        assert!(matches!(
+            quote_dlmm_swap(&lb_pair(), &two_bins(), 0, true, true, 0),
+            Err(QuoteError::ZeroAmount)
+        ));
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{bail, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    decode_dlmm_bin_array, decode_dlmm_lb_pair, dlmm_bin_array_address,
+    dlmm_bin_array_has_liquidity, dlmm_bin_array_index, DlmmBinArray, DlmmLbPair,
+};
+
+/// Bin arrays handed to one swap, the active one included.
+const MAX_SWAP_BIN_ARRAYS: usize = 3;
+/// How far the bitmap is scanned for arrays with liquidity.
+const MAX_BIN_ARRAY_SCAN: i64 = 64;
+
+/// Live pair state plus the bin arrays a swap may cross.
+pub struct DlmmSwapState {
+    pub lb_pair: DlmmLbPair,
+    /// Bin arrays with liquidity in swap direction, starting with the active one.
+    pub bin_array_addresses: Vec<Pubkey>,
+    pub bin_arrays: Vec<DlmmBinArray>,
+}
+
+pub async fn fetch_dlmm_swap_state(
+    client: &RpcClient,
+    lb_pair_id: &Pubkey,
+    swap_for_y: bool,
+) -> Result<DlmmSwapState> {
+    let data = client
+        .get_account_data(lb_pair_id)
+        .await
+        .with_context(|| format!("failed to fetch lb pair {}", lb_pair_id))?;
+    let lb_pair = decode_dlmm_lb_pair(&data)
+        .with_context(|| format!("failed to decode lb pair {}", lb_pair_id))?;
+
+    let active_index = dlmm_bin_array_index(lb_pair.active_id);
+    let step = if swap_for_y { -1 } else { 1 };
+    let indexes: Vec<i64> = std::iter::once(active_index)
+        .chain(
+            (1..=MAX_BIN_ARRAY_SCAN)
+                .map(|i| active_index + i * step)
+                .filter(|index| dlmm_bin_array_has_liquidity(&lb_pair, *index)),
+        )
+        .take(MAX_SWAP_BIN_ARRAYS)
+        .collect();
+    let addresses: Vec<Pubkey> = indexes
+        .iter()
+        .map(|index| dlmm_bin_array_address(lb_pair_id, *index))
+        .collect();
+
+    let accounts = client
+        .get_multiple_accounts(&addresses)
+        .await
+        .context("failed to fetch bin arrays")?;
+
+    let mut bin_array_addresses = Vec::with_capacity(addresses.len());
+    let mut bin_arrays = Vec::with_capacity(addresses.len());
+    for (address, account) in addresses.iter().zip(&accounts) {
+        let Some(account) = account else {
+            continue;
+        };
+        bin_arrays
+            .push(decode_dlmm_bin_array(&account.data).context("failed to decode bin array")?);
+        bin_array_addresses.push(*address);
+    }
+    if bin_arrays.is_empty() {
+        bail!(
+            "no bin array with liquidity around bin {}",
+            lb_pair.active_id
+        );
+    }
+
+    Ok(DlmmSwapState {
+        lb_pair,
+        bin_array_addresses,
+        bin_arrays,
+    })
+}
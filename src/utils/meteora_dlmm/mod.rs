@@ -0,0 +1,7 @@
+pub mod decode_dlmm_state;
+pub mod dlmm_quote;
+pub mod fetch_dlmm_state;
+
+pub use decode_dlmm_state::*;
+pub use dlmm_quote::*;
+pub use fetch_dlmm_state::*;
@@ -1,19 +1,31 @@
 pub mod build_tx;
 pub mod fast_landing_api;
 pub mod file;
+pub mod fixed_point;
 pub mod macros;
+pub mod meteora_dlmm;
 pub mod oracle;
 pub mod pumpswap;
+pub mod quote;
 pub mod race;
+pub mod raydium_cpmm;
+pub mod store;
 pub mod token;
 pub mod web3;
+pub mod whirlpool;
 
 pub use build_tx::*;
 pub use fast_landing_api::*;
 pub use file::*;
+pub use fixed_point::*;
 pub use macros::*;
+pub use meteora_dlmm::*;
 pub use oracle::*;
 pub use pumpswap::*;
+pub use quote::*;
 pub use race::*;
+pub use raydium_cpmm::*;
+pub use store::*;
 pub use token::*;
 pub use web3::*;
+pub use whirlpool::*;
@@ -0,0 +1,42 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{ConstantProductFees, PUMP_SWAP_ID};
+
+pub fn pumpswap_global_config_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &["global_config".as_bytes()],
+        &Pubkey::from_str_const(PUMP_SWAP_ID),
+    )
+    .0
+}
+
+/// Fees a PumpSwap pool charges, from the `GlobalConfig` account and the pool
+/// account. The coin creator fee only applies to pools with a coin creator;
+/// configs and pools from before creator fees decode with it at zero.
+pub fn decode_pumpswap_fees(global_config: &[u8], pool: &[u8]) -> Option<ConstantProductFees> {
+    let read_u64 = |data: &[u8], offset: usize| {
+        data.get(offset..offset + 8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+    };
+
+    // discriminator, admin, then the fees; protocol_fee_recipients sit in between
+    let lp_fee_basis_points = read_u64(global_config, 40)?;
+    let protocol_fee_basis_points = read_u64(global_config, 48)?;
+    let coin_creator_fee_basis_points = read_u64(global_config, 313).unwrap_or_default();
+
+    // coin_creator follows lp_supply in the pool account
+    let has_coin_creator = pool
+        .get(211..243)
+        .is_some_and(|coin_creator| coin_creator.iter().any(|byte| *byte != 0));
+
+    Some(ConstantProductFees::PumpSwap {
+        lp_fee_basis_points,
+        protocol_fee_basis_points,
+        coin_creator_fee_basis_points: if has_coin_creator {
+            coin_creator_fee_basis_points
+        } else {
+            0
+        },
+    })
+}
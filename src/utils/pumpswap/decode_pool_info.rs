@@ -14,7 +14,8 @@ pub fn decode_pumpswap_pool_info(data: Vec<u8>) -> PumpswapPool {
     let lp_supply = u64::from_le_bytes(data[offset + 195..offset + 203].try_into().unwrap());
 
     // Pool struct
-    let pool = PumpswapPool {
+
+    PumpswapPool {
         pool_bump,
         index,
         creator,
@@ -24,7 +25,5 @@ pub fn decode_pumpswap_pool_info(data: Vec<u8>) -> PumpswapPool {
         pool_base_token_account,
         pool_quote_token_account,
         lp_supply,
-    };
-
-    pool
+    }
 }
@@ -1,5 +1,7 @@
+pub mod decode_global_config;
 pub mod decode_pool_info;
 pub mod get_pumpswap_keys;
 
+pub use decode_global_config::*;
 pub use decode_pool_info::*;
 pub use get_pumpswap_keys::*;
@@ -0,0 +1,252 @@
+use crate::{
+    cpmm_swap_base_input, cpmm_swap_base_output, pumpswap_buy_base_out, pumpswap_buy_quote_in,
+    pumpswap_sell_base_in, raydium_amm_swap_base_in, raydium_amm_swap_base_out,
+    ConstantProductFees, QuoteError, Reserves, SwapQuote,
+};
+
+/// `x * y = k` output for `amount_in` already net of fees, rounded down.
+pub fn constant_product_amount_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+) -> Result<u64, QuoteError> {
+    let denominator = reserve_in as u128 + amount_in as u128;
+    if denominator == 0 {
+        return Err(QuoteError::InsufficientLiquidity);
+    }
+
+    Ok((reserve_out as u128 * amount_in as u128 / denominator) as u64)
+}
+
+/// Fee-less numerator and denominator of the input `x * y = k` needs to release
+/// `amount_out`; each venue rounds the division its own way.
+pub fn constant_product_amount_in_ratio(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+) -> Result<(u128, u128), QuoteError> {
+    if amount_out >= reserve_out {
+        return Err(QuoteError::InsufficientLiquidity);
+    }
+
+    Ok((
+        reserve_in as u128 * amount_out as u128,
+        (reserve_out - amount_out) as u128,
+    ))
+}
+
+pub(crate) fn ceil_div(numerator: u128, denominator: u128) -> Result<u128, QuoteError> {
+    if denominator == 0 {
+        return Err(QuoteError::Overflow);
+    }
+
+    Ok(numerator.div_ceil(denominator))
+}
+
+pub(crate) fn to_u64(amount: u128) -> Result<u64, QuoteError> {
+    u64::try_from(amount).map_err(|_| QuoteError::Overflow)
+}
+
+impl ConstantProductFees {
+    /// Spends exactly `amount_in` against `reserves`: lamports when `sol_in`,
+    /// raw token units otherwise.
+    pub fn quote_exact_in(
+        &self,
+        reserves: &Reserves,
+        amount_in: u64,
+        sol_in: bool,
+    ) -> Result<SwapQuote, QuoteError> {
+        let (reserve_in, reserve_out) = direction(reserves, sol_in);
+
+        match *self {
+            ConstantProductFees::RaydiumAmm {
+                swap_fee_numerator,
+                swap_fee_denominator,
+            } => raydium_amm_swap_base_in(
+                reserve_in,
+                reserve_out,
+                amount_in,
+                swap_fee_numerator,
+                swap_fee_denominator,
+            ),
+            ConstantProductFees::RaydiumCpmm { trade_fee_rate } => {
+                cpmm_swap_base_input(reserve_in, reserve_out, amount_in, trade_fee_rate)
+            }
+            // PumpSwap buys are exact-out on chain, see `pumpswap_buy_quote_in`
+            ConstantProductFees::PumpSwap { .. } if sol_in => pumpswap_buy_quote_in(
+                reserves.token,
+                reserves.sol,
+                amount_in,
+                &self.pumpswap_fee_bps(),
+            ),
+            ConstantProductFees::PumpSwap { .. } => pumpswap_sell_base_in(
+                reserves.token,
+                reserves.sol,
+                amount_in,
+                &self.pumpswap_fee_bps(),
+            ),
+        }
+    }
+
+    /// Receives exactly `amount_out` from `reserves`: raw token units when
+    /// `sol_in`, lamports otherwise.
+    pub fn quote_exact_out(
+        &self,
+        reserves: &Reserves,
+        amount_out: u64,
+        sol_in: bool,
+    ) -> Result<SwapQuote, QuoteError> {
+        let (reserve_in, reserve_out) = direction(reserves, sol_in);
+
+        match *self {
+            ConstantProductFees::RaydiumAmm {
+                swap_fee_numerator,
+                swap_fee_denominator,
+            } => raydium_amm_swap_base_out(
+                reserve_in,
+                reserve_out,
+                amount_out,
+                swap_fee_numerator,
+                swap_fee_denominator,
+            ),
+            ConstantProductFees::RaydiumCpmm { trade_fee_rate } => {
+                cpmm_swap_base_output(reserve_in, reserve_out, amount_out, trade_fee_rate)
+            }
+            ConstantProductFees::PumpSwap { .. } if sol_in => pumpswap_buy_base_out(
+                reserves.token,
+                reserves.sol,
+                amount_out,
+                &self.pumpswap_fee_bps(),
+            ),
+            // PumpSwap has no exact-out sell
+            ConstantProductFees::PumpSwap { .. } => Err(QuoteError::Unsupported),
+        }
+    }
+
+    fn pumpswap_fee_bps(&self) -> [u64; 3] {
+        match *self {
+            ConstantProductFees::PumpSwap {
+                lp_fee_basis_points,
+                protocol_fee_basis_points,
+                coin_creator_fee_basis_points,
+            } => [
+                lp_fee_basis_points,
+                protocol_fee_basis_points,
+                coin_creator_fee_basis_points,
+            ],
+            _ => [0; 3],
+        }
+    }
+}
+
+fn direction(reserves: &Reserves, sol_in: bool) -> (u64, u64) {
+    if sol_in {
+        (reserves.sol, reserves.token)
+    } else {
+        (reserves.token, reserves.sol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grid the invariants below are checked over, edges included.
+    const RESERVES: [u64; 5] = [0, 1, 987_654, 85_000_000_000, u64::MAX];
+    const AMOUNTS: [u64; 5] = [0, 1, 10_000, 5_000_000_000, u64::MAX];
+
+    const VENUES: [ConstantProductFees; 3] = [
+        ConstantProductFees::RaydiumAmm {
+            swap_fee_numerator: 25,
+            swap_fee_denominator: 10_000,
+        },
+        ConstantProductFees::RaydiumCpmm {
+            trade_fee_rate: 2_500,
+        },
+        ConstantProductFees::PumpSwap {
+            lp_fee_basis_points: 20,
+            protocol_fee_basis_points: 5,
+            coin_creator_fee_basis_points: 5,
+        },
+    ];
+
+    #[test]
+    fn output_never_exceeds_the_reserve() {
+        for reserve_in in RESERVES {
+            for reserve_out in RESERVES {
+                for amount_in in AMOUNTS {
+                    if let Ok(amount_out) =
+                        constant_product_amount_out(reserve_in, reserve_out, amount_in)
+                    {
+                        assert!(amount_out <= reserve_out);
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            constant_product_amount_out(0, 1_000, 0),
+            Err(QuoteError::InsufficientLiquidity)
+        );
+    }
+
+    #[test]
+    fn draining_the_pool_is_an_error() {
+        assert_eq!(
+            constant_product_amount_in_ratio(1_000, 1_000, 1_000),
+            Err(QuoteError::InsufficientLiquidity)
+        );
+        assert_eq!(
+            constant_product_amount_in_ratio(1_000, 1_000, 999),
+            Ok((999_000, 1))
+        );
+    }
+
+    #[test]
+    fn ceil_div_rounds_up() {
+        assert_eq!(ceil_div(10, 5), Ok(2));
+        assert_eq!(ceil_div(11, 5), Ok(3));
+        assert_eq!(ceil_div(1, 5), Ok(1));
+        assert_eq!(ceil_div(0, 5), Ok(0));
+        assert_eq!(ceil_div(1, 0), Err(QuoteError::Overflow));
+        assert_eq!(to_u64(u64::MAX as u128 + 1), Err(QuoteError::Overflow));
+    }
+
+    #[test]
+    fn venues_never_panic() {
+        for fees in VENUES {
+            for sol in RESERVES {
+                for token in RESERVES {
+                    let reserves = Reserves { sol, token };
+                    for amount in AMOUNTS {
+                        for sol_in in [true, false] {
+                            let _ = fees.quote_exact_in(&reserves, amount, sol_in);
+                            let _ = fees.quote_exact_out(&reserves, amount, sol_in);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quotes_follow_the_swap_direction() {
+        let reserves = Reserves {
+            sol: 85_000_000_000,
+            token: 310_000_000_000_000,
+        };
+        for fees in VENUES {
+            let buy = fees.quote_exact_in(&reserves, 1_000_000_000, true).unwrap();
+            assert!(buy.amount_out > 1_000_000_000);
+            let sell = fees
+                .quote_exact_in(&reserves, buy.amount_out, false)
+                .unwrap();
+            // Paying the fee both ways loses SOL
+            assert!(sell.amount_out < 1_000_000_000);
+        }
+
+        assert_eq!(
+            VENUES[2].quote_exact_out(&reserves, 1_000_000, false),
+            Err(QuoteError::Unsupported)
+        );
+    }
+}
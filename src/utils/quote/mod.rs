@@ -0,0 +1,11 @@
+pub mod constant_product;
+pub mod pumpswap_quote;
+pub mod raydium_amm_quote;
+pub mod raydium_cpmm_quote;
+pub mod swap_quote;
+
+pub use constant_product::*;
+pub use pumpswap_quote::*;
+pub use raydium_amm_quote::*;
+pub use raydium_cpmm_quote::*;
+pub use swap_quote::*;
@@ -0,0 +1,251 @@
+use crate::{
+    ceil_div, constant_product_amount_in_ratio, constant_product_amount_out, to_u64, QuoteError,
+    SwapQuote,
+};
+
+const BASIS_POINTS: u128 = 10_000;
+
+/// PumpSwap `buy`: receive exactly `base_amount_out`; every fee in `fee_bps`
+/// (LP, protocol, coin creator) is rounded up on its own and added to the quote
+/// input.
+pub fn pumpswap_buy_base_out(
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_amount_out: u64,
+    fee_bps: &[u64],
+) -> Result<SwapQuote, QuoteError> {
+    if base_amount_out == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let (numerator, denominator) =
+        constant_product_amount_in_ratio(quote_reserve, base_reserve, base_amount_out)?;
+    let quote_amount_in = to_u64(ceil_div(numerator, denominator)?)?;
+    let fee = fees_on(quote_amount_in, fee_bps)?;
+
+    Ok(SwapQuote {
+        amount_in: quote_amount_in
+            .checked_add(fee)
+            .ok_or(QuoteError::Overflow)?,
+        amount_out: base_amount_out,
+        fee_amount: fee,
+    })
+}
+
+/// Base amount to `buy` for about `quote_amount_in`, the way the PumpSwap SDK
+/// sizes it: fees are backed out of the input first. The quote input is then
+/// re-derived exactly, so it never exceeds `quote_amount_in` by more than the
+/// fee rounding.
+pub fn pumpswap_buy_quote_in(
+    base_reserve: u64,
+    quote_reserve: u64,
+    quote_amount_in: u64,
+    fee_bps: &[u64],
+) -> Result<SwapQuote, QuoteError> {
+    if quote_amount_in == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let total_bps: u128 = fee_bps.iter().map(|bps| *bps as u128).sum();
+    let effective_quote =
+        to_u64(quote_amount_in as u128 * BASIS_POINTS / (BASIS_POINTS + total_bps))?;
+    let base_amount_out =
+        constant_product_amount_out(quote_reserve, base_reserve, effective_quote)?;
+
+    pumpswap_buy_base_out(base_reserve, quote_reserve, base_amount_out, fee_bps)
+}
+
+/// PumpSwap `sell`: spend exactly `base_amount_in`; every fee in `fee_bps` is
+/// rounded up on its own and taken from the quote output.
+pub fn pumpswap_sell_base_in(
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_amount_in: u64,
+    fee_bps: &[u64],
+) -> Result<SwapQuote, QuoteError> {
+    if base_amount_in == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let quote_amount_out =
+        constant_product_amount_out(base_reserve, quote_reserve, base_amount_in)?;
+    let fee = fees_on(quote_amount_out, fee_bps)?;
+
+    Ok(SwapQuote {
+        amount_in: base_amount_in,
+        amount_out: quote_amount_out.saturating_sub(fee),
+        fee_amount: fee,
+    })
+}
+
+fn fees_on(amount: u64, fee_bps: &[u64]) -> Result<u64, QuoteError> {
+    fee_bps.iter().try_fold(0u64, |total, bps| {
+        let fee = to_u64(ceil_div(amount as u128 * *bps as u128, BASIS_POINTS)?)?;
+        total.checked_add(fee).ok_or(QuoteError::Overflow)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// LP, protocol and coin creator fees of the global config.
+    const FEE_BPS: [u64; 3] = [20, 5, 5];
+
+    /// Grid the invariants below are checked over, edges included.
+    const RESERVES: [u64; 5] = [
+        1_000,
+        987_654,
+        84_000_000_000,
+        206_900_000_000_000,
+        1_000_000_000_000_000_000,
+    ];
+    const AMOUNTS: [u64; 5] = [1, 399, 10_000, 123_456_789, 5_000_000_000];
+
+    #[test]
+    fn output_never_exceeds_the_reserve() {
+        for base_reserve in RESERVES {
+            for quote_reserve in RESERVES {
+                for amount in AMOUNTS {
+                    let sell = pumpswap_sell_base_in(base_reserve, quote_reserve, amount, &FEE_BPS)
+                        .unwrap();
+                    assert!(sell.amount_out < quote_reserve);
+
+                    if let Ok(buy) =
+                        pumpswap_buy_quote_in(base_reserve, quote_reserve, amount, &FEE_BPS)
+                    {
+                        assert!(buy.amount_out < base_reserve);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Each fee rounds up by less than a unit of input, which buys at most a
+    /// unit of output while the pool prices base no cheaper than quote.
+    #[test]
+    fn buy_round_trips_through_its_quote_input() {
+        for base_reserve in RESERVES {
+            for quote_reserve in RESERVES.into_iter().filter(|r| *r >= base_reserve) {
+                for base_amount_out in AMOUNTS.into_iter().filter(|a| *a < base_reserve) {
+                    let exact_out = pumpswap_buy_base_out(
+                        base_reserve,
+                        quote_reserve,
+                        base_amount_out,
+                        &FEE_BPS,
+                    )
+                    .unwrap();
+                    let quote_in = pumpswap_buy_quote_in(
+                        base_reserve,
+                        quote_reserve,
+                        exact_out.amount_in,
+                        &FEE_BPS,
+                    )
+                    .unwrap();
+
+                    assert!(quote_in.amount_out >= base_amount_out);
+                    assert!(quote_in.amount_out - base_amount_out <= FEE_BPS.len() as u64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn buy_input_stays_within_the_fee_rounding() {
+        for base_reserve in RESERVES {
+            for quote_reserve in RESERVES {
+                for quote_amount_in in AMOUNTS {
+                    let Ok(quote) = pumpswap_buy_quote_in(
+                        base_reserve,
+                        quote_reserve,
+                        quote_amount_in,
+                        &FEE_BPS,
+                    ) else {
+                        continue;
+                    };
+                    assert!(quote.amount_in <= quote_amount_in + FEE_BPS.len() as u64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fees_round_up() {
+        // Each fee rounds up on its own: 1 lamport owes one of each
+        assert_eq!(fees_on(1, &FEE_BPS), Ok(3));
+        assert_eq!(fees_on(0, &FEE_BPS), Ok(0));
+        for amount in AMOUNTS {
+            let exact: u128 = FEE_BPS
+                .iter()
+                .map(|bps| amount as u128 * *bps as u128)
+                .sum();
+            let fee = fees_on(amount, &FEE_BPS).unwrap() as u128;
+            assert!(fee * BASIS_POINTS >= exact, "fee on {}", amount);
+            assert!(fee * BASIS_POINTS < exact + FEE_BPS.len() as u128 * BASIS_POINTS);
+        }
+
+        let sell =
+            pumpswap_sell_base_in(206_900_000_000_000, 84_000_000_000, 5_000, &FEE_BPS).unwrap();
+        assert_eq!(sell.fee_amount, 3);
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert_eq!(
+            pumpswap_buy_base_out(u64::MAX, u64::MAX, u64::MAX - 1, &FEE_BPS),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            fees_on(u64::MAX, &[BASIS_POINTS as u64, BASIS_POINTS as u64]),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            pumpswap_buy_base_out(1_000, u64::MAX, 999, &FEE_BPS),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            pumpswap_buy_base_out(1_000, 1_000, 1_000, &FEE_BPS),
+            Err(QuoteError::InsufficientLiquidity)
+        );
+        assert!(pumpswap_sell_base_in(u64::MAX, u64::MAX, u64::MAX, &[u64::MAX]).is_err());
+    }
+
+    /// Worked by hand through the program's arithmetic for a pool of
+    /// 206.9M tokens of 6 decimals against 84 SOL.
+    /// They pin the rounding but, not being amounts read off mainnet
+    /// transactions, can't show the program agrees.
+    #[test]
+    fn hand_worked_vectors() {
+        let (base, quote) = (206_900_000_000_000, 84_000_000_000);
+
+        let buy = pumpswap_buy_base_out(base, quote, 1_000_000_000_000, &FEE_BPS).unwrap();
+        assert_eq!(
+            buy,
+            SwapQuote {
+                amount_in: 409_188_929,
+                amount_out: 1_000_000_000_000,
+                fee_amount: 1_223_897,
+            }
+        );
+
+        let buy_in = pumpswap_buy_quote_in(base, quote, 500_000_000, &FEE_BPS).unwrap();
+        assert_eq!(
+            buy_in,
+            SwapQuote {
+                amount_in: 500_000_001,
+                amount_out: 1_220_620_161_040,
+                fee_amount: 1_495_515,
+            }
+        );
+
+        let sell = pumpswap_sell_base_in(base, quote, 3_000_000_000_000, &FEE_BPS).unwrap();
+        assert_eq!(
+            sell,
+            SwapQuote {
+                amount_in: 3_000_000_000_000,
+                amount_out: 1_196_969_984,
+                fee_amount: 3_601_716,
+            }
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{
+    constant_product_amount_in_ratio, constant_product_amount_out, to_u64, LiquidityStateLayoutV4,
+    QuoteError, SwapQuote,
+};
+
+/// Raydium AMM v4 `swap_base_in`: the fee is taken from the input, rounded the
+/// program's way, before the constant-product step.
+///
+/// The program charges `swap_fee_numerator / swap_fee_denominator` of the pool
+/// state; `trade_fee_*` only splits that fee between LPs and the protocol.
+pub fn raydium_amm_swap_base_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<SwapQuote, QuoteError> {
+    if amount_in == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let fee = to_u64(raydium_ceil_div(
+        amount_in as u128 * fee_numerator as u128,
+        fee_denominator as u128,
+    )?)?;
+    let amount_out = constant_product_amount_out(
+        reserve_in,
+        reserve_out,
+        amount_in.checked_sub(fee).ok_or(QuoteError::Overflow)?,
+    )?;
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount: fee,
+    })
+}
+
+/// Raydium AMM v4 `swap_base_out`: the fee-less input is grossed up by the fee.
+pub fn raydium_amm_swap_base_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<SwapQuote, QuoteError> {
+    if amount_out == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let (numerator, denominator) =
+        constant_product_amount_in_ratio(reserve_in, reserve_out, amount_out)?;
+    let amount_in_before_fee = raydium_ceil_div(numerator, denominator)?;
+    let amount_in = to_u64(raydium_ceil_div(
+        amount_in_before_fee
+            .checked_mul(fee_denominator as u128)
+            .ok_or(QuoteError::Overflow)?,
+        fee_denominator.saturating_sub(fee_numerator) as u128,
+    )?)?;
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount: amount_in - amount_in_before_fee as u64,
+    })
+}
+
+/// Vault balances less the PnL the pool has not taken yet, which is what swaps
+/// are priced against. Returns (base, quote).
+pub fn raydium_amm_reserves(
+    state: &LiquidityStateLayoutV4,
+    base_vault_amount: u64,
+    quote_vault_amount: u64,
+) -> (u64, u64) {
+    (
+        base_vault_amount.saturating_sub(state.base_need_take_pnl),
+        quote_vault_amount.saturating_sub(state.quote_need_take_pnl),
+    )
+}
+
+/// The program's `checked_ceil_div`: rounds up, except that quotients below
+/// one round to the nearest integer.
+fn raydium_ceil_div(numerator: u128, denominator: u128) -> Result<u128, QuoteError> {
+    if denominator == 0 {
+        return Err(QuoteError::Overflow);
+    }
+
+    let quotient = numerator / denominator;
+    if quotient == 0 {
+        return Ok((numerator * 2 >= denominator && numerator > 0) as u128);
+    }
+
+    Ok(quotient + !numerator.is_multiple_of(denominator) as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The 0.25% every v4 pool charges.
+    const FEE: (u64, u64) = (25, 10_000);
+
+    /// Grid the invariants below are checked over, edges included.
+    const RESERVES: [u64; 6] = [
+        1_000,
+        987_654,
+        3_000_000_000,
+        85_000_000_000,
+        310_000_000_000_000,
+        u64::MAX / 4,
+    ];
+    const AMOUNTS: [u64; 6] = [1, 399, 10_000, 123_456_789, 5_000_000_000, u64::MAX / 8];
+
+    #[test]
+    fn output_never_exceeds_the_reserve() {
+        for reserve_in in RESERVES {
+            for reserve_out in RESERVES {
+                for amount_in in AMOUNTS {
+                    let quote =
+                        raydium_amm_swap_base_in(reserve_in, reserve_out, amount_in, FEE.0, FEE.1)
+                            .unwrap();
+                    assert!(quote.amount_out < reserve_out);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_round_trips_through_exact_in() {
+        for reserve_in in RESERVES {
+            // At most one output unit per input unit, so one unit of input
+            // rounding is at most one unit of output
+            for reserve_out in RESERVES.into_iter().filter(|r| *r <= reserve_in) {
+                for amount_out in AMOUNTS.into_iter().filter(|a| *a < reserve_out) {
+                    let Ok(exact_out) = raydium_amm_swap_base_out(
+                        reserve_in,
+                        reserve_out,
+                        amount_out,
+                        FEE.0,
+                        FEE.1,
+                    ) else {
+                        continue;
+                    };
+                    let exact_in = raydium_amm_swap_base_in(
+                        reserve_in,
+                        reserve_out,
+                        exact_out.amount_in,
+                        FEE.0,
+                        FEE.1,
+                    )
+                    .unwrap();
+
+                    assert!(exact_in.amount_out >= amount_out);
+                    assert!(exact_in.amount_out - amount_out <= 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_in_output_costs_no_more_than_the_input() {
+        for reserve_in in RESERVES {
+            for reserve_out in RESERVES {
+                // Inputs that owe at least one unit of fee, see
+                // `sub_unit_fees_round_to_nearest`
+                for amount_in in AMOUNTS
+                    .into_iter()
+                    .filter(|a| *a as u128 * FEE.0 as u128 >= FEE.1 as u128)
+                {
+                    let exact_in =
+                        raydium_amm_swap_base_in(reserve_in, reserve_out, amount_in, FEE.0, FEE.1)
+                            .unwrap();
+                    if exact_in.amount_out == 0 {
+                        continue;
+                    }
+                    let exact_out = raydium_amm_swap_base_out(
+                        reserve_in,
+                        reserve_out,
+                        exact_in.amount_out,
+                        FEE.0,
+                        FEE.1,
+                    )
+                    .unwrap();
+
+                    assert!(exact_out.amount_in <= amount_in);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fees_round_up() {
+        for amount_in in AMOUNTS
+            .into_iter()
+            .filter(|a| *a as u128 * FEE.0 as u128 >= FEE.1 as u128)
+        {
+            let quote = raydium_amm_swap_base_in(
+                85_000_000_000,
+                310_000_000_000_000,
+                amount_in,
+                FEE.0,
+                FEE.1,
+            )
+            .unwrap();
+            let exact_fee = amount_in as u128 * FEE.0 as u128;
+            assert_eq!(
+                quote.fee_amount as u128,
+                exact_fee.div_ceil(FEE.1 as u128),
+                "fee on {}",
+                amount_in
+            );
+        }
+
+        let quote =
+            raydium_amm_swap_base_out(85_000_000_000, 310_000_000_000_000, 10_000, FEE.0, FEE.1)
+                .unwrap();
+        let before_fee = quote.amount_in - quote.fee_amount;
+        assert!(quote.fee_amount as u128 * FEE.1 as u128 >= before_fee as u128 * FEE.0 as u128);
+    }
+
+    #[test]
+    fn sub_unit_fees_round_to_nearest() {
+        // The program's `checked_ceil_div` rounds quotients below one to the
+        // nearest integer rather than up
+        let quote = raydium_amm_swap_base_in(1_000_000, 1_000_000, 199, FEE.0, FEE.1).unwrap();
+        assert_eq!(quote.fee_amount, 0);
+        let quote = raydium_amm_swap_base_in(1_000_000, 1_000_000, 200, FEE.0, FEE.1).unwrap();
+        assert_eq!(quote.fee_amount, 1);
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert_eq!(
+            raydium_amm_swap_base_in(1_000, 1_000, 1_000, 2, 1),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            raydium_amm_swap_base_in(1_000, 1_000, 1_000, FEE.0, 0),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            raydium_amm_swap_base_out(1_000, 1_000, 10, FEE.1, FEE.1),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            raydium_amm_swap_base_out(u64::MAX, u64::MAX, u64::MAX - 1, FEE.0, FEE.1),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            raydium_amm_swap_base_in(u64::MAX, u64::MAX, u64::MAX, u64::MAX, 1),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            raydium_amm_swap_base_out(1_000, 1_000, 1_000, FEE.0, FEE.1),
+            Err(QuoteError::InsufficientLiquidity)
+        );
+    }
+
+    /// Worked by hand through the program's arithmetic for a pool of
+    /// 85 SOL against 310M tokens of 6 decimals.
+    /// They pin the rounding but, not being amounts read off mainnet
+    /// transactions, can't show the program agrees.
+    #[test]
+    fn hand_worked_vectors() {
+        let (sol, token) = (85_000_000_000, 310_000_000_000_000);
+
+        let buy = raydium_amm_swap_base_in(sol, token, 1_500_000_000, FEE.0, FEE.1).unwrap();
+        assert_eq!(
+            buy,
+            SwapQuote {
+                amount_in: 1_500_000_000,
+                amount_out: 5_362_515_715_999,
+                fee_amount: 3_750_000,
+            }
+        );
+
+        let sell = raydium_amm_swap_base_in(token, sol, 2_000_000_000_000, FEE.0, FEE.1).unwrap();
+        assert_eq!(
+            sell,
+            SwapQuote {
+                amount_in: 2_000_000_000_000,
+                amount_out: 543_518_325,
+                fee_amount: 5_000_000_000,
+            }
+        );
+
+        let buy_exact =
+            raydium_amm_swap_base_out(sol, token, 1_000_000_000_000, FEE.0, FEE.1).unwrap();
+        assert_eq!(
+            buy_exact,
+            SwapQuote {
+                amount_in: 275_770_333,
+                amount_out: 1_000_000_000_000,
+                fee_amount: 689_426,
+            }
+        );
+    }
+}
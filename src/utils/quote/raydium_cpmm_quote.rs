@@ -0,0 +1,245 @@
+use crate::{
+    ceil_div, constant_product_amount_in_ratio, constant_product_amount_out, to_u64, CpmmPoolState,
+    QuoteError, SwapQuote,
+};
+
+/// `trade_fee_rate` of the amm config is in millionths.
+pub const CPMM_FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+
+/// CPMM `swap_base_input`: the trade fee, rounded up, is taken from the input.
+pub fn cpmm_swap_base_input(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    trade_fee_rate: u64,
+) -> Result<SwapQuote, QuoteError> {
+    if amount_in == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let fee = to_u64(ceil_div(
+        amount_in as u128 * trade_fee_rate as u128,
+        CPMM_FEE_RATE_DENOMINATOR as u128,
+    )?)?;
+    let amount_out = constant_product_amount_out(
+        reserve_in,
+        reserve_out,
+        amount_in.checked_sub(fee).ok_or(QuoteError::Overflow)?,
+    )?;
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount: fee,
+    })
+}
+
+/// CPMM `swap_base_output`: the fee-less input, rounded up, is grossed up by
+/// the trade fee.
+pub fn cpmm_swap_base_output(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    trade_fee_rate: u64,
+) -> Result<SwapQuote, QuoteError> {
+    if amount_out == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+
+    let (numerator, denominator) =
+        constant_product_amount_in_ratio(reserve_in, reserve_out, amount_out)?;
+    let amount_in_before_fee = ceil_div(numerator, denominator)?;
+    let amount_in = to_u64(ceil_div(
+        amount_in_before_fee
+            .checked_mul(CPMM_FEE_RATE_DENOMINATOR as u128)
+            .ok_or(QuoteError::Overflow)?,
+        CPMM_FEE_RATE_DENOMINATOR.saturating_sub(trade_fee_rate) as u128,
+    )?)?;
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount: amount_in - amount_in_before_fee as u64,
+    })
+}
+
+/// Vault balances less the protocol and fund fees they still hold, which is
+/// what swaps are priced against. Returns (token_0, token_1).
+pub fn cpmm_reserves(
+    state: &CpmmPoolState,
+    vault_0_amount: u64,
+    vault_1_amount: u64,
+) -> (u64, u64) {
+    (
+        vault_0_amount
+            .saturating_sub(state.protocol_fees_token_0)
+            .saturating_sub(state.fund_fees_token_0),
+        vault_1_amount
+            .saturating_sub(state.protocol_fees_token_1)
+            .saturating_sub(state.fund_fees_token_1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The 0.25% tier of the amm configs.
+    const FEE_RATE: u64 = 2_500;
+
+    /// Grid the invariants below are checked over, edges included.
+    const RESERVES: [u64; 6] = [
+        1_000,
+        987_654,
+        3_000_000_000,
+        42_000_000_000,
+        1_250_000_000_000,
+        u64::MAX / 4,
+    ];
+    const AMOUNTS: [u64; 6] = [1, 399, 10_000, 123_456_789, 5_000_000_000, u64::MAX / 8];
+
+    #[test]
+    fn output_never_exceeds_the_reserve() {
+        for reserve_in in RESERVES {
+            for reserve_out in RESERVES {
+                for amount_in in AMOUNTS {
+                    let quote =
+                        cpmm_swap_base_input(reserve_in, reserve_out, amount_in, FEE_RATE).unwrap();
+                    assert!(quote.amount_out < reserve_out);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_round_trips_through_exact_in() {
+        for reserve_in in RESERVES {
+            // At most one output unit per input unit, so one unit of input
+            // rounding is at most one unit of output
+            for reserve_out in RESERVES.into_iter().filter(|r| *r <= reserve_in) {
+                for amount_out in AMOUNTS.into_iter().filter(|a| *a < reserve_out) {
+                    let Ok(exact_out) =
+                        cpmm_swap_base_output(reserve_in, reserve_out, amount_out, FEE_RATE)
+                    else {
+                        continue;
+                    };
+                    let exact_in = cpmm_swap_base_input(
+                        reserve_in,
+                        reserve_out,
+                        exact_out.amount_in,
+                        FEE_RATE,
+                    )
+                    .unwrap();
+
+                    assert!(exact_in.amount_out >= amount_out);
+                    assert!(exact_in.amount_out - amount_out <= 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_in_output_costs_no_more_than_the_input() {
+        for reserve_in in RESERVES {
+            for reserve_out in RESERVES {
+                for amount_in in AMOUNTS {
+                    let exact_in =
+                        cpmm_swap_base_input(reserve_in, reserve_out, amount_in, FEE_RATE).unwrap();
+                    if exact_in.amount_out == 0 {
+                        continue;
+                    }
+                    let exact_out = cpmm_swap_base_output(
+                        reserve_in,
+                        reserve_out,
+                        exact_in.amount_out,
+                        FEE_RATE,
+                    )
+                    .unwrap();
+
+                    assert!(exact_out.amount_in <= amount_in);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fees_round_up() {
+        for amount_in in AMOUNTS {
+            let quote =
+                cpmm_swap_base_input(42_000_000_000, 1_250_000_000_000, amount_in, FEE_RATE)
+                    .unwrap();
+            let exact_fee = amount_in as u128 * FEE_RATE as u128;
+            assert_eq!(
+                quote.fee_amount as u128,
+                exact_fee.div_ceil(CPMM_FEE_RATE_DENOMINATOR as u128),
+                "fee on {}",
+                amount_in
+            );
+        }
+
+        for amount_out in [1, 399, 10_000] {
+            let quote =
+                cpmm_swap_base_output(42_000_000_000, 1_250_000_000_000, amount_out, FEE_RATE)
+                    .unwrap();
+            let before_fee = quote.amount_in - quote.fee_amount;
+            assert!(
+                quote.fee_amount as u128 * CPMM_FEE_RATE_DENOMINATOR as u128
+                    >= before_fee as u128 * FEE_RATE as u128
+            );
+            assert!(quote.fee_amount > 0);
+        }
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert_eq!(
+            cpmm_swap_base_input(1_000, 1_000, 1_000, 2 * CPMM_FEE_RATE_DENOMINATOR),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            cpmm_swap_base_output(1_000, 1_000, 10, CPMM_FEE_RATE_DENOMINATOR),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            cpmm_swap_base_output(u64::MAX, u64::MAX, u64::MAX - 1, FEE_RATE),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            cpmm_swap_base_input(u64::MAX, u64::MAX, u64::MAX, u64::MAX),
+            Err(QuoteError::Overflow)
+        );
+        assert_eq!(
+            cpmm_swap_base_output(1_000, 1_000, 1_000, FEE_RATE),
+            Err(QuoteError::InsufficientLiquidity)
+        );
+    }
+
+    /// Worked by hand through the program's arithmetic for a pool of
+    /// 42 SOL against 1.25M tokens of 6 decimals.
+    /// They pin the rounding but, not being amounts read off mainnet
+    /// transactions, can't show the program agrees.
+    #[test]
+    fn hand_worked_vectors() {
+        let (sol, token) = (42_000_000_000, 1_250_000_000_000);
+
+        let buy = cpmm_swap_base_input(sol, token, 250_000_000, FEE_RATE).unwrap();
+        assert_eq!(
+            buy,
+            SwapQuote {
+                amount_in: 250_000_000,
+                amount_out: 7_378_067_722,
+                fee_amount: 625_000,
+            }
+        );
+
+        let buy_exact = cpmm_swap_base_output(sol, token, 5_000_000_000, FEE_RATE).unwrap();
+        assert_eq!(
+            buy_exact,
+            SwapQuote {
+                amount_in: 169_097_443,
+                amount_out: 5_000_000_000,
+                fee_amount: 422_744,
+            }
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+/// A swap simulated against decoded pool state, in raw units of the input /
+/// output mints. `amount_in` includes fees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QuoteError {
+    #[error("swap amount is zero")]
+    ZeroAmount,
+
+    #[error("swap runs past the loaded {0}")]
+    ArraysExhausted(&'static str),
+
+    #[error("swap would drain the pool")]
+    InsufficientLiquidity,
+
+    #[error("swap kind not offered by this venue")]
+    Unsupported,
+
+    #[error("pool math overflow")]
+    Overflow,
+}
@@ -3,10 +3,9 @@ use std::str::FromStr;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    system_program,
 };
 
-use crate::RACE_PRO;
+use crate::{RACE_PRO, SYSTEM_PRO};
 
 pub fn get_race_ix(payer: Pubkey, timestamp: u64) -> Instruction {
     // Program ID of the smart contract
@@ -23,15 +22,13 @@ pub fn get_race_ix(payer: Pubkey, timestamp: u64) -> Instruction {
     data.extend_from_slice(&instruction_discriminator); // Append the instruction discriminator
     data.extend_from_slice(&timestamp.to_le_bytes()); // Append the timestamp argument
 
-    let instruction = Instruction::new_with_bytes(
+    Instruction::new_with_bytes(
         program_id,
         &data,
         vec![
             AccountMeta::new(race_id_pda, false), // race_id (PDA) - writable
             AccountMeta::new(payer, true),        // payer - writable & signer
-            AccountMeta::new_readonly(system_program::ID, false), // system_program - readonly
+            AccountMeta::new_readonly(Pubkey::from_str_const(SYSTEM_PRO), false), // system_program - readonly
         ],
-    );
-
-    instruction
+    )
 }
@@ -0,0 +1,22 @@
+use borsh::BorshDeserialize;
+
+use crate::{CpmmAmmConfig, CpmmPoolState};
+
+/// Decodes a CPMM `PoolState` account; trailing bytes are ignored.
+pub fn decode_cpmm_pool_state(data: &[u8]) -> std::io::Result<CpmmPoolState> {
+    CpmmPoolState::deserialize(&mut skip_discriminator(data)?)
+}
+
+/// Decodes a CPMM `AmmConfig` account; trailing bytes are ignored.
+pub fn decode_cpmm_amm_config(data: &[u8]) -> std::io::Result<CpmmAmmConfig> {
+    CpmmAmmConfig::deserialize(&mut skip_discriminator(data)?)
+}
+
+pub(crate) fn skip_discriminator(data: &[u8]) -> std::io::Result<&[u8]> {
+    data.get(8..).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "account is shorter than its discriminator",
+        )
+    })
+}
@@ -0,0 +1,3 @@
+pub mod decode_cpmm_state;
+
+pub use decode_cpmm_state::*;
@@ -0,0 +1,176 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::{log, BuyHistoryInfo, TradeStore};
+
+/// Directory of the per-pool JSON trade history files the store replaces.
+pub const JSON_TRADE_HISTORY_DIR: &str = "src/assets/infos/trade_history";
+
+/// Outcome of importing a directory of JSON trade history files.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Pools the store already had a position for.
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Imports every `<pool>.json` trade history file in `dir` into `store`.
+///
+/// Pools already in the store are left untouched, so the import can be
+/// re-run safely; files that fail to parse are reported and skipped.
+pub fn import_json_history(store: &mut TradeStore, dir: &Path) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let entries = fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+
+        let info: BuyHistoryInfo = match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str(&content)?))
+        {
+            Ok(info) => info,
+            Err(e) => {
+                log!(format!("🔴 Skipping {}: {:#}", path.display(), e), "error");
+                summary.failed += 1;
+                continue;
+            }
+        };
+
+        if store.import_position(&info)? {
+            log!(
+                format!(
+                    "Imported {} ({} fills, {} tokens held)",
+                    info.pool_id,
+                    info.transactions.len(),
+                    info.total_ui_token_amount_out
+                ),
+                "update"
+            );
+            summary.imported += 1;
+        } else {
+            log!(
+                format!("{} already in the store, skipped", info.pool_id),
+                "info"
+            );
+            summary.skipped += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// History of a pool holding tokens from two buys, as the JSON files
+    /// were written.
+    const HELD: &str = r#"{
+        "pool_id": "held_pool",
+        "base_mint": "token_mint",
+        "quote_mint": "So11111111111111111111111111111111111111112",
+        "base_vault": "base_vault",
+        "quote_vault": "quote_vault",
+        "token_ata": "token_ata",
+        "symbol": "HELD",
+        "total_amount_in": 3000000,
+        "total_ui_amount_in": 0.003,
+        "total_token_amount_out": 4500000,
+        "total_ui_token_amount_out": 4.5,
+        "take_profit": 0.25,
+        "transactions": [
+            {"signature": "sig1", "amount_in": 1000000, "ui_amount_in": 0.001,
+             "amount_out": 2000000, "ui_amount_out": 2.0},
+            {"signature": "sig2", "amount_in": 2000000, "ui_amount_in": 0.002,
+             "amount_out": 2500000, "ui_amount_out": 2.5}
+        ],
+        "dex": "raydium_amm"
+    }"#;
+
+    /// A pool that was listed but never bought.
+    const FLAT: &str = r#"{
+        "pool_id": "flat_pool",
+        "base_mint": "So11111111111111111111111111111111111111112",
+        "quote_mint": "other_mint",
+        "base_vault": "", "quote_vault": "", "token_ata": "", "symbol": "FLAT",
+        "total_amount_in": 0, "total_ui_amount_in": 0.0,
+        "total_token_amount_out": 0, "total_ui_token_amount_out": 0.0,
+        "take_profit": 0.1, "transactions": [], "dex": "pump_swap"
+    }"#;
+
+    #[test]
+    fn json_history_becomes_positions_fills_and_lots() {
+        let dir = std::env::temp_dir().join(format!("json-history-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, content) in [
+            ("held_pool.json", HELD),
+            ("flat_pool.json", FLAT),
+            ("broken.json", "{ \"pool_id\": "),
+            ("notes.txt", "not a history file"),
+        ] {
+            fs::write(dir.join(name), content).unwrap();
+        }
+
+        let mut store = TradeStore::open(":memory:").unwrap();
+        let summary = import_json_history(&mut store, &dir).unwrap();
+        assert_eq!(
+            (summary.imported, summary.skipped, summary.failed),
+            (2, 0, 1)
+        );
+
+        let held = store.load_position("held_pool").unwrap();
+        assert_eq!(held.symbol, "HELD");
+        assert_eq!(held.take_profit, 0.25);
+        assert_eq!(held.total_amount_in, 3_000_000);
+        assert_eq!(held.total_token_amount_out, 4_500_000);
+        let fills: Vec<_> = held
+            .transactions
+            .iter()
+            .map(|fill| (fill.signature.as_str(), fill.amount_in, fill.amount_out))
+            .collect();
+        assert_eq!(
+            fills,
+            [
+                ("sig1", 1_000_000, 2_000_000),
+                ("sig2", 2_000_000, 2_500_000)
+            ]
+        );
+
+        // The inventory carries over as one lot at the SOL its buys swapped
+        let position = store.open_position("held_pool").unwrap();
+        assert_eq!(position.lots.len(), 1);
+        assert_eq!(position.token_amount(), 4_500_000);
+        assert_eq!(position.cost_lamports(), 3_000_000);
+        assert_eq!(position.realized_pnl_lamports, 0);
+
+        assert!(store.open_position("flat_pool").unwrap().lots.is_empty());
+        assert_eq!(
+            store.position_among(&["unknown", "flat_pool"]).unwrap(),
+            Some(("flat_pool".to_string(), "other_mint".to_string()))
+        );
+        assert_eq!(
+            store.pool_position("held_pool").unwrap().token_mint,
+            "token_mint"
+        );
+
+        // Re-running leaves the imported pools alone
+        let summary = import_json_history(&mut store, &dir).unwrap();
+        assert_eq!(
+            (summary.imported, summary.skipped, summary.failed),
+            (0, 2, 1)
+        );
+        assert_eq!(
+            store.load_position("held_pool").unwrap().transactions.len(),
+            2
+        );
+        assert_eq!(store.open_position("held_pool").unwrap().lots.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
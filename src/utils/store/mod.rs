@@ -0,0 +1,5 @@
+pub mod import_json_history;
+pub mod trade_store;
+
+pub use import_json_history::*;
+pub use trade_store::*;
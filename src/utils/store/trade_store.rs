@@ -0,0 +1,1255 @@
+use std::{
+    env, fmt, fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Mutex, OnceLock, PoisonError,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+use thiserror::Error;
+
+use crate::{
+    consume_fifo, lamports_to_sol, BuyHistoryInfo, BuyTxHistory, ConfirmedFill, ExitState,
+    GatewayStats, KillSwitch, ObservedSwap, OpenExposure, OpenPosition, PoolPosition, PositionLot,
+    RegionSend, RegionStats, Submission, SwapSide, TxOutcome, NATIVE_MINT,
+};
+
+/// SQLite file holding positions, fills and observed signals.
+pub const TRADE_DB_PATH: &str = "src/assets/infos/trades.db";
+
+/// Same schema as `TRADE_DB_PATH`, for the simulated fills of `mode.paper_trade`.
+pub const PAPER_TRADE_DB_PATH: &str = "src/assets/infos/paper_trades.db";
+
+/// Simulated fills of the last `replay` run.
+pub const REPLAY_TRADE_DB_PATH: &str = "src/assets/infos/replay_trades.db";
+
+/// How long a writer waits for another process (pre_start, the migration
+/// tool) to release the database.
+const BUSY_TIMEOUT_MS: u64 = 5_000;
+
+/// Schema changes in order; `PRAGMA user_version` counts the ones applied.
+const MIGRATIONS: &[&str] = &[
+    SCHEMA_V1, SCHEMA_V2, SCHEMA_V3, SCHEMA_V4, SCHEMA_V5, SCHEMA_V6,
+];
+
+const SCHEMA_V1: &str = "
+CREATE TABLE IF NOT EXISTS positions (
+    pool_id TEXT PRIMARY KEY,
+    base_mint TEXT NOT NULL,
+    quote_mint TEXT NOT NULL,
+    base_vault TEXT NOT NULL,
+    quote_vault TEXT NOT NULL,
+    token_ata TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    dex TEXT NOT NULL,
+    take_profit REAL NOT NULL,
+    total_amount_in INTEGER NOT NULL,
+    total_ui_amount_in REAL NOT NULL,
+    total_token_amount_out INTEGER NOT NULL,
+    total_ui_token_amount_out REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fills (
+    pool_id TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    amount_in INTEGER NOT NULL,
+    ui_amount_in REAL NOT NULL,
+    amount_out INTEGER NOT NULL,
+    ui_amount_out REAL NOT NULL,
+    PRIMARY KEY (pool_id, signature)
+);
+CREATE TABLE IF NOT EXISTS signals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    observed_at_ms INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    pool_id TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    dex TEXT NOT NULL,
+    side TEXT NOT NULL,
+    amount_in INTEGER NOT NULL,
+    amount_out INTEGER NOT NULL,
+    price_before REAL NOT NULL,
+    price_after REAL NOT NULL,
+    signer TEXT
+);
+CREATE INDEX IF NOT EXISTS signals_pool ON signals (pool_id, slot);
+";
+
+/// FIFO lots and realized PnL; inventory recorded before becomes one lot
+/// costed at the SOL its buys swapped.
+const SCHEMA_V2: &str = "
+ALTER TABLE positions ADD COLUMN realized_pnl_lamports INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fills ADD COLUMN side TEXT NOT NULL DEFAULT 'buy';
+ALTER TABLE fills ADD COLUMN wallet_lamports INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fills ADD COLUMN network_fee INTEGER NOT NULL DEFAULT 0;
+CREATE TABLE lots (
+    pool_id TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    token_amount INTEGER NOT NULL,
+    cost_lamports INTEGER NOT NULL,
+    remaining_token_amount INTEGER NOT NULL,
+    remaining_cost_lamports INTEGER NOT NULL,
+    PRIMARY KEY (pool_id, signature)
+);
+INSERT INTO lots
+SELECT pool_id, 'carried-over', total_token_amount_out, total_amount_in,
+       total_token_amount_out, total_amount_in
+FROM positions WHERE total_token_amount_out != 0;
+";
+
+/// When each lot was opened, for time-based exits; lots recorded before count
+/// from the upgrade.
+const SCHEMA_V3: &str = "
+ALTER TABLE lots ADD COLUMN opened_at_ms INTEGER NOT NULL DEFAULT 0;
+UPDATE lots SET opened_at_ms = CAST(unixepoch('subsec') * 1000 AS INTEGER);
+";
+
+/// When each fill was booked and what it realized, for the daily loss
+/// limit, and the kill switch the risk manager trips.
+const SCHEMA_V4: &str = "
+ALTER TABLE fills ADD COLUMN booked_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fills ADD COLUMN realized_pnl_lamports INTEGER NOT NULL DEFAULT 0;
+CREATE INDEX fills_booked_at ON fills (booked_at_ms);
+CREATE TABLE kill_switch (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    reason TEXT NOT NULL,
+    tripped_at_ms INTEGER NOT NULL
+);
+";
+
+const SCHEMA_V5: &str = "
+CREATE TABLE submissions (
+    signature TEXT PRIMARY KEY,
+    intent_tag TEXT NOT NULL,
+    pool_id TEXT NOT NULL,
+    gateway TEXT NOT NULL,
+    tip_lamports INTEGER NOT NULL,
+    trigger_slot INTEGER,
+    submitted_at_ms INTEGER NOT NULL,
+    outcome TEXT NOT NULL DEFAULT 'pending',
+    error TEXT,
+    landed_slot INTEGER,
+    fee_lamports INTEGER,
+    confirmed_at_ms INTEGER
+);
+CREATE INDEX submissions_submitted_at ON submissions (submitted_at_ms);
+CREATE TABLE submission_sends (
+    signature TEXT NOT NULL REFERENCES submissions (signature),
+    region TEXT NOT NULL,
+    sent_at_ms INTEGER NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    error TEXT,
+    PRIMARY KEY (signature, region)
+);
+";
+
+/// What the exit rules remember about each position, so trailing stops and
+/// scale-outs survive restarts.
+const SCHEMA_V6: &str = "
+ALTER TABLE positions ADD COLUMN exit_peak_lamports INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE positions ADD COLUMN exit_peak_token_amount INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE positions ADD COLUMN exit_scale_outs_done INTEGER NOT NULL DEFAULT 0;
+";
+
+/// Signature of the lot an imported position's inventory is carried in.
+const CARRIED_OVER_LOT: &str = "carried-over";
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("trade store: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("trade store: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("no position recorded for pool {0}, run pre_start for it")]
+    UnknownPool(String),
+}
+
+/// Positions, fills and observed signals in one SQLite database.
+///
+/// Every update runs in its own transaction on a WAL journal with full
+/// syncs, so concurrent confirmations can't lose each other's writes and a
+/// crash leaves either the old or the new state on disk. Inventory is kept
+/// as FIFO lots, see `record_fill`.
+pub struct TradeStore {
+    conn: Connection,
+}
+
+/// Book the process-wide store keeps: real trades, or the simulated fills
+/// of paper trading and of replays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeAccount {
+    Live,
+    Paper,
+    Replay,
+}
+
+impl TradeAccount {
+    pub fn db_path(self) -> &'static str {
+        match self {
+            TradeAccount::Live => TRADE_DB_PATH,
+            TradeAccount::Paper => PAPER_TRADE_DB_PATH,
+            TradeAccount::Replay => REPLAY_TRADE_DB_PATH,
+        }
+    }
+
+    /// Whether intents are filled on paper rather than submitted.
+    pub fn is_simulated(self) -> bool {
+        self != TradeAccount::Live
+    }
+}
+
+impl fmt::Display for TradeAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeAccount::Live => write!(f, "live"),
+            TradeAccount::Paper => write!(f, "paper"),
+            TradeAccount::Replay => write!(f, "replay"),
+        }
+    }
+}
+
+static TRADE_ACCOUNT: OnceLock<TradeAccount> = OnceLock::new();
+
+/// Picks the account of the process-wide store; only the first pick counts,
+/// the account in use is returned.
+pub fn select_trade_account(account: TradeAccount) -> TradeAccount {
+    *TRADE_ACCOUNT.get_or_init(|| account)
+}
+
+/// Account of the process-wide store: the one selected, else the one
+/// `TRADE_ACCOUNT` names (`paper` or `replay`, for the tools), else live.
+pub fn trade_account() -> TradeAccount {
+    *TRADE_ACCOUNT.get_or_init(|| match env::var("TRADE_ACCOUNT").as_deref() {
+        Ok("paper") => TradeAccount::Paper,
+        Ok("replay") => TradeAccount::Replay,
+        _ => TradeAccount::Live,
+    })
+}
+
+/// Process-wide store of `trade_account()`, opened on first use.
+static TRADE_STORE: Mutex<Option<TradeStore>> = Mutex::new(None);
+
+/// Runs `f` on the process-wide store, opening it first if needed. The paper
+/// accounts pick up the pools the live one knows as they open.
+pub fn with_trade_store<T>(
+    f: impl FnOnce(&mut TradeStore) -> Result<T, StoreError>,
+) -> Result<T, StoreError> {
+    let mut store = TRADE_STORE.lock().unwrap_or_else(PoisonError::into_inner);
+    if store.is_none() {
+        let account = trade_account();
+        let mut opened = TradeStore::open(account.db_path())?;
+        if account.is_simulated() {
+            opened.adopt_pools(TRADE_DB_PATH)?;
+        }
+        *store = Some(opened);
+    }
+
+    f(store.as_mut().expect("store opened above"))
+}
+
+/// Serializes the tests that use the process-wide store.
+#[cfg(test)]
+static TEST_STORE: Mutex<()> = Mutex::new(());
+
+/// Puts an empty in-memory database in place of the process-wide store and
+/// of the position book read from it, for tests; the store is theirs for as
+/// long as they hold the guard.
+#[cfg(test)]
+pub(crate) fn use_test_trade_store() -> std::sync::MutexGuard<'static, ()> {
+    let guard = TEST_STORE.lock().unwrap_or_else(PoisonError::into_inner);
+    *TRADE_STORE.lock().unwrap_or_else(PoisonError::into_inner) =
+        Some(TradeStore::open(":memory:").expect("in-memory store"));
+    crate::clear_position_book();
+    guard
+}
+
+impl TradeStore {
+    /// Opens (or creates) the database at `path` and brings its schema up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(std::time::Duration::from_millis(BUSY_TIMEOUT_MS))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "FULL")?;
+        let mut store = TradeStore { conn };
+        store.migrate()?;
+
+        Ok(store)
+    }
+
+    /// Applies the migrations the database has not seen yet, each in its own transaction.
+    fn migrate(&mut self) -> Result<(), StoreError> {
+        let version: usize = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self
+                .conn
+                .transaction_with_behavior(TransactionBehavior::Immediate)?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Records the pool's keys and take profit, creating an empty position the
+    /// first time; an existing position keeps its lots, fills and PnL.
+    pub fn save_position(&mut self, info: &BuyHistoryInfo) -> Result<(), StoreError> {
+        self.conn.execute(
+            "INSERT INTO positions
+                (pool_id, base_mint, quote_mint, base_vault, quote_vault, token_ata, symbol,
+                 dex, take_profit, total_amount_in, total_ui_amount_in,
+                 total_token_amount_out, total_ui_token_amount_out)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, 0, 0, 0)
+             ON CONFLICT (pool_id) DO UPDATE SET
+                base_mint = excluded.base_mint, quote_mint = excluded.quote_mint,
+                base_vault = excluded.base_vault, quote_vault = excluded.quote_vault,
+                token_ata = excluded.token_ata, symbol = excluded.symbol,
+                dex = excluded.dex, take_profit = excluded.take_profit",
+            params![
+                info.pool_id,
+                info.base_mint,
+                info.quote_mint,
+                info.base_vault,
+                info.quote_vault,
+                info.token_ata,
+                info.symbol,
+                info.dex,
+                info.take_profit,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Gives every pool recorded in the store at `path` an empty position
+    /// here, with its keys and take profit; returns how many were added.
+    pub fn adopt_pools(&mut self, path: impl AsRef<Path>) -> Result<usize, StoreError> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Ok(0);
+        }
+
+        self.conn.execute(
+            "ATTACH DATABASE ?1 AS adopted",
+            params![path.to_string_lossy()],
+        )?;
+        let adopted = self.conn.execute(
+            "INSERT OR IGNORE INTO positions
+                (pool_id, base_mint, quote_mint, base_vault, quote_vault, token_ata, symbol,
+                 dex, take_profit, total_amount_in, total_ui_amount_in,
+                 total_token_amount_out, total_ui_token_amount_out)
+             SELECT pool_id, base_mint, quote_mint, base_vault, quote_vault, token_ata, symbol,
+                    dex, take_profit, 0, 0, 0, 0
+             FROM adopted.positions",
+            [],
+        );
+        self.conn.execute_batch("DETACH DATABASE adopted")?;
+
+        Ok(adopted?)
+    }
+
+    /// Records `info` unless the pool already has a position; returns whether it was added.
+    pub fn import_position(&mut self, info: &BuyHistoryInfo) -> Result<bool, StoreError> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let inserted = insert_position(&tx, info)?;
+        tx.commit()?;
+
+        Ok(inserted)
+    }
+
+    /// The pool's position with its fills, in the order they were recorded.
+    pub fn load_position(&self, pool_id: &str) -> Result<BuyHistoryInfo, StoreError> {
+        let mut info = self
+            .conn
+            .query_row(
+                "SELECT pool_id, base_mint, quote_mint, base_vault, quote_vault, token_ata,
+                        symbol, dex, take_profit, total_amount_in, total_ui_amount_in,
+                        total_token_amount_out, total_ui_token_amount_out
+                 FROM positions WHERE pool_id = ?1",
+                [pool_id],
+                position_from_row,
+            )
+            .optional()?
+            .ok_or_else(|| StoreError::UnknownPool(pool_id.to_string()))?;
+
+        let mut fills = self.conn.prepare(
+            "SELECT signature, amount_in, ui_amount_in, amount_out, ui_amount_out
+             FROM fills WHERE pool_id = ?1 ORDER BY rowid",
+        )?;
+        info.transactions = fills
+            .query_map([pool_id], |row| {
+                Ok(BuyTxHistory {
+                    signature: row.get(0)?,
+                    amount_in: from_sql_u64(row.get(1)?),
+                    ui_amount_in: row.get(2)?,
+                    amount_out: from_sql_u64(row.get(3)?),
+                    ui_amount_out: row.get(4)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(info)
+    }
+
+    /// Pool and traded mint of the first position among `accounts`, which
+    /// identifies the pool one of our transactions swapped on.
+    pub fn position_among(
+        &self,
+        accounts: &[&str],
+    ) -> Result<Option<(String, String)>, StoreError> {
+        let mut query = self
+            .conn
+            .prepare("SELECT base_mint, quote_mint FROM positions WHERE pool_id = ?1")?;
+
+        for account in accounts {
+            let mints: Option<(String, String)> = query
+                .query_row([account], |row| Ok((row.get(0)?, row.get(1)?)))
+                .optional()?;
+            if let Some((base_mint, quote_mint)) = mints {
+                let token_mint = if base_mint == NATIVE_MINT {
+                    quote_mint
+                } else {
+                    base_mint
+                };
+                return Ok(Some((account.to_string(), token_mint)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Open lots of the pool, oldest first, with its realized PnL.
+    pub fn open_position(&self, pool_id: &str) -> Result<OpenPosition, StoreError> {
+        let realized_pnl_lamports = self
+            .conn
+            .query_row(
+                "SELECT realized_pnl_lamports FROM positions WHERE pool_id = ?1",
+                [pool_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| StoreError::UnknownPool(pool_id.to_string()))?;
+
+        Ok(OpenPosition {
+            lots: open_lots(&self.conn, pool_id)?,
+            realized_pnl_lamports,
+        })
+    }
+
+    /// Open position of every recorded pool, flat ones included, for the
+    /// position book.
+    pub fn pool_positions(&self) -> Result<Vec<PoolPosition>, StoreError> {
+        let mut query = self
+            .conn
+            .prepare("SELECT pool_id FROM positions ORDER BY pool_id")?;
+        let pools: Vec<String> = query
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        pools
+            .iter()
+            .map(|pool_id| self.pool_position(pool_id))
+            .collect()
+    }
+
+    /// Open position of the pool with the mint it trades.
+    pub fn pool_position(&self, pool_id: &str) -> Result<PoolPosition, StoreError> {
+        let (base_mint, quote_mint): (String, String) = self
+            .conn
+            .query_row(
+                "SELECT base_mint, quote_mint FROM positions WHERE pool_id = ?1",
+                [pool_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| StoreError::UnknownPool(pool_id.to_string()))?;
+
+        Ok(PoolPosition {
+            pool_id: pool_id.to_string(),
+            token_mint: if base_mint == NATIVE_MINT {
+                quote_mint
+            } else {
+                base_mint
+            },
+            position: self.open_position(pool_id)?,
+        })
+    }
+
+    /// Books a confirmed fill in one transaction: a buy opens a lot at its full
+    /// cost, a sell closes lots oldest first and realizes the difference.
+    ///
+    /// A signature is only booked once, however many landing services report
+    /// it; returns `None` for a repeat, else the PnL realized by the fill.
+    pub fn record_fill(
+        &mut self,
+        pool_id: &str,
+        fill: &ConfirmedFill,
+    ) -> Result<Option<i64>, StoreError> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let realized_pnl_lamports: i64 = tx
+            .query_row(
+                "SELECT realized_pnl_lamports FROM positions WHERE pool_id = ?1",
+                [pool_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| StoreError::UnknownPool(pool_id.to_string()))?;
+
+        let decimals = 10f64.powi(fill.token_decimals as i32);
+        let (side, amount_in, ui_amount_in, amount_out, ui_amount_out) = match fill.side {
+            SwapSide::Buy => (
+                "buy",
+                fill.lamports(),
+                lamports_to_sol(fill.lamports()),
+                fill.token_amount,
+                fill.token_amount as f64 / decimals,
+            ),
+            SwapSide::Sell => (
+                "sell",
+                fill.token_amount,
+                fill.token_amount as f64 / decimals,
+                fill.lamports(),
+                lamports_to_sol(fill.lamports()),
+            ),
+        };
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO fills
+                (pool_id, signature, amount_in, ui_amount_in, amount_out, ui_amount_out,
+                 side, wallet_lamports, network_fee, booked_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                pool_id,
+                fill.signature,
+                to_sql_u64(amount_in),
+                ui_amount_in,
+                to_sql_u64(amount_out),
+                ui_amount_out,
+                side,
+                fill.wallet_lamports,
+                to_sql_u64(fill.network_fee),
+                now_ms(),
+            ],
+        )? == 1;
+        if !inserted {
+            return Ok(None);
+        }
+
+        let realized = match fill.side {
+            SwapSide::Buy => {
+                tx.execute(
+                    "INSERT INTO lots
+                        (pool_id, signature, token_amount, cost_lamports,
+                         remaining_token_amount, remaining_cost_lamports, opened_at_ms)
+                     VALUES (?1, ?2, ?3, ?4, ?3, ?4, ?5)",
+                    params![
+                        pool_id,
+                        fill.signature,
+                        to_sql_u64(fill.token_amount),
+                        to_sql_u64(fill.lamports()),
+                        now_ms(),
+                    ],
+                )?;
+                tx.execute(
+                    "UPDATE positions SET
+                        total_amount_in = total_amount_in + ?2,
+                        total_ui_amount_in = total_ui_amount_in + ?3,
+                        total_token_amount_out = total_token_amount_out + ?4,
+                        total_ui_token_amount_out = total_ui_token_amount_out + ?5
+                     WHERE pool_id = ?1",
+                    params![
+                        pool_id,
+                        to_sql_u64(amount_in),
+                        ui_amount_in,
+                        to_sql_u64(amount_out),
+                        ui_amount_out,
+                    ],
+                )?;
+                0
+            }
+            SwapSide::Sell => {
+                let mut lots = open_lots(&tx, pool_id)?;
+                let cost = consume_fifo(&mut lots, fill.token_amount);
+                for lot in &lots {
+                    tx.execute(
+                        "UPDATE lots SET remaining_token_amount = ?3, remaining_cost_lamports = ?4
+                         WHERE pool_id = ?1 AND signature = ?2",
+                        params![
+                            pool_id,
+                            lot.signature,
+                            to_sql_u64(lot.token_amount),
+                            to_sql_u64(lot.cost_lamports),
+                        ],
+                    )?;
+                }
+
+                let realized = fill.wallet_lamports - cost as i64;
+                tx.execute(
+                    "UPDATE positions SET realized_pnl_lamports = ?2 WHERE pool_id = ?1",
+                    params![pool_id, realized_pnl_lamports + realized],
+                )?;
+                tx.execute(
+                    "UPDATE fills SET realized_pnl_lamports = ?3
+                     WHERE pool_id = ?1 AND signature = ?2",
+                    params![pool_id, fill.signature, realized],
+                )?;
+                // Totals and exit state describe the holding since it was last flat
+                if lots.iter().all(|lot| lot.token_amount == 0) {
+                    tx.execute(
+                        "UPDATE positions SET total_amount_in = 0, total_ui_amount_in = 0,
+                                total_token_amount_out = 0, total_ui_token_amount_out = 0,
+                                exit_peak_lamports = 0, exit_peak_token_amount = 0,
+                                exit_scale_outs_done = 0
+                         WHERE pool_id = ?1",
+                        [pool_id],
+                    )?;
+                }
+                realized
+            }
+        };
+        tx.commit()?;
+
+        Ok(Some(realized))
+    }
+
+    /// What the exit rules remember about `pool_id`'s position; the default
+    /// for unknown pools.
+    pub fn exit_state(&self, pool_id: &str) -> Result<ExitState, StoreError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT exit_peak_lamports, exit_peak_token_amount, exit_scale_outs_done
+                 FROM positions WHERE pool_id = ?1",
+                [pool_id],
+                |row| {
+                    Ok(ExitState {
+                        peak_lamports: from_sql_u64(row.get(0)?),
+                        peak_token_amount: from_sql_u64(row.get(1)?),
+                        scale_outs_done: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?
+            .unwrap_or_default())
+    }
+
+    /// Keeps `state` next to `pool_id`'s position while it holds tokens; a
+    /// flat position keeps the empty state its last sell left.
+    pub fn save_exit_state(&self, pool_id: &str, state: &ExitState) -> Result<(), StoreError> {
+        self.conn.execute(
+            "UPDATE positions SET exit_peak_lamports = ?2, exit_peak_token_amount = ?3,
+                    exit_scale_outs_done = ?4
+             WHERE pool_id = ?1 AND EXISTS (
+                 SELECT 1 FROM lots WHERE pool_id = ?1 AND remaining_token_amount != 0
+             )",
+            params![
+                pool_id,
+                to_sql_u64(state.peak_lamports),
+                to_sql_u64(state.peak_token_amount),
+                state.scale_outs_done,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Whether any fill was booked on `pool_id`.
+    pub fn has_fills(&self, pool_id: &str) -> Result<bool, StoreError> {
+        Ok(self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM fills WHERE pool_id = ?1)",
+            [pool_id],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// PnL realized by the fills booked since `since_ms`, in lamports.
+    pub fn realized_pnl_since(&self, since_ms: i64) -> Result<i64, StoreError> {
+        Ok(self.conn.query_row(
+            "SELECT COALESCE(SUM(realized_pnl_lamports), 0) FROM fills WHERE booked_at_ms >= ?1",
+            [since_ms],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Booking time and PnL of every fill since `since_ms` that realized
+    /// any, oldest first.
+    pub fn realized_fills_since(&self, since_ms: i64) -> Result<Vec<(i64, i64)>, StoreError> {
+        let mut query = self.conn.prepare(
+            "SELECT booked_at_ms, realized_pnl_lamports FROM fills
+             WHERE booked_at_ms >= ?1 AND realized_pnl_lamports != 0
+             ORDER BY booked_at_ms",
+        )?;
+        let fills = query
+            .query_map([since_ms], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        Ok(fills)
+    }
+
+    /// Cost basis of every pool still holding tokens.
+    pub fn open_exposures(&self) -> Result<Vec<OpenExposure>, StoreError> {
+        let mut query = self.conn.prepare(
+            "SELECT positions.pool_id, positions.base_mint, positions.quote_mint,
+                    SUM(lots.remaining_cost_lamports)
+             FROM lots JOIN positions ON positions.pool_id = lots.pool_id
+             WHERE lots.remaining_token_amount != 0
+             GROUP BY positions.pool_id",
+        )?;
+        let exposures = query
+            .query_map([], |row| {
+                let base_mint: String = row.get(1)?;
+                let quote_mint: String = row.get(2)?;
+                Ok(OpenExposure {
+                    pool_id: row.get(0)?,
+                    token_mint: if base_mint == NATIVE_MINT {
+                        quote_mint
+                    } else {
+                        base_mint
+                    },
+                    cost_lamports: from_sql_u64(row.get(3)?),
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(exposures)
+    }
+
+    /// The kill switch, if it is tripped.
+    pub fn kill_switch(&self) -> Result<Option<KillSwitch>, StoreError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT reason, tripped_at_ms FROM kill_switch WHERE id = 1",
+                [],
+                |row| {
+                    Ok(KillSwitch {
+                        reason: row.get(0)?,
+                        tripped_at_ms: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Trips the kill switch unless it already is; returns whether this call tripped it.
+    pub fn trip_kill_switch(&self, reason: &str) -> Result<bool, StoreError> {
+        Ok(self.conn.execute(
+            "INSERT OR IGNORE INTO kill_switch (id, reason, tripped_at_ms) VALUES (1, ?1, ?2)",
+            params![reason, now_ms()],
+        )? == 1)
+    }
+
+    /// Clears the kill switch; returns whether it was tripped.
+    pub fn reset_kill_switch(&self) -> Result<bool, StoreError> {
+        Ok(self.conn.execute("DELETE FROM kill_switch", [])? == 1)
+    }
+
+    /// Logs a transaction handed to a gateway; a repeated signature is ignored.
+    pub fn record_submission(&self, submission: &Submission) -> Result<(), StoreError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO submissions
+                (signature, intent_tag, pool_id, gateway, tip_lamports, trigger_slot,
+                 submitted_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                submission.signature,
+                submission.intent_tag.to_string(),
+                submission.pool_id,
+                submission.gateway.name(),
+                to_sql_u64(submission.tip_lamports),
+                submission.trigger_slot.map(to_sql_u64),
+                submission.submitted_at_ms,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Logs one region's answer to a recorded submission.
+    pub fn record_region_send(&self, send: &RegionSend) -> Result<(), StoreError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO submission_sends
+                (signature, region, sent_at_ms, latency_ms, error)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                send.signature,
+                send.region,
+                send.sent_at_ms,
+                to_sql_u64(send.latency_ms),
+                send.error,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Stores what became of a recorded submission.
+    pub fn record_landing(&self, signature: &str, outcome: &TxOutcome) -> Result<(), StoreError> {
+        self.conn.execute(
+            "UPDATE submissions
+             SET outcome = ?2, error = ?3, landed_slot = ?4, fee_lamports = ?5,
+                 confirmed_at_ms = ?6
+             WHERE signature = ?1",
+            params![
+                signature,
+                outcome.label(),
+                outcome.error(),
+                outcome.slot().map(to_sql_u64),
+                outcome.fee().map(to_sql_u64),
+                now_ms(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Landing counts, slot delays and costs per gateway of the submissions
+    /// made since `since_ms`.
+    pub fn gateway_stats(&self, since_ms: i64) -> Result<Vec<GatewayStats>, StoreError> {
+        let mut query = self.conn.prepare(
+            "SELECT gateway,
+                    COUNT(*),
+                    SUM(outcome = 'landed'),
+                    SUM(outcome = 'failed'),
+                    SUM(outcome = 'expired'),
+                    SUM(outcome = 'rejected'),
+                    AVG(CASE WHEN outcome = 'landed' THEN landed_slot - trigger_slot END),
+                    COALESCE(SUM(CASE outcome
+                        WHEN 'landed' THEN fee_lamports + tip_lamports
+                        WHEN 'failed' THEN fee_lamports
+                        ELSE 0 END), 0)
+             FROM submissions
+             WHERE submitted_at_ms >= ?1
+             GROUP BY gateway
+             ORDER BY gateway",
+        )?;
+        let stats = query
+            .query_map([since_ms], |row| {
+                Ok(GatewayStats {
+                    gateway: row.get(0)?,
+                    submissions: from_sql_u64(row.get(1)?),
+                    landed: from_sql_u64(row.get(2)?),
+                    failed: from_sql_u64(row.get(3)?),
+                    expired: from_sql_u64(row.get(4)?),
+                    rejected: from_sql_u64(row.get(5)?),
+                    avg_slot_delay: row.get(6)?,
+                    cost_lamports: from_sql_u64(row.get(7)?),
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(stats)
+    }
+
+    /// Acceptance and response latency per gateway region of the sends made
+    /// since `since_ms`.
+    pub fn region_stats(&self, since_ms: i64) -> Result<Vec<RegionStats>, StoreError> {
+        let mut query = self.conn.prepare(
+            "SELECT submissions.gateway, submission_sends.region,
+                    COUNT(*),
+                    SUM(submission_sends.error IS NULL),
+                    AVG(submission_sends.latency_ms),
+                    MAX(submission_sends.latency_ms)
+             FROM submission_sends
+             JOIN submissions ON submissions.signature = submission_sends.signature
+             WHERE submission_sends.sent_at_ms >= ?1
+             GROUP BY submissions.gateway, submission_sends.region
+             ORDER BY submissions.gateway, submission_sends.region",
+        )?;
+        let stats = query
+            .query_map([since_ms], |row| {
+                Ok(RegionStats {
+                    gateway: row.get(0)?,
+                    region: row.get(1)?,
+                    sends: from_sql_u64(row.get(2)?),
+                    accepted: from_sql_u64(row.get(3)?),
+                    avg_latency_ms: row.get(4)?,
+                    max_latency_ms: from_sql_u64(row.get(5)?),
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(stats)
+    }
+
+    /// Appends an observed swap to the signal log.
+    pub fn record_signal(&self, swap: &ObservedSwap) -> Result<(), StoreError> {
+        self.conn.execute(
+            "INSERT INTO signals
+                (observed_at_ms, slot, signature, pool_id, mint, dex, side,
+                 amount_in, amount_out, price_before, price_after, signer)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                now_ms(),
+                to_sql_u64(swap.slot),
+                swap.signature,
+                swap.pool.to_string(),
+                swap.mint.to_string(),
+                swap.dex.to_string(),
+                match swap.side {
+                    SwapSide::Buy => "buy",
+                    SwapSide::Sell => "sell",
+                },
+                to_sql_u64(swap.amount_in),
+                to_sql_u64(swap.amount_out),
+                swap.price_before(),
+                swap.price_after(),
+                swap.signer.map(|signer| signer.to_string()),
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Inserts the position with its fills and carries its inventory over as
+/// one lot, unless the pool already has a position. Returns whether `info`
+/// was written.
+fn insert_position(tx: &Connection, info: &BuyHistoryInfo) -> Result<bool, StoreError> {
+    let inserted = tx.execute(
+        "INSERT OR IGNORE INTO positions
+            (pool_id, base_mint, quote_mint, base_vault, quote_vault, token_ata, symbol,
+             dex, take_profit, total_amount_in, total_ui_amount_in,
+             total_token_amount_out, total_ui_token_amount_out)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            info.pool_id,
+            info.base_mint,
+            info.quote_mint,
+            info.base_vault,
+            info.quote_vault,
+            info.token_ata,
+            info.symbol,
+            info.dex,
+            info.take_profit,
+            to_sql_u64(info.total_amount_in),
+            info.total_ui_amount_in,
+            to_sql_u64(info.total_token_amount_out),
+            info.total_ui_token_amount_out,
+        ],
+    )? == 1;
+    if !inserted {
+        return Ok(false);
+    }
+
+    for fill in &info.transactions {
+        tx.execute(
+            "INSERT OR REPLACE INTO fills
+                (pool_id, signature, amount_in, ui_amount_in, amount_out, ui_amount_out)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                info.pool_id,
+                fill.signature,
+                to_sql_u64(fill.amount_in),
+                fill.ui_amount_in,
+                to_sql_u64(fill.amount_out),
+                fill.ui_amount_out,
+            ],
+        )?;
+    }
+    if info.total_token_amount_out > 0 {
+        tx.execute(
+            "INSERT INTO lots
+                (pool_id, signature, token_amount, cost_lamports,
+                 remaining_token_amount, remaining_cost_lamports, opened_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?3, ?4, ?5)",
+            params![
+                info.pool_id,
+                CARRIED_OVER_LOT,
+                to_sql_u64(info.total_token_amount_out),
+                to_sql_u64(info.total_amount_in),
+                now_ms(),
+            ],
+        )?;
+    }
+
+    Ok(true)
+}
+
+fn open_lots(conn: &Connection, pool_id: &str) -> Result<Vec<PositionLot>, StoreError> {
+    let mut query = conn.prepare(
+        "SELECT signature, remaining_token_amount, remaining_cost_lamports, opened_at_ms
+         FROM lots WHERE pool_id = ?1 AND remaining_token_amount != 0 ORDER BY rowid",
+    )?;
+    let lots = query
+        .query_map([pool_id], |row| {
+            Ok(PositionLot {
+                signature: row.get(0)?,
+                token_amount: from_sql_u64(row.get(1)?),
+                cost_lamports: from_sql_u64(row.get(2)?),
+                opened_at_ms: row.get(3)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    Ok(lots)
+}
+
+fn position_from_row(row: &Row) -> rusqlite::Result<BuyHistoryInfo> {
+    Ok(BuyHistoryInfo {
+        pool_id: row.get(0)?,
+        base_mint: row.get(1)?,
+        quote_mint: row.get(2)?,
+        base_vault: row.get(3)?,
+        quote_vault: row.get(4)?,
+        token_ata: row.get(5)?,
+        symbol: row.get(6)?,
+        dex: row.get(7)?,
+        take_profit: row.get(8)?,
+        total_amount_in: from_sql_u64(row.get(9)?),
+        total_ui_amount_in: row.get(10)?,
+        total_token_amount_out: from_sql_u64(row.get(11)?),
+        total_ui_token_amount_out: row.get(12)?,
+        transactions: vec![],
+    })
+}
+
+/// Receive time of the recorded update being replayed; 0 outside replays.
+static REPLAYED_AT_MS: AtomicI64 = AtomicI64::new(0);
+
+/// Makes `now_ms` read `ms` from now on: replays set it to the receive time
+/// of each update they feed, so positions age and fills are stamped on the
+/// recording's clock whatever the replay speed.
+pub fn set_replay_time_ms(ms: i64) {
+    REPLAYED_AT_MS.store(ms, Ordering::Relaxed);
+}
+
+/// Wall clock time in Unix milliseconds, as the store records it; the
+/// recording's time during a replay.
+pub fn now_ms() -> i64 {
+    let replayed_at_ms = REPLAYED_AT_MS.load(Ordering::Relaxed);
+    if replayed_at_ms > 0 {
+        return replayed_at_ms;
+    }
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+// SQLite integers are signed; raw amounts are stored bit for bit so the
+// largest token supplies survive the round trip.
+fn to_sql_u64(value: u64) -> i64 {
+    value as i64
+}
+
+fn from_sql_u64(value: i64) -> u64 {
+    value as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: &str = "pool";
+
+    fn memory_store() -> TradeStore {
+        let mut store = TradeStore::open(":memory:").unwrap();
+        store
+            .save_position(&BuyHistoryInfo {
+                pool_id: POOL.to_string(),
+                base_mint: "mint".to_string(),
+                quote_mint: NATIVE_MINT.to_string(),
+                base_vault: String::new(),
+                quote_vault: String::new(),
+                token_ata: String::new(),
+                symbol: "TEST".to_string(),
+                total_amount_in: 0,
+                total_ui_amount_in: 0.0,
+                total_token_amount_out: 0,
+                total_ui_token_amount_out: 0.0,
+                take_profit: 0.0,
+                transactions: vec![],
+                dex: "raydium_amm".to_string(),
+            })
+            .unwrap();
+        store
+    }
+
+    fn fill(
+        signature: &str,
+        side: SwapSide,
+        token_amount: u64,
+        wallet_lamports: i64,
+    ) -> ConfirmedFill {
+        ConfirmedFill {
+            signature: signature.to_string(),
+            side,
+            token_amount,
+            token_decimals: 6,
+            wallet_lamports,
+            network_fee: 5_000,
+        }
+    }
+
+    #[test]
+    fn exit_state_is_kept_while_the_position_is_open() {
+        let mut store = memory_store();
+        let state = ExitState {
+            peak_lamports: 1_500,
+            peak_token_amount: 100,
+            scale_outs_done: 1,
+        };
+
+        // Nothing held yet, nothing to remember
+        store.save_exit_state(POOL, &state).unwrap();
+        assert_eq!(store.exit_state(POOL).unwrap(), ExitState::default());
+
+        store
+            .record_fill(POOL, &fill("buy", SwapSide::Buy, 100, -1_000))
+            .unwrap();
+        store.save_exit_state(POOL, &state).unwrap();
+        assert_eq!(store.exit_state(POOL).unwrap(), state);
+
+        store
+            .record_fill(POOL, &fill("sell", SwapSide::Sell, 100, 1_200))
+            .unwrap();
+        assert_eq!(store.exit_state(POOL).unwrap(), ExitState::default());
+        assert_eq!(store.exit_state("unknown").unwrap(), ExitState::default());
+    }
+
+    #[test]
+    fn buys_open_lots_at_their_full_cost() {
+        let mut store = memory_store();
+
+        assert_eq!(
+            store
+                .record_fill(POOL, &fill("b1", SwapSide::Buy, 100, -1_005_000))
+                .unwrap(),
+            Some(0)
+        );
+        store
+            .record_fill(POOL, &fill("b2", SwapSide::Buy, 200, -3_005_000))
+            .unwrap();
+
+        let position = store.open_position(POOL).unwrap();
+        let lots: Vec<_> = position
+            .lots
+            .iter()
+            .map(|lot| (lot.signature.as_str(), lot.token_amount, lot.cost_lamports))
+            .collect();
+        assert_eq!(lots, [("b1", 100, 1_005_000), ("b2", 200, 3_005_000)]);
+
+        let info = store.load_position(POOL).unwrap();
+        assert_eq!(info.total_amount_in, 4_010_000);
+        assert_eq!(info.total_token_amount_out, 300);
+        assert_eq!(info.transactions.len(), 2);
+    }
+
+    #[test]
+    fn sells_realize_against_the_oldest_lots_fees_included() {
+        let mut store = memory_store();
+        store
+            .record_fill(POOL, &fill("b1", SwapSide::Buy, 100, -1_005_000))
+            .unwrap();
+        store
+            .record_fill(POOL, &fill("b2", SwapSide::Buy, 100, -2_005_000))
+            .unwrap();
+
+        // All of b1 and half of b2, for what reached the wallet after fees
+        let realized = store
+            .record_fill(POOL, &fill("s1", SwapSide::Sell, 150, 2_995_000))
+            .unwrap();
+        assert_eq!(realized, Some(2_995_000 - 1_005_000 - 1_002_500));
+
+        let position = store.open_position(POOL).unwrap();
+        assert_eq!(position.token_amount(), 50);
+        assert_eq!(position.cost_lamports(), 1_002_500);
+        assert_eq!(position.lots[0].signature, "b2");
+        assert_eq!(position.realized_pnl_lamports, 987_500);
+
+        // Selling out at a loss flattens the totals
+        let realized = store
+            .record_fill(POOL, &fill("s2", SwapSide::Sell, 50, 995_000))
+            .unwrap();
+        assert_eq!(realized, Some(995_000 - 1_002_500));
+        let position = store.open_position(POOL).unwrap();
+        assert!(position.lots.is_empty());
+        assert_eq!(position.realized_pnl_lamports, 987_500 - 7_500);
+        assert_eq!(store.load_position(POOL).unwrap().total_amount_in, 0);
+        assert_eq!(store.realized_pnl_since(0).unwrap(), 980_000);
+        assert_eq!(
+            store.realized_fills_since(0).unwrap().len(),
+            2,
+            "buys realize nothing"
+        );
+    }
+
+    #[test]
+    fn each_signature_is_booked_once() {
+        let mut store = memory_store();
+        let buy = fill("b1", SwapSide::Buy, 100, -1_000_000);
+
+        assert_eq!(store.record_fill(POOL, &buy).unwrap(), Some(0));
+        assert_eq!(store.record_fill(POOL, &buy).unwrap(), None);
+        assert_eq!(store.open_position(POOL).unwrap().token_amount(), 100);
+
+        assert!(matches!(
+            store.record_fill("unknown", &buy),
+            Err(StoreError::UnknownPool(_))
+        ));
+    }
+
+    #[test]
+    fn migrations_upgrade_a_first_version_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA_V1).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute_batch(
+            "INSERT INTO positions VALUES
+                ('held', 'mint', 'So11111111111111111111111111111111111111112', '', '', '',
+                 'HELD', 'raydium_amm', 0.2, 2000000, 0.002, 500, 0.0005),
+                ('flat', 'mint', 'So11111111111111111111111111111111111111112', '', '', '',
+                 'FLAT', 'raydium_amm', 0.2, 0, 0, 0, 0);
+             INSERT INTO fills VALUES ('held', 'old', 2000000, 0.002, 500, 0.0005);",
+        )
+        .unwrap();
+
+        let mut store = TradeStore { conn };
+        store.migrate().unwrap();
+        let version: usize = store
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+
+        // Inventory became one lot, aged from the upgrade
+        let held = store.open_position("held").unwrap();
+        assert_eq!(held.realized_pnl_lamports, 0);
+        assert_eq!(held.lots.len(), 1);
+        assert_eq!(held.lots[0].signature, CARRIED_OVER_LOT);
+        assert_eq!(held.lots[0].token_amount, 500);
+        assert_eq!(held.lots[0].cost_lamports, 2_000_000);
+        assert!(held.lots[0].opened_at_ms > 0);
+        assert!(store.open_position("flat").unwrap().lots.is_empty());
+        assert_eq!(store.exit_state("held").unwrap(), ExitState::default());
+        assert!(store.kill_switch().unwrap().is_none());
+
+        // Fills booked before count as buys, and the carried lot sells like any other
+        assert_eq!(store.load_position("held").unwrap().transactions.len(), 1);
+        let realized = store
+            .record_fill("held", &fill("sell", SwapSide::Sell, 500, 2_500_000))
+            .unwrap();
+        assert_eq!(realized, Some(500_000));
+
+        // Nothing left to apply
+        store.migrate().unwrap();
+    }
+}
@@ -0,0 +1,10 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+/// Converts a UI SOL amount to lamports, truncating sub-lamport dust.
+pub fn sol_to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL as f64) as u64
+}
+
+pub fn lamports_to_sol(lamports: u64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL as f64
+}
@@ -1,4 +1,10 @@
 pub mod get_swap_keys;
+pub mod lamports;
 pub mod price_impact;
+pub mod token_2022;
+pub mod transfer_fee_watch;
 pub use get_swap_keys::*;
+pub use lamports::*;
 pub use price_impact::*;
+pub use token_2022::*;
+pub use transfer_fee_watch::*;
@@ -2,11 +2,11 @@ use yellowstone_grpc_proto::prelude::TokenBalance;
 
 use crate::{log, NATIVE_MINT};
 pub fn get_price_impact(
-    pre_token_balance: &Vec<TokenBalance>,
-    post_token_balance: &Vec<TokenBalance>,
+    pre_token_balance: &[TokenBalance],
+    post_token_balance: &[TokenBalance],
     owner: &str,
     sol_price: f64,
-) -> (f64, f64, f64, String, f64 , f64 , f64) {
+) -> (f64, f64, f64, String, f64, f64, f64) {
     let pre_native_ui_amount = pre_token_balance
         .iter()
         .find(|token_info| token_info.mint == NATIVE_MINT && token_info.owner == owner)
@@ -53,13 +53,13 @@ pub fn get_price_impact(
         "info"
     );
 
-    return (
+    (
         price_change,
         2.0 * post_native_ui_amount * sol_price,
         pre_native_ui_amount - post_native_ui_amount,
         token_mint,
         post_native_ui_amount / post_token_ui_amount,
         post_native_ui_amount,
-        post_token_ui_amount
-    );
+        post_token_ui_amount,
+    )
 }
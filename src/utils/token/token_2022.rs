@@ -0,0 +1,240 @@
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint,
+};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use crate::{TokenListInfos, TokenTransferFee, NATIVE_MINT, TOKEN_2022_PRO};
+
+pub fn is_token_2022(token_program: &Pubkey) -> bool {
+    *token_program == Pubkey::from_str_const(TOKEN_2022_PRO)
+}
+
+/// Transfer fee a Token-2022 mint charges in `epoch`, `None` for mints without
+/// the extension or with a zero fee.
+pub fn decode_mint_transfer_fee(mint_data: &[u8], epoch: u64) -> Option<TokenTransferFee> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data).ok()?;
+    let fee = mint
+        .get_extension::<TransferFeeConfig>()
+        .ok()?
+        .get_epoch_fee(epoch);
+
+    let transfer_fee = TokenTransferFee {
+        basis_points: fee.transfer_fee_basis_points.into(),
+        maximum_fee: fee.maximum_fee.into(),
+    };
+    (transfer_fee.basis_points > 0 && transfer_fee.maximum_fee > 0).then_some(transfer_fee)
+}
+
+/// First epoch of a scheduled transfer fee change that differs from the fee in
+/// effect at `epoch`.
+pub fn scheduled_transfer_fee_change(mint_data: &[u8], epoch: u64) -> Option<u64> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data).ok()?;
+    let config = mint.get_extension::<TransferFeeConfig>().ok()?;
+
+    let newer_epoch = u64::from(config.newer_transfer_fee.epoch);
+    (newer_epoch > epoch && config.newer_transfer_fee != config.older_transfer_fee)
+        .then_some(newer_epoch)
+}
+
+/// Symbol from the Token-2022 metadata extension, for mints without a Metaplex
+/// metadata account.
+pub fn decode_mint_metadata_symbol(mint_data: &[u8]) -> Option<String> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data).ok()?;
+    let metadata = mint.get_variable_len_extension::<TokenMetadata>().ok()?;
+
+    Some(metadata.symbol)
+}
+
+impl TokenTransferFee {
+    fn as_spl(&self) -> TransferFee {
+        TransferFee {
+            epoch: 0.into(),
+            maximum_fee: self.maximum_fee.into(),
+            transfer_fee_basis_points: self.basis_points.into(),
+        }
+    }
+
+    /// Fee withheld when `amount` raw units are transferred.
+    pub fn fee_on(&self, amount: u64) -> u64 {
+        self.as_spl().calculate_fee(amount).unwrap_or(amount)
+    }
+
+    /// What the receiver gets when `amount` raw units are sent.
+    pub fn amount_after_fee(&self, amount: u64) -> u64 {
+        amount - self.fee_on(amount)
+    }
+
+    /// What must be sent for the receiver to get `amount` raw units.
+    pub fn amount_before_fee(&self, amount: u64) -> u64 {
+        self.as_spl()
+            .calculate_pre_fee_amount(amount)
+            .unwrap_or(u64::MAX)
+    }
+}
+
+impl TokenListInfos {
+    /// Whether the recorded transfer fee change has started by `epoch`.
+    pub fn transfer_fee_change_due(&self, epoch: u64) -> bool {
+        self.transfer_fee_change_epoch
+            .is_some_and(|change| change <= epoch)
+    }
+
+    /// Takes the transfer fee `mint_data` charges in `epoch` once the recorded
+    /// change has started, and the change after it. False when none is due.
+    pub fn apply_transfer_fee_change(&mut self, mint_data: &[u8], epoch: u64) -> bool {
+        if !self.transfer_fee_change_due(epoch) {
+            return false;
+        }
+        self.transfer_fee = decode_mint_transfer_fee(mint_data, epoch);
+        self.transfer_fee_change_epoch = scheduled_transfer_fee_change(mint_data, epoch);
+        true
+    }
+
+    /// Token programs of the base and quote mints; wrapped SOL is always SPL Token.
+    pub fn mint_token_programs(&self) -> (Pubkey, Pubkey) {
+        let token_program = Pubkey::from_str_const(&self.token_program);
+        if self.base_mint == NATIVE_MINT {
+            (spl_token::ID, token_program)
+        } else {
+            (token_program, spl_token::ID)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spl_token_2022::extension::{
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    };
+
+    use super::*;
+
+    /// 1%, at most 5_000 raw units a transfer.
+    const FEE: TokenTransferFee = TokenTransferFee {
+        basis_points: 100,
+        maximum_fee: 5_000,
+    };
+
+    fn spl_fee(epoch: u64, fee: TokenTransferFee) -> TransferFee {
+        TransferFee {
+            epoch: epoch.into(),
+            maximum_fee: fee.maximum_fee.into(),
+            transfer_fee_basis_points: fee.basis_points.into(),
+        }
+    }
+
+    /// Mint charging `older` until `newer` takes over at its epoch.
+    fn mint_data(older: TransferFee, newer: TransferFee) -> Vec<u8> {
+        let len =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let mut data = vec![0u8; len];
+        let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let config = mint.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee = older;
+        config.newer_transfer_fee = newer;
+        mint.base = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        data
+    }
+
+    fn recorded(transfer_fee_change_epoch: Option<u64>) -> TokenListInfos {
+        let mut info: TokenListInfos = serde_json::from_value(serde_json::json!({
+            "id_bs64": "", "base_vault_b64": "", "quote_vault_b64": "",
+            "base_mint": "", "quote_mint": NATIVE_MINT, "clean_symbol": "",
+            "ata": "", "dex": "",
+        }))
+        .unwrap();
+        info.transfer_fee = Some(FEE);
+        info.transfer_fee_change_epoch = transfer_fee_change_epoch;
+        info
+    }
+
+    #[test]
+    fn fees_round_up_and_stop_at_the_cap() {
+        assert_eq!(FEE.fee_on(0), 0);
+        assert_eq!(FEE.fee_on(1), 1);
+        assert_eq!(FEE.fee_on(10_000), 100);
+        assert_eq!(FEE.fee_on(10_001), 101);
+        assert_eq!(FEE.fee_on(500_000), 5_000);
+        assert_eq!(FEE.fee_on(u64::MAX), 5_000);
+
+        assert_eq!(FEE.amount_after_fee(10_001), 9_900);
+        assert_eq!(FEE.amount_after_fee(1_000_000), 995_000);
+    }
+
+    #[test]
+    fn pre_fee_amounts_invert_the_fee() {
+        for received in [1, 99, 9_900, 10_000, 494_999, 495_000, 1_000_000, 1 << 40] {
+            let sent = FEE.amount_before_fee(received);
+            assert_eq!(
+                FEE.amount_after_fee(sent),
+                received,
+                "{} received",
+                received
+            );
+            assert!(
+                FEE.amount_after_fee(sent - 1) < received,
+                "{} is not the least to send",
+                sent
+            );
+        }
+        // Past the cap the fee is flat
+        assert_eq!(FEE.amount_before_fee(1_000_000), 1_005_000);
+    }
+
+    #[test]
+    fn mint_fees_follow_the_epoch() {
+        let raised = TokenTransferFee {
+            basis_points: 250,
+            maximum_fee: 5_000,
+        };
+        let data = mint_data(spl_fee(0, FEE), spl_fee(500, raised));
+
+        assert_eq!(decode_mint_transfer_fee(&data, 499), Some(FEE));
+        assert_eq!(decode_mint_transfer_fee(&data, 500), Some(raised));
+        assert_eq!(scheduled_transfer_fee_change(&data, 499), Some(500));
+        assert_eq!(scheduled_transfer_fee_change(&data, 500), None);
+
+        let lifted = TokenTransferFee {
+            basis_points: 0,
+            maximum_fee: 0,
+        };
+        let data = mint_data(spl_fee(0, FEE), spl_fee(500, lifted));
+        assert_eq!(decode_mint_transfer_fee(&data, 500), None);
+        // Unchanged fees are no change
+        let data = mint_data(spl_fee(500, FEE), spl_fee(500, FEE));
+        assert_eq!(scheduled_transfer_fee_change(&data, 0), None);
+    }
+
+    #[test]
+    fn recorded_fees_change_once_the_epoch_starts() {
+        let raised = TokenTransferFee {
+            basis_points: 250,
+            maximum_fee: 5_000,
+        };
+        let data = mint_data(spl_fee(0, FEE), spl_fee(500, raised));
+
+        let mut info = recorded(Some(500));
+        assert!(!info.apply_transfer_fee_change(&data, 499));
+        assert_eq!(info.transfer_fee, Some(FEE));
+
+        assert!(info.apply_transfer_fee_change(&data, 501));
+        assert_eq!(info.transfer_fee, Some(raised));
+        assert_eq!(info.transfer_fee_change_epoch, None);
+
+        let mut info = recorded(None);
+        assert!(!info.apply_transfer_fee_change(&data, 501));
+        assert_eq!(info.transfer_fee, Some(FEE));
+    }
+}
@@ -0,0 +1,72 @@
+use std::{sync::Arc, time::Duration};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{load_token_info, log, save_token_info, NATIVE_MINT};
+
+/// How often the current epoch is checked against the recorded fee changes.
+const TRANSFER_FEE_REFRESH: Duration = Duration::from_secs(300);
+
+/// Re-reads the transfer fee of the traded mint of each of `pools` once its
+/// scheduled change starts, and records it in the pool's keys.
+pub fn spawn_transfer_fee_watch(client: Arc<RpcClient>, pools: Vec<String>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TRANSFER_FEE_REFRESH);
+        loop {
+            interval.tick().await;
+            let pending: Vec<_> = pools
+                .iter()
+                .filter_map(|pool| Some((pool, load_token_info(pool).ok()?)))
+                .filter(|(_, info)| info.transfer_fee_change_epoch.is_some())
+                .collect();
+            if pending.is_empty() {
+                continue;
+            }
+            let epoch = match client.get_epoch_info().await {
+                Ok(info) => info.epoch,
+                Err(e) => {
+                    log!(format!("🔴 Failed to read the epoch: {}", e), "error");
+                    continue;
+                }
+            };
+
+            for (pool, mut info) in pending {
+                if !info.transfer_fee_change_due(epoch) {
+                    continue;
+                }
+                let mint = if info.base_mint == NATIVE_MINT {
+                    &info.quote_mint
+                } else {
+                    &info.base_mint
+                };
+                let mint_account = match client.get_account(&Pubkey::from_str_const(mint)).await {
+                    Ok(account) => account,
+                    Err(e) => {
+                        log!(format!("🔴 Failed to fetch mint {}: {}", mint, e), "error");
+                        continue;
+                    }
+                };
+
+                info.apply_transfer_fee_change(&mint_account.data, epoch);
+                match &info.transfer_fee {
+                    Some(fee) => log!(
+                        format!(
+                            "🔄 Transfer fee of {} from epoch {} : {} bps, max {}",
+                            info.clean_symbol, epoch, fee.basis_points, fee.maximum_fee
+                        ),
+                        "update"
+                    ),
+                    None => log!(
+                        format!(
+                            "🔄 Transfer fee of {} lifted from epoch {}",
+                            info.clean_symbol, epoch
+                        ),
+                        "update"
+                    ),
+                }
+                save_token_info(&info, pool);
+            }
+        }
+    });
+}
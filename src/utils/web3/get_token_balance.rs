@@ -3,7 +3,7 @@ use solana_transaction_status::{
 };
 use yellowstone_grpc_proto::prelude::{TokenBalance, TransactionStatusMeta};
 
-use crate::{init_token_trade_total_info, log, update_token_buy_info, update_token_trade_total_info};
+use crate::log;
 
 pub fn get_pre_post_token_balance(
     meta: &TransactionStatusMeta,
@@ -99,113 +99,39 @@ pub fn display_balance_change(
                     token_mint,
                     post_token_amount,
                     pre_token_amount,
-                    (pre_token_amount as f64) - (post_token_amount as f64)
+                    pre_token_amount - post_token_amount
                 ),
                 "info"
             );
         }
     }
 }
-pub fn display_and_save_ui_balance_change(
-    signature: &str,
-    pool: &str,
-    ray_auth_pre_token_balance: &[UiTransactionTokenBalance],
-    ray_auth_post_token_balance: &[UiTransactionTokenBalance],
+/// Raw balance of `mint` held by `owner` before and after the transaction,
+/// with the mint's decimals; accounts created by the transaction start at zero.
+pub fn owner_token_balance_change(
+    meta: &UiTransactionStatusMeta,
     owner: &str,
-    is_buy : bool
-) {
-    let min_len = ray_auth_pre_token_balance
-        .len()
-        .min(ray_auth_post_token_balance.len());
-
-    // Initialize totals
-    let mut total_amount_in: u64 = 0;
-    let mut total_ui_amount_in: f64 = 0.0;
-    let mut total_token_amount_out: u64 = 0;
-    let mut total_ui_token_amount_out: f64 = 0.0;
-
-    for i in 0..min_len {
-        let pre_token_amount_str = &ray_auth_pre_token_balance[i]
-            .ui_token_amount
-            .ui_amount_string;
-        let post_token_amount_str = &ray_auth_post_token_balance[i]
-            .ui_token_amount
-            .ui_amount_string;
-
-        let pre_token_amount_decimal = &ray_auth_pre_token_balance[i].ui_token_amount.amount;
-        let post_token_amount_decimal = &ray_auth_post_token_balance[i].ui_token_amount.amount;
-
-        let pre_token_amount = pre_token_amount_str.parse::<f64>().unwrap_or(0.0);
-        let post_token_amount = post_token_amount_str.parse::<f64>().unwrap_or(0.0);
-
-        let pre_token_amount_dec: u64 = pre_token_amount_decimal.parse::<u64>().unwrap_or(0);
-        let post_token_amount_dec: u64 = post_token_amount_decimal.parse::<u64>().unwrap_or(0);
-
-        let pre_token_owner = &ray_auth_pre_token_balance[i].owner;
-        let token_mint = &ray_auth_post_token_balance[i].mint;
-
-        let pre_token_owner_matches = match pre_token_owner {
-            OptionSerializer::Some(owner_str) => owner_str == owner,
-            OptionSerializer::None | OptionSerializer::Skip => false,
+    mint: &str,
+) -> (u64, u64, u8) {
+    let mut decimals = 0;
+    let mut sum = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| -> u64 {
+        let OptionSerializer::Some(balances) = balances else {
+            return 0;
         };
+        balances
+            .iter()
+            .filter(|balance| {
+                balance.mint == mint
+                    && matches!(&balance.owner, OptionSerializer::Some(o) if o == owner)
+            })
+            .map(|balance| {
+                decimals = balance.ui_token_amount.decimals;
+                balance.ui_token_amount.amount.parse::<u64>().unwrap_or(0)
+            })
+            .sum()
+    };
 
-        if pre_token_amount != post_token_amount && pre_token_owner_matches {
-            let raw_diff =
-                (pre_token_amount_dec as i64 - post_token_amount_dec as i64).abs() as u64;
-            let ui_diff = (post_token_amount - pre_token_amount).abs();
-            let is_in = post_token_amount_dec as i64 - pre_token_amount_dec as i64 > 0;
-
-            log!(
-                format!(
-                    "\tToken [ {} ]\t{} -> \t{}\t(Δ{})",
-                    token_mint,
-                    post_token_amount,
-                    pre_token_amount,
-                    pre_token_amount - post_token_amount
-                ),
-                "update"
-            );
-
-            if is_buy {
-                update_token_buy_info(signature, pool, raw_diff, ui_diff, is_in);
-            }
-
-            // Update totals
-            if is_in {
-                total_amount_in += raw_diff;
-                total_ui_amount_in += ui_diff;
-            } else {
-                total_token_amount_out += raw_diff;
-                total_ui_token_amount_out += ui_diff;
-            }
-        } else if !pre_token_owner_matches {
-            log!(
-                format!(
-                    "Owner mismatch for token mint: {}. Expected owner: {}",
-                    token_mint, owner
-                ),
-                "warn"
-            );
-        }
-    }
-
-    if is_buy {
-        update_token_trade_total_info(
-            pool,
-            total_amount_in,
-            total_ui_amount_in,
-            total_token_amount_out,
-            total_ui_token_amount_out,
-        );
-    } else {
-        init_token_trade_total_info(pool);
-    }
-    // Optional: log or use totals here
-    log!(
-        format!(
-            "Totals - In: {} ({} UI), Out: {} ({} UI)",
-            total_amount_in, total_ui_amount_in, total_token_amount_out, total_ui_token_amount_out
-        ),
-        "info"
-    );
+    let pre = sum(&meta.pre_token_balances);
+    let post = sum(&meta.post_token_balances);
+    (pre, post, decimals)
 }
@@ -1,8 +1,6 @@
 pub mod ensure_ata_created;
-pub mod get_sig_status;
 pub mod get_token_balance;
 pub mod get_token_metadata;
 pub use ensure_ata_created::*;
-pub use get_sig_status::*;
 pub use get_token_balance::*;
 pub use get_token_metadata::*;
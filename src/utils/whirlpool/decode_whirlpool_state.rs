@@ -0,0 +1,39 @@
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    skip_discriminator, WhirlpoolState, WhirlpoolTickArray, ORCA_WHIRLPOOL_ID_PUBKEY,
+    WHIRLPOOL_TICK_ARRAY_SIZE,
+};
+
+/// Decodes a `Whirlpool` account; reward infos and trailing bytes are ignored.
+pub fn decode_whirlpool_state(data: &[u8]) -> std::io::Result<WhirlpoolState> {
+    WhirlpoolState::deserialize(&mut skip_discriminator(data)?)
+}
+
+/// Decodes a fixed-size `TickArray` account.
+pub fn decode_whirlpool_tick_array(data: &[u8]) -> std::io::Result<WhirlpoolTickArray> {
+    WhirlpoolTickArray::deserialize(&mut skip_discriminator(data)?)
+}
+
+/// Start index of the tick array holding `tick`.
+pub fn whirlpool_tick_array_start_index(tick: i32, tick_spacing: u16) -> i32 {
+    let ticks_in_array = WHIRLPOOL_TICK_ARRAY_SIZE * tick_spacing as i32;
+    tick.div_euclid(ticks_in_array) * ticks_in_array
+}
+
+pub fn whirlpool_tick_array_address(whirlpool: &Pubkey, start_tick_index: i32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"tick_array",
+            whirlpool.as_ref(),
+            start_tick_index.to_string().as_bytes(),
+        ],
+        &ORCA_WHIRLPOOL_ID_PUBKEY,
+    )
+    .0
+}
+
+pub fn whirlpool_oracle_address(whirlpool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"oracle", whirlpool.as_ref()], &ORCA_WHIRLPOOL_ID_PUBKEY).0
+}
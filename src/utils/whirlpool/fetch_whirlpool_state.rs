@@ -0,0 +1,69 @@
+use anyhow::{bail, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    decode_whirlpool_state, decode_whirlpool_tick_array, whirlpool_tick_array_address,
+    whirlpool_tick_array_start_index, WhirlpoolState, WhirlpoolTickArray,
+    WHIRLPOOL_TICK_ARRAY_SIZE,
+};
+
+/// Live pool state plus the tick arrays a swap may cross.
+pub struct WhirlpoolSwapState {
+    pub pool: WhirlpoolState,
+    /// The three tick arrays the swap instruction takes, in swap direction.
+    /// Uninitialized ones are replaced by the last initialized one.
+    pub tick_array_addresses: [Pubkey; 3],
+    /// The initialized tick arrays, for quoting.
+    pub tick_arrays: Vec<WhirlpoolTickArray>,
+}
+
+pub async fn fetch_whirlpool_swap_state(
+    client: &RpcClient,
+    pool_id: &Pubkey,
+    a_to_b: bool,
+) -> Result<WhirlpoolSwapState> {
+    let data = client
+        .get_account_data(pool_id)
+        .await
+        .with_context(|| format!("failed to fetch whirlpool {}", pool_id))?;
+    let pool = decode_whirlpool_state(&data)
+        .with_context(|| format!("failed to decode whirlpool {}", pool_id))?;
+
+    let ticks_in_array = WHIRLPOOL_TICK_ARRAY_SIZE * pool.tick_spacing as i32;
+    let step = if a_to_b {
+        -ticks_in_array
+    } else {
+        ticks_in_array
+    };
+    let start = whirlpool_tick_array_start_index(pool.tick_current_index, pool.tick_spacing);
+    let mut addresses = [0, 1, 2].map(|i| whirlpool_tick_array_address(pool_id, start + i * step));
+
+    let accounts = client
+        .get_multiple_accounts(&addresses)
+        .await
+        .context("failed to fetch tick arrays")?;
+
+    // The swap can only walk through a contiguous run of initialized arrays
+    let mut tick_arrays = Vec::with_capacity(3);
+    for account in accounts.iter().map_while(Option::as_ref) {
+        tick_arrays.push(
+            decode_whirlpool_tick_array(&account.data).context("failed to decode tick array")?,
+        );
+    }
+    if tick_arrays.is_empty() {
+        bail!(
+            "tick array {} holding the current tick is not initialized",
+            addresses[0]
+        );
+    }
+    for i in tick_arrays.len()..addresses.len() {
+        addresses[i] = addresses[i - 1];
+    }
+
+    Ok(WhirlpoolSwapState {
+        pool,
+        tick_array_addresses: addresses,
+        tick_arrays,
+    })
+}
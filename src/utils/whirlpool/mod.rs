@@ -0,0 +1,7 @@
+pub mod decode_whirlpool_state;
+pub mod fetch_whirlpool_state;
+pub mod whirlpool_quote;
+
+pub use decode_whirlpool_state::*;
+pub use fetch_whirlpool_state::*;
+pub use whirlpool_quote::*;
@@ -0,0 +1,660 @@
+use std::collections::BTreeMap;
+
+use num_bigint::BigUint;
+
+use crate::{div_to_u128, mul_div_u128, QuoteError, SwapQuote, WhirlpoolState, WhirlpoolTickArray};
+
+pub const WHIRLPOOL_TICK_ARRAY_SIZE: i32 = 88;
+pub const WHIRLPOOL_MIN_TICK: i32 = -443636;
+pub const WHIRLPOOL_MAX_TICK: i32 = 443636;
+pub const WHIRLPOOL_MIN_SQRT_PRICE: u128 = 4295048016;
+pub const WHIRLPOOL_MAX_SQRT_PRICE: u128 = 79226673515401279992447579055;
+
+/// `fee_rate` is in millionths.
+const FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+/// Q64.64 sqrt price at `tick`, i.e. `sqrt(1.0001^tick) << 64`, bit-exact with the program.
+pub fn whirlpool_sqrt_price_from_tick(tick: i32) -> u128 {
+    if tick >= 0 {
+        sqrt_price_positive_tick(tick)
+    } else {
+        sqrt_price_negative_tick(tick)
+    }
+}
+
+/// Simulates a swap the way the Whirlpool program executes it, crossing every
+/// initialized tick of `tick_arrays` on the way.
+///
+/// `tick_arrays` must be contiguous and cover the current tick; the quote fails
+/// with `ArraysExhausted` when the swap would need more of them. `amount` is the
+/// input when `amount_specified_is_input`, the output otherwise.
+pub fn quote_whirlpool_swap(
+    pool: &WhirlpoolState,
+    tick_arrays: &[WhirlpoolTickArray],
+    amount: u64,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<SwapQuote, QuoteError> {
+    if amount == 0 {
+        return Err(QuoteError::ZeroAmount);
+    }
+    let exhausted = QuoteError::ArraysExhausted("tick arrays");
+
+    let spacing = pool.tick_spacing as i32;
+    let ticks_in_array = WHIRLPOOL_TICK_ARRAY_SIZE * spacing;
+    let (Some(lowest), Some(highest)) = (
+        tick_arrays.iter().map(|array| array.start_tick_index).min(),
+        tick_arrays
+            .iter()
+            .map(|array| array.start_tick_index + ticks_in_array - 1)
+            .max(),
+    ) else {
+        return Err(exhausted);
+    };
+    if pool.tick_current_index < lowest || pool.tick_current_index > highest {
+        return Err(exhausted);
+    }
+
+    let initialized: BTreeMap<i32, i128> = tick_arrays
+        .iter()
+        .flat_map(|array| {
+            array
+                .ticks
+                .iter()
+                .enumerate()
+                .filter_map(move |(offset, tick)| {
+                    tick.initialized.then_some((
+                        array.start_tick_index + offset as i32 * spacing,
+                        tick.liquidity_net,
+                    ))
+                })
+        })
+        .collect();
+
+    let sqrt_price_limit = if a_to_b {
+        WHIRLPOOL_MIN_SQRT_PRICE
+    } else {
+        WHIRLPOOL_MAX_SQRT_PRICE
+    };
+
+    let mut remaining = amount;
+    let mut calculated: u64 = 0;
+    let mut fee_total: u64 = 0;
+    let mut sqrt_price = pool.sqrt_price;
+    let mut tick = pool.tick_current_index;
+    let mut liquidity = pool.liquidity;
+
+    while remaining > 0 && sqrt_price != sqrt_price_limit {
+        if (a_to_b && tick < lowest) || (!a_to_b && tick >= highest) {
+            return Err(exhausted);
+        }
+
+        // Past the last initialized tick the loaded arrays end at their edge
+        let next = if a_to_b {
+            initialized.range(..=tick).next_back()
+        } else {
+            initialized.range(tick + 1..).next()
+        };
+        let (next_tick, liquidity_net) = match next {
+            Some((tick, net)) => (*tick, Some(*net)),
+            None if a_to_b => (lowest, None),
+            None => (highest, None),
+        };
+        let next_tick = next_tick.clamp(WHIRLPOOL_MIN_TICK, WHIRLPOOL_MAX_TICK);
+        let next_sqrt_price = whirlpool_sqrt_price_from_tick(next_tick);
+        let target = if a_to_b {
+            next_sqrt_price.max(sqrt_price_limit)
+        } else {
+            next_sqrt_price.min(sqrt_price_limit)
+        };
+
+        let step = compute_swap_step(
+            remaining,
+            pool.fee_rate as u128,
+            liquidity,
+            sqrt_price,
+            target,
+            amount_specified_is_input,
+            a_to_b,
+        )?;
+
+        if amount_specified_is_input {
+            remaining = remaining
+                .checked_sub(step.amount_in + step.fee_amount)
+                .ok_or(QuoteError::Overflow)?;
+            calculated = calculated
+                .checked_add(step.amount_out)
+                .ok_or(QuoteError::Overflow)?;
+        } else {
+            remaining = remaining
+                .checked_sub(step.amount_out)
+                .ok_or(QuoteError::Overflow)?;
+            calculated = calculated
+                .checked_add(step.amount_in + step.fee_amount)
+                .ok_or(QuoteError::Overflow)?;
+        }
+        fee_total += step.fee_amount;
+
+        if step.next_sqrt_price == next_sqrt_price {
+            if let Some(net) = liquidity_net {
+                let net = if a_to_b { -net } else { net };
+                liquidity = liquidity
+                    .checked_add_signed(net)
+                    .ok_or(QuoteError::Overflow)?;
+            }
+            tick = if a_to_b { next_tick - 1 } else { next_tick };
+        }
+        sqrt_price = step.next_sqrt_price;
+    }
+
+    let (amount_in, amount_out) = if amount_specified_is_input {
+        (amount - remaining, calculated)
+    } else {
+        (calculated, amount - remaining)
+    };
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount: fee_total,
+    })
+}
+
+struct SwapStep {
+    amount_in: u64,
+    amount_out: u64,
+    next_sqrt_price: u128,
+    fee_amount: u64,
+}
+
+fn compute_swap_step(
+    amount_remaining: u64,
+    fee_rate: u128,
+    liquidity: u128,
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<SwapStep, QuoteError> {
+    let fixed_delta = |next: u128| {
+        amount_fixed_delta(
+            sqrt_price_current,
+            next,
+            liquidity,
+            amount_specified_is_input,
+            a_to_b,
+        )
+    };
+
+    let amount_calc = if amount_specified_is_input {
+        mul_div_u128(
+            amount_remaining as u128,
+            FEE_RATE_DENOMINATOR - fee_rate,
+            FEE_RATE_DENOMINATOR,
+            false,
+        )
+        .ok_or(QuoteError::Overflow)? as u64
+    } else {
+        amount_remaining
+    };
+
+    // `None` when the full step does not fit in a u64, so it cannot be reached
+    let initial_fixed_delta = fixed_delta(sqrt_price_target);
+    let next_sqrt_price = match initial_fixed_delta {
+        Some(delta) if delta <= amount_calc => sqrt_price_target,
+        _ => next_sqrt_price(
+            sqrt_price_current,
+            liquidity,
+            amount_calc,
+            amount_specified_is_input,
+            a_to_b,
+        )?,
+    };
+    let is_max_swap = next_sqrt_price == sqrt_price_target;
+
+    let unfixed_delta = amount_unfixed_delta(
+        sqrt_price_current,
+        next_sqrt_price,
+        liquidity,
+        amount_specified_is_input,
+        a_to_b,
+    )
+    .ok_or(QuoteError::Overflow)?;
+    let fixed_delta = match initial_fixed_delta {
+        Some(delta) if is_max_swap => delta,
+        _ => fixed_delta(next_sqrt_price).ok_or(QuoteError::Overflow)?,
+    };
+
+    let (amount_in, mut amount_out) = if amount_specified_is_input {
+        (fixed_delta, unfixed_delta)
+    } else {
+        (unfixed_delta, fixed_delta)
+    };
+    if !amount_specified_is_input && amount_out > amount_remaining {
+        amount_out = amount_remaining;
+    }
+
+    let fee_amount = if amount_specified_is_input && !is_max_swap {
+        amount_remaining - amount_in
+    } else {
+        mul_div_u128(
+            amount_in as u128,
+            fee_rate,
+            FEE_RATE_DENOMINATOR - fee_rate,
+            true,
+        )
+        .ok_or(QuoteError::Overflow)? as u64
+    };
+
+    Ok(SwapStep {
+        amount_in,
+        amount_out,
+        next_sqrt_price,
+        fee_amount,
+    })
+}
+
+/// The side of the step whose amount is given: input for exact-in, output for exact-out.
+fn amount_fixed_delta(
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Option<u64> {
+    if a_to_b == amount_specified_is_input {
+        amount_delta_a(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            amount_specified_is_input,
+        )
+    } else {
+        amount_delta_b(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            amount_specified_is_input,
+        )
+    }
+}
+
+fn amount_unfixed_delta(
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Option<u64> {
+    if a_to_b == amount_specified_is_input {
+        amount_delta_b(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            !amount_specified_is_input,
+        )
+    } else {
+        amount_delta_a(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            !amount_specified_is_input,
+        )
+    }
+}
+
+/// Token A between two sqrt prices: `L * (upper - lower) / (upper * lower)`.
+fn amount_delta_a(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u64> {
+    let (lower, upper) = (
+        sqrt_price_0.min(sqrt_price_1),
+        sqrt_price_0.max(sqrt_price_1),
+    );
+    let numerator = (BigUint::from(liquidity) * (upper - lower)) << 64;
+    let denominator = BigUint::from(upper) * lower;
+
+    u64::try_from(div_to_u128(&numerator, &denominator, round_up)?).ok()
+}
+
+/// Token B between two sqrt prices: `L * (upper - lower)`.
+fn amount_delta_b(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u64> {
+    let diff = sqrt_price_0.abs_diff(sqrt_price_1);
+    let product = BigUint::from(liquidity) * diff;
+
+    u64::try_from(div_to_u128(
+        &product,
+        &(BigUint::from(1u32) << 64),
+        round_up,
+    )?)
+    .ok()
+}
+
+fn next_sqrt_price(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<u128, QuoteError> {
+    let next = if amount_specified_is_input == a_to_b {
+        next_sqrt_price_from_a_round_up(sqrt_price, liquidity, amount, amount_specified_is_input)
+    } else {
+        next_sqrt_price_from_b_round_down(sqrt_price, liquidity, amount, amount_specified_is_input)
+    }
+    .ok_or(QuoteError::Overflow)?;
+
+    if !(WHIRLPOOL_MIN_SQRT_PRICE..=WHIRLPOOL_MAX_SQRT_PRICE).contains(&next) {
+        return Err(QuoteError::Overflow);
+    }
+    Ok(next)
+}
+
+/// `L * p / (L ± amount * p)`, rounded up.
+fn next_sqrt_price_from_a_round_up(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Option<u128> {
+    if amount == 0 {
+        return Some(sqrt_price);
+    }
+    let product = BigUint::from(sqrt_price) * amount;
+    let liquidity_shifted = BigUint::from(liquidity) << 64;
+    let numerator = (BigUint::from(liquidity) * sqrt_price) << 64;
+    let denominator = if add {
+        liquidity_shifted + product
+    } else {
+        if product >= liquidity_shifted {
+            return None;
+        }
+        liquidity_shifted - product
+    };
+
+    div_to_u128(&numerator, &denominator, true)
+}
+
+/// `p ± amount / L`, rounded down.
+fn next_sqrt_price_from_b_round_down(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Option<u128> {
+    let delta = div_to_u128(
+        &(BigUint::from(amount) << 64),
+        &BigUint::from(liquidity),
+        !add,
+    )?;
+
+    if add {
+        sqrt_price.checked_add(delta)
+    } else {
+        sqrt_price.checked_sub(delta)
+    }
+}
+
+fn mul_shift_96(a: u128, b: u128) -> u128 {
+    u128::try_from((BigUint::from(a) * b) >> 96).unwrap_or(u128::MAX)
+}
+
+// Powers of sqrt(1.0001) in Q32.96, one per bit of the tick
+fn sqrt_price_positive_tick(tick: i32) -> u128 {
+    const FACTORS: [u128; 18] = [
+        79236085330515764027303304731,
+        79244008939048815603706035061,
+        79259858533276714757314932305,
+        79291567232598584799939703904,
+        79355022692464371645785046466,
+        79482085999252804386437311141,
+        79736823300114093921829183326,
+        80248749790819932309965073892,
+        81282483887344747381513967011,
+        83390072131320151908154831281,
+        87770609709833776024991924138,
+        97234110755111693312479820773,
+        119332217159966728226237229890,
+        179736315981702064433883588727,
+        407748233172238350107850275304,
+        2098478828474011932436660412517,
+        55581415166113811149459800483533,
+        38992368544603139932233054999993551,
+    ];
+
+    let mut ratio: u128 = if tick & 1 != 0 {
+        79232123823359799118286999567
+    } else {
+        79228162514264337593543950336
+    };
+    for (bit, factor) in FACTORS.iter().enumerate() {
+        if tick & (2 << bit) != 0 {
+            ratio = mul_shift_96(ratio, *factor);
+        }
+    }
+    ratio >> 32
+}
+
+// Inverse powers of sqrt(1.0001) in Q64.64, one per bit of the tick
+fn sqrt_price_negative_tick(tick: i32) -> u128 {
+    const FACTORS: [u128; 18] = [
+        18444899583751176498,
+        18443055278223354162,
+        18439367220385604838,
+        18431993317065449817,
+        18417254355718160513,
+        18387811781193591352,
+        18329067761203520168,
+        18212142134806087854,
+        17980523815641551639,
+        17526086738831147013,
+        16651378430235024244,
+        15030750278693429944,
+        12247334978882834399,
+        8131365268884726200,
+        3584323654723342297,
+        696457651847595233,
+        26294789957452057,
+        37481735321082,
+    ];
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio: u128 = if abs_tick & 1 != 0 {
+        18445821805675392311
+    } else {
+        18446744073709551616
+    };
+    for (bit, factor) in FACTORS.iter().enumerate() {
+        if abs_tick & (2 << bit) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+    ratio
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshDeserialize;
+
+    use super::*;
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+    const SPACING: i32 = 64;
+    /// 0.3%, in millionths.
+    const FEE_RATE: u16 = 3_000;
+    /// Initialized ticks on each side of the current tick 0.
+    const CROSSED_TICK: i32 = 640;
+
+    /// Pool at tick 0 with `LIQUIDITY` in range, and the two tick arrays
+    /// around it. With `crossing`, half the liquidity ends `CROSSED_TICK`
+    /// away on either side.
+    fn pool(crossing: bool) -> (WhirlpoolState, Vec<WhirlpoolTickArray>) {
+        let zeros = [0u8; 16_384];
+        let mut pool = WhirlpoolState::deserialize(&mut zeros.as_slice()).unwrap();
+        pool.tick_spacing = SPACING as u16;
+        pool.fee_rate = FEE_RATE;
+        pool.liquidity = LIQUIDITY;
+        pool.sqrt_price = 1 << 64;
+
+        let ticks_in_array = WHIRLPOOL_TICK_ARRAY_SIZE * SPACING;
+        let mut arrays: Vec<WhirlpoolTickArray> = [-ticks_in_array, 0]
+            .into_iter()
+            .map(|start_tick_index| {
+                let mut array = WhirlpoolTickArray::deserialize(&mut zeros.as_slice()).unwrap();
+                array.start_tick_index = start_tick_index;
+                array
+            })
+            .collect();
+        if crossing {
+            let half = (LIQUIDITY / 2) as i128;
+            for (array, tick, liquidity_net) in [(0, -CROSSED_TICK, half), (1, CROSSED_TICK, -half)]
+            {
+                let start = arrays[array].start_tick_index;
+                let tick = &mut arrays[array].ticks[((tick - start) / SPACING) as usize];
+                tick.initialized = true;
+                tick.liquidity_net = liquidity_net;
+            }
+        }
+        (pool, arrays)
+    }
+
+    /// Exact-in output worked in floating point from the range formulas:
+    /// `dA = L * (1/p1 - 1/p0)` and `dB = L * (p1 - p0)` on sqrt prices,
+    /// with the fee taken from the input of every range.
+    fn reference_out(amount_in: f64, a_to_b: bool, crossing: bool) -> f64 {
+        let fee = FEE_RATE as f64 / 1e6;
+        let liquidity = LIQUIDITY as f64;
+        let edge = 1.0001f64.powf(CROSSED_TICK as f64 / 2.0);
+        let edge = if a_to_b { 1.0 / edge } else { edge };
+        // (input to reach the price, output on the way) in range `liquidity`
+        let range = |liquidity: f64, from: f64, to: f64| {
+            let (delta_a, delta_b) = (liquidity * (1.0 / to - 1.0 / from), liquidity * (to - from));
+            if a_to_b {
+                (delta_a, -delta_b)
+            } else {
+                (delta_b, -delta_a)
+            }
+        };
+        // Price reached by `net` input within `liquidity` from `from`
+        let reached = |liquidity: f64, from: f64, net: f64| {
+            if a_to_b {
+                1.0 / (1.0 / from + net / liquidity)
+            } else {
+                from + net / liquidity
+            }
+        };
+
+        let net = amount_in * (1.0 - fee);
+        let (to_edge, out_to_edge) = range(liquidity, 1.0, edge);
+        if !crossing || net <= to_edge {
+            return range(liquidity, 1.0, reached(liquidity, 1.0, net)).1;
+        }
+        let rest = (amount_in - to_edge / (1.0 - fee)) * (1.0 - fee);
+        let half = liquidity / 2.0;
+        out_to_edge + range(half, edge, reached(half, edge, rest)).1
+    }
+
+    fn assert_near(quoted: u64, reference: f64, units: f64) {
+        assert!(
+            (quoted as f64 - reference).abs() <= units,
+            "quoted {} against {}",
+            quoted,
+            reference
+        );
+    }
+
+    #[test]
+    fn sqrt_prices_match_the_program_bounds() {
+        assert_eq!(whirlpool_sqrt_price_from_tick(0), 1 << 64);
+        assert_eq!(
+            whirlpool_sqrt_price_from_tick(WHIRLPOOL_MIN_TICK),
+            WHIRLPOOL_MIN_SQRT_PRICE
+        );
+        assert_eq!(
+            whirlpool_sqrt_price_from_tick(WHIRLPOOL_MAX_TICK),
+            WHIRLPOOL_MAX_SQRT_PRICE
+        );
+
+        // Each factor truncates, which costs the smallest prices a unit or two
+        for tick in (-400_000..=400_000).step_by(12_345) {
+            let exact = 1.0001f64.powf(tick as f64 / 2.0) * 2f64.powi(64);
+            let sqrt_price = whirlpool_sqrt_price_from_tick(tick) as f64;
+            assert!(
+                (sqrt_price - exact).abs() <= (exact * 1e-11).max(2.0),
+                "tick {}: {} against {}",
+                tick,
+                sqrt_price,
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn swaps_within_a_range_follow_the_curve() {
+        let (pool, arrays) = pool(false);
+
+        for a_to_b in [true, false] {
+            let quote = quote_whirlpool_swap(&pool, &arrays, 1_000_000_000, true, a_to_b).unwrap();
+            assert_eq!(quote.amount_in, 1_000_000_000);
+            assert_eq!(quote.fee_amount, 3_000_000);
+            assert_near(quote.amount_out, reference_out(1e9, a_to_b, false), 2.0);
+        }
+    }
+
+    #[test]
+    fn swaps_cross_initialized_ticks() {
+        let (flat, flat_arrays) = pool(false);
+        let (pool, arrays) = pool(true);
+        // Past the crossed tick, in both directions
+        let amount = 50_000_000_000;
+
+        for a_to_b in [true, false] {
+            let quote = quote_whirlpool_swap(&pool, &arrays, amount, true, a_to_b).unwrap();
+            assert_eq!(quote.amount_in, amount);
+            assert_near(
+                quote.amount_out,
+                reference_out(amount as f64, a_to_b, true),
+                4.0,
+            );
+            assert_near(quote.fee_amount, amount as f64 * 0.003, 2.0);
+
+            // Half the liquidity past the tick gives a worse price
+            let uncrossed =
+                quote_whirlpool_swap(&flat, &flat_arrays, amount, true, a_to_b).unwrap();
+            assert!(quote.amount_out < uncrossed.amount_out);
+
+            // Asking for that output back costs at most the rounding
+            let exact_out =
+                quote_whirlpool_swap(&pool, &arrays, quote.amount_out, false, a_to_b).unwrap();
+            assert_eq!(exact_out.amount_out, quote.amount_out);
+            assert!(exact_out.amount_in <= amount);
+            assert!(amount - exact_out.amount_in <= 2);
+        }
+    }
+
+    #[test]
+    fn swaps_past_the_loaded_arrays_fail() {
+        let (pool, arrays) = pool(true);
+
+        assert!(matches!(
+            quote_whirlpool_swap(&pool, &arrays, 10_000_000_000_000, true, true),
+            Err(QuoteError::ArraysExhausted(_))
+        ));
+        assert!(matches!(
+            quote_whirlpool_swap(&pool, &arrays[..1], 1_000, true, false),
+            Err(QuoteError::ArraysExhausted(_))
+        ));
+        assert!(matches!(
+            quote_whirlpool_swap(&pool, &arrays, 0, true, true),
+            Err(QuoteError::ZeroAmount)
+        ));
+    }
+}